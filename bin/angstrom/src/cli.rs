@@ -1,12 +1,16 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
 use alloy::signers::local::PrivateKeySigner;
+use angstrom_eth::{indexer::BundleIndexer, rewards::RewardsLedger};
 use angstrom_metrics::initialize_prometheus_metrics;
+use angstrom_rpc::rate_limit::RateLimitConfig;
 use angstrom_types::primitive::{
     AngstromSigner, CHAIN_ID, ETH_ANGSTROM_RPC, ETH_DEFAULT_RPC, ETH_MEV_RPC
 };
 use consensus::ConsensusTimingConfig;
 use hsm_signer::{Pkcs11Signer, Pkcs11SignerConfig};
+use matching_engine::solver::SolverConfig;
+use uniswap_v4::checkpoint::PoolRegistryCheckpoint;
 
 #[derive(Debug, Clone, Default, clap::Args)]
 pub struct AngstromConfig {
@@ -30,7 +34,56 @@ pub struct AngstromConfig {
     #[clap(flatten)]
     pub key_config:                KeyConfig,
     #[clap(flatten)]
-    pub consensus_timing:          ConsensusTimingConfig
+    pub consensus_timing:          ConsensusTimingConfig,
+    /// the amount of threads dedicated to solving pools. Defaults to the
+    /// available parallelism
+    #[clap(long)]
+    pub solver_threads:            Option<usize>,
+    /// persists the history of bundles that landed on chain to this file.
+    /// history is only kept in memory if not set
+    #[clap(long)]
+    pub bundle_index_path:         Option<PathBuf>,
+    /// the amount of blocks of bundle history to keep. everything is kept if
+    /// not set
+    #[clap(long)]
+    pub bundle_index_retention:    Option<u64>,
+    /// persists the lp rewards ledger to this file. the ledger is only kept in
+    /// memory if not set
+    #[clap(long)]
+    pub rewards_ledger_path:       Option<PathBuf>,
+    /// persists the discovered angstrom pools to this file so startup only
+    /// has to scan the blocks since the last run
+    #[clap(long)]
+    pub pool_checkpoint_path:      Option<PathBuf>,
+    /// loads the current angstrom pools from the controller contract instead
+    /// of scanning the chain history
+    #[clap(long)]
+    pub pools_from_controller:     bool,
+    /// persists the state of the uniswap pools to this file so startup only
+    /// has to replay the pool logs since the last save instead of loading
+    /// every pool from scratch
+    #[clap(long)]
+    pub pool_snapshot_path:        Option<PathBuf>,
+    /// runs angstrom as a sidecar to the node at this websocket url or ipc
+    /// path instead of syncing the embedded reth node
+    #[clap(long)]
+    pub external_rpc_url:          Option<String>,
+    /// the address the angstrom rpc is served on. It has its own server, also
+    /// when running in the reth node, so clients can be rate limited by
+    /// their address
+    #[clap(long, alias = "sidecar-rpc-addr", default_value = "127.0.0.1:8489")]
+    pub rpc_addr:                  String,
+    /// the port the p2p network listens on when running as a sidecar
+    #[clap(long, default_value = "30304")]
+    pub sidecar_p2p_port:          u16,
+    /// serves the admin rpc on this address, which has to be a loopback
+    /// address. The admin rpc is disabled if not set
+    #[clap(long)]
+    pub admin_rpc_addr:            Option<SocketAddr>,
+    /// toml file with the rate limits of the angstrom rpc and the api keys
+    /// that get their own tier. The defaults are used if not set
+    #[clap(long)]
+    pub rpc_rate_limits:           Option<PathBuf>
 }
 
 impl AngstromConfig {
@@ -49,6 +102,50 @@ impl AngstromConfig {
             .transpose()
     }
 
+    pub fn bundle_indexer(&self) -> eyre::Result<BundleIndexer> {
+        match self.bundle_index_path.clone() {
+            Some(path) => BundleIndexer::load(path, self.bundle_index_retention),
+            None => Ok(BundleIndexer::in_memory(self.bundle_index_retention))
+        }
+    }
+
+    pub fn rate_limits(&self) -> eyre::Result<RateLimitConfig> {
+        match self.rpc_rate_limits.as_ref() {
+            Some(path) => RateLimitConfig::load(path),
+            None => Ok(RateLimitConfig::default())
+        }
+    }
+
+    /// the solve budget isn't part of it, consensus hands it to every solve
+    /// from what is left of the slot.
+    pub fn solver_config(&self) -> SolverConfig {
+        SolverConfig {
+            threads: self
+                .solver_threads
+                .unwrap_or(SolverConfig::default().threads)
+        }
+    }
+
+    pub fn rewards_ledger(&self) -> eyre::Result<RewardsLedger> {
+        match self.rewards_ledger_path.clone() {
+            Some(path) => RewardsLedger::load(path),
+            None => Ok(RewardsLedger::in_memory())
+        }
+    }
+
+    /// the saved pool checkpoint, or one that starts discovery at the deploy
+    /// block if there is none.
+    pub fn pool_checkpoint(&self, deploy_block: u64) -> eyre::Result<PoolRegistryCheckpoint> {
+        let checkpoint = self
+            .pool_checkpoint_path
+            .as_deref()
+            .map(PoolRegistryCheckpoint::load)
+            .transpose()?
+            .flatten();
+
+        Ok(checkpoint.unwrap_or_else(|| PoolRegistryCheckpoint::at_deploy(deploy_block)))
+    }
+
     pub fn get_hsm_signer(&self) -> eyre::Result<Option<AngstromSigner<Pkcs11Signer>>> {
         Ok((self.key_config.hsm_enabled)
             .then(|| {
@@ -1,6 +1,9 @@
 //! CLI definition and entrypoint to executable
 use std::{
     collections::{HashMap, HashSet},
+    fmt::Debug,
+    future::Future,
+    net::SocketAddr,
     pin::Pin,
     str::FromStr,
     sync::Arc,
@@ -9,21 +12,28 @@ use std::{
 
 use alloy::{
     self,
-    eips::{BlockId, BlockNumberOrTag},
+    eips::BlockId,
     primitives::Address,
     providers::{Provider, ProviderBuilder, network::Ethereum}
 };
 use alloy_chains::Chain;
-use angstrom_amm_quoter::{QuoterManager, Slot0Update};
+use angstrom_amm_quoter::{QuoterHandle, QuoterManager, QuoterRequest};
 use angstrom_eth::{
     handle::{Eth, EthCommand},
-    manager::{EthDataCleanser, EthEvent}
+    indexer::BundleIndexer,
+    manager::{EthDataCleanser, EthEvent},
+    rewards::RewardsLedger
 };
 use angstrom_network::{
     NetworkBuilder as StromNetworkBuilder, NetworkOrderEvent, PoolManagerBuilder, StatusState,
-    VerificationSidecar,
+    StromNetworkHandle, VerificationSidecar,
     pool_manager::{OrderCommand, PoolHandle}
 };
+use angstrom_rpc::{
+    AdminApi, ConsensusApi, HistoryApi, OrderApi,
+    api::{AdminApiServer, ConsensusApiServer, HistoryApiServer, OrderApiServer},
+    rate_limit::serve_rate_limited
+};
 use angstrom_types::{
     block_sync::{BlockSyncProducer, GlobalBlockSync},
     consensus::{SlotClock, StromConsensusEvent, SystemTimeSlotClock},
@@ -35,35 +45,41 @@ use angstrom_types::{
         UniswapPoolRegistry
     },
     reth_db_provider::RethDbLayer,
-    reth_db_wrapper::RethDbWrapper,
-    submission::SubmissionHandler
+    reth_db_wrapper::{RethDbWrapper, SetBlock},
+    submission::{SubmissionHandler, SubmitterToggles}
 };
 use consensus::{AngstromValidator, ConsensusHandler, ConsensusManager, ManagerNetworkDeps};
 use futures::Stream;
-use matching_engine::{MatchingManager, manager::MatcherCommand};
+use jsonrpsee::server::ServerBuilder;
+use matching_engine::{
+    MatchingManager,
+    manager::{MatcherCommand, MatcherHandle}
+};
 use order_pool::{PoolConfig, PoolManagerUpdate, order_storage::OrderStorage};
 use parking_lot::RwLock;
 use reth::{
     api::NodeAddOns,
     builder::FullNodeComponents,
-    chainspec::ChainSpec,
-    core::exit::NodeExitFuture,
     primitives::EthPrimitives,
     providers::{BlockNumReader, CanonStateNotification, CanonStateSubscriptions},
     tasks::TaskExecutor
 };
 use reth_metrics::common::mpsc::{UnboundedMeteredReceiver, UnboundedMeteredSender};
 use reth_network::{NetworkHandle, Peers};
-use reth_node_builder::{FullNode, NodeTypes, node::FullNodeTypes, rpc::RethRpcAddOns};
+use reth_node_builder::{FullNode, rpc::RethRpcAddOns};
 use reth_provider::{
-    BlockReader, DatabaseProviderFactory, ReceiptProvider, TryIntoHistoricalStateProvider
+    DatabaseProviderFactory, ReceiptProvider, StateProviderFactory, TryIntoHistoricalStateProvider
 };
 use telemetry::init_telemetry;
 use tokio::sync::{
     mpsc,
     mpsc::{Receiver, Sender, UnboundedReceiver, UnboundedSender, channel, unbounded_channel}
 };
-use uniswap_v4::{DEFAULT_TICKS, configure_uniswap_manager, fetch_angstrom_pools};
+use uniswap_v4::{
+    DEFAULT_TICKS,
+    checkpoint::{PoolRegistryCheckpoint, track_pool_registry},
+    configure_uniswap_manager, fetch_angstrom_pools, fetch_angstrom_pools_from_controller
+};
 use url::Url;
 use validation::{
     common::TokenPriceGenerator,
@@ -107,8 +123,8 @@ pub struct StromHandles {
     pub orderpool_tx: UnboundedSender<DefaultOrderCommand>,
     pub orderpool_rx: UnboundedReceiver<DefaultOrderCommand>,
 
-    pub quoter_tx: mpsc::Sender<(HashSet<PoolId>, mpsc::Sender<Slot0Update>)>,
-    pub quoter_rx: mpsc::Receiver<(HashSet<PoolId>, mpsc::Sender<Slot0Update>)>,
+    pub quoter_tx: mpsc::Sender<QuoterRequest>,
+    pub quoter_rx: mpsc::Receiver<QuoterRequest>,
 
     pub validator_tx: UnboundedSender<ValidationRequest>,
     pub validator_rx: UnboundedReceiver<ValidationRequest>,
@@ -174,56 +190,143 @@ pub fn initialize_strom_handles() -> StromHandles {
     }
 }
 
-pub async fn initialize_strom_components<Node, AddOns, P: Peers + Unpin + 'static, S>(
+/// The chain the angstrom modules are started on. This is all that differs
+/// between running inside of a reth node and as a sidecar to a external node.
+pub trait ChainSource: Send + Sync + 'static {
+    /// used for every query that isn't a order simulation
+    type Provider: Provider + 'static;
+    type CanonState: CanonStateSubscriptions<Primitives = EthPrimitives> + Clone + 'static;
+    /// the state orders are simulated against
+    type Db: SetBlock
+        + BlockNumReader
+        + revm::DatabaseRef<Error: Send + Sync + Debug>
+        + Unpin
+        + Clone
+        + Send
+        + Sync
+        + 'static;
+
+    fn provider(&self) -> Arc<Self::Provider>;
+
+    fn canon_state(&self) -> Self::CanonState;
+
+    fn db(&self, block_number: u64) -> Self::Db;
+
+    /// scans the blocks after the checkpoint up until `end_block` for pool
+    /// changes.
+    fn fetch_angstrom_pools(
+        &self,
+        checkpoint: PoolRegistryCheckpoint,
+        end_block: u64,
+        angstrom_address: Address
+    ) -> impl Future<Output = eyre::Result<PoolRegistryCheckpoint>> + Send;
+}
+
+/// The reth node angstrom is installed into. Pools are scanned for and orders
+/// simulated straight from its database.
+pub struct RethChain<N, P> {
+    node:     N,
+    provider: Arc<P>
+}
+
+/// connects to the node's database, falling back to its ipc endpoint.
+pub async fn reth_chain<Node, AddOns>(
+    node: &FullNode<Node, AddOns>
+) -> eyre::Result<RethChain<Node::Provider, impl Provider + 'static>>
+where
+    Node: FullNodeComponents,
+    Node::Provider: DatabaseProviderFactory<Provider: TryIntoHistoricalStateProvider + BlockNumReader>
+        + Clone
+        + 'static,
+    AddOns: NodeAddOns<Node> + RethRpcAddOns<Node>
+{
+    // NOTE:
+    // no key is installed and this is strictly for internal usage. Realsically, we
+    // should build a alloy provider impl that just uses the raw underlying db
+    // so it will be quicker than rpc + won't be bounded by the rpc threadpool.
+    let url = node.rpc_server_handle().ipc_endpoint().unwrap();
+    tracing::info!(?url, "backup to database is");
+    let provider = ProviderBuilder::<_, _, Ethereum>::default()
+        .with_recommended_fillers()
+        .layer(RethDbLayer::new(node.provider().clone()))
+        // backup
+        .connect(&url)
+        .await?;
+
+    Ok(RethChain { node: node.provider().clone(), provider: Arc::new(provider) })
+}
+
+impl<N, P> ChainSource for RethChain<N, P>
+where
+    N: DatabaseProviderFactory<
+            Provider: TryIntoHistoricalStateProvider + ReceiptProvider + BlockNumReader
+        > + StateProviderFactory
+        + ReceiptProvider
+        + CanonStateSubscriptions<Primitives = EthPrimitives>
+        + Clone
+        + Unpin
+        + 'static,
+    P: Provider + 'static
+{
+    type CanonState = N;
+    type Db = RethDbWrapper<N>;
+    type Provider = P;
+
+    fn provider(&self) -> Arc<P> {
+        self.provider.clone()
+    }
+
+    fn canon_state(&self) -> N {
+        self.node.clone()
+    }
+
+    fn db(&self, block_number: u64) -> RethDbWrapper<N> {
+        RethDbWrapper::new(self.node.clone(), block_number)
+    }
+
+    async fn fetch_angstrom_pools(
+        &self,
+        checkpoint: PoolRegistryCheckpoint,
+        end_block: u64,
+        angstrom_address: Address
+    ) -> eyre::Result<PoolRegistryCheckpoint> {
+        Ok(fetch_angstrom_pools(checkpoint, end_block, angstrom_address, &self.node).await)
+    }
+}
+
+/// starts all of the angstrom modules on the chain. resolves once `exit` does,
+/// which is when the chain can no longer be followed.
+pub async fn initialize_strom_components<C: ChainSource, P: Peers + Unpin + 'static, S>(
     config: AngstromConfig,
     signer: AngstromSigner<S>,
     mut handles: StromHandles,
     network_builder: StromNetworkBuilder<P, S>,
-    node: &FullNode<Node, AddOns>,
+    chain: C,
     executor: TaskExecutor,
-    exit: NodeExitFuture,
+    exit: impl Future<Output = eyre::Result<()>>,
     node_set: HashSet<Address>,
-    consensus_client: ConsensusHandler
+    consensus_client: ConsensusHandler,
+    indexer: BundleIndexer,
+    rewards: RewardsLedger
 ) -> eyre::Result<()>
 where
-    Node: FullNodeComponents
-        + FullNodeTypes<Types: NodeTypes<ChainSpec = ChainSpec, Primitives = EthPrimitives>>,
-    Node::Provider: BlockReader<
-            Block = reth::primitives::Block,
-            Receipt = reth::primitives::Receipt,
-            Header = reth::primitives::Header
-        > + DatabaseProviderFactory,
-    AddOns: NodeAddOns<Node> + RethRpcAddOns<Node>,
-    <<Node as FullNodeTypes>::Provider as DatabaseProviderFactory>::Provider:
-        TryIntoHistoricalStateProvider + ReceiptProvider,
-    <<Node as FullNodeTypes>::Provider as DatabaseProviderFactory>::Provider: BlockNumReader,
     S: AngstromMetaSigner
 {
     // Check to assert that the timeing config is valid.
     assert!(config.consensus_timing.is_valid(), "consensus timing config is invalid");
 
     let node_address = signer.address();
-
-    // NOTE:
-    // no key is installed and this is strictly for internal usage. Realsically, we
-    // should build a alloy provider impl that just uses the raw underlying db
-    // so it will be quicker than rpc + won't be bounded by the rpc threadpool.
-    let url = node.rpc_server_handle().ipc_endpoint().unwrap();
-    tracing::info!(?url, ?config.mev_boost_endpoints, "backup to database is");
-    let querying_provider: Arc<_> = ProviderBuilder::<_, _, Ethereum>::default()
-        .with_recommended_fillers()
-        .layer(RethDbLayer::new(node.provider().clone()))
-        // backup
-        .connect(&url)
-        .await
-        .unwrap()
-        .into();
+    let mut exit = std::pin::pin!(exit);
+    let querying_provider = chain.provider();
+    let canon_state = chain.canon_state();
+    tracing::info!(?config.mev_boost_endpoints);
 
     let angstrom_address = *ANGSTROM_ADDRESS.get().unwrap();
     let controller = *CONTROLLER_V1_ADDRESS.get().unwrap();
     let deploy_block = *ANGSTROM_DEPLOYED_BLOCK.get().unwrap();
     let gas_token = *GAS_TOKEN_ADDRESS.get().unwrap();
     let pool_manager = *POOL_MANAGER_ADDRESS.get().unwrap();
+    let checkpoint = config.pool_checkpoint(deploy_block)?;
 
     let normal_nodes = config
         .normal_nodes
@@ -251,40 +354,69 @@ where
         angstrom_address,
         signer.clone()
     );
+    let submitter_toggles = submission_handler.toggles.clone();
 
     tracing::info!(target: "angstrom::startup-sequence", "waiting for the next block to continue startup sequence. \
         this is done to ensure all modules start on the same state and we don't hit the rare  \
         condition of a block while starting modules");
 
     // wait for the next block so that we have a full 12 seconds on startup.
-    let mut sub = node.provider.subscribe_to_canonical_state();
-    handle_init_block_spam(&mut sub).await;
-    let _ = sub.recv().await.expect("next block");
+    let mut sub = canon_state.subscribe_to_canonical_state();
+    tokio::select! {
+        res = &mut exit => return res,
+        _ = async {
+            handle_init_block_spam(&mut sub).await;
+            sub.recv().await.expect("next block")
+        } => {}
+    }
 
     tracing::info!(target: "angstrom::startup-sequence", "new block detected. initializing all modules");
 
-    let block_id = querying_provider.get_block_number().await.unwrap();
+    let block_id = querying_provider.get_block_number().await?;
 
     let pool_config_store = Arc::new(
         AngstromPoolConfigStore::load_from_chain(
             angstrom_address,
-            BlockId::Number(BlockNumberOrTag::Latest),
+            BlockId::number(block_id),
             &querying_provider
         )
         .await
-        .unwrap()
+        .map_err(|e| eyre::eyre!("{e}"))?
     );
 
     // load the angstrom pools;
     tracing::info!("starting search for pools");
-    let pools = fetch_angstrom_pools(
-        deploy_block as usize,
-        block_id as usize,
-        angstrom_address,
-        &node.provider
-    )
-    .await;
-    tracing::info!("found pools");
+    let checkpoint = if config.pools_from_controller {
+        fetch_angstrom_pools_from_controller(
+            controller,
+            angstrom_address,
+            &pool_config_store,
+            block_id,
+            querying_provider.as_ref()
+        )
+        .await?
+    } else {
+        tracing::info!(checkpoint_block = checkpoint.block, "scanning for pools since checkpoint");
+        chain
+            .fetch_angstrom_pools(checkpoint, block_id, angstrom_address)
+            .await?
+    };
+
+    let block_id = tokio::select! {
+        res = &mut exit => return res,
+        block = sub.recv() => match block.expect("first block") {
+            CanonStateNotification::Commit { new } => new.tip().number,
+            CanonStateNotification::Reorg { new, .. } => new.tip().number
+        }
+    };
+
+    // fetching the pools takes awhile so we scan the blocks that came in meanwhile
+    // as well. from here on out the eth manager picks up any new pools.
+    let checkpoint = chain
+        .fetch_angstrom_pools(checkpoint, block_id, angstrom_address)
+        .await?;
+    let pools = checkpoint.pool_keys();
+    tracing::info!(pools = pools.len(), "found pools");
 
     let angstrom_tokens = pools
         .iter()
@@ -294,22 +426,22 @@ where
             acc
         });
 
-    // re-fetch given the fetch pools takes awhile. given this, we do techincally
-    // have a gap in which a pool is deployed durning startup. This isn't
-    // critical but we will want to fix this down the road.
-    // let block_id = querying_provider.get_block_number().await.unwrap();
-    let block_id = match sub.recv().await.expect("first block") {
-        CanonStateNotification::Commit { new } => new.tip().number,
-        CanonStateNotification::Reorg { new, .. } => new.tip().number
-    };
-
     tracing::info!(?block_id, "starting up with block");
-    let eth_data_sub = node.provider.subscribe_to_canonical_state();
+    let eth_data_sub = canon_state.subscribe_to_canonical_state();
 
     let global_block_sync = GlobalBlockSync::new(block_id);
 
     // this right here problem
     let uniswap_registry: UniswapPoolRegistry = pools.into();
+    for (angstrom_pool_id, uniswap_pool_id) in &uniswap_registry.conversion_map {
+        rewards.track_pool(*angstrom_pool_id, *uniswap_pool_id);
+    }
+    if let Err(error) = rewards
+        .backfill(querying_provider.as_ref(), pool_manager, deploy_block, block_id)
+        .await
+    {
+        tracing::warn!(%error, "failed to backfill the rewards ledger");
+    }
     let uni_ang_registry =
         UniswapAngstromRegistry::new(uniswap_registry.clone(), pool_config_store.clone());
 
@@ -326,16 +458,24 @@ where
         pool_config_store.clone(),
         global_block_sync.clone(),
         node_set.clone(),
-        vec![handles.eth_handle_tx.take().unwrap()]
+        vec![handles.eth_handle_tx.take().unwrap()],
+        Some(indexer),
+        Some(rewards)
     )
     .unwrap();
 
+    if let Some(path) = config.pool_checkpoint_path.clone() {
+        executor.spawn_critical(
+            "pool registry checkpoint",
+            track_pool_registry(path, checkpoint, eth_handle.subscribe_network())
+        );
+    }
+
     let network_stream = Box::pin(eth_handle.subscribe_network())
         as Pin<Box<dyn Stream<Item = EthEvent> + Send + Sync>>;
 
-    let signer_addr = signer.address();
     executor.spawn_critical_with_graceful_shutdown_signal("telemetry init", |grace_shutdown| {
-        init_telemetry(signer_addr, grace_shutdown)
+        init_telemetry(node_address, grace_shutdown)
     });
 
     let uniswap_pool_manager = configure_uniswap_manager::<_, DEFAULT_TICKS>(
@@ -345,7 +485,8 @@ where
         block_id,
         global_block_sync.clone(),
         pool_manager,
-        network_stream
+        network_stream,
+        config.pool_snapshot_path.clone()
     )
     .await;
 
@@ -354,7 +495,9 @@ where
     let uniswap_pools = uniswap_pool_manager.pools();
     let pool_ids = uniswap_pool_manager.pool_addresses().collect::<Vec<_>>();
 
-    executor.spawn_critical("uniswap pool manager", Box::pin(uniswap_pool_manager));
+    executor.spawn_critical_with_graceful_shutdown_signal("uniswap pool manager", |grace| {
+        uniswap_pool_manager.run_until_graceful_shutdown(grace)
+    });
     let price_generator = TokenPriceGenerator::new(
         querying_provider.clone(),
         block_id,
@@ -367,14 +510,14 @@ where
 
     let update_stream = Box::pin(PairsWithPrice::into_price_update_stream(
         angstrom_address,
-        node.provider.canonical_state_stream(),
+        canon_state.canonical_state_stream(),
         querying_provider.clone()
     ));
 
-    let block_height = node.provider.best_block_number().unwrap();
+    let block_height = querying_provider.get_block_number().await?;
 
     init_validation(
-        RethDbWrapper::new(node.provider.clone(), block_height),
+        chain.db(block_height),
         block_height,
         angstrom_address,
         node_address,
@@ -391,14 +534,15 @@ where
     let network_handle = network_builder
         .with_pool_manager(handles.pool_tx)
         .with_consensus_manager(handles.consensus_tx_op)
-        .build_handle(executor.clone(), node.provider.clone());
+        .build_handle(executor.clone(), querying_provider.clone());
 
     // fetch pool ids
 
     let pool_config = PoolConfig::with_pool_ids(pool_ids);
     let order_storage = Arc::new(OrderStorage::new(&pool_config));
+    let (price_observer, price_observations) = tokio::sync::mpsc::unbounded_channel();
 
-    let _pool_handle = PoolManagerBuilder::new(
+    let pool_handle = PoolManagerBuilder::new(
         validation_handle.clone(),
         Some(order_storage.clone()),
         network_handle.clone(),
@@ -407,6 +551,7 @@ where
         global_block_sync.clone()
     )
     .with_config(pool_config)
+    .with_price_observations(price_observations)
     .build_with_channels(
         executor.clone(),
         handles.orderpool_tx,
@@ -415,6 +560,17 @@ where
         block_id,
         |_| {}
     );
+    if let Some(addr) = config.admin_rpc_addr {
+        spawn_admin_rpc(
+            addr,
+            pool_handle.clone(),
+            consensus_client.clone(),
+            network_handle.clone(),
+            submitter_toggles,
+            &executor
+        )
+        .await?;
+    }
     let validators = node_set
         .into_iter()
         // use same weight for all validators
@@ -423,7 +579,9 @@ where
     tracing::info!("pool manager start");
 
     // spinup matching engine
-    let matching_handle = MatchingManager::spawn(executor.clone(), validation_handle.clone());
+    let matching_handle =
+        MatchingManager::spawn(executor.clone(), validation_handle.clone(), config.solver_config());
+    spawn_bundle_fault_reporter(&matching_handle, pool_handle, &executor);
 
     // spin up amm quoter
     let amm = QuoterManager::new(
@@ -437,7 +595,8 @@ where
             .expect("failed to build rayon thread pool"),
         Duration::from_millis(100),
         consensus_client.subscribe_consensus_round_event()
-    );
+    )
+    .with_price_observer(price_observer);
 
     executor.spawn_critical("amm quoting service", amm);
 
@@ -472,6 +631,98 @@ where
     exit.await
 }
 
+/// serves the angstrom namespace on its own server. The server accepts the
+/// connections itself so the rate limits can tell clients apart by the peer of
+/// their connection, which reth's rpc server doesn't hand to middleware.
+pub async fn spawn_angstrom_rpc(
+    config: &AngstromConfig,
+    pool: PoolHandle,
+    validation_client: ValidationClient,
+    quoter_handle: QuoterHandle,
+    consensus_client: ConsensusHandler,
+    indexer: BundleIndexer,
+    rewards: RewardsLedger,
+    executor: &TaskExecutor
+) -> eyre::Result<()> {
+    let mut rpcs =
+        OrderApi::new(pool, executor.clone(), validation_client, quoter_handle).into_rpc();
+    rpcs.merge(ConsensusApi::new(consensus_client, executor.clone()).into_rpc())?;
+    rpcs.merge(HistoryApi::new(indexer, rewards).into_rpc())?;
+
+    let (addr, server_handle) =
+        serve_rate_limited(config.rpc_addr.clone(), rpcs, config.rate_limits()?).await?;
+    tracing::info!(%addr, "rpc server started");
+    executor.spawn_critical(
+        "rpc",
+        Box::pin(async move {
+            let _ = server_handle.stopped().await;
+        })
+    );
+
+    Ok(())
+}
+
+/// serves the admin rpc on its own server. As it lets whoever can reach it
+/// drop orders and ban peers, it is only ever bound to a loopback address.
+pub async fn spawn_admin_rpc(
+    addr: SocketAddr,
+    pool: PoolHandle,
+    consensus: ConsensusHandler,
+    network: StromNetworkHandle,
+    submitters: SubmitterToggles,
+    executor: &TaskExecutor
+) -> eyre::Result<()> {
+    ensure_loopback(addr)?;
+
+    let server = ServerBuilder::default().build(addr).await?;
+    let addr = server.local_addr()?;
+    let rpcs = AdminApi::new(pool, consensus, network, submitters).into_rpc();
+    executor.spawn_critical(
+        "admin rpc",
+        Box::pin(async move {
+            let server_handle = server.start(rpcs);
+            tracing::info!(%addr, "admin rpc server started");
+            let _ = server_handle.stopped().await;
+        })
+    );
+
+    Ok(())
+}
+
+/// drops the orders the matching engine had to leave out of a bundle from the
+/// pool, so they can't fail the simulation of the next bundle as well.
+pub fn spawn_bundle_fault_reporter(
+    matching_handle: &MatcherHandle,
+    pool: PoolHandle,
+    executor: &TaskExecutor
+) {
+    let mut faults = matching_handle.subscribe_faults();
+    executor.spawn(Box::pin(async move {
+        loop {
+            match faults.recv().await {
+                Ok(faults) => {
+                    if !faults.orders.is_empty() {
+                        pool.invalidate_orders(faults.orders);
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "missed bundle faults");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break
+            }
+        }
+    }));
+}
+
+fn ensure_loopback(addr: SocketAddr) -> eyre::Result<()> {
+    eyre::ensure!(
+        addr.ip().is_loopback(),
+        "the admin rpc can only be served on a loopback address, got {addr}"
+    );
+
+    Ok(())
+}
+
 async fn handle_init_block_spam(
     canon: &mut tokio::sync::broadcast::Receiver<CanonStateNotification>
 ) {
@@ -493,3 +744,16 @@ async fn handle_init_block_spam(
     }
     tracing::info!("finished handling block-spam");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_rpc_is_only_served_on_loopback() {
+        assert!(ensure_loopback("127.0.0.1:5555".parse().unwrap()).is_ok());
+        assert!(ensure_loopback("[::1]:5555".parse().unwrap()).is_ok());
+        assert!(ensure_loopback("0.0.0.0:5555".parse().unwrap()).is_err());
+        assert!(ensure_loopback("192.168.1.10:5555".parse().unwrap()).is_err());
+    }
+}
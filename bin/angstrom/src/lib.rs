@@ -1,6 +1,11 @@
 //! Angstrom binary executable.
 //!
 //! ## Feature Flags
+//!
+//! - `jemalloc`: uses jemalloc as the global allocator. Enabled by default.
+//! - `jemalloc-prof`: enables jemalloc heap profiling.
+//! - `otlp`: adds the `--tracing-otlp` flag, which exports spans to an
+//!   OpenTelemetry collector. See `docs/node/Tracing.md`.
 
 use std::{collections::HashSet, sync::Arc};
 
@@ -8,12 +13,9 @@ use alloy::providers::{ProviderBuilder, network::Ethereum};
 use alloy_chains::NamedChain;
 use alloy_primitives::Address;
 use angstrom_amm_quoter::QuoterHandle;
+use angstrom_eth::{indexer::BundleIndexer, rewards::RewardsLedger};
 use angstrom_metrics::METRICS_ENABLED;
 use angstrom_network::{AngstromNetworkBuilder, pool_manager::PoolHandle};
-use angstrom_rpc::{
-    ConsensusApi, OrderApi,
-    api::{ConsensusApiServer, OrderApiServer}
-};
 use angstrom_types::{
     contract_bindings::controller_v_1::ControllerV1,
     primitive::{
@@ -26,8 +28,9 @@ use cli::AngstromConfig;
 use consensus::ConsensusHandler;
 use parking_lot::RwLock;
 use reth::{
+    CliRunner,
     chainspec::{ChainSpec, EthChainSpec, EthereumChainSpecParser},
-    cli::Cli,
+    cli::{Cli, Commands},
     tasks::TaskExecutor
 };
 use reth_db::DatabaseEnv;
@@ -36,99 +39,148 @@ use reth_node_ethereum::node::{EthereumAddOns, EthereumNode};
 use validation::validator::ValidationClient;
 
 use crate::components::{
-    StromHandles, init_network_builder, initialize_strom_components, initialize_strom_handles
+    StromHandles, init_network_builder, initialize_strom_components, initialize_strom_handles,
+    reth_chain, spawn_angstrom_rpc
 };
 
 pub mod cli;
 pub mod components;
+#[cfg(feature = "otlp")]
+mod otlp;
+pub mod sidecar;
+
+/// How angstrom gets to the chain.
+enum Launch {
+    /// installed into the reth node that is launched with it
+    Reth(WithLaunchContext<NodeBuilder<Arc<DatabaseEnv>, ChainSpec>>),
+    /// following the external node at `--external-rpc-url`
+    Sidecar(Arc<ChainSpec>)
+}
 
 /// Convenience function for parsing CLI options, set up logging and run the
 /// chosen command.
 #[inline]
 pub fn run() -> eyre::Result<()> {
-    Cli::<EthereumChainSpecParser, AngstromConfig>::parse().run(|builder, args| async move {
+    let cli = Cli::<EthereumChainSpecParser, AngstromConfig>::parse();
+    #[cfg(feature = "otlp")]
+    otlp::init();
+
+    // a sidecar doesn't sync anything itself, so reth's node launcher, which opens
+    // the database in the datadir, is skipped entirely.
+    let sidecar = match &cli.command {
+        Commands::Node(command) => command
+            .ext
+            .external_rpc_url
+            .is_some()
+            .then(|| (command.chain.clone(), command.ext.clone())),
+        _ => None
+    };
+    if let Some((chain_spec, args)) = sidecar {
+        let _guard = cli.init_tracing()?;
+        return CliRunner::try_default_runtime()?.run_command_until_exit(|ctx| {
+            run_angstrom(ctx.task_executor, args, Launch::Sidecar(chain_spec))
+        });
+    }
+
+    cli.run(|builder, args| async move {
         let executor = builder.task_executor().clone();
-        let chain = builder.config().chain.chain().named().unwrap();
-
-        match chain {
-            NamedChain::Sepolia => {
-                init_with_chain_id(NamedChain::Sepolia as u64);
-            }
-            NamedChain::Mainnet => {
-                init_with_chain_id(NamedChain::Mainnet as u64);
-            }
-            chain => panic!("we do not support chain {chain}")
-        }
+        run_angstrom(executor, args, Launch::Reth(builder)).await
+    })
+}
 
-        if args.metrics_enabled {
-            executor.spawn_critical("metrics", crate::cli::init_metrics(args.metrics_port));
-            METRICS_ENABLED.set(true).unwrap();
-        } else {
-            METRICS_ENABLED.set(false).unwrap();
-        }
+async fn run_angstrom(
+    executor: TaskExecutor,
+    args: AngstromConfig,
+    launch: Launch
+) -> eyre::Result<()> {
+    let chain = match &launch {
+        Launch::Reth(builder) => builder.config().chain.chain(),
+        Launch::Sidecar(chain_spec) => chain_spec.chain()
+    };
 
-        tracing::info!(domain=?ANGSTROM_DOMAIN);
-
-        let channels = initialize_strom_handles();
-        let quoter_handle = QuoterHandle(channels.quoter_tx.clone());
-
-        // for rpc
-        let pool = channels.get_pool_handle();
-        let executor_clone = executor.clone();
-        let validation_client = ValidationClient(channels.validator_tx.clone());
-        let consensus_client = ConsensusHandler(channels.consensus_tx_rpc.clone());
-
-        // get provider and node set for startup, we need this so when reth startup
-        // happens, we directly can connect to the nodes.
-
-        let startup_provider = ProviderBuilder::<_, _, Ethereum>::default()
-            .with_recommended_fillers()
-            .connect(&args.boot_node)
-            .await
-            .unwrap();
-
-        let periphery_c =
-            ControllerV1::new(*CONTROLLER_V1_ADDRESS.get().unwrap(), startup_provider);
-        let node_set = periphery_c
-            .nodes()
-            .call()
-            .await
-            .unwrap()
-            .into_iter()
-            .collect::<HashSet<_>>();
-
-        if let Some(signer) = args.get_local_signer()? {
-            run_with_signer(
-                pool,
-                executor_clone,
-                node_set,
-                validation_client,
-                quoter_handle,
-                consensus_client,
-                signer,
-                args,
-                channels,
-                builder
-            )
-            .await
-        } else if let Some(signer) = args.get_hsm_signer()? {
-            run_with_signer(
-                pool,
-                executor_clone,
-                node_set,
-                validation_client,
-                quoter_handle,
-                consensus_client,
-                signer,
-                args,
-                channels,
-                builder
-            )
-            .await
-        } else {
-            unreachable!()
+    match chain.named().unwrap() {
+        NamedChain::Sepolia => {
+            init_with_chain_id(NamedChain::Sepolia as u64);
         }
-    })
+        NamedChain::Mainnet => {
+            init_with_chain_id(NamedChain::Mainnet as u64);
+        }
+        chain => panic!("we do not support chain {chain}")
+    }
+
+    if args.metrics_enabled {
+        executor.spawn_critical("metrics", crate::cli::init_metrics(args.metrics_port));
+        METRICS_ENABLED.set(true).unwrap();
+    } else {
+        METRICS_ENABLED.set(false).unwrap();
+    }
+
+    tracing::info!(domain=?ANGSTROM_DOMAIN);
+
+    let channels = initialize_strom_handles();
+    let quoter_handle = QuoterHandle(channels.quoter_tx.clone());
+
+    // for rpc
+    let pool = channels.get_pool_handle();
+    let executor_clone = executor.clone();
+    let validation_client = ValidationClient(channels.validator_tx.clone());
+    let consensus_client = ConsensusHandler(channels.consensus_tx_rpc.clone());
+    let indexer = args.bundle_indexer()?;
+    let rewards = args.rewards_ledger()?;
+
+    // get provider and node set for startup, we need this so when reth startup
+    // happens, we directly can connect to the nodes.
+
+    let startup_provider = ProviderBuilder::<_, _, Ethereum>::default()
+        .with_recommended_fillers()
+        .connect(&args.boot_node)
+        .await
+        .unwrap();
+
+    let periphery_c = ControllerV1::new(*CONTROLLER_V1_ADDRESS.get().unwrap(), startup_provider);
+    let node_set = periphery_c
+        .nodes()
+        .call()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    if let Some(signer) = args.get_local_signer()? {
+        run_with_signer(
+            pool,
+            executor_clone,
+            node_set,
+            validation_client,
+            quoter_handle,
+            consensus_client,
+            indexer,
+            rewards,
+            signer,
+            args,
+            channels,
+            launch
+        )
+        .await
+    } else if let Some(signer) = args.get_hsm_signer()? {
+        run_with_signer(
+            pool,
+            executor_clone,
+            node_set,
+            validation_client,
+            quoter_handle,
+            consensus_client,
+            indexer,
+            rewards,
+            signer,
+            args,
+            channels,
+            launch
+        )
+        .await
+    } else {
+        unreachable!()
+    }
 }
 
 async fn run_with_signer<S: AngstromMetaSigner>(
@@ -138,11 +190,35 @@ async fn run_with_signer<S: AngstromMetaSigner>(
     validation_client: ValidationClient,
     quoter_handle: QuoterHandle,
     consensus_client: ConsensusHandler,
+    indexer: BundleIndexer,
+    rewards: RewardsLedger,
     secret_key: AngstromSigner<S>,
     args: AngstromConfig,
     mut channels: StromHandles,
-    builder: WithLaunchContext<NodeBuilder<Arc<DatabaseEnv>, ChainSpec>>
+    launch: Launch
 ) -> eyre::Result<()> {
+    let builder = match launch {
+        Launch::Reth(builder) => builder,
+        Launch::Sidecar(chain_spec) => {
+            let external_rpc_url = args.external_rpc_url.clone().unwrap();
+            return sidecar::run_sidecar(
+                external_rpc_url,
+                pool,
+                executor,
+                node_set,
+                validation_client,
+                quoter_handle,
+                consensus_client,
+                indexer,
+                rewards,
+                secret_key,
+                args,
+                channels,
+                chain_spec
+            )
+            .await;
+        }
+    };
     let mut network = init_network_builder(
         secret_key.clone(),
         channels.eth_handle_rx.take().unwrap(),
@@ -150,8 +226,18 @@ async fn run_with_signer<S: AngstromMetaSigner>(
     )?;
 
     let protocol_handle = network.build_protocol_handler();
-    let cloned_consensus_client = consensus_client.clone();
-    let executor_clone = executor.clone();
+    // served apart from reth's rpc, see `spawn_angstrom_rpc`
+    spawn_angstrom_rpc(
+        &args,
+        pool,
+        validation_client,
+        quoter_handle,
+        consensus_client.clone(),
+        indexer.clone(),
+        rewards.clone(),
+        &executor
+    )
+    .await?;
     let NodeHandle { node, node_exit_future } = builder
         .with_types::<EthereumNode>()
         .with_components(
@@ -160,19 +246,6 @@ async fn run_with_signer<S: AngstromMetaSigner>(
                 .network(AngstromNetworkBuilder::new(protocol_handle))
         )
         .with_add_ons::<EthereumAddOns<_, _, _>>(Default::default())
-        .extend_rpc_modules(move |rpc_context| {
-            let order_api = OrderApi::new(
-                pool.clone(),
-                executor_clone.clone(),
-                validation_client,
-                quoter_handle
-            );
-            let consensus = ConsensusApi::new(cloned_consensus_client, executor_clone);
-            rpc_context.modules.merge_configured(order_api.into_rpc())?;
-            rpc_context.modules.merge_configured(consensus.into_rpc())?;
-
-            Ok(())
-        })
         .launch()
         .await?;
     network = network.with_reth(node.network.clone());
@@ -182,11 +255,13 @@ async fn run_with_signer<S: AngstromMetaSigner>(
         secret_key,
         channels,
         network,
-        &node,
+        reth_chain(&node).await?,
         executor,
         node_exit_future,
         node_set,
-        consensus_client
+        consensus_client,
+        indexer,
+        rewards
     )
     .await
 }
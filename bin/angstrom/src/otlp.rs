@@ -0,0 +1,45 @@
+//! Carries the w3c trace context of the exported spans between the tasks and
+//! nodes an order passes through.
+
+use std::collections::HashMap;
+
+use angstrom_types::trace_context::{TraceContext, TracePropagator};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+#[derive(Debug, Default)]
+pub struct W3cPropagator(TraceContextPropagator);
+
+impl TracePropagator for W3cPropagator {
+    fn inject(&self, span: &Span, context: &mut TraceContext) {
+        let mut fields = HashMap::new();
+        self.0.inject_context(&span.context(), &mut fields);
+
+        for (key, value) in fields {
+            context.set(key, value);
+        }
+    }
+
+    fn set_parent(&self, span: &Span, context: &TraceContext) {
+        span.set_parent(self.0.extract(&Fields(context)));
+    }
+}
+
+struct Fields<'a>(&'a TraceContext);
+
+impl Extractor for Fields<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().collect()
+    }
+}
+
+/// installs the propagator, the spans of an order are only linked with it.
+pub fn init() {
+    angstrom_types::trace_context::set_trace_propagator(W3cPropagator::default());
+}
@@ -0,0 +1,219 @@
+//! Runs angstrom as a sidecar to a external node. Blocks, receipts and state
+//! are all fetched over json-rpc from the node at `--external-rpc-url`, so no
+//! reth node is synced. The angstrom rpc and p2p network are served standalone.
+use std::{collections::HashSet, future::Future, sync::Arc};
+
+use alloy::{
+    primitives::Address,
+    providers::{Provider, ProviderBuilder, network::Ethereum}
+};
+use angstrom_amm_quoter::QuoterHandle;
+use angstrom_eth::{indexer::BundleIndexer, rewards::RewardsLedger, rpc_chain::RpcCanonChain};
+use angstrom_network::pool_manager::PoolHandle;
+use angstrom_types::{
+    primitive::{AngstromMetaSigner, AngstromSigner},
+    rpc_db_wrapper::RpcDbWrapper
+};
+use consensus::ConsensusHandler;
+use parking_lot::RwLock;
+use reth::{
+    chainspec::{ChainSpec, EthChainSpec},
+    tasks::TaskExecutor
+};
+use reth_network::{NetworkConfig, NetworkHandle, NetworkManager, config::rng_secret_key};
+use uniswap_v4::{checkpoint::PoolRegistryCheckpoint, fetch_angstrom_pools_from_rpc};
+use validation::validator::ValidationClient;
+
+use crate::{
+    AngstromConfig,
+    components::{
+        ChainSource, StromHandles, init_network_builder, initialize_strom_components,
+        spawn_angstrom_rpc
+    }
+};
+
+pub async fn run_sidecar<S: AngstromMetaSigner>(
+    external_rpc_url: String,
+    pool: PoolHandle,
+    executor: TaskExecutor,
+    node_set: HashSet<Address>,
+    validation_client: ValidationClient,
+    quoter_handle: QuoterHandle,
+    consensus_client: ConsensusHandler,
+    indexer: BundleIndexer,
+    rewards: RewardsLedger,
+    signer: AngstromSigner<S>,
+    args: AngstromConfig,
+    mut channels: StromHandles,
+    chain_spec: Arc<ChainSpec>
+) -> eyre::Result<()> {
+    tracing::info!(%external_rpc_url, "running as a sidecar");
+    let querying_provider: Arc<_> = ProviderBuilder::<_, _, Ethereum>::default()
+        .with_recommended_fillers()
+        .connect(&external_rpc_url)
+        .await?
+        .into();
+
+    let mut network = init_network_builder(
+        signer.clone(),
+        channels.eth_handle_rx.take().unwrap(),
+        Arc::new(RwLock::new(node_set.clone()))
+    )?;
+    let p2p = spawn_p2p_network(
+        network.build_protocol_handler(),
+        args.sidecar_p2p_port,
+        chain_spec,
+        &executor
+    )
+    .await?;
+    network = network.with_reth(p2p);
+
+    spawn_angstrom_rpc(
+        &args,
+        pool,
+        validation_client,
+        quoter_handle,
+        consensus_client.clone(),
+        indexer.clone(),
+        rewards.clone(),
+        &executor
+    )
+    .await?;
+
+    let chain = RpcChain::new(querying_provider);
+    // we can't keep running once we lose the external node
+    let exit = tokio::spawn(chain.canon_chain.clone().run());
+
+    initialize_strom_components(
+        args,
+        signer,
+        channels,
+        network,
+        chain,
+        executor,
+        async move { exit.await? },
+        node_set,
+        consensus_client,
+        indexer,
+        rewards
+    )
+    .await
+}
+
+/// starts a standalone p2p network for the angstrom protocol. The eth protocol
+/// is still spoken but nothing is synced over it.
+async fn spawn_p2p_network(
+    protocol: impl reth_network::protocol::IntoRlpxSubProtocol,
+    port: u16,
+    chain_spec: Arc<ChainSpec>,
+    executor: &TaskExecutor
+) -> eyre::Result<NetworkHandle> {
+    // peers are verified with the angstrom signer, so the p2p key doesn't need to
+    // be stable across restarts.
+    let mut network_config = NetworkConfig::builder(rng_secret_key())
+        .listener_port(port)
+        .discovery_port(port)
+        .boot_nodes(chain_spec.bootnodes().unwrap_or_default())
+        .build_with_noop_provider(chain_spec);
+    network_config.extra_protocols.push(protocol);
+
+    let network = NetworkManager::new(network_config).await?;
+    let handle = network.handle().clone();
+    executor.spawn_critical("p2p network", network);
+    tracing::info!(enode=%handle.local_node_record(), "P2P networking initialized");
+
+    Ok(handle)
+}
+
+/// A external node followed over json-rpc.
+pub struct RpcChain<P> {
+    provider:    Arc<P>,
+    canon_chain: RpcCanonChain<P>
+}
+
+impl<P: Provider + 'static> RpcChain<P> {
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { canon_chain: RpcCanonChain::new(provider.clone()), provider }
+    }
+}
+
+impl<P: Provider + 'static> ChainSource for RpcChain<P> {
+    type CanonState = RpcCanonChain<P>;
+    type Db = RpcDbWrapper<P>;
+    type Provider = P;
+
+    fn provider(&self) -> Arc<P> {
+        self.provider.clone()
+    }
+
+    fn canon_state(&self) -> RpcCanonChain<P> {
+        self.canon_chain.clone()
+    }
+
+    fn db(&self, block_number: u64) -> RpcDbWrapper<P> {
+        RpcDbWrapper::new(self.provider.clone(), block_number)
+    }
+
+    fn fetch_angstrom_pools(
+        &self,
+        checkpoint: PoolRegistryCheckpoint,
+        end_block: u64,
+        angstrom_address: Address
+    ) -> impl Future<Output = eyre::Result<PoolRegistryCheckpoint>> + Send {
+        fetch_angstrom_pools_from_rpc(
+            checkpoint,
+            end_block,
+            angstrom_address,
+            self.provider.as_ref()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{node_bindings::Anvil, primitives::U256, providers::ext::AnvilApi};
+    use reth::providers::CanonStateSubscriptions;
+    use revm::DatabaseRef;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn follows_anvil_as_a_chain_source() {
+        let anvil = Anvil::new().try_spawn().unwrap();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .connect(&anvil.ws_endpoint())
+                .await
+                .unwrap()
+        );
+        let chain = RpcChain::new(provider.clone());
+        let mut notifications = chain.canon_state().subscribe_to_canonical_state();
+        tokio::spawn(chain.canon_chain.clone().run());
+        // give the subscription time to be installed
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let user = Address::with_last_byte(0xaa);
+        provider
+            .anvil_set_balance(user, U256::from(5))
+            .await
+            .unwrap();
+        provider.anvil_mine(Some(1), None).await.unwrap();
+        let tip = notifications.recv().await.unwrap().tip().number;
+        assert_eq!(tip, provider.get_block_number().await.unwrap());
+
+        let db = chain.db(tip);
+        assert_eq!(db.basic_ref(user).unwrap().unwrap().balance, U256::from(5));
+
+        // nothing is deployed, so no pools are found but the scan still moves on
+        let checkpoint = chain
+            .fetch_angstrom_pools(
+                PoolRegistryCheckpoint::at_deploy(0),
+                tip,
+                Address::with_last_byte(1)
+            )
+            .await
+            .unwrap();
+        assert_eq!(checkpoint.block, tip);
+        assert!(checkpoint.pool_keys().is_empty());
+    }
+}
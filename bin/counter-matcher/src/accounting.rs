@@ -6,17 +6,18 @@ use alloy::{
 };
 use alloy_primitives::{Address, B256, TxKind};
 use alloy_rpc_types::TransactionRequest;
-use angstrom_types::primitive::AngstromSigner;
+use angstrom_types::{
+    contract_payloads::angstrom::OrderFill, matching::Ray, primitive::AngstromSigner
+};
 use sepolia_bundle_lander::env::ProviderType;
 
+use crate::config::RiskLimits;
+
 // fetch balances.
 alloy::sol!(
     function balanceOf(address _owner) public view returns (uint256 balance);
 );
 
-/// 20% of our total token for the account can be used.
-const MAX_AMOUNT_PER_TOKEN: f64 = 0.2;
-
 /// used to see what wallet should create the orders
 pub struct WalletAccounting {
     pub pk:              AngstromSigner<PrivateKeySigner>,
@@ -82,16 +83,39 @@ impl WalletAccounting {
             .remove(order_id);
     }
 
-    /// the order here is the counter matched order.
-    /// NOTABLY, we have a max allocation of 20% of the balance of the account
-    /// that can be swapped.
-    pub fn can_support_amount(&self, token_in: &Address, amount: u128) -> bool {
-        let funds = (self.available_funds(token_in) as f64 * MAX_AMOUNT_PER_TOKEN) as u128;
+    /// whether the wallet can place a order of `amount` without going over
+    /// its share of the balance or its open order limit.
+    pub fn can_support_amount(
+        &self,
+        token_in: &Address,
+        amount: u128,
+        limits: &RiskLimits
+    ) -> bool {
+        if self.open_orders() >= limits.max_open_orders_per_wallet {
+            return false;
+        }
+        let funds = (self.available_funds(token_in) as f64 * limits.max_wallet_fraction) as u128;
 
         funds >= amount
     }
 
-    fn available_funds(&self, token: &Address) -> u128 {
+    pub fn open_orders(&self) -> usize {
+        self.pending_adjustments.values().map(HashMap::len).sum()
+    }
+
+    /// the amount of the token in our open orders.
+    pub fn pending_amount(&self, token: &Address) -> u128 {
+        self.pending_adjustments
+            .get(token)
+            .map(|pending| pending.values().sum())
+            .unwrap_or_default()
+    }
+
+    pub fn tokens(&self) -> &[Address] {
+        &self.tokens
+    }
+
+    pub fn available_funds(&self, token: &Address) -> u128 {
         self.on_chain
             .get(token)
             .cloned()
@@ -129,3 +153,144 @@ impl WalletAccounting {
         TY::abi_decode_returns(&bytes).unwrap()
     }
 }
+
+/// The running pnl of one pair, from the fills of our orders on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PairPnl {
+    /// net amount of token0 we got (positive) or gave (negative)
+    pub delta0:  i128,
+    /// net amount of token1 we got (positive) or gave (negative)
+    pub delta1:  i128,
+    /// the gas and lp fees our fills paid, in token0
+    pub fees_t0: u128,
+    pub fills:   u64
+}
+
+impl PairPnl {
+    /// the pnl in token1, with the token0 delta valued at the price (t1 / t0).
+    pub fn marked_in_token1(&self, price: Ray) -> i128 {
+        let value0 = price.quantity(self.delta0.unsigned_abs(), false) as i128;
+        let value0 = if self.delta0.is_negative() { -value0 } else { value0 };
+
+        self.delta1.saturating_add(value0)
+    }
+}
+
+/// Profit and loss over all of our wallets, keyed by (token0, token1).
+#[derive(Debug, Default)]
+pub struct PnlLedger {
+    pairs: HashMap<(Address, Address), PairPnl>
+}
+
+impl PnlLedger {
+    pub fn record_fill(&mut self, fill: &OrderFill) {
+        let zero_for_one = fill.token_in < fill.token_out;
+        let key = if zero_for_one {
+            (fill.token_in, fill.token_out)
+        } else {
+            (fill.token_out, fill.token_in)
+        };
+
+        let pair = self.pairs.entry(key).or_default();
+        let amount_in = fill.amount_in as i128;
+        let amount_out = fill.amount_out as i128;
+        if zero_for_one {
+            pair.delta0 -= amount_in;
+            pair.delta1 += amount_out;
+        } else {
+            pair.delta1 -= amount_in;
+            pair.delta0 += amount_out;
+        }
+        pair.fees_t0 += fill.gas_fee_t0 + fill.lp_fee_t0;
+        pair.fills += 1;
+    }
+
+    pub fn pair(&self, token0: Address, token1: Address) -> PairPnl {
+        self.pairs
+            .get(&(token0, token1))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(token_in: Address, token_out: Address, amount_in: u128, amount_out: u128) -> OrderFill {
+        OrderFill {
+            order_hash: B256::ZERO,
+            user: Address::ZERO,
+            is_tob: false,
+            token_in,
+            token_out,
+            amount_in,
+            amount_out,
+            gas_fee_t0: 1,
+            lp_fee_t0: 2
+        }
+    }
+
+    fn wallet(token: Address, balance: u128) -> WalletAccounting {
+        WalletAccounting {
+            pk:                  AngstromSigner::random(),
+            tokens:              vec![token],
+            on_chain:            HashMap::from([(token, balance)]),
+            pending_adjustments: HashMap::new()
+        }
+    }
+
+    #[test]
+    fn pnl_nets_fills_of_both_directions() {
+        let token0 = Address::with_last_byte(1);
+        let token1 = Address::with_last_byte(2);
+        let mut ledger = PnlLedger::default();
+
+        // sold 100 t0 for 210 t1, then bought 50 t0 back for 100 t1
+        ledger.record_fill(&fill(token0, token1, 100, 210));
+        ledger.record_fill(&fill(token1, token0, 100, 50));
+
+        let pair = ledger.pair(token0, token1);
+        assert_eq!(pair, PairPnl { delta0: -50, delta1: 110, fees_t0: 6, fills: 2 });
+        // both directions are booked on the same pair
+        assert_eq!(ledger.pair(token1, token0), PairPnl::default());
+
+        // the 50 t0 we are short are worth 100 t1 at a price of 2
+        let price = Ray::generate_ray_decimal(2, 0);
+        assert_eq!(pair.marked_in_token1(price), 10);
+        // and we'd be at a loss if t0 went up to 3
+        assert_eq!(pair.marked_in_token1(Ray::generate_ray_decimal(3, 0)), -40);
+    }
+
+    #[test]
+    fn wallet_stays_within_its_risk_limits() {
+        let token = Address::with_last_byte(1);
+        let limits = RiskLimits {
+            max_wallet_fraction: 0.5,
+            max_open_orders_per_wallet: 2,
+            ..Default::default()
+        };
+        let mut wallet = wallet(token, 1_000);
+
+        assert!(wallet.can_support_amount(&token, 500, &limits));
+        assert!(!wallet.can_support_amount(&token, 501, &limits));
+        // nothing on chain for tokens we don't track
+        assert!(!wallet.can_support_amount(&Address::with_last_byte(2), 1, &limits));
+
+        // open orders come out of the balance before taking the fraction
+        wallet.add_order(token, B256::with_last_byte(1), 400);
+        assert_eq!(wallet.pending_amount(&token), 400);
+        assert_eq!(wallet.available_funds(&token), 600);
+        assert!(wallet.can_support_amount(&token, 300, &limits));
+        assert!(!wallet.can_support_amount(&token, 301, &limits));
+
+        // no more orders once the wallet is at its open order limit
+        wallet.add_order(token, B256::with_last_byte(2), 100);
+        assert_eq!(wallet.open_orders(), 2);
+        assert!(!wallet.can_support_amount(&token, 1, &limits));
+
+        wallet.remove_order(token, &B256::with_last_byte(1));
+        assert_eq!(wallet.pending_amount(&token), 100);
+        assert!(wallet.can_support_amount(&token, 450, &limits));
+    }
+}
@@ -0,0 +1,181 @@
+use std::{collections::HashMap, path::Path};
+
+use alloy_primitives::{Address, U256};
+use angstrom_types::primitive::PoolId;
+use serde::{Deserialize, Serialize};
+
+use crate::strategy::{CounterMatch, InventorySkew, SpreadQuoter, Strategy};
+
+/// Loaded from the toml file passed with `--config`, e.g.
+///
+/// ```toml
+/// [strategy]
+/// kind = "inventory_skew"
+/// half_spread_bps = 30
+/// order_size_bps = 500
+/// max_skew_bps = 50
+///
+/// [risk]
+/// max_wallet_fraction = 0.2
+///
+/// [risk.max_token_exposure]
+/// "0xfff9976782d46cc05630d1f6ebab18b2324d6b14" = "1000000000000000000"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterMatcherConfig {
+    pub strategy: StrategyConfig,
+    pub risk:     RiskLimits
+}
+
+impl CounterMatcherConfig {
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StrategyConfig {
+    CounterMatch(CounterMatchConfig),
+    Spread(SpreadConfig),
+    InventorySkew(InventorySkewConfig)
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self::CounterMatch(CounterMatchConfig::default())
+    }
+}
+
+impl StrategyConfig {
+    pub fn build(&self) -> Box<dyn Strategy> {
+        match self.clone() {
+            Self::CounterMatch(config) => Box::new(CounterMatch::new(config)),
+            Self::Spread(config) => Box::new(SpreadQuoter::new(config)),
+            Self::InventorySkew(config) => Box::new(InventorySkew::new(config))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterMatchConfig {
+    /// how much worse than the user's price we ask for, to give the counter
+    /// order more chances to land
+    pub price_discount_bps: u32,
+    /// how much more than the user order we offer
+    pub amount_buffer_bps:  u32
+}
+
+impl Default for CounterMatchConfig {
+    fn default() -> Self {
+        Self { price_discount_bps: 1_000, amount_buffer_bps: 1_000 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpreadConfig {
+    /// the pools to quote on, all pools if empty
+    pub pools:           Vec<PoolId>,
+    /// distance of each side from the amm price
+    pub half_spread_bps: u32,
+    /// size of each quote as a share of our inventory of the token
+    pub order_size_bps:  u32,
+    /// how far the amm price has to move before we replace our quotes
+    pub requote_bps:     u32,
+    /// how long a quote stays on the book if it isn't replaced
+    pub quote_ttl_secs:  u64
+}
+
+impl Default for SpreadConfig {
+    fn default() -> Self {
+        Self {
+            pools:           vec![],
+            half_spread_bps: 30,
+            order_size_bps:  500,
+            requote_bps:     10,
+            // 3 blocks
+            quote_ttl_secs:  36
+        }
+    }
+}
+
+impl SpreadConfig {
+    pub fn quotes_pool(&self, pool: &PoolId) -> bool {
+        self.pools.is_empty() || self.pools.contains(pool)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventorySkewConfig {
+    #[serde(flatten)]
+    pub spread:       SpreadConfig,
+    /// how far the quotes are shifted when we only hold one of the tokens
+    #[serde(default = "default_max_skew_bps")]
+    pub max_skew_bps: u32
+}
+
+fn default_max_skew_bps() -> u32 {
+    50
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    /// share of a wallet's balance of a token that can be in open orders
+    pub max_wallet_fraction:        f64,
+    pub max_open_orders_per_wallet: usize,
+    /// max amount of the token in open orders over all wallets
+    pub max_token_exposure:         HashMap<Address, U256>
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_wallet_fraction:        0.2,
+            max_open_orders_per_wallet: 32,
+            max_token_exposure:         HashMap::new()
+        }
+    }
+}
+
+impl RiskLimits {
+    /// whether the order fits the limits of the token, given the amount that
+    /// all wallets already have in open orders.
+    pub fn within_token_exposure(&self, token: &Address, open: u128, amount: u128) -> bool {
+        self.max_token_exposure
+            .get(token)
+            .is_none_or(|max| U256::from(open.saturating_add(amount)) <= *max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_exposure_is_capped_over_all_wallets() {
+        let capped = Address::with_last_byte(1);
+        let config: CounterMatcherConfig = toml::from_str(&format!(
+            r#"
+            [risk.max_token_exposure]
+            "{capped}" = "1000"
+            "#
+        ))
+        .unwrap();
+        let risk = config.risk;
+        assert_eq!(
+            risk.max_open_orders_per_wallet,
+            RiskLimits::default().max_open_orders_per_wallet
+        );
+
+        assert!(risk.within_token_exposure(&capped, 600, 400));
+        assert!(!risk.within_token_exposure(&capped, 600, 401));
+        assert!(!risk.within_token_exposure(&capped, u128::MAX, 1));
+        // tokens without a limit are only bound by the wallets
+        assert!(risk.within_token_exposure(&Address::with_last_byte(2), u128::MAX, 1));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, pin::pin, sync::Arc, time::Duration};
+use std::{collections::HashSet, path::PathBuf, pin::pin, sync::Arc, time::Duration};
 
 use accounting::WalletAccounting;
 use alloy::providers::Provider;
@@ -8,6 +8,7 @@ use angstrom_rpc::{
 };
 use angstrom_types::primitive::ANGSTROM_DOMAIN;
 use clap::Parser;
+use config::CounterMatcherConfig;
 use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use jsonrpsee::ws_client::{PingConfig, WsClientBuilder};
@@ -17,23 +18,43 @@ use sepolia_bundle_lander::{
     cli::{BundleLander, JsonPKs},
     env::BundleWashTraderEnv
 };
+use strategy::PoolInfo;
 use tracing::Level;
 use tracing_subscriber::{filter, layer::SubscriberExt, util::SubscriberInitExt};
 
 pub mod accounting;
+pub mod config;
 pub mod order_manager;
+pub mod strategy;
+
+#[derive(Debug, Clone, clap::Parser)]
+pub struct CounterMatcherCli {
+    #[clap(flatten)]
+    pub lander: BundleLander,
+    /// toml file with the strategy and risk limits. Counter matches with the
+    /// default limits if not set
+    #[clap(long)]
+    pub config: Option<PathBuf>
+}
 
 #[inline]
 pub fn run() -> eyre::Result<()> {
-    let args = BundleLander::parse();
+    let args = CounterMatcherCli::parse();
     reth::CliRunner::try_default_runtime()
         .unwrap()
         .run_command_until_exit(|ctx| start(args, ctx.task_executor))
 }
 
-pub async fn start(cfg: BundleLander, executor: TaskExecutor) -> eyre::Result<()> {
+pub async fn start(args: CounterMatcherCli, executor: TaskExecutor) -> eyre::Result<()> {
     init_tracing();
 
+    let config = args
+        .config
+        .as_ref()
+        .map(CounterMatcherConfig::load)
+        .transpose()?
+        .unwrap_or_default();
+    let cfg = args.lander;
     let keys: JsonPKs = serde_json::from_str(&std::fs::read_to_string(&cfg.secret_keys_path)?)?;
     let env = BundleWashTraderEnv::init(&cfg, keys).await?;
 
@@ -56,6 +77,14 @@ pub async fn start(cfg: BundleLander, executor: TaskExecutor) -> eyre::Result<()
                 .flat_map(|pool| [pool.token0, pool.token1])
                 .unique()
                 .collect::<Vec<_>>();
+            let pool_infos = pools
+                .iter()
+                .map(|pool| PoolInfo {
+                    id:     pool.public_address(),
+                    token0: pool.token0,
+                    token1: pool.token1
+                })
+                .collect::<Vec<_>>();
 
             let block_subscription = provider
                 .clone()
@@ -84,7 +113,15 @@ pub async fn start(cfg: BundleLander, executor: TaskExecutor) -> eyre::Result<()
                     WalletAccounting::new(bn, signer, all_tokens.clone(), provider.clone()).await
                 );
             }
-            let mut order_manager = OrderManager::new(bn, provider.clone(), wallet_acc, ws.clone());
+            let mut order_manager = OrderManager::new(
+                bn,
+                provider.clone(),
+                wallet_acc,
+                ws.clone(),
+                config.strategy.build(),
+                config.risk,
+                pool_infos.clone()
+            );
 
             let mut filters = HashSet::new();
             let mut subscriptions = HashSet::new();
@@ -100,6 +137,12 @@ pub async fn start(cfg: BundleLander, executor: TaskExecutor) -> eyre::Result<()
                 .await
                 .unwrap()
                 .into_stream();
+            // the amm prices are used for quoting and to mark our pnl
+            let mut amm_sub = ws
+                .subscribe_amm(pool_infos.iter().map(|pool| pool.id).collect())
+                .await
+                .unwrap()
+                .into_stream();
             loop {
                 tokio::select! {
                     _ = &mut signal => {
@@ -109,6 +152,9 @@ pub async fn start(cfg: BundleLander, executor: TaskExecutor) -> eyre::Result<()
                      Some(Ok(event)) = sub.next() => {
                          order_manager.handle_event(event).await;
                     }
+                     Some(Ok(update)) = amm_sub.next() => {
+                         order_manager.on_amm_update(update).await;
+                     }
                      Some(Ok(Some(block))) = block_sub.next() => {
                          tracing::info!("new block");
                          order_manager.new_block(block.header.number).await;
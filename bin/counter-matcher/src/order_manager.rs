@@ -1,31 +1,46 @@
 use std::{collections::HashMap, sync::Arc};
 
 use alloy_primitives::B256;
+use angstrom_amm_quoter::Slot0Update;
+use angstrom_client::{UserOrderBuilder, price};
 use angstrom_rpc::{api::OrderApiClient, types::OrderSubscriptionResult};
 use angstrom_types::{
-    matching::Ray,
+    contract_payloads::angstrom::FillReceipt,
+    matching::{Ray, SqrtPriceX96},
     orders::CancelOrderRequest,
+    primitive::{ANGSTROM_DOMAIN, PoolId},
     sol_bindings::{RawPoolOrder, grouped_orders::AllOrders}
 };
 use itertools::Itertools;
 use jsonrpsee::ws_client::WsClient;
 use secp256k1::rand;
 use sepolia_bundle_lander::env::ProviderType;
-use testing_tools::type_generator::orders::UserOrderBuilder;
 
-use crate::accounting::WalletAccounting;
+use crate::{
+    accounting::{PnlLedger, WalletAccounting},
+    config::RiskLimits,
+    strategy::{Inventory, OrderIntent, PoolInfo, Strategy}
+};
 
 /// holds orders that are currently being processed.
 pub struct OrderManager {
     block_number: u64,
     wallets:      Vec<WalletAccounting>,
     provider:     Arc<ProviderType>,
+    strategy:     Box<dyn Strategy>,
+    risk:         RiskLimits,
+    pools:        HashMap<PoolId, PoolInfo>,
+    /// the last amm price (t1 / t0) of each pool
+    prices:       HashMap<PoolId, Ray>,
+    pnl:          PnlLedger,
 
     /// our active orders to the wallet that signed them.
     /// due to how we do accounting, we don't have to store the order
     active_orders: HashMap<B256, (usize, AllOrders)>,
     /// map of user order to our active counter order
     user_orders:   HashMap<B256, B256>,
+    /// our quotes on each pool
+    quotes:        HashMap<PoolId, Vec<B256>>,
     client:        Arc<WsClient>
 }
 
@@ -34,24 +49,35 @@ impl OrderManager {
         block_number: u64,
         provider: Arc<ProviderType>,
         wallets: Vec<WalletAccounting>,
-        client: Arc<WsClient>
+        client: Arc<WsClient>,
+        strategy: Box<dyn Strategy>,
+        risk: RiskLimits,
+        pools: Vec<PoolInfo>
     ) -> Self {
+        tracing::info!(strategy = strategy.name(), "starting order manager");
         Self {
             block_number,
             provider,
             wallets,
             client,
+            strategy,
+            risk,
+            pools: pools.into_iter().map(|pool| (pool.id, pool)).collect(),
+            prices: Default::default(),
+            pnl: Default::default(),
             active_orders: Default::default(),
-            user_orders: Default::default()
+            user_orders: Default::default(),
+            quotes: Default::default()
         }
     }
 
     pub async fn shutdown(mut self) {
         tracing::info!("canceling all orders");
-        let keys = self.user_orders.keys().cloned().collect_vec();
+        let keys = self.active_orders.keys().cloned().collect_vec();
         for hash in keys {
-            self.on_order_cancel(hash).await;
+            self.cancel_our_order(hash).await;
         }
+        self.log_pnl();
     }
 
     pub async fn new_block(&mut self, block_number: u64) {
@@ -61,6 +87,7 @@ impl OrderManager {
                 .await;
         }
         self.block_number = block_number;
+        self.log_pnl();
     }
 
     pub async fn handle_event(&mut self, event: OrderSubscriptionResult) {
@@ -69,7 +96,9 @@ impl OrderManager {
             OrderSubscriptionResult::NewOrder(order) => {
                 self.on_new_order(order).await;
             }
-            OrderSubscriptionResult::FilledOrder(_, order) => self.on_filled_order(order).await,
+            OrderSubscriptionResult::FilledOrder(_, order, receipt) => {
+                self.on_filled_order(order, receipt).await
+            }
             OrderSubscriptionResult::ExpiredOrder(hash) => {
                 self.on_expired_order(hash.order_hash());
             }
@@ -78,6 +107,39 @@ impl OrderManager {
         }
     }
 
+    /// gives the strategy the new price of the pool and replaces our quotes on
+    /// it if the strategy wants to.
+    pub async fn on_amm_update(&mut self, update: Slot0Update) {
+        let Some(pool) = self.pools.get(&update.angstrom_pool_id).copied() else { return };
+        let price = Ray::from(SqrtPriceX96::from(update.sqrt_price_x96));
+        self.prices.insert(pool.id, price);
+
+        let inventory = self.inventory();
+        let has_quotes = self
+            .quotes
+            .get(&pool.id)
+            .is_some_and(|quotes| !quotes.is_empty());
+        let Some(intents) = self
+            .strategy
+            .on_amm_update(&pool, price, &inventory, has_quotes)
+        else {
+            return;
+        };
+
+        for hash in self.quotes.remove(&pool.id).unwrap_or_default() {
+            self.cancel_our_order(hash).await;
+        }
+
+        let mut placed = Vec::with_capacity(intents.len());
+        for intent in intents {
+            if let Some(hash) = self.place_intent(intent).await {
+                placed.push(hash);
+            }
+        }
+        tracing::info!(pool = ?pool.id, ?price, quotes = placed.len(), "requoted pool");
+        self.quotes.insert(pool.id, placed);
+    }
+
     /// handles when a new order is pulled from the stream.
     pub async fn on_new_order(&mut self, order: AllOrders) {
         let hash = order.order_hash();
@@ -86,7 +148,11 @@ impl OrderManager {
             return;
         }
 
-        self.try_create_counter_order(&order).await;
+        let Some(intent) = self.strategy.on_user_order(&order) else { return };
+        if let Some(our_order_hash) = self.place_intent(intent).await {
+            tracing::info!(?our_order_hash, user_hash = ?hash, "placed counter order");
+            self.user_orders.insert(hash, our_order_hash);
+        }
     }
 
     pub fn on_expired_order(&mut self, hash: B256) {
@@ -96,6 +162,7 @@ impl OrderManager {
             tracing::info!(?hash, "our order expired");
             let wallet = &mut self.wallets[index];
             wallet.remove_order(order.token_in(), &hash);
+            self.remove_quote(&hash);
             return;
         }
 
@@ -104,7 +171,7 @@ impl OrderManager {
     }
 
     /// if one of our order is filled. we will remove the pending allocation
-    pub async fn on_filled_order(&mut self, order: AllOrders) {
+    pub async fn on_filled_order(&mut self, order: AllOrders, receipt: Option<FillReceipt>) {
         let hash = order.order_hash();
         if order.is_tob() {
             return;
@@ -117,11 +184,21 @@ impl OrderManager {
             tracing::info!(?hash, "our order filled");
             let wallet = &mut self.wallets[index];
             wallet.remove_order(order.token_in(), &hash);
+            self.remove_quote(&hash);
+
+            match receipt {
+                Some(receipt) => self.pnl.record_fill(&receipt.fill),
+                None => tracing::warn!(?hash, "fill without receipt, not in pnl")
+            }
         }
     }
 
     pub async fn on_order_cancel(&mut self, hash: B256) {
         let Some(our_hash) = self.user_orders.remove(&hash) else { return };
+        self.cancel_our_order(our_hash).await;
+    }
+
+    async fn cancel_our_order(&mut self, our_hash: B256) {
         let Some((wallet, order)) = self.active_orders.remove(&our_hash) else { return };
 
         let order_wallet = &mut self.wallets[wallet];
@@ -138,53 +215,67 @@ impl OrderManager {
         order_wallet.remove_order(order.token_in(), &our_hash);
     }
 
-    // this order will be partial
-    async fn try_create_counter_order(&mut self, placed_user_order: &AllOrders) {
-        // calculate the flipped order token and amounts
-        let price_1_0 = if placed_user_order.is_bid() {
-            Ray::from(placed_user_order.limit_price()).inv_ray_round(true)
-        } else {
-            Ray::from(placed_user_order.limit_price())
-        };
+    fn remove_quote(&mut self, hash: &B256) {
+        self.quotes
+            .values_mut()
+            .for_each(|quotes| quotes.retain(|quote| quote != hash));
+    }
 
-        let (token_in, token_out, mut amount_needed, mut price) = if !placed_user_order.exact_in() {
-            (
-                placed_user_order.token_out(),
-                placed_user_order.token_in(),
-                placed_user_order.amount(),
-                Ray::from(placed_user_order.limit_price())
-                    .inv_ray_round(!placed_user_order.is_bid())
-            )
-        } else {
-            // is exact in. need to get amount out
-
-            let amount = if placed_user_order.is_bid() {
-                // one for zero
-                price_1_0.inverse_quantity(placed_user_order.amount(), true)
-            } else {
-                // zero for 1
-                price_1_0.quantity(placed_user_order.amount(), false)
-            };
-
-            (
-                placed_user_order.token_out(),
-                placed_user_order.token_in(),
-                amount,
-                // always other side.
-                Ray::from(placed_user_order.limit_price())
-                    .inv_ray_round(!placed_user_order.is_bid())
-            )
-        };
-        // lower price to give more landing ops
-        let pct = Ray::generate_ray_decimal(90, 2);
-        price.mul_ray_assign(pct);
+    /// the funds of all wallets that aren't in open orders.
+    fn inventory(&self) -> Inventory {
+        let mut inventory = Inventory::default();
+        for wallet in &self.wallets {
+            for token in wallet.tokens() {
+                *inventory.0.entry(*token).or_default() += wallet.available_funds(token);
+            }
+        }
+
+        inventory
+    }
 
-        // see if there is any wallet that can supply these amounts
+    fn log_pnl(&self) {
+        for pool in self.pools.values() {
+            let pnl = self.pnl.pair(pool.token0, pool.token1);
+            if pnl.fills == 0 {
+                continue;
+            }
+            let marked = self
+                .prices
+                .get(&pool.id)
+                .map(|price| pnl.marked_in_token1(*price));
+
+            tracing::info!(
+                block = self.block_number,
+                pool = ?pool.id,
+                delta0 = pnl.delta0,
+                delta1 = pnl.delta1,
+                fees_t0 = pnl.fees_t0,
+                fills = pnl.fills,
+                ?marked,
+                "pnl"
+            );
+        }
+    }
 
-        // will add an extra 10% to the amount
-        amount_needed = (amount_needed as f64 * 1.1) as u128;
-        // see if there is any wallet that can supply these amounts
-        // will add an extra 5% to the amount
+    /// signs the intent with a wallet that has the funds for it and sends it.
+    /// Returns the hash of the placed order.
+    async fn place_intent(&mut self, intent: OrderIntent) -> Option<B256> {
+        let OrderIntent { token_in, token_out, amount_in, min_price, deadline } = intent;
+
+        let open = self
+            .wallets
+            .iter()
+            .map(|wallet| wallet.pending_amount(&token_in))
+            .sum();
+        if !self.risk.within_token_exposure(&token_in, open, amount_in) {
+            tracing::info!(
+                ?token_in,
+                amount_in,
+                open,
+                "order would go over the token exposure limit"
+            );
+            return None;
+        }
 
         let Some((wallet_index, wallet)) = self
             .wallets
@@ -193,65 +284,53 @@ impl OrderManager {
             // we randomize the order here so that we don't always use the same wallet, yet we will
             // check all of them
             .sorted_by_key(|(..)| rand::random::<usize>())
-            .find(|(_, wallet)| wallet.can_support_amount(&token_in, amount_needed))
+            .find(|(_, wallet)| wallet.can_support_amount(&token_in, amount_in, &self.risk))
         else {
-            tracing::info!(?placed_user_order, "no wallet has enough to support user order");
-            return;
+            tracing::info!(?token_in, amount_in, "no wallet has enough to support order");
+            return None;
         };
 
+        // partials fill at least their fee, a fifth of the amount.
+        let zfo = price::zero_for_one(token_in, token_out);
+        let max_fee = price::input_as_asset0(amount_in, zfo, min_price) / 5;
+        let builder = UserOrderBuilder::new()
+            .partial(amount_in)
+            .assets(token_in, token_out)
+            .min_price(min_price)
+            .max_extra_fee_asset0(max_fee)
+            .recipient(wallet.pk.address());
+
         // order with deadline and nonce
-        let order: AllOrders = if let Some(deadline) = placed_user_order.deadline() {
+        let builder = if let Some(deadline) = deadline {
             let nonce = self.client.valid_nonce(wallet.pk.address()).await.unwrap();
-            UserOrderBuilder::default()
-                .partial()
-                .standing()
-                .nonce(nonce)
-                .deadline(deadline)
-                .exact_in(true)
-                .amount(amount_needed)
-                .asset_in(token_in)
-                .asset_out(token_out)
-                .min_price(price)
-                .recipient(wallet.pk.address())
-                .signing_key(Some(wallet.pk.clone()))
-                .build()
+            builder.standing(nonce, deadline.saturating_to())
         } else {
-            UserOrderBuilder::default()
-                .partial()
-                .kill_or_fill()
-                .exact_in(true)
-                .amount(amount_needed)
-                .asset_in(token_in)
-                .asset_out(token_out)
-                .min_price(price)
-                .recipient(wallet.pk.address())
-                .signing_key(Some(wallet.pk.clone()))
-                .build()
+            builder.flash(self.block_number + 1)
+        };
+        let order = match builder.sign_sync(ANGSTROM_DOMAIN.get().unwrap(), &*wallet.pk) {
+            Ok(order) => order,
+            Err(e) => {
+                tracing::error!(%e, "failed to build order");
+                return None;
+            }
         };
 
         let our_order_hash = order.order_hash();
-
-        self.user_orders
-            .insert(placed_user_order.order_hash(), our_order_hash);
         self.active_orders
             .insert(our_order_hash, (wallet_index, order.clone()));
 
         let res = self.client.send_order(order).await.unwrap();
 
         if !res.is_success {
-            tracing::error!(?res, "failed to place counter order");
+            tracing::error!(?res, "failed to place order");
 
             self.active_orders.remove(&our_order_hash);
-            self.user_orders.remove(&placed_user_order.order_hash());
-            return;
+            return None;
         }
-        tracing::info!(
-            ?our_order_hash,
-            user_hash = ?placed_user_order.order_hash(),
-            "placed counter order"
-        );
 
         // add order to wallet accounting
-        wallet.add_order(token_in, our_order_hash, amount_needed);
+        wallet.add_order(token_in, our_order_hash, amount_in);
+
+        Some(our_order_hash)
     }
 }
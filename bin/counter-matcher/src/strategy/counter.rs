@@ -0,0 +1,61 @@
+use angstrom_types::{
+    matching::Ray,
+    sol_bindings::{RawPoolOrder, grouped_orders::AllOrders}
+};
+
+use super::{OrderIntent, Strategy, shift_price};
+use crate::config::CounterMatchConfig;
+
+/// Mirrors every user order with a counter order on the other side of it.
+#[derive(Debug, Clone)]
+pub struct CounterMatch {
+    config: CounterMatchConfig
+}
+
+impl CounterMatch {
+    pub fn new(config: CounterMatchConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Strategy for CounterMatch {
+    fn name(&self) -> &'static str {
+        "counter_match"
+    }
+
+    fn on_user_order(&mut self, placed_user_order: &AllOrders) -> Option<OrderIntent> {
+        // calculate the flipped order token and amounts
+        let price_1_0 = if placed_user_order.is_bid() {
+            Ray::from(placed_user_order.limit_price()).inv_ray_round(true)
+        } else {
+            Ray::from(placed_user_order.limit_price())
+        };
+
+        let amount_needed = if !placed_user_order.exact_in() {
+            placed_user_order.amount()
+        } else if placed_user_order.is_bid() {
+            // is exact in. need to get amount out, one for zero
+            price_1_0.inverse_quantity(placed_user_order.amount(), true)
+        } else {
+            // zero for 1
+            price_1_0.quantity(placed_user_order.amount(), false)
+        };
+
+        // always other side.
+        let price =
+            Ray::from(placed_user_order.limit_price()).inv_ray_round(!placed_user_order.is_bid());
+        // lower price to give more landing ops
+        let price = shift_price(price, -(self.config.price_discount_bps as i64));
+        // add some extra to the amount
+        let amount_in =
+            amount_needed.saturating_mul(10_000 + self.config.amount_buffer_bps as u128) / 10_000;
+
+        Some(OrderIntent {
+            token_in: placed_user_order.token_out(),
+            token_out: placed_user_order.token_in(),
+            amount_in,
+            min_price: price,
+            deadline: placed_user_order.deadline()
+        })
+    }
+}
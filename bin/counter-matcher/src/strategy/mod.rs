@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{Address, U256};
+use angstrom_types::{matching::Ray, primitive::PoolId, sol_bindings::grouped_orders::AllOrders};
+
+mod counter;
+mod spread;
+
+pub use counter::*;
+pub use spread::*;
+
+/// A pool the bot trades on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolInfo {
+    pub id:     PoolId,
+    pub token0: Address,
+    pub token1: Address
+}
+
+/// The funds of all our wallets that aren't tied up in orders.
+#[derive(Debug, Clone, Default)]
+pub struct Inventory(pub HashMap<Address, u128>);
+
+impl Inventory {
+    pub fn balance(&self, token: &Address) -> u128 {
+        self.0.get(token).copied().unwrap_or_default()
+    }
+}
+
+/// A order a strategy wants placed. The order manager picks the wallet that
+/// signs it and checks it against the risk limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderIntent {
+    pub token_in:  Address,
+    pub token_out: Address,
+    pub amount_in: u128,
+    /// the min amount of token out we want per token in
+    pub min_price: Ray,
+    /// standing orders live until their deadline, orders without one are kill
+    /// or fill orders for the next block
+    pub deadline:  Option<U256>
+}
+
+pub trait Strategy: Send {
+    fn name(&self) -> &'static str;
+
+    /// a counter order for a order someone else placed. The counter order is
+    /// cancelled together with the user order.
+    fn on_user_order(&mut self, _order: &AllOrders) -> Option<OrderIntent> {
+        None
+    }
+
+    /// the quotes for the pool after its amm price (t1 / t0) moved. `Some`
+    /// replaces all of our quotes on the pool, an empty vec pulls them.
+    fn on_amm_update(
+        &mut self,
+        _pool: &PoolInfo,
+        _price: Ray,
+        _inventory: &Inventory,
+        _has_quotes: bool
+    ) -> Option<Vec<OrderIntent>> {
+        None
+    }
+}
+
+/// multiplies the price by `1 + bps / 10_000`.
+pub(crate) fn shift_price(price: Ray, bps: i64) -> Ray {
+    let factor = (10_000 + bps).max(1) as u128;
+    price.mul_ray(Ray::generate_ray_decimal(factor, 4))
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+
+use alloy_primitives::U256;
+use angstrom_types::{matching::Ray, primitive::PoolId};
+
+use super::{Inventory, OrderIntent, PoolInfo, Strategy, shift_price};
+use crate::config::{InventorySkewConfig, SpreadConfig};
+
+/// Quotes both sides of the pool around the amm price.
+#[derive(Debug, Clone)]
+pub struct SpreadQuoter {
+    config:    SpreadConfig,
+    last_mids: HashMap<PoolId, Ray>
+}
+
+impl SpreadQuoter {
+    pub fn new(config: SpreadConfig) -> Self {
+        Self { config, last_mids: HashMap::new() }
+    }
+
+    /// only requote once the price moved far enough away from our last quotes.
+    fn should_requote(&mut self, pool: &PoolInfo, price: Ray, has_quotes: bool) -> bool {
+        if !self.config.quotes_pool(&pool.id) {
+            return false;
+        }
+
+        let moved = self.last_mids.get(&pool.id).is_none_or(|last| {
+            let last = last.as_f64();
+            ((price.as_f64() - last) / last).abs() * 10_000.0 >= self.config.requote_bps as f64
+        });
+        if !moved && has_quotes {
+            return false;
+        }
+        self.last_mids.insert(pool.id, price);
+
+        true
+    }
+}
+
+impl Strategy for SpreadQuoter {
+    fn name(&self) -> &'static str {
+        "spread"
+    }
+
+    fn on_amm_update(
+        &mut self,
+        pool: &PoolInfo,
+        price: Ray,
+        inventory: &Inventory,
+        has_quotes: bool
+    ) -> Option<Vec<OrderIntent>> {
+        self.should_requote(pool, price, has_quotes)
+            .then(|| two_sided_quotes(&self.config, pool, price, inventory, 0.0))
+    }
+}
+
+/// Quotes both sides of the pool around the amm price, shifted against our
+/// inventory. When we hold more of a token than of the other, we quote it
+/// cheaper and in larger size so that fills bring us back to balance.
+#[derive(Debug, Clone)]
+pub struct InventorySkew {
+    quoter:       SpreadQuoter,
+    max_skew_bps: u32
+}
+
+impl InventorySkew {
+    pub fn new(config: InventorySkewConfig) -> Self {
+        Self { quoter: SpreadQuoter::new(config.spread), max_skew_bps: config.max_skew_bps }
+    }
+}
+
+impl Strategy for InventorySkew {
+    fn name(&self) -> &'static str {
+        "inventory_skew"
+    }
+
+    fn on_amm_update(
+        &mut self,
+        pool: &PoolInfo,
+        price: Ray,
+        inventory: &Inventory,
+        has_quotes: bool
+    ) -> Option<Vec<OrderIntent>> {
+        if !self.quoter.should_requote(pool, price, has_quotes) {
+            return None;
+        }
+
+        let imbalance = inventory_imbalance(pool, price, inventory);
+        let skewed = shift_price(price, -(imbalance * self.max_skew_bps as f64) as i64);
+
+        Some(two_sided_quotes(&self.quoter.config, pool, skewed, inventory, imbalance))
+    }
+}
+
+/// how much more of token0 than token1 we hold, valued at the pool price.
+/// 1 if we only hold token0, -1 if we only hold token1.
+pub fn inventory_imbalance(pool: &PoolInfo, price: Ray, inventory: &Inventory) -> f64 {
+    let value0 = price.quantity(inventory.balance(&pool.token0), false) as f64;
+    let value1 = inventory.balance(&pool.token1) as f64;
+    if value0 + value1 == 0.0 {
+        return 0.0;
+    }
+
+    (value0 - value1) / (value0 + value1)
+}
+
+/// an ask selling token0 above the price and a bid buying token0 below it.
+/// a positive imbalance grows the ask and shrinks the bid.
+fn two_sided_quotes(
+    config: &SpreadConfig,
+    pool: &PoolInfo,
+    price: Ray,
+    inventory: &Inventory,
+    imbalance: f64
+) -> Vec<OrderIntent> {
+    let deadline = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+        + Duration::from_secs(config.quote_ttl_secs))
+    .as_secs();
+    let size = |token, side_weight: f64| {
+        let base = inventory.balance(token) as f64 * config.order_size_bps as f64 / 10_000.0;
+        (base * side_weight) as u128
+    };
+
+    let ask = OrderIntent {
+        token_in:  pool.token0,
+        token_out: pool.token1,
+        amount_in: size(&pool.token0, 1.0 + imbalance),
+        // t1 per t0
+        min_price: shift_price(price, config.half_spread_bps as i64),
+        deadline:  Some(U256::from(deadline))
+    };
+    let bid = OrderIntent {
+        token_in:  pool.token1,
+        token_out: pool.token0,
+        amount_in: size(&pool.token1, 1.0 - imbalance),
+        // t0 per t1
+        min_price: shift_price(price, -(config.half_spread_bps as i64)).inv_ray_round(true),
+        deadline:  Some(U256::from(deadline))
+    };
+
+    [ask, bid]
+        .into_iter()
+        .filter(|intent| intent.amount_in > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::Address;
+
+    use super::*;
+
+    fn pool() -> PoolInfo {
+        PoolInfo {
+            id:     PoolId::ZERO,
+            token0: Address::with_last_byte(1),
+            token1: Address::with_last_byte(2)
+        }
+    }
+
+    #[test]
+    fn skew_sells_the_token_we_hold_too_much_of() {
+        let pool = pool();
+        let price = Ray::generate_ray_decimal(2, 0);
+        let inventory =
+            Inventory(HashMap::from([(pool.token0, 3_000_000), (pool.token1, 2_000_000)]));
+
+        // 6m vs 2m in token1 terms
+        let imbalance = inventory_imbalance(&pool, price, &inventory);
+        assert_eq!(imbalance, 0.5);
+
+        let mut strategy = InventorySkew::new(InventorySkewConfig {
+            spread:       SpreadConfig::default(),
+            max_skew_bps: 100
+        });
+        let quotes = strategy
+            .on_amm_update(&pool, price, &inventory, false)
+            .unwrap();
+        let ask = quotes.iter().find(|q| q.token_in == pool.token0).unwrap();
+        let bid = quotes.iter().find(|q| q.token_in == pool.token1).unwrap();
+
+        assert!(ask.amount_in > bid.amount_in);
+        // the ask is below what the unskewed quoter would ask for
+        let unskewed = shift_price(price, SpreadConfig::default().half_spread_bps as i64);
+        assert!(ask.min_price < unskewed);
+
+        // the price didn't move, so no requote
+        assert!(
+            strategy
+                .on_amm_update(&pool, price, &inventory, true)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, path::Path};
+
+use alloy::hex;
+use alloy_primitives::Address;
+use angstrom_types::{
+    contract_bindings::angstrom::Angstrom::PoolKey,
+    contract_payloads::angstrom::AngstromPoolConfigStore,
+    primitive::PoolId,
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder},
+    uni_structure::BaselinePoolState
+};
+use eyre::{Context, ContextCompat};
+use matching_engine::book::BookOrder;
+use telemetry::blocklog::BlockLog;
+
+/// prefix the matching engine logs the hex encoded proposal snapshot with.
+const BAD_BUNDLE_PREFIX: &str = "bad_bundle=";
+
+pub type PoolSnapshots = HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>;
+
+/// everything the matching engine is handed when it builds a proposal.
+#[derive(Debug, Clone)]
+pub struct SolverInput {
+    pub limit:          Vec<BookOrder>,
+    pub searcher:       Vec<OrderWithStorageData<TopOfBlockOrder>>,
+    pub pool_snapshots: PoolSnapshots
+}
+
+impl SolverInput {
+    /// loads the hex encoded snapshot that is logged as `bad_bundle` when a
+    /// bundle fails to simulate. The log line prefix can be left in.
+    pub fn from_bad_bundle(path: &Path) -> eyre::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read bad bundle file {path:?}"))?;
+        let raw = raw.trim();
+        let raw = raw
+            .find(BAD_BUNDLE_PREFIX)
+            .map(|start| &raw[start + BAD_BUNDLE_PREFIX.len()..])
+            .unwrap_or(raw)
+            .trim_matches('"');
+
+        let bytes = hex::decode(raw).wrap_err("bad bundle is not valid hex")?;
+        let (limit, searcher, pool_snapshots) = serde_json::from_slice(&bytes)
+            .wrap_err("bad bundle is not a matching engine snapshot")?;
+
+        Ok(Self { limit, searcher, pool_snapshots })
+    }
+
+    /// loads the pools and orders out of a block log. The logged pool keys
+    /// aren't in store order, so the store index of each pool is taken from
+    /// the config store of the block.
+    pub fn from_block_log(
+        log: BlockLog,
+        config_store: &AngstromPoolConfigStore
+    ) -> eyre::Result<Self> {
+        let snapshots = log
+            .pool_snapshots
+            .wrap_err("block log has no pool snapshots")?;
+        let pool_keys = log.pool_keys.wrap_err("block log has no pool keys")?;
+        let orders = log
+            .order_pool_snapshot
+            .wrap_err("block log has no order pool snapshot")?
+            .order_storage
+            .get_all_orders();
+
+        Ok(Self {
+            limit:          orders.limit,
+            searcher:       orders.searcher,
+            pool_snapshots: pool_snapshots(&pool_keys, &snapshots, config_store)
+        })
+    }
+
+    /// drops everything that isn't for the given pool.
+    pub fn retain_pool(&mut self, pool: PoolId) {
+        self.limit.retain(|order| order.pool_id == pool);
+        self.searcher.retain(|order| order.pool_id == pool);
+        self.pool_snapshots.retain(|id, _| *id == pool);
+    }
+}
+
+/// reads a deflated base64 block log.
+pub fn read_block_log(path: &Path) -> eyre::Result<BlockLog> {
+    let raw =
+        std::fs::read(path).wrap_err_with(|| format!("could not read block log file {path:?}"))?;
+
+    Ok(BlockLog::from_deflate_base64(raw.trim_ascii()))
+}
+
+/// pairs the snapshot of every pool with its tokens and store index. Pools
+/// that aren't in the config store are skipped, the node can't build a
+/// bundle for them either.
+fn pool_snapshots(
+    pool_keys: &[PoolKey],
+    snapshots: &HashMap<PoolId, BaselinePoolState>,
+    config_store: &AngstromPoolConfigStore
+) -> PoolSnapshots {
+    pool_keys
+        .iter()
+        .filter_map(|key| {
+            let id = PoolId::from(key);
+            let snapshot = snapshots.get(&id)?.clone();
+            let Some(entry) = config_store.get_entry(key.currency0, key.currency1) else {
+                tracing::warn!(?id, "pool is not in the config store, skipping it");
+                return None;
+            };
+
+            Some((id, (key.currency0, key.currency1, snapshot, entry.store_index as u16)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::{
+        contract_payloads::angstrom::AngPoolConfigEntry, matching::SqrtPriceX96,
+        uni_structure::liquidity_base::BaselineLiquidity
+    };
+
+    use super::*;
+
+    fn snapshot() -> BaselinePoolState {
+        BaselinePoolState::new(
+            BaselineLiquidity::new(
+                10,
+                0,
+                SqrtPriceX96::at_tick(0).unwrap(),
+                0,
+                HashMap::new(),
+                HashMap::new()
+            ),
+            1,
+            0
+        )
+    }
+
+    fn pool_key(token: u8) -> PoolKey {
+        PoolKey {
+            currency0: Address::with_last_byte(token),
+            currency1: Address::with_last_byte(token + 1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn store_index_comes_from_the_config_store() {
+        let keys = [pool_key(1), pool_key(3), pool_key(5)];
+        let snapshots = keys
+            .iter()
+            .map(|key| (PoolId::from(key), snapshot()))
+            .collect::<HashMap<_, _>>();
+
+        // the store has the pools in the reverse order of the log and doesn't
+        // know the last one
+        let config_store = AngstromPoolConfigStore::default();
+        for (store_index, key) in keys[..2].iter().rev().enumerate() {
+            config_store.new_pool(
+                key.currency0,
+                key.currency1,
+                AngPoolConfigEntry {
+                    pool_partial_key: AngstromPoolConfigStore::derive_store_key(
+                        key.currency0,
+                        key.currency1
+                    ),
+                    tick_spacing: 10,
+                    fee_in_e6: 0,
+                    store_index
+                }
+            );
+        }
+
+        let pools = pool_snapshots(&keys, &snapshots, &config_store);
+        let store_index = |key: &PoolKey| pools.get(&PoolId::from(key)).map(|pool| pool.3);
+
+        assert_eq!(pools.len(), 2);
+        assert_eq!(store_index(&keys[0]), Some(1));
+        assert_eq!(store_index(&keys[1]), Some(0));
+        assert_eq!(store_index(&keys[2]), None);
+    }
+
+    #[test]
+    fn bad_bundle_is_read_with_the_log_prefix() {
+        let snapshot: (Vec<BookOrder>, Vec<OrderWithStorageData<TopOfBlockOrder>>, PoolSnapshots) = (
+            vec![],
+            vec![],
+            HashMap::from([(
+                PoolId::repeat_byte(1),
+                (Address::with_last_byte(1), Address::with_last_byte(2), snapshot(), 3)
+            )])
+        );
+        let encoded = hex::encode(serde_json::to_vec(&snapshot).unwrap());
+
+        let path = std::env::temp_dir().join(format!("bad_bundle_{}", std::process::id()));
+        std::fs::write(&path, format!("2025-01-01 ERROR {BAD_BUNDLE_PREFIX}\"{encoded}\"\n"))
+            .unwrap();
+        let input = SolverInput::from_bad_bundle(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let input = input.unwrap();
+        assert!(input.limit.is_empty() && input.searcher.is_empty());
+        assert_eq!(input.pool_snapshots[&PoolId::repeat_byte(1)].3, 3);
+    }
+}
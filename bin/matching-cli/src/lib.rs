@@ -0,0 +1,209 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant}
+};
+
+use alloy::{eips::BlockId, providers::ProviderBuilder};
+use alloy_primitives::Address;
+use angstrom_types::{
+    contract_payloads::angstrom::{AngstromBundle, AngstromPoolConfigStore},
+    primitive::{ANGSTROM_ADDRESS, PoolId, try_init_with_chain_id}
+};
+use clap::Parser;
+use eyre::ContextCompat;
+use input::{SolverInput, read_block_log};
+use matching_engine::{
+    manager::{build_books, searcher_orders_by_pool},
+    solver::{SolverConfig, SolverPool}
+};
+use report::{PoolReport, SolverReport};
+use simulate::{ForkConfig, simulate_bundle};
+use tracing::Level;
+use tracing_subscriber::{filter, layer::SubscriberExt, util::SubscriberInitExt};
+
+pub mod input;
+pub mod report;
+pub mod simulate;
+
+/// Re-runs the matching engine over a captured set of pools and orders.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct MatchingCli {
+    /// file with the hex encoded `bad_bundle` snapshot logged by the matching
+    /// engine
+    #[clap(long, conflicts_with = "block_log", required_unless_present = "block_log")]
+    pub bad_bundle:       Option<PathBuf>,
+    /// file with a deflated base64 block log
+    #[clap(long)]
+    pub block_log:        Option<PathBuf>,
+    /// endpoint the pool config store of a block log's block is read from,
+    /// defaults to the fork url
+    #[clap(long)]
+    pub rpc_url:          Option<String>,
+    /// only solve this pool
+    #[clap(long)]
+    pub pool:             Option<PoolId>,
+    /// how long each pool has to solve before it falls back to only its top
+    /// of block order. Pools aren't limited if not set
+    #[clap(long)]
+    pub solve_budget_ms:  Option<u64>,
+    #[clap(long, default_value_t = 1)]
+    pub chain_id:         u64,
+    /// simulates the bundle on an anvil fork of this endpoint
+    #[clap(long, requires = "node_address")]
+    pub fork_url:         Option<String>,
+    /// block to fork at, defaults to the latest
+    #[clap(long)]
+    pub fork_block:       Option<u64>,
+    /// defaults to the angstrom deployment on the chain
+    #[clap(long)]
+    pub angstrom_address: Option<Address>,
+    /// angstrom node the bundle is executed from
+    #[clap(long)]
+    pub node_address:     Option<Address>
+}
+
+impl MatchingCli {
+    pub async fn load_input(&self) -> eyre::Result<SolverInput> {
+        let mut input = match (&self.bad_bundle, &self.block_log) {
+            (Some(path), _) => SolverInput::from_bad_bundle(path)?,
+            (None, Some(path)) => {
+                let log = read_block_log(path)?;
+                let config_store = self.pool_config_store(log.blocknum).await?;
+                SolverInput::from_block_log(log, &config_store)?
+            }
+            (None, None) => eyre::bail!("one of --bad-bundle or --block-log is required")
+        };
+        if let Some(pool) = self.pool {
+            input.retain_pool(pool);
+        }
+
+        Ok(input)
+    }
+
+    /// the pool config store of the angstrom contract at the block.
+    async fn pool_config_store(&self, block: u64) -> eyre::Result<AngstromPoolConfigStore> {
+        let rpc_url = self
+            .rpc_url
+            .as_ref()
+            .or(self.fork_url.as_ref())
+            .wrap_err("--rpc-url is required to read the store indices of a block log")?;
+        let provider = ProviderBuilder::new().connect(rpc_url).await?;
+
+        AngstromPoolConfigStore::load_from_chain(
+            self.angstrom_address()?,
+            BlockId::number(block),
+            &provider
+        )
+        .await
+        .map_err(|e| eyre::eyre!("{e}"))
+    }
+
+    fn angstrom_address(&self) -> eyre::Result<Address> {
+        self.angstrom_address
+            .or_else(|| ANGSTROM_ADDRESS.get().copied())
+            .wrap_err("no angstrom address for this chain")
+    }
+
+    pub fn fork_config(&self) -> eyre::Result<Option<ForkConfig>> {
+        let Some(fork_url) = self.fork_url.clone() else { return Ok(None) };
+        let angstrom = self.angstrom_address()?;
+        let node = self
+            .node_address
+            .wrap_err("--node-address is required to simulate")?;
+
+        Ok(Some(ForkConfig { fork_url, fork_block: self.fork_block, angstrom, node }))
+    }
+
+    pub fn solve_budget(&self) -> Duration {
+        self.solve_budget_ms
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::MAX)
+    }
+}
+
+#[inline]
+pub fn run() -> eyre::Result<()> {
+    let args = MatchingCli::parse();
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(start(args))
+}
+
+pub async fn start(args: MatchingCli) -> eyre::Result<()> {
+    init_tracing();
+    try_init_with_chain_id(args.chain_id)?;
+
+    let input = args.load_input().await?;
+    let fork = args.fork_config()?;
+    tracing::info!(
+        limit = input.limit.len(),
+        searcher = input.searcher.len(),
+        pools = input.pool_snapshots.len(),
+        "loaded solver input"
+    );
+
+    let solver = SolverPool::new(SolverConfig::default());
+
+    let report = solve(&solver, input, args.solve_budget(), fork.as_ref()).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    Ok(())
+}
+
+/// solves every pool the same way the matching engine does when building a
+/// proposal and builds the bundle out of the solutions.
+pub async fn solve(
+    solver: &SolverPool,
+    input: SolverInput,
+    solve_budget: Duration,
+    fork: Option<&ForkConfig>
+) -> eyre::Result<SolverReport> {
+    let SolverInput { limit, searcher, pool_snapshots } = input;
+
+    let books = build_books(limit.clone(), &pool_snapshots);
+    let searcher_orders = searcher_orders_by_pool(searcher);
+    let mut solutions = solver
+        .solve(books, &searcher_orders, Instant::now().checked_add(solve_budget))
+        .await;
+    solutions.sort_by_key(|solution| solution.id);
+
+    let pools = solutions.iter().map(PoolReport::from).collect();
+    let (bundle, error) =
+        match AngstromBundle::for_gas_finalization(limit, solutions, &pool_snapshots) {
+            Ok(bundle) => (Some(bundle), None),
+            Err(e) => (None, Some(e.to_string()))
+        };
+
+    let simulation = match (fork, &bundle) {
+        (Some(fork), Some(bundle)) => Some(simulate_bundle(fork, bundle).await?),
+        _ => None
+    };
+
+    Ok(SolverReport { pools, bundle, error, simulation })
+}
+
+fn init_tracing() {
+    let level = Level::INFO;
+
+    let envfilter = filter::EnvFilter::builder().try_from_env().ok();
+    let format = tracing_subscriber::fmt::layer()
+        .with_ansi(true)
+        .with_target(true)
+        .with_writer(std::io::stderr);
+
+    if let Some(f) = envfilter {
+        let _ = tracing_subscriber::registry()
+            .with(format)
+            .with(f)
+            .try_init();
+    } else {
+        let filter = filter::Targets::new()
+            .with_target("matching_cli", level)
+            .with_target("matching_engine", level);
+        let _ = tracing_subscriber::registry()
+            .with(format)
+            .with(filter)
+            .try_init();
+    }
+}
@@ -0,0 +1,5 @@
+use matching_cli::run;
+
+fn main() {
+    run().unwrap()
+}
@@ -0,0 +1,63 @@
+use alloy_primitives::{B256, U256};
+use angstrom_types::{
+    contract_payloads::angstrom::AngstromBundle,
+    matching::Ray,
+    orders::{NetAmmOrder, OrderFillState, PoolSolution},
+    primitive::PoolId
+};
+use serde::Serialize;
+
+/// what the solver did for a single pool.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolReport {
+    pub pool:       PoolId,
+    pub ucp:        Ray,
+    pub amm_swap:   Option<NetAmmOrder>,
+    pub tob_order:  Option<B256>,
+    pub tob_reward: Option<U256>,
+    pub reward_t0:  u128,
+    pub fee:        u32,
+    pub orders:     Vec<OrderReport>
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderReport {
+    pub hash:    B256,
+    pub outcome: OrderFillState
+}
+
+impl From<&PoolSolution> for PoolReport {
+    fn from(solution: &PoolSolution) -> Self {
+        Self {
+            pool:       solution.id,
+            ucp:        solution.ucp,
+            amm_swap:   solution.amm_quantity.clone(),
+            tob_order:  solution.searcher.as_ref().map(|tob| tob.order_id.hash),
+            tob_reward: solution.searcher.as_ref().map(|tob| tob.tob_reward),
+            reward_t0:  solution.reward_t0,
+            fee:        solution.fee,
+            orders:     solution
+                .limit
+                .iter()
+                .map(|outcome| OrderReport { hash: outcome.id.hash, outcome: outcome.outcome })
+                .collect()
+        }
+    }
+}
+
+/// result of running the bundle against a fork.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationReport {
+    Success { gas_used: u64 },
+    Reverted { error: String }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverReport {
+    pub pools:      Vec<PoolReport>,
+    pub bundle:     Option<AngstromBundle>,
+    /// why the bundle couldn't be built, if it couldn't.
+    pub error:      Option<String>,
+    pub simulation: Option<SimulationReport>
+}
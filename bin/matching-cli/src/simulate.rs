@@ -0,0 +1,50 @@
+use alloy::{
+    network::TransactionBuilder,
+    node_bindings::Anvil,
+    providers::{Provider, ProviderBuilder},
+    rpc::types::TransactionRequest,
+    sol_types::SolCall
+};
+use alloy_primitives::Address;
+use angstrom_types::{
+    contract_bindings::angstrom::Angstrom, contract_payloads::angstrom::AngstromBundle
+};
+use pade::PadeEncode;
+
+use crate::report::SimulationReport;
+
+/// where and as who the bundle is simulated.
+#[derive(Debug, Clone)]
+pub struct ForkConfig {
+    pub fork_url:   String,
+    pub fork_block: Option<u64>,
+    pub angstrom:   Address,
+    /// has to be one of the angstrom nodes, otherwise `execute` reverts.
+    pub node:       Address
+}
+
+/// spawns an anvil fork and calls `execute` with the bundle from the node.
+pub async fn simulate_bundle(
+    config: &ForkConfig,
+    bundle: &AngstromBundle
+) -> eyre::Result<SimulationReport> {
+    let mut anvil = Anvil::new().fork(config.fork_url.clone());
+    if let Some(block) = config.fork_block {
+        anvil = anvil.fork_block_number(block);
+    }
+    let anvil = anvil.try_spawn()?;
+    let provider = ProviderBuilder::new().connect_http(anvil.endpoint_url());
+
+    let encoded = Angstrom::executeCall::new((bundle.pade_encode().into(),)).abi_encode();
+    let tx = TransactionRequest::default()
+        .with_from(config.node)
+        .with_to(config.angstrom)
+        .with_input(encoded);
+
+    if let Err(e) = provider.call(tx.clone()).await {
+        return Ok(SimulationReport::Reverted { error: e.to_string() });
+    }
+
+    let gas_used = provider.estimate_gas(tx).await?;
+    Ok(SimulationReport::Success { gas_used })
+}
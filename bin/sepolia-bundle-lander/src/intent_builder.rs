@@ -13,13 +13,13 @@ use alloy::{
 };
 use alloy_primitives::TxKind;
 use alloy_rpc_types::TransactionRequest;
+use angstrom_client::{TobOrderBuilder, UserOrderBuilder};
 use angstrom_rpc::api::OrderApiClient;
 use angstrom_types::{
     matching::{Ray, SqrtPriceX96},
     primitive::{ANGSTROM_DOMAIN, AngstromSigner},
     sol_bindings::{grouped_orders::AllOrders, rpc_orders::OmitOrderMeta}
 };
-use testing_tools::type_generator::orders::{ToBOrderBuilder, UserOrderBuilder};
 use uniswap_v4::uniswap::pool::{EnhancedUniswapPool, SwapResult};
 
 use crate::env::ProviderType;
@@ -129,16 +129,16 @@ where
         let range = (amount_in / 100).max(101);
         amount_in += self.gen_range(100, range);
 
-        let order = ToBOrderBuilder::new()
-            .signing_key(Some(key.clone()))
-            .asset_in(if zfo { self.pool.token0 } else { self.pool.token1 })
-            .asset_out(if !zfo { self.pool.token0 } else { self.pool.token1 })
-            .quantity_in(amount_in)
-            .max_gas(gas.to())
-            .quantity_out(amount_out)
+        let order = TobOrderBuilder::new()
+            .assets(
+                if zfo { self.pool.token0 } else { self.pool.token1 },
+                if !zfo { self.pool.token0 } else { self.pool.token1 }
+            )
+            .quantities(amount_in, amount_out)
+            .max_gas_asset0(gas.to())
             .recipient(key.address())
-            .valid_block(self.block_number + 1)
-            .build();
+            .block(self.block_number + 1)
+            .sign_sync(ANGSTROM_DOMAIN.get().unwrap(), &**key)?;
         let recovery_order_hash = order.no_meta_eip712_signing_hash(ANGSTROM_DOMAIN.get().unwrap());
         tracing::info!(?order, ?recovery_order_hash);
 
@@ -233,21 +233,23 @@ where
 
         let nonce = self.angstrom_client.valid_nonce(key.address()).await?;
 
-        Ok(UserOrderBuilder::new()
-            .signing_key(Some(key.clone()))
-            .is_exact(!is_partial)
-            .asset_in(if zfo { self.pool.token0 } else { self.pool.token1 })
-            .asset_out(if !zfo { self.pool.token0 } else { self.pool.token1 })
-            .is_standing(true)
-            .gas_price_asset_zero(gas.to())
-            .deadline(U256::from(deadline))
+        let builder = UserOrderBuilder::new()
+            .standing(nonce, deadline)
+            .assets(
+                if zfo { self.pool.token0 } else { self.pool.token1 },
+                if !zfo { self.pool.token0 } else { self.pool.token1 }
+            )
+            .max_extra_fee_asset0(gas.to())
+            .fee_on_top()
             .recipient(key.address())
-            .nonce(nonce)
-            .exact_in(exact_in)
-            .min_price(clearing_price)
-            .block(self.block_number + 1)
-            .amount(amount)
-            .build())
+            .min_price(clearing_price);
+        let builder = match (is_partial, exact_in) {
+            (true, _) => builder.partial(amount),
+            (false, true) => builder.exact_in(amount),
+            (false, false) => builder.exact_out(amount)
+        };
+
+        Ok(builder.sign_sync(ANGSTROM_DOMAIN.get().unwrap(), &**key)?)
     }
 
     async fn make_call<TY: SolCall>(&self, from: Address, target: Address, call: TY) -> TY::Return {
@@ -0,0 +1,140 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use clap::Parser;
+use serde::Serialize;
+
+use super::testnet::TestnetCli;
+
+#[derive(Parser, Clone, Debug)]
+pub struct LoadCli {
+    #[clap(flatten)]
+    pub testnet_config:  TestnetCli,
+    #[clap(flatten)]
+    pub profile:         LoadProfile,
+    /// the amount of blocks to submit orders for
+    #[clap(long, default_value = "10")]
+    pub blocks:          u64,
+    /// latency targets the run has to meet, as `<stage>:p<percentile>=<ms>`
+    /// where stage is one of validated, propagated or included. eg
+    /// `validated:p99=250`
+    #[clap(long = "slo")]
+    pub slos:            Vec<LatencySlo>,
+    /// the most orders that may be queued for verification at once. Needs
+    /// `--metrics`
+    #[clap(long)]
+    pub max_queue_depth: Option<i64>
+}
+
+/// The order flow that is sent to the first node of the network.
+#[derive(clap::Args, Clone, Debug)]
+pub struct LoadProfile {
+    /// book orders submitted per second, spread randomly over the pools
+    #[clap(long, default_value = "20")]
+    pub orders_per_sec: f64,
+    /// the share of book orders that are partial, eg 0.3 is 30%
+    #[clap(long, default_value = "0.5")]
+    pub partial_share:  f64,
+    /// the standard deviation of order prices around the pools true price, in
+    /// percent
+    #[clap(long, default_value = "2")]
+    pub price_sd_pct:   f64,
+    /// the share of accepted orders that are cancelled right away
+    #[clap(long, default_value = "0")]
+    pub cancel_rate:    f64,
+    /// the amount of users that sign orders
+    #[clap(long, default_value = "10")]
+    pub users:          usize,
+    /// the amount of users that sign `hot_share` of the orders
+    #[clap(long, default_value = "0")]
+    pub hot_users:      usize,
+    /// the share of orders signed by the hot users
+    #[clap(long, default_value = "0.8")]
+    pub hot_share:      f64
+}
+
+impl LoadProfile {
+    pub fn validate(&self) -> eyre::Result<()> {
+        eyre::ensure!(self.orders_per_sec >= 0.0, "orders per sec can't be negative");
+        eyre::ensure!(self.users > 0, "need at least one user");
+        for (name, share) in [
+            ("partial share", self.partial_share),
+            ("cancel rate", self.cancel_rate),
+            ("hot share", self.hot_share)
+        ] {
+            eyre::ensure!((0.0..=1.0).contains(&share), "{name} has to be within 0 and 1");
+        }
+
+        Ok(())
+    }
+}
+
+/// A point in an orders life that latency is measured to, from when it was
+/// submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// the submitting node validated the order and answered the rpc call
+    Validated,
+    /// the order showed up on a second node
+    Propagated,
+    /// the order was filled in a block
+    Included
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validated => write!(f, "validated"),
+            Self::Propagated => write!(f, "propagated"),
+            Self::Included => write!(f, "included")
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = eyre::ErrReport;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "validated" => Ok(Self::Validated),
+            "propagated" => Ok(Self::Propagated),
+            "included" => Ok(Self::Included),
+            _ => eyre::bail!("unknown stage {s}, expected validated, propagated or included")
+        }
+    }
+}
+
+/// `percentile` of the latencies to `stage` have to be within `max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySlo {
+    pub stage:      Stage,
+    pub percentile: f64,
+    pub max:        Duration
+}
+
+impl fmt::Display for LatencySlo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:p{}={}", self.stage, self.percentile, self.max.as_millis())
+    }
+}
+
+impl FromStr for LatencySlo {
+    type Err = eyre::ErrReport;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (stage, target) = s
+            .split_once(':')
+            .ok_or_else(|| eyre::eyre!("expected <stage>:p<percentile>=<ms>, got {s}"))?;
+        let (percentile, max_ms) = target
+            .strip_prefix('p')
+            .and_then(|target| target.split_once('='))
+            .ok_or_else(|| eyre::eyre!("expected p<percentile>=<ms>, got {target}"))?;
+
+        let percentile = percentile.parse::<f64>()?;
+        if percentile <= 0.0 || percentile > 100.0 {
+            eyre::bail!("percentile has to be in (0, 100], got {percentile}");
+        }
+
+        Ok(Self { stage: stage.parse()?, percentile, max: Duration::from_millis(max_ms.parse()?) })
+    }
+}
@@ -1,11 +1,13 @@
 pub mod devnet;
 pub mod e2e_orders;
+pub mod load;
 pub mod testnet;
 use angstrom_metrics::{METRICS_ENABLED, initialize_prometheus_metrics};
 use angstrom_types::primitive::AngstromAddressConfig;
 use clap::{ArgAction, Parser, Subcommand};
 use devnet::DevnetCli;
 use e2e_orders::End2EndOrdersCli;
+use load::LoadCli;
 use reth_tasks::TaskExecutor;
 use testing_tools::types::config::{DevnetConfig, TestnetConfig};
 use testnet::TestnetCli;
@@ -14,7 +16,10 @@ use tracing_subscriber::{
     EnvFilter, Layer, Registry, filter, layer::SubscriberExt, util::SubscriberInitExt
 };
 
-use crate::{run_devnet, run_testnet, simulations::e2e_orders::run_e2e_orders};
+use crate::{
+    run_devnet, run_testnet,
+    simulations::{e2e_orders::run_e2e_orders, load::run_load}
+};
 
 #[derive(Parser)]
 pub struct AngstromTestnetCli {
@@ -74,7 +79,11 @@ pub enum TestnetSubcommmand {
     #[command(name = "devnet")]
     Devnet(DevnetCli),
     #[command(name = "e2e")]
-    End2EndOrders(End2EndOrdersCli)
+    End2EndOrders(End2EndOrdersCli),
+    /// sends a configurable order flow to a local network and reports the
+    /// order latencies against the given slos
+    #[command(name = "load")]
+    Load(LoadCli)
 }
 
 impl TestnetSubcommmand {
@@ -82,7 +91,8 @@ impl TestnetSubcommmand {
         match self {
             TestnetSubcommmand::Testnet(testnet_cli) => run_testnet(executor, testnet_cli).await,
             TestnetSubcommmand::Devnet(devnet_cli) => run_devnet(executor, devnet_cli).await,
-            TestnetSubcommmand::End2EndOrders(e2e_cli) => run_e2e_orders(executor, e2e_cli).await
+            TestnetSubcommmand::End2EndOrders(e2e_cli) => run_e2e_orders(executor, e2e_cli).await,
+            TestnetSubcommmand::Load(load_cli) => run_load(executor, load_cli).await
         }
     }
 }
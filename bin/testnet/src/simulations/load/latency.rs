@@ -0,0 +1,296 @@
+use std::{collections::HashMap, time::Duration};
+
+use alloy_primitives::B256;
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::cli::load::{LatencySlo, Stage};
+
+/// when an order submitted by the load run reached each stage.
+#[derive(Debug, Clone, Copy)]
+struct OrderTimes {
+    submitted:  Instant,
+    validated:  Option<Instant>,
+    propagated: Option<Instant>,
+    included:   Option<Instant>,
+    rejected:   bool,
+    cancelled:  bool
+}
+
+impl OrderTimes {
+    fn latency(&self, stage: Stage) -> Option<Duration> {
+        let reached = match stage {
+            Stage::Validated => self.validated,
+            Stage::Propagated => self.propagated,
+            Stage::Included => self.included
+        }?;
+
+        Some(reached.saturating_duration_since(self.submitted))
+    }
+}
+
+/// Records the stages of every submitted order along with the verification
+/// queue depth, and turns them into a [`LoadReport`].
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    orders:      HashMap<B256, OrderTimes>,
+    queue_depth: Vec<i64>
+}
+
+impl LatencyTracker {
+    pub fn submitted(&mut self, order: B256) {
+        self.orders.insert(
+            order,
+            OrderTimes {
+                submitted:  Instant::now(),
+                validated:  None,
+                propagated: None,
+                included:   None,
+                rejected:   false,
+                cancelled:  false
+            }
+        );
+    }
+
+    pub fn validated(&mut self, order: B256, accepted: bool) {
+        if let Some(times) = self.orders.get_mut(&order) {
+            times.validated.get_or_insert_with(Instant::now);
+            times.rejected = !accepted;
+        }
+    }
+
+    /// only the first time an order is seen counts.
+    pub fn propagated(&mut self, order: B256) {
+        if let Some(times) = self.orders.get_mut(&order) {
+            times.propagated.get_or_insert_with(Instant::now);
+        }
+    }
+
+    pub fn included(&mut self, order: B256) {
+        if let Some(times) = self.orders.get_mut(&order) {
+            times.included.get_or_insert_with(Instant::now);
+        }
+    }
+
+    pub fn cancelled(&mut self, order: B256) {
+        if let Some(times) = self.orders.get_mut(&order) {
+            times.cancelled = true;
+        }
+    }
+
+    pub fn sample_queue_depth(&mut self, depth: i64) {
+        self.queue_depth.push(depth);
+    }
+
+    pub fn report(&self, slos: &[LatencySlo], max_queue_depth: Option<i64>) -> LoadReport {
+        let accepted = self
+            .orders
+            .values()
+            .filter(|times| times.validated.is_some() && !times.rejected);
+        let not_included = accepted
+            .clone()
+            .filter(|times| !times.cancelled && times.included.is_none())
+            .count();
+
+        let summary = |stage| {
+            // rejected orders answer fast, only accepted orders count.
+            let latencies = accepted
+                .clone()
+                .filter_map(|times| times.latency(stage))
+                .collect::<Vec<_>>();
+            LatencySummary::new(latencies)
+        };
+
+        let mut report = LoadReport {
+            submitted: self.orders.len(),
+            rejected: self.orders.values().filter(|times| times.rejected).count(),
+            cancelled: self.orders.values().filter(|times| times.cancelled).count(),
+            not_included,
+            validated: summary(Stage::Validated),
+            propagated: summary(Stage::Propagated),
+            included: summary(Stage::Included),
+            queue_depth: QueueDepthSummary::new(self.queue_depth.clone()),
+            slo_violations: vec![]
+        };
+        report.slo_violations = report.check(slos, max_queue_depth);
+
+        report
+    }
+}
+
+/// latency percentiles of the orders that reached a stage, in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub count:  usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    #[serde(skip)]
+    sorted:     Vec<Duration>
+}
+
+impl LatencySummary {
+    fn new(mut latencies: Vec<Duration>) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort();
+
+        let ms = |latency: Duration| latency.as_secs_f64() * 1000.0;
+        Some(Self {
+            count:  latencies.len(),
+            p50_ms: ms(percentile(&latencies, 50.0)),
+            p90_ms: ms(percentile(&latencies, 90.0)),
+            p99_ms: ms(percentile(&latencies, 99.0)),
+            max_ms: ms(*latencies.last().unwrap()),
+            sorted: latencies
+        })
+    }
+
+    pub fn percentile(&self, pct: f64) -> Duration {
+        percentile(&self.sorted, pct)
+    }
+}
+
+/// nearest rank percentile of a sorted, non empty, set of samples.
+fn percentile<T: Copy>(sorted: &[T], pct: f64) -> T {
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// orders queued for verification, sampled while the load was running.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueDepthSummary {
+    pub samples: usize,
+    pub p50:     i64,
+    pub p99:     i64,
+    pub max:     i64
+}
+
+impl QueueDepthSummary {
+    fn new(mut samples: Vec<i64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+
+        Some(Self {
+            samples: samples.len(),
+            p50:     percentile(&samples, 50.0),
+            p99:     percentile(&samples, 99.0),
+            max:     *samples.last().unwrap()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub submitted:      usize,
+    pub rejected:       usize,
+    pub cancelled:      usize,
+    /// accepted orders that weren't cancelled and never got filled.
+    pub not_included:   usize,
+    pub validated:      Option<LatencySummary>,
+    pub propagated:     Option<LatencySummary>,
+    pub included:       Option<LatencySummary>,
+    /// `None` when metrics are disabled.
+    pub queue_depth:    Option<QueueDepthSummary>,
+    pub slo_violations: Vec<String>
+}
+
+impl LoadReport {
+    fn stage(&self, stage: Stage) -> Option<&LatencySummary> {
+        match stage {
+            Stage::Validated => self.validated.as_ref(),
+            Stage::Propagated => self.propagated.as_ref(),
+            Stage::Included => self.included.as_ref()
+        }
+    }
+
+    fn check(&self, slos: &[LatencySlo], max_queue_depth: Option<i64>) -> Vec<String> {
+        let mut violations = slos
+            .iter()
+            .filter_map(|slo| {
+                let Some(summary) = self.stage(slo.stage) else {
+                    return Some(format!("{slo}: no order was {}", slo.stage));
+                };
+                let latency = summary.percentile(slo.percentile);
+
+                (latency > slo.max).then(|| format!("{slo}: was {}ms", latency.as_millis()))
+            })
+            .collect::<Vec<_>>();
+
+        match (max_queue_depth, &self.queue_depth) {
+            (Some(max), Some(depth)) if depth.max > max => {
+                violations.push(format!("queue depth of {} is over the max of {max}", depth.max))
+            }
+            (Some(_), None) => {
+                violations.push("queue depth wasn't sampled, run with --metrics".to_string())
+            }
+            _ => {}
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: &[u64]) -> Vec<Duration> {
+        ms.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let summary = LatencySummary::new(millis(&(1..=100).rev().collect::<Vec<_>>())).unwrap();
+
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(summary.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(summary.percentile(100.0), Duration::from_millis(100));
+        assert_eq!(summary.max_ms, 100.0);
+        assert!(LatencySummary::new(vec![]).is_none());
+    }
+
+    #[test]
+    fn reports_missed_slos() {
+        let report = LoadReport {
+            submitted:      3,
+            rejected:       0,
+            cancelled:      0,
+            not_included:   3,
+            validated:      LatencySummary::new(millis(&[10, 20, 300])),
+            propagated:     None,
+            included:       None,
+            queue_depth:    QueueDepthSummary::new(vec![0, 4, 12]),
+            slo_violations: vec![]
+        };
+        let slos = ["validated:p50=25", "validated:p99=250", "included:p50=12000"]
+            .map(|slo| slo.parse::<LatencySlo>().unwrap());
+
+        let violations = report.check(&slos, Some(10));
+        assert_eq!(
+            violations,
+            vec![
+                "validated:p99=250: was 300ms".to_string(),
+                "included:p50=12000: no order was included".to_string(),
+                "queue depth of 12 is over the max of 10".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_slos() {
+        let slo = "propagated:p99.9=400".parse::<LatencySlo>().unwrap();
+        assert_eq!(slo.stage, Stage::Propagated);
+        assert_eq!(slo.percentile, 99.9);
+        assert_eq!(slo.max, Duration::from_millis(400));
+
+        assert!("validated=250".parse::<LatencySlo>().is_err());
+        assert!("filled:p99=250".parse::<LatencySlo>().is_err());
+        assert!("validated:p0=250".parse::<LatencySlo>().is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Sends a configurable order flow to the first node of a local network and
+//! measures how long orders take to be validated by it, to propagate to a
+//! second node and to be included in a block.
+
+use std::{collections::HashSet, net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+
+use alloy_primitives::B256;
+use angstrom_metrics::validation::ValidationMetrics;
+use angstrom_rpc::{
+    api::OrderApiClient,
+    types::{OrderSubscriptionFilter, OrderSubscriptionKind, OrderSubscriptionResult}
+};
+use angstrom_types::{
+    orders::CancelOrderRequest,
+    sol_bindings::{RawPoolOrder, grouped_orders::AllOrders},
+    testnet::InitialTestnetState
+};
+use eyre::OptionExt;
+use futures::{Future, StreamExt, stream::FuturesUnordered};
+use jsonrpsee::{http_client::HttpClient, ws_client::WsClientBuilder};
+use rand::Rng;
+use reth_provider::{CanonStateSubscriptions, test_utils::NoopProvider};
+use reth_tasks::TaskExecutor;
+use testing_tools::{
+    agents::AgentConfig,
+    controllers::enviroments::AngstromTestnet,
+    order_generator::{GeneratedPoolOrders, InternalBalanceMode, PoolOrderGenerator, UserFlow},
+    types::config::TestingNodeConfig
+};
+use tokio::sync::mpsc;
+use tracing::{Instrument, Level, span};
+
+use crate::cli::load::{LatencySlo, LoadCli, LoadProfile};
+
+mod latency;
+pub use latency::*;
+
+/// how often orders are sent and the verification queue is sampled.
+const TICK: Duration = Duration::from_millis(100);
+/// blocks to wait for fills once the last orders have been sent.
+const INCLUSION_GRACE_BLOCKS: u64 = 2;
+
+pub async fn run_load(executor: TaskExecutor, cli: LoadCli) -> eyre::Result<()> {
+    cli.profile.validate()?;
+    let config = cli.testnet_config.make_config()?;
+    let observer_port = (cli.testnet_config.nodes_in_network > 1)
+        .then(|| TestingNodeConfig::new(1, config.clone(), 100).strom_rpc_port() as u16);
+    let run = LoadRun {
+        profile: cli.profile,
+        blocks: cli.blocks,
+        slos: cli.slos,
+        max_queue_depth: cli.max_queue_depth,
+        observer_port
+    };
+    let (report_tx, mut report_rx) = mpsc::unbounded_channel();
+
+    let testnet = AngstromTestnet::spawn_testnet(
+        NoopProvider::default(),
+        config,
+        vec![load_agent(Arc::new(run), report_tx)],
+        executor.clone()
+    )
+    .await?;
+    tracing::info!("load testnet is alive");
+
+    let testnet =
+        executor.spawn_critical_blocking("testnet", testnet.run_to_completion(executor.clone()));
+    let report = tokio::select! {
+        _ = testnet => eyre::bail!("testnet stopped before the load run finished"),
+        report = report_rx.recv() => report.ok_or_eyre("load agent stopped without a report")?
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.slo_violations.is_empty() {
+        eyre::bail!("missed {} slos: {:?}", report.slo_violations.len(), report.slo_violations);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct LoadRun {
+    profile:         LoadProfile,
+    blocks:          u64,
+    slos:            Vec<LatencySlo>,
+    max_queue_depth: Option<i64>,
+    /// rpc port of the second node, that orders are watched propagating to
+    observer_port:   Option<u16>
+}
+
+fn load_agent(
+    run: Arc<LoadRun>,
+    reports: mpsc::UnboundedSender<LoadReport>
+) -> impl for<'a> Fn(
+    &'a InitialTestnetState,
+    AgentConfig
+) -> Pin<Box<dyn Future<Output = eyre::Result<()>> + Send + 'a>>
++ Clone {
+    move |_, agent_config| {
+        let run = run.clone();
+        let reports = reports.clone();
+
+        Box::pin(async move {
+            // every node runs the agents, the load is only sent to the first one.
+            if agent_config.agent_id != 0 {
+                return Ok(());
+            }
+            tracing::info!("starting load agent");
+
+            let agent_id = agent_config.agent_id;
+            tokio::spawn(
+                async move {
+                    match LoadDriver::new(&run, agent_config).run(&run).await {
+                        Ok(report) => {
+                            let _ = reports.send(report);
+                        }
+                        Err(e) => tracing::error!(%e, "load run failed")
+                    }
+                }
+                .instrument(span!(Level::ERROR, "load", ?agent_id))
+            );
+
+            Ok(())
+        })
+    }
+}
+
+/// Sends orders at the profiles rate and follows them through the network.
+struct LoadDriver {
+    agent_config: AgentConfig,
+    client:       HttpClient,
+    pools:        Vec<PoolOrderGenerator<HttpClient>>,
+    users:        UserFlow,
+    tracker:      LatencyTracker,
+    metrics:      ValidationMetrics
+}
+
+impl LoadDriver {
+    fn new(run: &LoadRun, agent_config: AgentConfig) -> Self {
+        let client = HttpClient::builder()
+            .build(format!("http://{}", agent_config.rpc_address))
+            .unwrap();
+        let users = UserFlow::random(run.profile.users)
+            .with_hot_users(run.profile.hot_users, run.profile.hot_share);
+
+        let pools = agent_config
+            .uniswap_pools
+            .iter()
+            .map(|pool| {
+                PoolOrderGenerator::new_with_cfg_distro(
+                    *pool.key(),
+                    pool.value().clone(),
+                    agent_config.current_block,
+                    run.profile.price_sd_pct,
+                    InternalBalanceMode::Never
+                )
+                .with_client(client.clone())
+                .with_user_flow(users.clone())
+            })
+            .collect();
+
+        Self {
+            agent_config,
+            client,
+            pools,
+            users,
+            tracker: LatencyTracker::default(),
+            metrics: ValidationMetrics::new()
+        }
+    }
+
+    async fn run(mut self, run: &LoadRun) -> eyre::Result<LoadReport> {
+        let mut blocks = self
+            .agent_config
+            .state_provider
+            .canonical_state_stream()
+            .map(|node| match node {
+                reth_provider::CanonStateNotification::Commit { new }
+                | reth_provider::CanonStateNotification::Reorg { new, .. } => new.tip_number()
+            });
+        // agents start while the network is still being built, the rpcs of the
+        // other nodes are only up once blocks are being produced.
+        let start = blocks.next().await.ok_or_eyre("block stream ended")?;
+        tracing::info!(start, "sending load");
+
+        let rpc_address = self.agent_config.rpc_address;
+        let node = WsClientBuilder::new()
+            .build(format!("ws://{rpc_address}"))
+            .await?;
+        let mut filled = node
+            .subscribe_orders(
+                HashSet::from([OrderSubscriptionKind::FilledOrders]),
+                HashSet::from([OrderSubscriptionFilter::None])
+            )
+            .await?
+            .into_stream();
+
+        let observer = if let Some(port) = run.observer_port {
+            let address = SocketAddr::new(rpc_address.ip(), port);
+            Some(
+                WsClientBuilder::new()
+                    .build(format!("ws://{address}"))
+                    .await?
+            )
+        } else {
+            None
+        };
+        let mut propagated = match &observer {
+            Some(observer) => observer
+                .subscribe_orders(
+                    HashSet::from([OrderSubscriptionKind::NewOrders]),
+                    HashSet::from([OrderSubscriptionFilter::None])
+                )
+                .await?
+                .into_stream()
+                .boxed(),
+            None => futures::stream::pending().boxed()
+        };
+
+        let mut ticks = tokio::time::interval(TICK);
+        let mut pending_sends = FuturesUnordered::new();
+        let mut pending_cancels = FuturesUnordered::new();
+        // fractional orders carried over to the next tick
+        let mut owed = 0.0;
+        let mut seen_blocks = 0;
+
+        loop {
+            let sending = seen_blocks < run.blocks;
+
+            tokio::select! {
+                Some(block_number) = blocks.next() => {
+                    seen_blocks += 1;
+                    if seen_blocks >= run.blocks + INCLUSION_GRACE_BLOCKS {
+                        break;
+                    }
+                    self.pools.iter_mut().for_each(|pool| pool.new_block(block_number));
+
+                    if seen_blocks < run.blocks {
+                        // one top of block order for every pool per block.
+                        let sets = futures::future::join_all(
+                            self.pools
+                                .iter()
+                                .map(|pool| pool.generate_set(0, run.profile.partial_share))
+                        )
+                        .await;
+                        for GeneratedPoolOrders { tob, .. } in sets {
+                            pending_sends.push(self.send(tob.into()));
+                        }
+                    }
+                }
+                _ = ticks.tick() => {
+                    if let Some(depth) = self.metrics.pending_verification() {
+                        self.tracker.sample_queue_depth(depth);
+                    }
+                    if !sending || self.pools.is_empty() {
+                        continue;
+                    }
+
+                    owed += run.profile.orders_per_sec * TICK.as_secs_f64();
+                    let due = owed.floor() as usize;
+                    owed -= due as f64;
+
+                    for (pool, amount) in self.spread_over_pools(due).into_iter().enumerate() {
+                        if amount == 0 {
+                            continue;
+                        }
+                        let GeneratedPoolOrders { book, .. } =
+                            self.pools[pool].generate_set(amount, run.profile.partial_share).await;
+                        for order in book {
+                            pending_sends.push(self.send(order));
+                        }
+                    }
+                }
+                Some((order, accepted)) = pending_sends.next() => {
+                    self.tracker.validated(order.order_hash(), accepted);
+                    if accepted && rand::rng().random_bool(run.profile.cancel_rate) {
+                        pending_cancels.extend(self.cancel(&order));
+                    }
+                }
+                Some((order, cancelled)) = pending_cancels.next() => {
+                    if cancelled {
+                        self.tracker.cancelled(order);
+                    }
+                }
+                Some(Ok(event)) = filled.next() => {
+                    if let OrderSubscriptionResult::FilledOrder(_, order, _) = event {
+                        self.tracker.included(order.order_hash());
+                    }
+                }
+                Some(Ok(event)) = propagated.next() => {
+                    if let OrderSubscriptionResult::NewOrder(order) = event {
+                        self.tracker.propagated(order.order_hash());
+                    }
+                }
+            }
+        }
+
+        Ok(self.tracker.report(&run.slos, run.max_queue_depth))
+    }
+
+    /// how many of `amount` orders go to each pool.
+    fn spread_over_pools(&self, amount: usize) -> Vec<usize> {
+        let mut rng = rand::rng();
+        let mut per_pool = vec![0; self.pools.len()];
+        for _ in 0..amount {
+            per_pool[rng.random_range(0..self.pools.len())] += 1;
+        }
+
+        per_pool
+    }
+
+    /// submits the order, resolving to whether the node accepted it.
+    fn send(&mut self, order: AllOrders) -> impl Future<Output = (AllOrders, bool)> + use<> {
+        self.tracker.submitted(order.order_hash());
+        let client = self.client.clone();
+
+        async move {
+            let accepted = client
+                .send_order(order.clone())
+                .await
+                .is_ok_and(|res| res.is_ok());
+            (order, accepted)
+        }
+    }
+
+    fn cancel(&self, order: &AllOrders) -> Option<impl Future<Output = (B256, bool)> + use<>> {
+        let hash = order.order_hash();
+        let signer = self.users.signer_of(order.from())?;
+        let request = CancelOrderRequest::new(order.from(), hash, signer);
+        let client = self.client.clone();
+
+        Some(async move { (hash, client.cancel_order(request).await.unwrap_or_default()) })
+    }
+}
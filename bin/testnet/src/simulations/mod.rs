@@ -1,2 +1,3 @@
 pub mod e2e_orders;
+pub mod load;
 pub mod token_prices_update_new_pools;
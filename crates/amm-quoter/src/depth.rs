@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use angstrom_types::{
+    matching::SqrtPriceX96,
+    quoting::{BookDepth, BookDepthRequest, DepthLevel},
+    sol_bindings::{RawPoolOrder, Ray},
+    uni_structure::BaselinePoolState
+};
+use matching_engine::book::BookOrder;
+
+/// builds the depth of the book given the current amm state and the limit
+/// orders of the pool.
+pub fn build_book_depth<'a>(
+    request: BookDepthRequest,
+    current_block: u64,
+    amm: &BaselinePoolState,
+    orders: impl IntoIterator<Item = &'a BookOrder>
+) -> BookDepth {
+    let BookDepthRequest { pool_id, tick_grouping, levels } = request;
+    let current_tick = amm.current_tick();
+    let current_bucket = bucket_of(current_tick, tick_grouping);
+
+    let mut bids: BTreeMap<i32, DepthLevel> = BTreeMap::new();
+    let mut asks: BTreeMap<i32, DepthLevel> = BTreeMap::new();
+
+    // the amm sells token0 as the price moves up, and buys token0 as the price
+    // moves down. we swap to the edge of every bucket and diff the cumulative
+    // amounts to get the liquidity inside of it.
+    let mut last_t0 = 0;
+    for i in 0..levels as i32 {
+        let tick = current_bucket - i * tick_grouping;
+        let Some(t0) = amm_t0_to_tick(amm, tick) else { break };
+        level_at(&mut bids, tick).amm_quantity = t0.saturating_sub(last_t0);
+        last_t0 = t0;
+    }
+
+    let mut last_t0 = 0;
+    for i in 0..levels as i32 {
+        let tick = current_bucket + i * tick_grouping;
+        let Some(t0) = amm_t0_to_tick(amm, tick + tick_grouping) else { break };
+        level_at(&mut asks, tick).amm_quantity = t0.saturating_sub(last_t0);
+        last_t0 = t0;
+    }
+
+    for order in orders {
+        let price = order.price_t1_over_t0();
+        let Ok(tick) = SqrtPriceX96::from(price).to_tick() else { continue };
+        let side = if order.is_bid { &mut bids } else { &mut asks };
+
+        let level = level_at(side, bucket_of(tick, tick_grouping));
+        level.book_quantity = level
+            .book_quantity
+            .saturating_add(order_t0_quantity(order, price));
+    }
+
+    BookDepth {
+        current_block,
+        angstrom_pool_id: pool_id,
+        tick_grouping,
+        current_tick,
+        bids: bids.into_values().rev().take(levels).collect(),
+        asks: asks.into_values().take(levels).collect()
+    }
+}
+
+fn level_at(side: &mut BTreeMap<i32, DepthLevel>, tick: i32) -> &mut DepthLevel {
+    side.entry(tick).or_insert_with(|| DepthLevel {
+        tick,
+        price: SqrtPriceX96::at_tick(tick)
+            .map(Ray::from)
+            .unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+/// the lowest tick of the bucket the tick falls in.
+fn bucket_of(tick: i32, tick_grouping: i32) -> i32 {
+    tick.div_euclid(tick_grouping) * tick_grouping
+}
+
+/// the amount of token0 the amm trades to get from the current price to the
+/// tick. `None` if the amm can't be moved to the tick.
+fn amm_t0_to_tick(amm: &BaselinePoolState, tick: i32) -> Option<u128> {
+    let price = SqrtPriceX96::at_tick(tick).ok()?;
+    let swap = amm.swap_current_to_price_raw(price).ok()?;
+
+    Some(swap.total_d_t0)
+}
+
+/// the amount of token0 the order wants to trade.
+fn order_t0_quantity(order: &BookOrder, price: Ray) -> u128 {
+    let amount = order.amount();
+    // bids that are exact in and asks that are exact out are specified in token1
+    if order.is_bid == order.exact_in() { price.inverse_quantity(amount, false) } else { amount }
+}
+
+/// a pool at tick 0 with a single position over ticks -1000 to 1000.
+#[cfg(test)]
+pub(crate) fn single_position_amm(liquidity: u128) -> BaselinePoolState {
+    use std::collections::HashMap;
+
+    use alloy::primitives::U256;
+    use angstrom_types::{
+        matching::uniswap::TickInfo, uni_structure::liquidity_base::BaselineLiquidity
+    };
+
+    const TICK_SPACING: i32 = 10;
+    let mut ticks = HashMap::new();
+    let mut bitmap: HashMap<i16, U256> = HashMap::new();
+    for (tick, liquidity_net) in [(-1000, liquidity as i128), (1000, -(liquidity as i128))] {
+        ticks.insert(
+            tick,
+            TickInfo { liquidity_gross: liquidity, liquidity_net, initialized: true }
+        );
+
+        let compressed = tick.div_euclid(TICK_SPACING);
+        *bitmap.entry((compressed >> 8) as i16).or_default() |=
+            U256::from(1) << (compressed & 0xff) as usize;
+    }
+
+    BaselinePoolState::new(
+        BaselineLiquidity::new(
+            TICK_SPACING,
+            0,
+            SqrtPriceX96::at_tick(0).unwrap(),
+            liquidity,
+            ticks,
+            bitmap
+        ),
+        1,
+        0
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+    use angstrom_types::{
+        primitive::PoolId,
+        quoting::{MAX_DEPTH_LEVELS, MAX_TICK_GROUPING},
+        sol_bindings::{grouped_orders::AllOrders, rpc_orders::PartialStandingOrder}
+    };
+
+    use super::*;
+
+    #[test]
+    fn buckets_round_down() {
+        assert_eq!(bucket_of(0, 10), 0);
+        assert_eq!(bucket_of(9, 10), 0);
+        assert_eq!(bucket_of(10, 10), 10);
+        assert_eq!(bucket_of(-1, 10), -10);
+        assert_eq!(bucket_of(-10, 10), -10);
+        assert_eq!(bucket_of(-11, 10), -20);
+    }
+
+    #[test]
+    fn max_request_doesnt_overflow() {
+        let request = BookDepthRequest {
+            pool_id:       PoolId::default(),
+            tick_grouping: MAX_TICK_GROUPING,
+            levels:        MAX_DEPTH_LEVELS
+        };
+        assert!(request.validate().is_ok());
+
+        let depth = build_book_depth(request, 1, &single_position_amm(1_000_000), []);
+        assert!(depth.bids.len() <= MAX_DEPTH_LEVELS);
+        assert!(depth.asks.len() <= MAX_DEPTH_LEVELS);
+    }
+
+    /// t0 the amm trades between the ticks of a single position, priced with
+    /// floats independently of the swap math.
+    fn expected_t0(liquidity: u128, lower: i32, upper: i32) -> f64 {
+        let sqrt = |tick: i32| 1.0001f64.powi(tick).sqrt();
+        liquidity as f64 * (1.0 / sqrt(lower) - 1.0 / sqrt(upper))
+    }
+
+    fn assert_close(actual: u128, expected: f64) {
+        let error = (actual as f64 - expected).abs() / expected;
+        assert!(error < 1e-3, "{actual} isn't close to {expected}");
+    }
+
+    #[test]
+    fn depth_splits_amm_and_book_liquidity_into_levels() {
+        let liquidity = 10u128.pow(18);
+        let amm = single_position_amm(liquidity);
+        let request = BookDepthRequest {
+            pool_id:       PoolId::default(),
+            tick_grouping: 10,
+            levels:        3
+        };
+
+        // a resting ask at tick 25 falls in the level starting at tick 20
+        let price = Ray::from(SqrtPriceX96::at_tick(25).unwrap());
+        let ask = BookOrder {
+            order: AllOrders::PartialStanding(PartialStandingOrder {
+                asset_in: Address::with_last_byte(1),
+                asset_out: Address::with_last_byte(2),
+                max_amount_in: 1_000,
+                min_price: price.into(),
+                ..Default::default()
+            }),
+            is_bid: false,
+            ..Default::default()
+        };
+
+        let depth = build_book_depth(request, 7, &amm, [&ask]);
+        assert_eq!(depth.current_block, 7);
+        assert_eq!(depth.current_tick, 0);
+
+        // the pool sits at tick 0, so the first bid level is already crossed
+        let bid_ticks = depth
+            .bids
+            .iter()
+            .map(|level| level.tick)
+            .collect::<Vec<_>>();
+        assert_eq!(bid_ticks, vec![0, -10, -20]);
+        assert_eq!(depth.bids[0].amm_quantity, 0);
+        assert_close(depth.bids[1].amm_quantity, expected_t0(liquidity, -10, 0));
+        assert_close(depth.bids[2].amm_quantity, expected_t0(liquidity, -20, -10));
+
+        let ask_ticks = depth
+            .asks
+            .iter()
+            .map(|level| level.tick)
+            .collect::<Vec<_>>();
+        assert_eq!(ask_ticks, vec![0, 10, 20]);
+        assert_close(depth.asks[0].amm_quantity, expected_t0(liquidity, 0, 10));
+        assert_close(depth.asks[2].amm_quantity, expected_t0(liquidity, 20, 30));
+        assert_eq!(depth.asks[2].book_quantity, 1_000);
+        assert!(depth.asks[..2].iter().all(|level| level.book_quantity == 0));
+        assert_eq!(depth.asks[2].price, Ray::from(SqrtPriceX96::at_tick(20).unwrap()));
+    }
+}
@@ -7,13 +7,18 @@ use std::{
     time::Duration
 };
 
-use alloy::primitives::U160;
+use alloy::primitives::{Address, U256};
 use angstrom_types::{
     block_sync::BlockSyncConsumer,
     consensus::{ConsensusRoundEvent, ConsensusRoundOrderHashes},
-    orders::OrderSet,
+    matching::SqrtPriceX96,
+    orders::{OrderId, OrderPriorityData, OrderSet, PriceObservation},
     primitive::PoolId,
-    sol_bindings::{grouped_orders::AllOrders, rpc_orders::TopOfBlockOrder},
+    sol_bindings::{
+        RawPoolOrder, Ray,
+        grouped_orders::{AllOrders, OrderWithStorageData},
+        rpc_orders::TopOfBlockOrder
+    },
     uni_structure::BaselinePoolState
 };
 use futures::{
@@ -26,7 +31,6 @@ use matching_engine::{
 };
 use order_pool::order_storage::OrderStorage;
 use rayon::ThreadPool;
-use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{mpsc, oneshot},
     time::{Interval, interval}
@@ -34,18 +38,24 @@ use tokio::{
 use tokio_stream::wrappers::ReceiverStream;
 use uniswap_v4::uniswap::{pool_data_loader::PoolDataLoader, pool_manager::SyncedUniswapPools};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
-pub struct Slot0Update {
-    /// there will be 120 updates per block or per 100ms
-    pub seq_id:           u16,
-    /// in case of block lag on node
-    pub current_block:    u64,
-    pub angstrom_pool_id: PoolId,
-    pub uni_pool_id:      PoolId,
-
-    pub sqrt_price_x96: U160,
-    pub liquidity:      u128,
-    pub tick:           i32
+pub mod depth;
+pub use angstrom_types::quoting::{
+    BookDepth, BookDepthRequest, DEFAULT_DEPTH_LEVELS, DepthLevel, MAX_DEPTH_LEVELS,
+    MAX_TICK_GROUPING, OrderQuote, Slot0Update
+};
+
+pub enum QuoterRequest {
+    Subscribe(HashSet<PoolId>, mpsc::Sender<Slot0Update>),
+    /// quote the given order against the current book, with the given gas cost
+    /// in token0 applied to the order
+    Quote {
+        order:       Box<AllOrders>,
+        gas_token_0: U256,
+        sender:      oneshot::Sender<eyre::Result<OrderQuote>>
+    },
+    BookDepth(BookDepthRequest, oneshot::Sender<eyre::Result<BookDepth>>),
+    /// will send a depth update every execution interval
+    SubscribeBookDepth(BookDepthRequest, mpsc::Sender<BookDepth>)
 }
 
 pub trait AngstromBookQuoter: Send + Sync + Unpin + 'static {
@@ -54,9 +64,29 @@ pub trait AngstromBookQuoter: Send + Sync + Unpin + 'static {
         &self,
         pool_id: HashSet<PoolId>
     ) -> impl Future<Output = Pin<Box<dyn Stream<Item = Slot0Update> + Send + 'static>>> + Send + Sync;
+
+    /// simulates the order against the current book and amm state.
+    fn quote_order(
+        &self,
+        order: AllOrders,
+        gas_token_0: U256
+    ) -> impl Future<Output = eyre::Result<OrderQuote>> + Send + Sync;
+
+    /// the aggregated depth of the pools book and amm.
+    fn book_depth(
+        &self,
+        request: BookDepthRequest
+    ) -> impl Future<Output = eyre::Result<BookDepth>> + Send + Sync;
+
+    /// will configure this stream to receive depth updates for the requested
+    /// pool
+    fn subscribe_to_book_depth(
+        &self,
+        request: BookDepthRequest
+    ) -> impl Future<Output = Pin<Box<dyn Stream<Item = BookDepth> + Send + 'static>>> + Send + Sync;
 }
 
-pub struct QuoterHandle(pub mpsc::Sender<(HashSet<PoolId>, mpsc::Sender<Slot0Update>)>);
+pub struct QuoterHandle(pub mpsc::Sender<QuoterRequest>);
 
 impl AngstromBookQuoter for QuoterHandle {
     async fn subscribe_to_updates(
@@ -64,7 +94,40 @@ impl AngstromBookQuoter for QuoterHandle {
         pool_ids: HashSet<PoolId>
     ) -> Pin<Box<dyn Stream<Item = Slot0Update> + Send + 'static>> {
         let (tx, rx) = mpsc::channel(5);
-        let _ = self.0.send((pool_ids, tx)).await;
+        let _ = self.0.send(QuoterRequest::Subscribe(pool_ids, tx)).await;
+
+        ReceiverStream::new(rx).boxed()
+    }
+
+    async fn quote_order(&self, order: AllOrders, gas_token_0: U256) -> eyre::Result<OrderQuote> {
+        let (sender, rx) = oneshot::channel();
+        self.0
+            .send(QuoterRequest::Quote { order: Box::new(order), gas_token_0, sender })
+            .await
+            .map_err(|_| eyre::eyre!("amm quoter is not running"))?;
+
+        rx.await?
+    }
+
+    async fn book_depth(&self, request: BookDepthRequest) -> eyre::Result<BookDepth> {
+        let (sender, rx) = oneshot::channel();
+        self.0
+            .send(QuoterRequest::BookDepth(request, sender))
+            .await
+            .map_err(|_| eyre::eyre!("amm quoter is not running"))?;
+
+        rx.await?
+    }
+
+    async fn subscribe_to_book_depth(
+        &self,
+        request: BookDepthRequest
+    ) -> Pin<Box<dyn Stream<Item = BookDepth> + Send + 'static>> {
+        let (tx, rx) = mpsc::channel(5);
+        let _ = self
+            .0
+            .send(QuoterRequest::SubscribeBookDepth(request, tx))
+            .await;
 
         ReceiverStream::new(rx).boxed()
     }
@@ -77,16 +140,22 @@ pub struct QuoterManager<BlockSync: BlockSyncConsumer> {
     orders: Arc<OrderStorage>,
     amms: SyncedUniswapPools,
     threadpool: ThreadPool,
-    recv: mpsc::Receiver<(HashSet<PoolId>, mpsc::Sender<Slot0Update>)>,
+    recv: mpsc::Receiver<QuoterRequest>,
     book_snapshots: HashMap<PoolId, (PoolId, BaselinePoolState)>,
+    /// (token0, token1) -> angstrom pool id
+    pool_by_pair: HashMap<(Address, Address), PoolId>,
     pending_tasks: FuturesUnordered<BoxFuture<'static, eyre::Result<Slot0Update>>>,
     pool_to_subscribers: HashMap<PoolId, Vec<mpsc::Sender<Slot0Update>>>,
+    depth_subscribers: HashMap<BookDepthRequest, Vec<mpsc::Sender<BookDepth>>>,
     consensus_stream: Pin<Box<dyn Stream<Item = ConsensusRoundOrderHashes> + Send>>,
     /// The unique order hashes of the current PreProposalAggregate consensus
     /// round. Used to build the book for the slot0 stream, so that all
     /// orders are valid, and the subscription can't be manipulated by orders
     /// submitted after this round and between the next block
     active_pre_proposal_aggr_order_hashes: Option<ConsensusRoundOrderHashes>,
+    /// gets the prices of every pool each update, used to trigger conditional
+    /// orders
+    price_observer: Option<mpsc::UnboundedSender<PriceObservation>>,
 
     execution_interval: Interval
 }
@@ -97,13 +166,14 @@ impl<BlockSync: BlockSyncConsumer> QuoterManager<BlockSync> {
     pub fn new(
         block_sync: BlockSync,
         orders: Arc<OrderStorage>,
-        recv: mpsc::Receiver<(HashSet<PoolId>, mpsc::Sender<Slot0Update>)>,
+        recv: mpsc::Receiver<QuoterRequest>,
         amms: SyncedUniswapPools,
         threadpool: ThreadPool,
         update_interval: Duration,
         consensus_stream: Pin<Box<dyn Stream<Item = ConsensusRoundOrderHashes> + Send>>
     ) -> Self {
         let cur_block = block_sync.current_block_number();
+        let mut pool_by_pair = HashMap::default();
         let book_snapshots = amms
             .iter()
             .map(|entry| {
@@ -111,7 +181,8 @@ impl<BlockSync: BlockSyncConsumer> QuoterManager<BlockSync> {
 
                 let pk = pool_lock.public_address();
                 let uni_key = pool_lock.data_loader().private_address();
-                let snapshot_data = pool_lock.fetch_pool_snapshot().unwrap().2;
+                let (token0, token1, snapshot_data) = pool_lock.fetch_pool_snapshot().unwrap();
+                pool_by_pair.insert((token0, token1), pk);
                 (pk, (uni_key, snapshot_data))
             })
             .collect();
@@ -129,15 +200,23 @@ impl<BlockSync: BlockSyncConsumer> QuoterManager<BlockSync> {
             recv,
             cur_block,
             book_snapshots,
+            pool_by_pair,
             threadpool,
             pending_tasks: FuturesUnordered::new(),
             pool_to_subscribers: HashMap::default(),
+            depth_subscribers: HashMap::default(),
             execution_interval: interval(update_interval),
             consensus_stream,
-            active_pre_proposal_aggr_order_hashes: None
+            active_pre_proposal_aggr_order_hashes: None,
+            price_observer: None
         }
     }
 
+    pub fn with_price_observer(mut self, tx: mpsc::UnboundedSender<PriceObservation>) -> Self {
+        self.price_observer = Some(tx);
+        self
+    }
+
     fn handle_new_subscription(&mut self, pools: HashSet<PoolId>, chan: mpsc::Sender<Slot0Update>) {
         let keys = self
             .book_snapshots
@@ -173,29 +252,7 @@ impl<BlockSync: BlockSyncConsumer> QuoterManager<BlockSync> {
     fn spawn_book_solvers(&mut self, seq_id: u16) {
         let OrderSet { limit, searcher } = self.all_orders_with_consensus();
         let mut books = build_non_proposal_books(limit, &self.book_snapshots);
-
-        let searcher_orders = searcher
-            .into_iter()
-            .fold(HashMap::new(), |mut acc, searcher| {
-                match acc.entry(searcher.pool_id) {
-                    Entry::Vacant(v) => {
-                        v.insert(searcher);
-                    }
-                    Entry::Occupied(mut o) => {
-                        let current = o.get();
-                        // if this order on same pool_id has a higher tob reward or they are the
-                        // same and it has a lower order hash. replace
-                        if searcher.tob_reward > current.tob_reward
-                            || (searcher.tob_reward == current.tob_reward
-                                && searcher.order_id.hash < current.order_id.hash)
-                        {
-                            o.insert(searcher);
-                        }
-                    }
-                };
-
-                acc
-            });
+        let searcher_orders = best_searcher_per_pool(searcher);
 
         for (book_id, (uni_pool_id, amm)) in &self.book_snapshots {
             // Default as if we don't have a book, we want to still send update.
@@ -208,11 +265,21 @@ impl<BlockSync: BlockSyncConsumer> QuoterManager<BlockSync> {
             let block = self.cur_block;
 
             let uni_pool_id = *uni_pool_id;
+            let amm_sqrt_price = SqrtPriceX96::from(*amm.current_price());
+            let price_observer = self.price_observer.clone();
 
             self.threadpool.spawn(move || {
                 let b = book;
-                let (sqrt_price, tick, liquidity) =
-                    BinarySearchStrategy::give_end_amm_state(&b, searcher);
+                let (solution, (sqrt_price, tick, liquidity)) =
+                    BinarySearchStrategy::run_with_end_amm_state(&b, searcher);
+                if let Some(observer) = price_observer {
+                    let _ = observer.send(PriceObservation {
+                        block,
+                        pool_id: b.id(),
+                        amm_sqrt_price,
+                        ucp: (!solution.ucp.is_zero()).then_some(solution.ucp)
+                    });
+                }
                 let update = Slot0Update {
                     current_block: block,
                     seq_id,
@@ -231,17 +298,140 @@ impl<BlockSync: BlockSyncConsumer> QuoterManager<BlockSync> {
         }
     }
 
+    /// builds the book of the orders pool with the order added and solves it on
+    /// the threadpool.
+    fn handle_quote_request(
+        &mut self,
+        order: AllOrders,
+        gas_token_0: U256,
+        sender: oneshot::Sender<eyre::Result<OrderQuote>>
+    ) {
+        if order.is_tob() {
+            let _ = sender.send(Err(eyre::eyre!("can only quote limit orders")));
+            return;
+        }
+
+        let (mut token0, mut token1) = (order.token_in(), order.token_out());
+        if token0 > token1 {
+            std::mem::swap(&mut token0, &mut token1);
+        }
+
+        let Some((pool_id, (_, amm))) = self
+            .pool_by_pair
+            .get(&(token0, token1))
+            .and_then(|pool_id| Some((*pool_id, self.book_snapshots.get(pool_id)?.clone())))
+        else {
+            let _ = sender.send(Err(eyre::eyre!("no angstrom pool for {token0:?}/{token1:?}")));
+            return;
+        };
+
+        let quoted_order = quoted_book_order(order, pool_id, gas_token_0, self.cur_block);
+        let quoted_id = quoted_order.order_id;
+
+        let OrderSet { limit, searcher } = self.all_orders_with_consensus();
+        let orders = limit
+            .into_iter()
+            .filter(|order| order.pool_id == pool_id && order.order_id.hash != quoted_id.hash)
+            .chain(std::iter::once(quoted_order))
+            .collect::<HashSet<_>>();
+        let searcher = best_searcher_per_pool(
+            searcher
+                .into_iter()
+                .filter(|searcher| searcher.pool_id == pool_id)
+                .collect()
+        )
+        .remove(&pool_id);
+
+        let book = build_book(pool_id, Some(amm), orders);
+        let current_block = self.cur_block;
+
+        self.threadpool.spawn(move || {
+            let _ = sender.send(Ok(quote_order_on_book(
+                &book,
+                searcher,
+                quoted_id,
+                gas_token_0,
+                current_block
+            )));
+        });
+    }
+
+    /// builds the depth for the request on the threadpool, passing the result
+    /// to `on_depth`.
+    fn spawn_depth_builder(
+        &self,
+        request: BookDepthRequest,
+        on_depth: impl FnOnce(eyre::Result<BookDepth>) + Send + 'static
+    ) {
+        if let Err(e) = request.validate() {
+            on_depth(Err(e));
+            return;
+        }
+        let Some((_, amm)) = self.book_snapshots.get(&request.pool_id).cloned() else {
+            on_depth(Err(eyre::eyre!("no angstrom pool with id {:?}", request.pool_id)));
+            return;
+        };
+
+        let OrderSet { limit, .. } = self.all_orders_with_consensus();
+        let orders = limit
+            .into_iter()
+            .filter(|order| order.pool_id == request.pool_id)
+            .collect::<Vec<_>>();
+        let block = self.cur_block;
+
+        self.threadpool.spawn(move || {
+            on_depth(Ok(depth::build_book_depth(request, block, &amm, &orders)));
+        });
+    }
+
+    fn handle_new_depth_subscription(
+        &mut self,
+        request: BookDepthRequest,
+        chan: mpsc::Sender<BookDepth>
+    ) {
+        if request.validate().is_err() || !self.book_snapshots.contains_key(&request.pool_id) {
+            // invalid subscription
+            return;
+        }
+
+        self.depth_subscribers
+            .entry(request)
+            .or_default()
+            .push(chan);
+    }
+
+    fn spawn_depth_builders(&mut self) {
+        self.depth_subscribers.retain(|_, subscribers| {
+            subscribers.retain(|subscriber| !subscriber.is_closed());
+            !subscribers.is_empty()
+        });
+
+        for (request, subscribers) in &self.depth_subscribers {
+            let subscribers = subscribers.clone();
+            self.spawn_depth_builder(*request, move |depth| {
+                let Ok(depth) = depth else { return };
+                for subscriber in subscribers {
+                    // slow subscribers just miss this update.
+                    let _ = subscriber.try_send(depth.clone());
+                }
+            });
+        }
+    }
+
     fn update_book_state(&mut self) {
+        let mut pool_by_pair = HashMap::default();
         self.book_snapshots = self
             .amms
             .iter()
             .map(|entry| {
                 let pool_lock = entry.value().read().unwrap();
                 let uni_key = pool_lock.data_loader().private_address();
-                let snapshot_data = pool_lock.fetch_pool_snapshot().unwrap().2;
+                let (token0, token1, snapshot_data) = pool_lock.fetch_pool_snapshot().unwrap();
+                pool_by_pair.insert((token0, token1), *entry.key());
                 (*entry.key(), (uni_key, snapshot_data))
             })
             .collect();
+        self.pool_by_pair = pool_by_pair;
     }
 
     fn update_consensus_state(&mut self, round: ConsensusRoundOrderHashes) {
@@ -271,8 +461,23 @@ impl<BlockSync: BlockSyncConsumer> Future for QuoterManager<BlockSync> {
         mut self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>
     ) -> std::task::Poll<Self::Output> {
-        while let Poll::Ready(Some((pools, subscriber))) = self.recv.poll_recv(cx) {
-            self.handle_new_subscription(pools, subscriber);
+        while let Poll::Ready(Some(request)) = self.recv.poll_recv(cx) {
+            match request {
+                QuoterRequest::Subscribe(pools, subscriber) => {
+                    self.handle_new_subscription(pools, subscriber)
+                }
+                QuoterRequest::Quote { order, gas_token_0, sender } => {
+                    self.handle_quote_request(*order, gas_token_0, sender)
+                }
+                QuoterRequest::BookDepth(request, sender) => {
+                    self.spawn_depth_builder(request, move |depth| {
+                        let _ = sender.send(depth);
+                    })
+                }
+                QuoterRequest::SubscribeBookDepth(request, subscriber) => {
+                    self.handle_new_depth_subscription(request, subscriber)
+                }
+            }
         }
 
         while let Poll::Ready(Some(consensus_update)) = self.consensus_stream.poll_next_unpin(cx) {
@@ -307,12 +512,80 @@ impl<BlockSync: BlockSyncConsumer> Future for QuoterManager<BlockSync> {
             self.seq_id += 1;
 
             self.spawn_book_solvers(seq_id);
+            self.spawn_depth_builders();
         }
 
         Poll::Pending
     }
 }
 
+/// the order as it would sit in the book of the pool.
+pub fn quoted_book_order(
+    order: AllOrders,
+    pool_id: PoolId,
+    gas_token_0: U256,
+    current_block: u64
+) -> BookOrder {
+    OrderWithStorageData {
+        is_bid: order.is_bid(),
+        priority_data: OrderPriorityData {
+            price:     order.limit_price(),
+            volume:    order.amount(),
+            gas:       gas_token_0,
+            gas_units: 0
+        },
+        pool_id,
+        is_currently_valid: None,
+        is_valid: true,
+        valid_block: current_block,
+        order_id: OrderId::from_all_orders(&order, pool_id),
+        order,
+        ..Default::default()
+    }
+}
+
+/// solves the book, which has to contain the quoted order and a amm, and
+/// reports how the quoted order fills.
+pub fn quote_order_on_book(
+    book: &OrderBook,
+    searcher: Option<OrderWithStorageData<TopOfBlockOrder>>,
+    quoted_id: OrderId,
+    gas_token_0: U256,
+    current_block: u64
+) -> OrderQuote {
+    let (start_price, start_tick) = book
+        .amm()
+        .map(|amm| (*amm.current_price(), amm.current_tick()))
+        .unwrap_or_default();
+    let amount = book
+        .all_orders_iter()
+        .find(|order| order.order_id == quoted_id)
+        .map(|order| order.amount())
+        .unwrap_or_default();
+
+    let (solution, (end_price, end_tick, _)) =
+        BinarySearchStrategy::run_with_end_amm_state(book, searcher);
+
+    let outcome = solution
+        .limit
+        .iter()
+        .find(|outcome| outcome.id == quoted_id);
+
+    OrderQuote {
+        current_block,
+        angstrom_pool_id: book.id(),
+        fill_state: outcome.map(|outcome| outcome.outcome).unwrap_or_default(),
+        fill_amount: outcome
+            .map(|outcome| outcome.fill_amount(amount))
+            .unwrap_or_default(),
+        ucp: solution.ucp,
+        gas_token_0,
+        start_sqrt_price_x96: start_price,
+        end_sqrt_price_x96: end_price,
+        amm_price_impact: end_tick - start_tick
+    }
+}
+
 pub fn build_non_proposal_books(
     limit: Vec<BookOrder>,
     pool_snapshots: &HashMap<PoolId, (PoolId, BaselinePoolState)>
@@ -328,9 +601,96 @@ pub fn build_non_proposal_books(
         .collect()
 }
 
+/// picks the searcher order with the highest tob reward for every pool. ties
+/// are broken by the lowest order hash.
+pub fn best_searcher_per_pool(
+    searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>
+) -> HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>> {
+    searcher
+        .into_iter()
+        .fold(HashMap::new(), |mut acc, searcher| {
+            match acc.entry(searcher.pool_id) {
+                Entry::Vacant(v) => {
+                    v.insert(searcher);
+                }
+                Entry::Occupied(mut o) => {
+                    let current = o.get();
+                    // if this order on same pool_id has a higher tob reward or they are the
+                    // same and it has a lower order hash. replace
+                    if searcher.tob_reward > current.tob_reward
+                        || (searcher.tob_reward == current.tob_reward
+                            && searcher.order_id.hash < current.order_id.hash)
+                    {
+                        o.insert(searcher);
+                    }
+                }
+            };
+
+            acc
+        })
+}
+
 pub fn orders_sorted_by_pool_id(limit: Vec<BookOrder>) -> HashMap<PoolId, HashSet<BookOrder>> {
     limit.into_iter().fold(HashMap::new(), |mut acc, order| {
         acc.entry(order.pool_id).or_default().insert(order);
         acc
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::{orders::OrderFillState, sol_bindings::rpc_orders::ExactStandingOrder};
+
+    use super::*;
+    use crate::depth::single_position_amm;
+
+    /// a bid that spends `amount` of token1, paying at most the price at
+    /// `limit_tick`.
+    fn bid(amount: u128, limit_tick: i32) -> AllOrders {
+        // bids carry their price as t0 / t1
+        let price = Ray::from(SqrtPriceX96::at_tick(limit_tick).unwrap()).inv_ray_round(true);
+        AllOrders::ExactStanding(ExactStandingOrder {
+            exact_in: true,
+            amount,
+            min_price: price.into(),
+            asset_in: Address::with_last_byte(2),
+            asset_out: Address::with_last_byte(1),
+            ..Default::default()
+        })
+    }
+
+    fn quote(order: AllOrders) -> OrderQuote {
+        let pool_id = PoolId::default();
+        let order = quoted_book_order(order, pool_id, U256::ZERO, 3);
+        let quoted_id = order.order_id;
+        let book =
+            build_book(pool_id, Some(single_position_amm(10u128.pow(18))), HashSet::from([order]));
+
+        quote_order_on_book(&book, None, quoted_id, U256::ZERO, 3)
+    }
+
+    #[test]
+    fn quotes_a_fill_against_the_amm() {
+        let amount = 10u128.pow(15);
+        let quote = quote(bid(amount, 500));
+
+        assert_eq!(quote.current_block, 3);
+        assert_eq!(quote.fill_state, OrderFillState::CompleteFill);
+        assert_eq!(quote.fill_amount, amount);
+        // buying token0 from the amm pushes its price up, a 0.1% trade against
+        // this pool moves it by ~20 ticks
+        assert!(quote.end_sqrt_price_x96 > quote.start_sqrt_price_x96);
+        assert!((1..=40).contains(&quote.amm_price_impact), "{}", quote.amm_price_impact);
+        assert!(!quote.ucp.is_zero());
+    }
+
+    #[test]
+    fn quotes_no_fill_below_the_amm_price() {
+        let quote = quote(bid(10u128.pow(15), -500));
+
+        assert_eq!(quote.fill_state, OrderFillState::Unfilled);
+        assert_eq!(quote.fill_amount, 0);
+        assert_eq!(quote.amm_price_impact, 0);
+        assert_eq!(quote.start_sqrt_price_x96, quote.end_sqrt_price_x96);
+    }
+}
@@ -9,7 +9,9 @@ use std::{
 
 use alloy::primitives::{Address, FixedBytes};
 use angstrom_eth::manager::EthEvent;
-use angstrom_types::{consensus::StromConsensusEvent, primitive::PeerId};
+use angstrom_types::{
+    consensus::StromConsensusEvent, primitive::PeerId, trace_context::TraceContext
+};
 use futures::{FutureExt, StreamExt};
 use itertools::Itertools;
 use once_cell::unsync::Lazy;
@@ -24,7 +26,7 @@ use tracing::error;
 
 use crate::{
     CachedPeer, CachedPeers, NetworkOrderEvent, StromMessage, StromNetworkHandle,
-    StromNetworkHandleMsg, Swarm, SwarmEvent
+    StromNetworkHandleMsg, StromPeerInfo, Swarm, SwarmEvent
 };
 
 // use a thread local lazy to avoid synchronization overhead since path is
@@ -211,6 +213,27 @@ impl<DB: Unpin, P: Peers + Unpin> StromNetworkManager<DB, P> {
         self.handle.clone()
     }
 
+    fn peers(&mut self) -> Vec<StromPeerInfo> {
+        let sessions = self
+            .swarm
+            .sessions()
+            .active_sessions
+            .values()
+            .map(|session| (session.remote_id, session.socket_addr))
+            .collect::<Vec<_>>();
+
+        let peers = self.swarm.state_mut().peers_mut();
+        sessions
+            .into_iter()
+            .map(|(peer_id, addr)| StromPeerInfo {
+                peer_id,
+                addr,
+                reputation: peers.reputation(&peer_id).unwrap_or_default(),
+                banned: peers.is_banned(&peer_id)
+            })
+            .collect()
+    }
+
     // Handler for received messages from a handle
     fn on_handle_message(&mut self, msg: StromNetworkHandleMsg) {
         tracing::trace!(?msg, "received network message");
@@ -241,6 +264,20 @@ impl<DB: Unpin, P: Peers + Unpin> StromNetworkManager<DB, P> {
             StromNetworkHandleMsg::DisconnectPeer(id, reason) => {
                 self.swarm_mut().sessions_mut().disconnect(id, reason);
             }
+            StromNetworkHandleMsg::Peers(tx) => {
+                let _ = tx.send(self.peers());
+            }
+            StromNetworkHandleMsg::BanPeer(peer_id) => {
+                tracing::info!(?peer_id, "banning peer");
+                self.swarm.state_mut().peers_mut().ban_peer(peer_id);
+                self.swarm
+                    .sessions_mut()
+                    .disconnect(peer_id, Some(DisconnectReason::DisconnectRequested));
+            }
+            StromNetworkHandleMsg::UnbanPeer(peer_id) => {
+                tracing::info!(?peer_id, "unbanning peer");
+                self.swarm.state_mut().peers_mut().unban_peer(peer_id);
+            }
         }
     }
 
@@ -341,11 +378,14 @@ impl<DB: Unpin, P: Peers + Unpin> Future for StromNetworkManager<DB, P> {
                                     let _ = tx.send(StromConsensusEvent::Proposal(address, a));
                                 });
                             }
-                            StromMessage::PropagatePooledOrders(a) => {
+                            StromMessage::PropagatePooledOrders(a, trace) => {
                                 self.to_pool_manager.as_ref().inspect(|tx| {
                                     let _ = tx.send(NetworkOrderEvent::IncomingOrders {
                                         peer_id,
-                                        orders: a
+                                        orders: a,
+                                        // only the bounded w3c context of what the peer sent is
+                                        // kept
+                                        trace: trace.and_then(TraceContext::sanitized)
                                     });
                                 });
                             }
@@ -357,6 +397,20 @@ impl<DB: Unpin, P: Peers + Unpin> Future for StromNetworkManager<DB, P> {
                                     });
                                 });
                             }
+                            StromMessage::OrderReplacement(order) => {
+                                self.to_pool_manager.as_ref().inspect(|tx| {
+                                    let _ =
+                                        tx.send(NetworkOrderEvent::ReplaceOrder { peer_id, order });
+                                });
+                            }
+                            StromMessage::PropagateRoutes(routes) => {
+                                self.to_pool_manager.as_ref().inspect(|tx| {
+                                    let _ = tx.send(NetworkOrderEvent::IncomingRoutes {
+                                        peer_id,
+                                        routes
+                                    });
+                                });
+                            }
                             StromMessage::Status(_) => {}
                         }
                     }
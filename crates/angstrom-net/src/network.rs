@@ -1,10 +1,17 @@
-use std::sync::{Arc, atomic::AtomicUsize};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, atomic::AtomicUsize}
+};
 
 use angstrom_types::{
-    orders::CancelOrderRequest, primitive::PeerId, sol_bindings::grouped_orders::AllOrders
+    orders::{CancelOrderRequest, RoutedOrder},
+    primitive::PeerId,
+    sol_bindings::grouped_orders::AllOrders,
+    trace_context::TraceContext
 };
 use reth_metrics::common::mpsc::UnboundedMeteredSender;
 use reth_network::DisconnectReason;
+use serde::{Deserialize, Serialize};
 use tokio::sync::{
     mpsc::{UnboundedSender, unbounded_channel},
     oneshot
@@ -73,6 +80,22 @@ impl StromNetworkHandle {
         self.send_to_network_manager(StromNetworkHandleMsg::RemovePeer(peer))
     }
 
+    /// The peers we currently have a session with.
+    pub async fn peers(&self) -> Result<Vec<StromPeerInfo>, oneshot::error::RecvError> {
+        let (tx, rx) = oneshot::channel();
+        self.send_to_network_manager(StromNetworkHandleMsg::Peers(tx));
+        rx.await
+    }
+
+    /// Bans the peer and disconnects it if connected.
+    pub fn ban_peer(&self, peer: PeerId) {
+        self.send_to_network_manager(StromNetworkHandleMsg::BanPeer(peer))
+    }
+
+    pub fn unban_peer(&self, peer: PeerId) {
+        self.send_to_network_manager(StromNetworkHandleMsg::UnbanPeer(peer))
+    }
+
     pub fn peer_count(&self) -> usize {
         self.inner
             .num_active_peers
@@ -91,8 +114,10 @@ struct StromNetworkInner {
 /// All events related to orders emitted by the network.
 #[derive(Debug, Clone, PartialEq)]
 pub enum NetworkOrderEvent {
-    IncomingOrders { peer_id: PeerId, orders: Vec<AllOrders> },
-    CancelOrder { peer_id: PeerId, request: CancelOrderRequest }
+    IncomingOrders { peer_id: PeerId, orders: Vec<AllOrders>, trace: Option<TraceContext> },
+    CancelOrder { peer_id: PeerId, request: CancelOrderRequest },
+    ReplaceOrder { peer_id: PeerId, order: AllOrders },
+    IncomingRoutes { peer_id: PeerId, routes: Vec<RoutedOrder> }
 }
 
 #[derive(Debug)]
@@ -116,6 +141,22 @@ pub enum StromNetworkHandleMsg {
 
     /// Apply a reputation change to the given peer.
     ReputationChange(PeerId, ReputationChangeKind),
+    /// The peers with an active session.
+    Peers(oneshot::Sender<Vec<StromPeerInfo>>),
+    /// Bans the peer and disconnects its session.
+    BanPeer(PeerId),
+    /// Lifts the ban of the peer.
+    UnbanPeer(PeerId),
     /// Gracefully shutdown network
     Shutdown(oneshot::Sender<()>)
 }
+
+/// A peer that we have a session with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StromPeerInfo {
+    pub peer_id:    PeerId,
+    pub addr:       SocketAddr,
+    pub reputation: i32,
+    pub banned:     bool
+}
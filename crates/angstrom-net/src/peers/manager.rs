@@ -6,7 +6,9 @@ use reth_network_peers::PeerId;
 use tracing::trace;
 
 pub use super::reputation::ReputationChangeWeights;
-use super::reputation::{ReputationChangeKind, is_banned_reputation};
+use super::reputation::{
+    DEFAULT_REPUTATION, Reputation, ReputationChangeKind, is_banned_reputation
+};
 
 /// Maintains the state of _all_ the peers known to the network.
 ///
@@ -76,6 +78,50 @@ impl PeersManager {
         }
     }
 
+    /// Tracks the peer of a new session, peers keep their reputation between
+    /// sessions.
+    pub fn on_session_established(&mut self, peer_id: PeerId) {
+        self.peers
+            .entry(peer_id)
+            .or_insert(Peer {
+                reputation: DEFAULT_REPUTATION,
+                kind:       PeerKind::Basic,
+                connected:  false
+            })
+            .connected = true;
+    }
+
+    pub fn on_session_closed(&mut self, peer_id: PeerId) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.connected = false;
+        }
+    }
+
+    pub fn reputation(&self, peer_id: &PeerId) -> Option<Reputation> {
+        self.peers.get(peer_id).map(|peer| peer.reputation)
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        self.ban_list.is_banned_peer(peer_id)
+    }
+
+    /// Bans the peer regardless of its reputation. The caller is responsible
+    /// for disconnecting the session.
+    pub fn ban_peer(&mut self, peer_id: PeerId) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.connected = false;
+        }
+        self.ban_list.ban_peer(peer_id);
+    }
+
+    /// Lifts the ban and resets the reputation of the peer.
+    pub fn unban_peer(&mut self, peer_id: PeerId) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.reputation = DEFAULT_REPUTATION;
+        }
+        self.ban_list.unban_peer(&peer_id);
+    }
+
     /// Removes the tracked node from the trusted set.
     pub fn remove_peer_from_trusted_set(&mut self, peer_id: PeerId) {
         let Entry::Occupied(mut entry) = self.peers.entry(peer_id) else { return };
@@ -237,3 +283,24 @@ pub enum PeerAction {
     /// Emit peerRemoved event
     PeerRemoved(PeerId)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ban_and_unban_peer() {
+        let mut peers = PeersManager::new();
+        let peer_id = PeerId::random();
+        peers.on_session_established(peer_id);
+        peers.change_weight(peer_id, ReputationChangeKind::BadOrder);
+        assert!(peers.reputation(&peer_id) < Some(DEFAULT_REPUTATION));
+
+        peers.ban_peer(peer_id);
+        assert!(peers.is_banned(&peer_id));
+
+        peers.unban_peer(peer_id);
+        assert!(!peers.is_banned(&peer_id));
+        assert_eq!(peers.reputation(&peer_id), Some(DEFAULT_REPUTATION));
+    }
+}
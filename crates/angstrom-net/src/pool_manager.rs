@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     num::NonZeroUsize,
     pin::Pin,
     sync::Arc,
@@ -10,20 +10,24 @@ use alloy::primitives::{Address, B256, FixedBytes};
 use angstrom_eth::manager::EthEvent;
 use angstrom_types::{
     block_sync::BlockSyncConsumer,
-    orders::{CancelOrderRequest, OrderLocation, OrderOrigin, OrderStatus},
+    orders::{
+        CancelOrderRequest, OrderEvent, OrderLocation, OrderOrigin, OrderStatus, PriceObservation,
+        RoutedOrder, TriggerCondition
+    },
     primitive::{NewInitializedPool, OrderValidationError, PeerId, PoolId},
-    sol_bindings::grouped_orders::AllOrders
+    sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
 };
 use futures::{Future, FutureExt, StreamExt};
 use order_pool::{
-    OrderIndexer, OrderPoolHandle, PoolConfig, PoolInnerEvent, PoolManagerUpdate,
-    order_storage::OrderStorage
+    OrderFilter, OrderIndexer, OrderPoolAdminHandle, OrderPoolHandle, PoolConfig, PoolInnerEvent,
+    PoolManagerUpdate, order_storage::OrderStorage
 };
 use reth_metrics::common::mpsc::UnboundedMeteredReceiver;
 use reth_tasks::TaskSpawner;
 use telemetry_recorder::telemetry_event;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, error::SendError};
 use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
+use tracing::Span;
 use validation::order::{OrderValidationResults, OrderValidatorHandle};
 
 use crate::{LruCache, NetworkOrderEvent, StromMessage, StromNetworkEvent, StromNetworkHandle};
@@ -42,18 +46,51 @@ pub struct PoolHandle {
 
 #[derive(Debug)]
 pub enum OrderCommand {
-    // new orders
-    NewOrder(OrderOrigin, AllOrders, tokio::sync::oneshot::Sender<OrderValidationResults>),
+    // new orders, with the span of the caller so that the pool continues its
+    // trace
+    NewOrder(OrderOrigin, AllOrders, tokio::sync::oneshot::Sender<OrderValidationResults>, Span),
+    ReplaceOrder(
+        OrderOrigin,
+        AllOrders,
+        tokio::sync::oneshot::Sender<OrderValidationResults>,
+        Span
+    ),
+    ConditionalOrder(
+        OrderOrigin,
+        AllOrders,
+        TriggerCondition,
+        tokio::sync::oneshot::Sender<OrderValidationResults>,
+        Span
+    ),
+    ConditionalOrders(Address, tokio::sync::oneshot::Sender<Vec<(TriggerCondition, AllOrders)>>),
+    RoutedOrder(
+        OrderOrigin,
+        RoutedOrder,
+        tokio::sync::oneshot::Sender<Result<B256, OrderValidationError>>,
+        Span
+    ),
     CancelOrder(CancelOrderRequest, tokio::sync::oneshot::Sender<bool>),
     PendingOrders(Address, tokio::sync::oneshot::Sender<Vec<AllOrders>>),
     OrdersByPool(FixedBytes<32>, OrderLocation, tokio::sync::oneshot::Sender<Vec<AllOrders>>),
-    OrderStatus(B256, tokio::sync::oneshot::Sender<Option<OrderStatus>>)
+    OrderStatus(B256, tokio::sync::oneshot::Sender<Option<OrderStatus>>),
+    OrderHistory(B256, tokio::sync::oneshot::Sender<Vec<OrderEvent>>),
+    // operator commands
+    AdminOrders(OrderFilter, tokio::sync::oneshot::Sender<Vec<OrderWithStorageData<AllOrders>>>),
+    DropOrders(OrderFilter, tokio::sync::oneshot::Sender<Vec<B256>>),
+    RevalidateUser(Address, tokio::sync::oneshot::Sender<bool>),
+    // orders the matching engine excluded from a bundle
+    InvalidateOrders(Vec<B256>)
 }
 
 impl PoolHandle {
     fn send(&self, cmd: OrderCommand) -> Result<(), SendError<OrderCommand>> {
         self.manager_tx.send(cmd)
     }
+
+    /// removes orders that made a bundle simulation fail from the pool.
+    pub fn invalidate_orders(&self, hashes: Vec<B256>) {
+        let _ = self.send(OrderCommand::InvalidateOrders(hashes));
+    }
 }
 
 impl OrderPoolHandle for PoolHandle {
@@ -64,7 +101,7 @@ impl OrderPoolHandle for PoolHandle {
     ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let order_hash = order.order_hash();
-        let _ = self.send(OrderCommand::NewOrder(origin, order, tx));
+        let _ = self.send(OrderCommand::NewOrder(origin, order, tx, Span::current()));
         rx.map(move |res| {
             let Ok(result) = res else {
                 return Err(OrderValidationError::Unknown {
@@ -79,6 +116,77 @@ impl OrderPoolHandle for PoolHandle {
         })
     }
 
+    fn replace_order(
+        &self,
+        origin: OrderOrigin,
+        order: AllOrders
+    ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let order_hash = order.order_hash();
+        let _ = self.send(OrderCommand::ReplaceOrder(origin, order, tx, Span::current()));
+        rx.map(move |res| {
+            let Ok(result) = res else {
+                return Err(OrderValidationError::Unknown {
+                    err: "a channel failed on the backend".to_string()
+                });
+            };
+            match result {
+                OrderValidationResults::TransitionedToBlock(_)
+                | OrderValidationResults::Valid(_) => Ok(order_hash),
+                OrderValidationResults::Invalid { error, .. } => Err(error)
+            }
+        })
+    }
+
+    fn new_conditional_order(
+        &self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        trigger: TriggerCondition
+    ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let order_hash = order.order_hash();
+        let _ =
+            self.send(OrderCommand::ConditionalOrder(origin, order, trigger, tx, Span::current()));
+        rx.map(move |res| {
+            let Ok(result) = res else {
+                return Err(OrderValidationError::Unknown {
+                    err: "a channel failed on the backend".to_string()
+                });
+            };
+            match result {
+                OrderValidationResults::TransitionedToBlock(_)
+                | OrderValidationResults::Valid(_) => Ok(order_hash),
+                OrderValidationResults::Invalid { error, .. } => Err(error)
+            }
+        })
+    }
+
+    fn new_routed_order(
+        &self,
+        origin: OrderOrigin,
+        route: RoutedOrder
+    ) -> impl Future<Output = Result<B256, OrderValidationError>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self.send(OrderCommand::RoutedOrder(origin, route, tx, Span::current()));
+        rx.map(|res| {
+            res.unwrap_or_else(|_| {
+                Err(OrderValidationError::Unknown {
+                    err: "a channel failed on the backend".to_string()
+                })
+            })
+        })
+    }
+
+    fn conditional_orders(
+        &self,
+        sender: Address
+    ) -> impl Future<Output = Vec<(TriggerCondition, AllOrders)>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self.send(OrderCommand::ConditionalOrders(sender, tx));
+        rx.map(|res| res.unwrap_or_default())
+    }
+
     fn subscribe_orders(&self) -> BroadcastStream<PoolManagerUpdate> {
         BroadcastStream::new(self.pool_manager_tx.subscribe())
     }
@@ -109,6 +217,18 @@ impl OrderPoolHandle for PoolHandle {
         rx.map(|v| v.ok().flatten())
     }
 
+    fn fetch_order_history(
+        &self,
+        order_hash: B256
+    ) -> impl Future<Output = Vec<OrderEvent>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self
+            .manager_tx
+            .send(OrderCommand::OrderHistory(order_hash, tx));
+
+        rx.map(|v| v.unwrap_or_default())
+    }
+
     fn pending_orders(&self, sender: Address) -> impl Future<Output = Vec<AllOrders>> + Send {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let _ = self.send(OrderCommand::PendingOrders(sender, tx)).is_ok();
@@ -122,6 +242,29 @@ impl OrderPoolHandle for PoolHandle {
     }
 }
 
+impl OrderPoolAdminHandle for PoolHandle {
+    fn admin_orders(
+        &self,
+        filter: OrderFilter
+    ) -> impl Future<Output = Vec<OrderWithStorageData<AllOrders>>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self.send(OrderCommand::AdminOrders(filter, tx));
+        rx.map(|res| res.unwrap_or_default())
+    }
+
+    fn drop_orders(&self, filter: OrderFilter) -> impl Future<Output = Vec<B256>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self.send(OrderCommand::DropOrders(filter, tx));
+        rx.map(|res| res.unwrap_or_default())
+    }
+
+    fn revalidate_user(&self, user: Address) -> impl Future<Output = bool> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self.send(OrderCommand::RevalidateUser(user, tx));
+        rx.map(|res| res.unwrap_or(false))
+    }
+}
+
 pub struct PoolManagerBuilder<V, GlobalSync>
 where
     V: OrderValidatorHandle,
@@ -134,6 +277,7 @@ where
     strom_network_events: UnboundedReceiverStream<StromNetworkEvent>,
     eth_network_events:   UnboundedReceiverStream<EthEvent>,
     order_events:         UnboundedMeteredReceiver<NetworkOrderEvent>,
+    price_observations:   Option<UnboundedReceiverStream<PriceObservation>>,
     config:               PoolConfig
 }
 
@@ -158,10 +302,17 @@ where
             network_handle,
             validator,
             order_storage,
+            price_observations: None,
             config: Default::default()
         }
     }
 
+    /// the pool prices that conditional orders are triggered on.
+    pub fn with_price_observations(mut self, rx: UnboundedReceiver<PriceObservation>) -> Self {
+        self.price_observations = Some(UnboundedReceiverStream::new(rx));
+        self
+    }
+
     pub fn with_config(mut self, config: PoolConfig) -> Self {
         self.config = config;
         self
@@ -206,6 +357,7 @@ where
                 order_indexer:        inner,
                 network:              self.network_handle,
                 command_rx:           rx,
+                price_observations:   self.price_observations,
                 global_sync:          self.global_sync
             })
         );
@@ -235,6 +387,8 @@ where
     command_rx:           UnboundedReceiverStream<OrderCommand>,
     /// Incoming events from the ProtocolManager.
     order_events:         UnboundedMeteredReceiver<NetworkOrderEvent>,
+    /// Pool prices from the quoter, used to trigger conditional orders.
+    price_observations:   Option<UnboundedReceiverStream<PriceObservation>>,
     /// All the connected peers.
     peer_to_info:         HashMap<PeerId, StromPeer>
 }
@@ -246,12 +400,61 @@ where
 {
     fn on_command(&mut self, cmd: OrderCommand) {
         match cmd {
-            OrderCommand::NewOrder(origin, order, validation_response) => {
+            OrderCommand::NewOrder(origin, order, validation_response, span) => {
                 let blocknum = self.global_sync.current_block_number();
                 telemetry_event!(blocknum, origin, order.clone());
 
-                self.order_indexer
-                    .new_rpc_order(OrderOrigin::External, order, validation_response)
+                span.in_scope(|| {
+                    self.order_indexer.new_rpc_order(
+                        OrderOrigin::External,
+                        order,
+                        validation_response
+                    )
+                })
+            }
+            OrderCommand::ReplaceOrder(origin, order, validation_response, span) => {
+                let blocknum = self.global_sync.current_block_number();
+                telemetry_event!(blocknum, origin, order.clone());
+
+                span.in_scope(|| {
+                    self.order_indexer.replace_rpc_order(
+                        OrderOrigin::External,
+                        order,
+                        validation_response
+                    )
+                })
+            }
+            OrderCommand::ConditionalOrder(origin, order, trigger, validation_response, span) => {
+                let blocknum = self.global_sync.current_block_number();
+                telemetry_event!(blocknum, origin, order.clone());
+
+                span.in_scope(|| {
+                    self.order_indexer.new_conditional_order(
+                        OrderOrigin::External,
+                        order,
+                        trigger,
+                        validation_response
+                    )
+                })
+            }
+            OrderCommand::RoutedOrder(origin, route, validation_response, span) => {
+                let blocknum = self.global_sync.current_block_number();
+                for leg in &route.legs {
+                    telemetry_event!(blocknum, origin, leg.clone());
+                }
+
+                span.in_scope(|| {
+                    self.order_indexer
+                        .new_rpc_route(origin, route, validation_response)
+                })
+            }
+            OrderCommand::ConditionalOrders(from, receiver) => {
+                let res = self.order_indexer.conditional_orders(from);
+                let _ = receiver.send(
+                    res.into_iter()
+                        .map(|(trigger, order)| (trigger, order.order))
+                        .collect()
+                );
             }
             OrderCommand::CancelOrder(req, receiver) => {
                 let blocknum = self.global_sync.current_block_number();
@@ -271,20 +474,47 @@ where
                 let res = self.order_indexer.order_status(order_hash);
                 let _ = tx.send(res);
             }
+            OrderCommand::OrderHistory(order_hash, tx) => {
+                let res = self.order_indexer.order_history(order_hash);
+                let _ = tx.send(res);
+            }
 
             OrderCommand::OrdersByPool(pool_id, location, tx) => {
                 let res = self.order_indexer.orders_by_pool(pool_id, location);
                 let _ = tx.send(res);
             }
+            OrderCommand::AdminOrders(filter, tx) => {
+                let res = self.order_indexer.admin_orders(&filter);
+                let _ = tx.send(res);
+            }
+            OrderCommand::DropOrders(filter, tx) => {
+                tracing::info!(?filter, "dropping orders on operator request");
+                let res = self.order_indexer.drop_orders(&filter);
+                let _ = tx.send(res);
+            }
+            OrderCommand::RevalidateUser(user, tx) => {
+                let res = self.order_indexer.revalidate_user(user);
+                let _ = tx.send(res);
+            }
+            OrderCommand::InvalidateOrders(hashes) => {
+                let removed = self.order_indexer.invalidate_orders(&hashes);
+                tracing::info!(?removed, "invalidated orders that failed bundle simulation");
+            }
         }
     }
 
     fn on_eth_event(&mut self, eth: EthEvent, waker: Waker) {
         match eth {
-            EthEvent::NewBlockTransitions { block_number, filled_orders, address_changeset } => {
+            EthEvent::NewBlockTransitions {
+                block_number,
+                filled_orders,
+                fill_receipts,
+                address_changeset
+            } => {
                 self.order_indexer.start_new_block_processing(
                     block_number,
                     filled_orders,
+                    fill_receipts,
                     address_changeset
                 );
                 waker.clone().wake_by_ref();
@@ -317,13 +547,21 @@ where
 
     fn on_network_order_event(&mut self, event: NetworkOrderEvent) {
         match event {
-            NetworkOrderEvent::IncomingOrders { peer_id, orders } => {
+            NetworkOrderEvent::IncomingOrders { peer_id, orders, trace } => {
                 let block_num = self.global_sync.current_block_number();
 
                 orders.into_iter().for_each(|order| {
+                    let order_hash = order.order_hash();
+                    let span = tracing::error_span!("propagation", %order_hash, %peer_id);
+                    // continues the trace of the order on the sending node
+                    if let Some(trace) = &trace {
+                        trace.set_as_parent_of(&span);
+                    }
+                    let _span = span.entered();
+
                     self.peer_to_info
                         .get_mut(&peer_id)
-                        .map(|peer| peer.orders.insert(order.order_hash()));
+                        .map(|peer| peer.orders.insert(order_hash));
 
                     telemetry_event!(block_num, OrderOrigin::External, order.clone());
                     self.order_indexer.new_network_order(
@@ -342,6 +580,30 @@ where
                     self.broadcast_cancel_to_peers(request);
                 }
             }
+            NetworkOrderEvent::ReplaceOrder { peer_id, order } => {
+                self.peer_to_info
+                    .get_mut(&peer_id)
+                    .map(|peer| peer.orders.insert(order.order_hash()));
+
+                let block_num = self.global_sync.current_block_number();
+                telemetry_event!(block_num, OrderOrigin::External, order.clone());
+                self.order_indexer.replace_network_order(peer_id, order);
+            }
+            NetworkOrderEvent::IncomingRoutes { peer_id, routes } => {
+                let block_num = self.global_sync.current_block_number();
+
+                for route in routes {
+                    if let Some(peer) = self.peer_to_info.get_mut(&peer_id) {
+                        for leg in route.leg_hashes() {
+                            peer.orders.insert(leg);
+                        }
+                    }
+                    for leg in &route.legs {
+                        telemetry_event!(block_num, OrderOrigin::External, leg.clone());
+                    }
+                    self.order_indexer.new_network_route(peer_id, route);
+                }
+            }
         }
     }
 
@@ -360,12 +622,25 @@ where
                         )
                     }
                 );
+                // legs go out with their route, so the peer doesn't fill them on their own
+                let routes = self.order_indexer.routes();
+                let legs = routes
+                    .iter()
+                    .flat_map(|route| route.leg_hashes())
+                    .collect::<HashSet<_>>();
                 let all_orders = self
                     .order_indexer
                     .get_all_orders_with_parked()
-                    .into_all_orders();
+                    .into_all_orders()
+                    .into_iter()
+                    .filter(|order| !legs.contains(&order.order_hash()))
+                    .collect();
 
                 self.broadcast_order_to_peer(all_orders, peer_id);
+                if !routes.is_empty() {
+                    self.network
+                        .send_message(peer_id, StromMessage::PropagateRoutes(routes));
+                }
             }
             StromNetworkEvent::SessionClosed { peer_id, .. } => {
                 // remove the peer
@@ -383,6 +658,14 @@ where
             .into_iter()
             .filter_map(|order| match order {
                 PoolInnerEvent::Propagation(order) => Some(order),
+                PoolInnerEvent::ReplacementPropagation(order) => {
+                    self.broadcast_replacement_to_peers(order);
+                    None
+                }
+                PoolInnerEvent::RoutePropagation(route) => {
+                    self.broadcast_route_to_peers(route);
+                    None
+                }
                 PoolInnerEvent::BadOrderMessages(o) => {
                     o.into_iter().for_each(|peer| {
                         self.network.peer_reputation_change(
@@ -416,19 +699,47 @@ where
         }
     }
 
+    fn broadcast_replacement_to_peers(&mut self, order: AllOrders) {
+        let order_hash = order.order_hash();
+        for (peer_id, info) in self.peer_to_info.iter_mut() {
+            if !info.orders.contains(&order_hash) {
+                self.network
+                    .send_message(*peer_id, StromMessage::OrderReplacement(order.clone()));
+                info.orders.insert(order_hash);
+            }
+        }
+    }
+
+    fn broadcast_route_to_peers(&mut self, route: RoutedOrder) {
+        let legs = route.leg_hashes();
+        for (peer_id, info) in self.peer_to_info.iter_mut() {
+            if legs.iter().all(|leg| info.orders.contains(leg)) {
+                continue;
+            }
+            tracing::debug!(route_id = %route.route_id(), %peer_id, "propagating route");
+            self.network
+                .send_message(*peer_id, StromMessage::PropagateRoutes(vec![route.clone()]));
+            for leg in &legs {
+                info.orders.insert(*leg);
+            }
+        }
+    }
+
     fn broadcast_order_to_peer(&mut self, valid_orders: Vec<AllOrders>, peer: PeerId) {
         self.network
-            .send_message(peer, StromMessage::PropagatePooledOrders(valid_orders));
+            .send_message(peer, StromMessage::PropagatePooledOrders(valid_orders, None));
     }
 
     fn broadcast_orders_to_peers(&mut self, valid_orders: Vec<AllOrders>) {
         for order in valid_orders.iter() {
+            let trace = self.order_indexer.trace_of(&order.order_hash());
             for (peer_id, info) in self.peer_to_info.iter_mut() {
                 let order_hash = order.order_hash();
                 if !info.orders.contains(&order_hash) {
+                    tracing::debug!(%order_hash, %peer_id, "propagating order");
                     self.network.send_message(
                         *peer_id,
-                        StromMessage::PropagatePooledOrders(vec![order.clone()])
+                        StromMessage::PropagatePooledOrders(vec![order.clone()], trace.clone())
                     );
                     info.orders.insert(order_hash);
                 }
@@ -471,6 +782,12 @@ where
 
             // halt dealing with these till we have synced
             if this.global_sync.can_operate() {
+                if let Some(observations) = this.price_observations.as_mut() {
+                    while let Poll::Ready(Some(observation)) = observations.poll_next_unpin(cx) {
+                        this.order_indexer.on_price_observation(observation);
+                    }
+                }
+
                 // drain commands
                 if let Poll::Ready(Some(cmd)) = this.command_rx.poll_next_unpin(cx) {
                     this.on_command(cmd);
@@ -5,6 +5,7 @@ use std::{
 
 use angstrom_types::primitive::PeerId;
 use futures::{Stream, StreamExt};
+use reth_network::DisconnectReason;
 
 use crate::{
     SessionEvent,
@@ -54,8 +55,19 @@ impl<DB: Unpin> Swarm<DB> {
             SessionEvent::ValidMessage { peer_id, message } => {
                 Some(SwarmEvent::ValidMessage { peer_id, msg: message.message })
             }
-            SessionEvent::Disconnected { peer_id } => Some(SwarmEvent::Disconnected { peer_id }),
+            SessionEvent::Disconnected { peer_id } => {
+                self.state.peers_mut().on_session_closed(peer_id);
+                Some(SwarmEvent::Disconnected { peer_id })
+            }
             SessionEvent::SessionEstablished { peer_id, .. } => {
+                if self.state.peers_mut().is_banned(&peer_id) {
+                    tracing::debug!(?peer_id, "rejecting session of banned peer");
+                    self.sessions
+                        .disconnect(peer_id, Some(DisconnectReason::DisconnectRequested));
+                    return None;
+                }
+                self.state.peers_mut().on_session_established(peer_id);
+
                 Some(SwarmEvent::SessionEstablished { peer_id })
             }
             _ => None
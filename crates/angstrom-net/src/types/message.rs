@@ -7,8 +7,9 @@ use alloy::{
 };
 use angstrom_types::{
     consensus::{PreProposal, PreProposalAggregation, Proposal},
-    orders::CancelOrderRequest,
-    sol_bindings::grouped_orders::AllOrders
+    orders::{CancelOrderRequest, RoutedOrder},
+    sol_bindings::grouped_orders::AllOrders,
+    trace_context::TraceContext
 };
 use reth_eth_wire::{Capability, protocol::Protocol};
 use reth_network_p2p::error::RequestError;
@@ -23,7 +24,10 @@ use crate::Status;
 // https://github.com/ethereum/go-ethereum/blob/30602163d5d8321fbc68afdcbbaf2362b2641bde/eth/protocols/eth/protocol.go#L50
 pub const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
 
-const STROM_CAPABILITY: Capability = Capability::new_static("strom", 1);
+/// bumped whenever the layout of a message changes, peers on another version
+/// can't decode our messages. Version 2 added the trace context of propagated
+/// orders and the routes of pre-proposals.
+const STROM_CAPABILITY: Capability = Capability::new_static("strom", 2);
 const STROM_PROTOCOL: Protocol = Protocol::new(STROM_CAPABILITY, 5);
 /// Represents message IDs for eth protocol messages.
 #[repr(u8)]
@@ -37,7 +41,11 @@ pub enum StromMessageID {
     BundleUnlockAttestation = 4,
     /// Propagation messages that broadcast new orders to all peers
     PropagatePooledOrders = 5,
-    OrderCancellation = 6
+    OrderCancellation = 6,
+    /// a order that replaces the pending order of the user with the same nonce
+    OrderReplacement  = 7,
+    /// multi-hop routes, the legs of a route are only propagated together
+    PropagateRoutes   = 8
 }
 
 impl Encodable for StromMessageID {
@@ -61,6 +69,8 @@ impl Decodable for StromMessageID {
             4 => StromMessageID::BundleUnlockAttestation,
             5 => StromMessageID::PropagatePooledOrders,
             6 => StromMessageID::OrderCancellation,
+            7 => StromMessageID::OrderReplacement,
+            8 => StromMessageID::PropagateRoutes,
             _ => return Err(alloy::rlp::Error::Custom("Invalid message ID"))
         };
         buf.advance(1);
@@ -125,9 +135,12 @@ pub enum StromMessage {
     Propose(Proposal),
     BundleUnlockAttestation(u64, Bytes),
 
-    // Propagation messages that broadcast new orders to all peers
-    PropagatePooledOrders(Vec<AllOrders>),
-    OrderCancellation(CancelOrderRequest)
+    // Propagation messages that broadcast new orders to all peers, with the
+    // trace the orders are part of on the sending node
+    PropagatePooledOrders(Vec<AllOrders>, Option<TraceContext>),
+    OrderCancellation(CancelOrderRequest),
+    OrderReplacement(AllOrders),
+    PropagateRoutes(Vec<RoutedOrder>)
 }
 impl StromMessage {
     /// Returns the message's ID.
@@ -138,8 +151,10 @@ impl StromMessage {
             StromMessage::PreProposeAgg(_) => StromMessageID::PreProposeAgg,
             StromMessage::Propose(_) => StromMessageID::Propose,
             StromMessage::BundleUnlockAttestation(..) => StromMessageID::BundleUnlockAttestation,
-            StromMessage::PropagatePooledOrders(_) => StromMessageID::PropagatePooledOrders,
-            StromMessage::OrderCancellation(_) => StromMessageID::OrderCancellation
+            StromMessage::PropagatePooledOrders(..) => StromMessageID::PropagatePooledOrders,
+            StromMessage::OrderCancellation(_) => StromMessageID::OrderCancellation,
+            StromMessage::OrderReplacement(_) => StromMessageID::OrderReplacement,
+            StromMessage::PropagateRoutes(_) => StromMessageID::PropagateRoutes
         }
     }
 }
@@ -0,0 +1,212 @@
+use std::{collections::HashSet, sync::Arc};
+
+use alloy::signers::Signer;
+use alloy_primitives::{Address, B256, Bytes, U256};
+use angstrom_rpc_api::{
+    api::OrderApiClient,
+    types::{CallResult, OrderSubscriptionFilter, OrderSubscriptionKind, OrderSubscriptionResult}
+};
+use angstrom_types::{
+    orders::{CancelOrderRequest, OrderStatus, RouteHop, RoutedOrder},
+    primitive::{PoolId, TokenBalance},
+    quoting::{BookDepth, Slot0Update},
+    sol_bindings::grouped_orders::AllOrders
+};
+use futures::{Stream, StreamExt};
+use jsonrpsee::core::client::{ClientT, SubscriptionClientT};
+use pade::PadeEncode;
+use serde_json::Value;
+
+use crate::{error::ClientError, nonce::NonceManager};
+
+/// Typed wrapper over the `angstrom` rpc namespace.
+///
+/// Works over http for calls, subscriptions need a ws client. Clones share
+/// their [`NonceManager`].
+#[derive(Debug, Clone)]
+pub struct AngstromClient<C> {
+    inner:  C,
+    nonces: Arc<NonceManager>
+}
+
+impl<C> AngstromClient<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner, nonces: Arc::new(NonceManager::new()) }
+    }
+
+    /// the underlying rpc client, for calls that aren't wrapped.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn nonces(&self) -> &NonceManager {
+        &self.nonces
+    }
+}
+
+impl<C: ClientT + Send + Sync> AngstromClient<C> {
+    /// submits the order, returning its hash once the node accepted it.
+    pub async fn send_order(&self, order: AllOrders) -> Result<B256, ClientError> {
+        accepted(self.inner.send_order(order).await?)
+    }
+
+    /// submits the orders in one request, with a result for every order.
+    pub async fn send_orders(
+        &self,
+        orders: Vec<AllOrders>
+    ) -> Result<Vec<Result<B256, ClientError>>, ClientError> {
+        let results = self.inner.send_orders(orders).await?;
+        Ok(results.into_iter().map(accepted).collect())
+    }
+
+    /// replaces the pending order of the same user and nonce.
+    pub async fn replace_order(&self, order: AllOrders) -> Result<B256, ClientError> {
+        accepted(self.inner.replace_order(order).await?)
+    }
+
+    /// submits a multi-hop route, returning its id once all legs are valid.
+    pub async fn send_routed_order(&self, route: RoutedOrder) -> Result<B256, ClientError> {
+        accepted(self.inner.send_routed_order(route).await?)
+    }
+
+    /// the pools a route between the tokens goes through.
+    pub async fn find_route(
+        &self,
+        token_in: Address,
+        token_out: Address
+    ) -> Result<Vec<RouteHop>, ClientError> {
+        Ok(self.inner.find_route(token_in, token_out).await?)
+    }
+
+    /// cancels an order of the signer, returning whether it was cancelled.
+    pub async fn cancel_order<S>(&self, order_hash: B256, signer: &S) -> Result<bool, ClientError>
+    where
+        S: Signer + Sync + ?Sized
+    {
+        let user_address = signer.address();
+        let message = CancelOrderRequest::signing_message(user_address, order_hash);
+        let signature = signer.sign_message(message.as_bytes()).await?;
+        let request = CancelOrderRequest {
+            signature: Bytes::from(signature.pade_encode()),
+            user_address,
+            order_id: order_hash
+        };
+
+        Ok(self.inner.cancel_order(request).await?)
+    }
+
+    pub async fn order_status(&self, order_hash: B256) -> Result<OrderStatus, ClientError> {
+        let status = accepted_data(self.inner.order_status(order_hash).await?)?;
+        Ok(serde_json::from_value(status)?)
+    }
+
+    /// the gas of an order between the tokens in asset0 and in gas units.
+    pub async fn estimate_gas(
+        &self,
+        is_book: bool,
+        is_internal: bool,
+        token_0: Address,
+        token_1: Address
+    ) -> Result<(U256, u64), ClientError> {
+        self.inner
+            .estimate_gas(is_book, is_internal, token_0, token_1)
+            .await?
+            .map_err(ClientError::GasEstimate)
+    }
+
+    /// the balances of `user` in the tokens, including what their pending
+    /// orders committed.
+    pub async fn balances(
+        &self,
+        user: Address,
+        tokens: Vec<Address>
+    ) -> Result<Vec<TokenBalance>, ClientError> {
+        Ok(self.inner.balances(user, tokens).await?)
+    }
+
+    /// a nonce for a standing order of `user`, see [`NonceManager`].
+    pub async fn next_nonce(&self, user: Address) -> Result<u64, ClientError> {
+        self.nonces.next(&self.inner, user).await
+    }
+
+    /// makes a nonce from [`Self::next_nonce`] available again.
+    pub fn release_nonce(&self, user: Address, nonce: u64) {
+        self.nonces.release(user, nonce);
+    }
+}
+
+impl<C: SubscriptionClientT + Send + Sync> AngstromClient<C> {
+    /// order events of the given kinds that pass all the filters.
+    pub async fn subscribe_orders(
+        &self,
+        kinds: HashSet<OrderSubscriptionKind>,
+        filters: HashSet<OrderSubscriptionFilter>
+    ) -> Result<
+        impl Stream<Item = Result<OrderSubscriptionResult, ClientError>> + use<C>,
+        ClientError
+    > {
+        let subscription = self.inner.subscribe_orders(kinds, filters).await?;
+        Ok(subscription.map(|event| event.map_err(ClientError::from)))
+    }
+
+    /// every order event of `user`.
+    pub async fn subscribe_user(
+        &self,
+        user: Address
+    ) -> Result<
+        impl Stream<Item = Result<OrderSubscriptionResult, ClientError>> + use<C>,
+        ClientError
+    > {
+        self.subscribe_orders(
+            OrderSubscriptionKind::all().collect(),
+            HashSet::from([OrderSubscriptionFilter::ByAddress(user)])
+        )
+        .await
+    }
+
+    /// the balances of `user` in the tokens every time they change.
+    pub async fn subscribe_balances(
+        &self,
+        user: Address,
+        tokens: Vec<Address>
+    ) -> Result<impl Stream<Item = Result<Vec<TokenBalance>, ClientError>> + use<C>, ClientError>
+    {
+        let subscription = self.inner.subscribe_balances(user, tokens).await?;
+        Ok(subscription.map(|balances| balances.map_err(ClientError::from)))
+    }
+
+    /// slot0 of the pools after every change.
+    pub async fn subscribe_amm(
+        &self,
+        pools: HashSet<PoolId>
+    ) -> Result<impl Stream<Item = Result<Slot0Update, ClientError>> + use<C>, ClientError> {
+        let subscription = self.inner.subscribe_amm(pools).await?;
+        Ok(subscription.map(|update| update.map_err(ClientError::from)))
+    }
+
+    /// the book depth of the pool every time it changes.
+    pub async fn subscribe_book_depth(
+        &self,
+        pool_id: PoolId,
+        tick_grouping: i32,
+        levels: Option<usize>
+    ) -> Result<impl Stream<Item = Result<BookDepth, ClientError>> + use<C>, ClientError> {
+        let subscription = self
+            .inner
+            .subscribe_book_depth(pool_id, tick_grouping, levels)
+            .await?;
+        Ok(subscription.map(|depth| depth.map_err(ClientError::from)))
+    }
+}
+
+fn accepted_data(result: CallResult) -> Result<Value, ClientError> {
+    if result.is_ok() {
+        Ok(result.data)
+    } else {
+        Err(ClientError::Rejected { msg: result.msg, data: result.data })
+    }
+}
+
+fn accepted(result: CallResult) -> Result<B256, ClientError> {
+    Ok(serde_json::from_value(accepted_data(result)?)?)
+}
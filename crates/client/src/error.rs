@@ -0,0 +1,27 @@
+use alloy_primitives::Address;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("order is missing its {0}")]
+    Missing(&'static str),
+    #[error("order trades {0} for itself")]
+    SameAsset(Address),
+    #[error("order amount is zero")]
+    ZeroAmount,
+    #[error("partial order has a min amount in of {min} over its max of {max}")]
+    InvalidPartial { min: u128, max: u128 },
+    #[error("deadline {0} doesn't fit into 40 bits")]
+    DeadlineOverflow(u64),
+    #[error("no unused nonce for {0} after {1} attempts")]
+    NoNonce(Address, usize),
+    #[error("node rejected the call: {msg}")]
+    Rejected { msg: String, data: serde_json::Value },
+    #[error("gas estimation failed: {0}")]
+    GasEstimate(String),
+    #[error(transparent)]
+    Signer(#[from] alloy::signers::Error),
+    #[error(transparent)]
+    Rpc(#[from] jsonrpsee::core::ClientError),
+    #[error(transparent)]
+    Decode(#[from] serde_json::Error)
+}
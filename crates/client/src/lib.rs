@@ -0,0 +1,18 @@
+//! Client for building, signing and submitting angstrom orders.
+//!
+//! Orders are built with [`UserOrderBuilder`] and [`TobOrderBuilder`], priced
+//! with the helpers in [`price`] and signed under the angstrom EIP-712 domain
+//! by any alloy [`Signer`](alloy::signers::Signer), which includes every
+//! `AngstromMetaSigner`. [`AngstromClient`] submits them and follows them
+//! through the node.
+
+mod client;
+mod error;
+mod nonce;
+mod orders;
+pub mod price;
+
+pub use client::AngstromClient;
+pub use error::ClientError;
+pub use nonce::NonceManager;
+pub use orders::{SignableOrder, TobOrderBuilder, UserOrderBuilder, sign_order, sign_order_sync};
@@ -0,0 +1,61 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy_primitives::Address;
+use angstrom_rpc_api::api::OrderApiClient;
+use jsonrpsee::core::client::ClientT;
+use parking_lot::Mutex;
+
+use crate::error::ClientError;
+
+/// how often `validNonce` is asked before giving up on finding a nonce that
+/// wasn't handed out yet.
+const MAX_NONCE_ATTEMPTS: usize = 16;
+
+/// Hands out nonces for standing orders.
+///
+/// Angstrom nonces are unordered, `validNonce` returns a random one the user
+/// hasn't used on chain yet. Orders that are signed but not yet landed don't
+/// use up their nonce, so the nonces handed out are reserved here until
+/// they're released, to not sign two orders with the same one.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    reserved: Mutex<HashMap<Address, HashSet<u64>>>
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a nonce for `user` that is unused on chain and not reserved.
+    pub async fn next<C>(&self, client: &C, user: Address) -> Result<u64, ClientError>
+    where
+        C: ClientT + Sync
+    {
+        for _ in 0..MAX_NONCE_ATTEMPTS {
+            let nonce = client.valid_nonce(user).await?;
+            if self.reserved.lock().entry(user).or_default().insert(nonce) {
+                return Ok(nonce);
+            }
+        }
+
+        Err(ClientError::NoNonce(user, MAX_NONCE_ATTEMPTS))
+    }
+
+    /// makes the nonce available again, eg when the order using it was
+    /// rejected, cancelled or landed.
+    pub fn release(&self, user: Address, nonce: u64) {
+        let mut reserved = self.reserved.lock();
+        if let Some(nonces) = reserved.get_mut(&user) {
+            nonces.remove(&nonce);
+            if nonces.is_empty() {
+                reserved.remove(&user);
+            }
+        }
+    }
+
+    /// the nonces of `user` that are reserved.
+    pub fn reserved(&self, user: Address) -> HashSet<u64> {
+        self.reserved.lock().get(&user).cloned().unwrap_or_default()
+    }
+}
@@ -0,0 +1,567 @@
+//! Builders for every [`AllOrders`] variant and signing of them.
+
+use alloy::{
+    primitives::{Address, B256, Bytes, aliases::U40},
+    signers::{Signer, SignerSync},
+    sol_types::Eip712Domain
+};
+use angstrom_types::{
+    matching::Ray,
+    sol_bindings::{
+        grouped_orders::AllOrders,
+        rpc_orders::{
+            ExactFlashOrder, ExactStandingOrder, OmitOrderMeta, OrderMeta, PartialFlashOrder,
+            PartialStandingOrder, TopOfBlockOrder
+        }
+    }
+};
+use pade::PadeEncode;
+
+use crate::{
+    error::ClientError,
+    price::{asset0_as_input, asset0_as_output, zero_for_one}
+};
+
+/// An order that is signed over its EIP-712 hash without the [`OrderMeta`].
+pub trait SignableOrder {
+    fn signing_hash(&self, domain: &Eip712Domain) -> B256;
+
+    fn set_meta(&mut self, meta: OrderMeta);
+}
+
+macro_rules! signable_order {
+    ($($order:ty),*) => {
+        $(
+            impl SignableOrder for $order {
+                fn signing_hash(&self, domain: &Eip712Domain) -> B256 {
+                    self.no_meta_eip712_signing_hash(domain)
+                }
+
+                fn set_meta(&mut self, meta: OrderMeta) {
+                    self.meta = meta;
+                }
+            }
+        )*
+    };
+}
+
+signable_order!(
+    ExactStandingOrder,
+    PartialStandingOrder,
+    ExactFlashOrder,
+    PartialFlashOrder,
+    TopOfBlockOrder
+);
+
+impl SignableOrder for AllOrders {
+    fn signing_hash(&self, domain: &Eip712Domain) -> B256 {
+        match self {
+            Self::ExactStanding(order) => order.signing_hash(domain),
+            Self::PartialStanding(order) => order.signing_hash(domain),
+            Self::ExactFlash(order) => order.signing_hash(domain),
+            Self::PartialFlash(order) => order.signing_hash(domain),
+            Self::TOB(order) => order.signing_hash(domain)
+        }
+    }
+
+    fn set_meta(&mut self, meta: OrderMeta) {
+        match self {
+            Self::ExactStanding(order) => order.set_meta(meta),
+            Self::PartialStanding(order) => order.set_meta(meta),
+            Self::ExactFlash(order) => order.set_meta(meta),
+            Self::PartialFlash(order) => order.set_meta(meta),
+            Self::TOB(order) => order.set_meta(meta)
+        }
+    }
+}
+
+/// Signs the order under `domain`. Works with signers that can only sign
+/// asynchronously, such as a Ledger or a remote HSM.
+pub async fn sign_order<O, S>(
+    mut order: O,
+    domain: &Eip712Domain,
+    signer: &S
+) -> Result<O, ClientError>
+where
+    O: SignableOrder,
+    S: Signer + Sync + ?Sized
+{
+    let signature = signer.sign_hash(&order.signing_hash(domain)).await?;
+    order.set_meta(OrderMeta {
+        isEcdsa:   true,
+        from:      signer.address(),
+        signature: signature.pade_encode().into()
+    });
+
+    Ok(order)
+}
+
+/// [`sign_order`] for signers that hold their key locally.
+pub fn sign_order_sync<O, S>(
+    mut order: O,
+    domain: &Eip712Domain,
+    signer: &S
+) -> Result<O, ClientError>
+where
+    O: SignableOrder,
+    S: SignerSync + Signer + ?Sized
+{
+    let signature = signer.sign_hash_sync(&order.signing_hash(domain))?;
+    order.set_meta(OrderMeta {
+        isEcdsa:   true,
+        from:      signer.address(),
+        signature: signature.pade_encode().into()
+    });
+
+    Ok(order)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Validity {
+    Standing { nonce: u64, deadline: u64 },
+    Flash { block: u64 }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Amount {
+    ExactIn(u128),
+    ExactOut(u128),
+    Partial { max_in: u128, min_in: Option<u128> }
+}
+
+/// Builds any of the four book orders.
+///
+/// The validity, amount, assets, min price and max fee have to be set, the
+/// rest is optional. A recipient of [`Address::ZERO`] sends the output to the
+/// signer.
+#[derive(Debug, Clone, Default)]
+pub struct UserOrderBuilder {
+    validity:     Option<Validity>,
+    amount:       Option<Amount>,
+    assets:       Option<(Address, Address)>,
+    min_price:    Option<Ray>,
+    max_fee:      Option<u128>,
+    fee_on_top:   bool,
+    recipient:    Address,
+    use_internal: bool,
+    hook_data:    Bytes
+}
+
+impl UserOrderBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// an order that rests in the book until `deadline`, a unix timestamp.
+    /// Nonces can be fetched with [`crate::AngstromClient::next_nonce`].
+    pub fn standing(self, nonce: u64, deadline: u64) -> Self {
+        Self { validity: Some(Validity::Standing { nonce, deadline }), ..self }
+    }
+
+    /// a kill or fill order that is only valid in `block`.
+    pub fn flash(self, block: u64) -> Self {
+        Self { validity: Some(Validity::Flash { block }), ..self }
+    }
+
+    /// sells exactly `amount` of the input asset.
+    pub fn exact_in(self, amount: u128) -> Self {
+        Self { amount: Some(Amount::ExactIn(amount)), ..self }
+    }
+
+    /// buys exactly `amount` of the output asset.
+    pub fn exact_out(self, amount: u128) -> Self {
+        Self { amount: Some(Amount::ExactOut(amount)), ..self }
+    }
+
+    /// sells up to `max_amount_in` of the input asset. By default the order
+    /// has to fill at least its max fee.
+    pub fn partial(self, max_amount_in: u128) -> Self {
+        Self { amount: Some(Amount::Partial { max_in: max_amount_in, min_in: None }), ..self }
+    }
+
+    /// the least amount a partial order has to fill.
+    pub fn min_amount_in(self, min_amount_in: u128) -> Self {
+        let amount = match self.amount {
+            Some(Amount::Partial { max_in, .. }) => {
+                Some(Amount::Partial { max_in, min_in: Some(min_amount_in) })
+            }
+            amount => amount
+        };
+
+        Self { amount, ..self }
+    }
+
+    pub fn assets(self, asset_in: Address, asset_out: Address) -> Self {
+        Self { assets: Some((asset_in, asset_out)), ..self }
+    }
+
+    /// the least amount of the output asset the order takes per input asset,
+    /// see [`crate::price`].
+    pub fn min_price(self, min_price: Ray) -> Self {
+        Self { min_price: Some(min_price), ..self }
+    }
+
+    /// the most the order pays for gas, in asset0.
+    pub fn max_extra_fee_asset0(self, max_fee: u128) -> Self {
+        Self { max_fee: Some(max_fee), ..self }
+    }
+
+    /// adds the max fee, at the min price, to the amount of the order so that
+    /// fees don't eat into the amount that is traded.
+    pub fn fee_on_top(self) -> Self {
+        Self { fee_on_top: true, ..self }
+    }
+
+    pub fn recipient(self, recipient: Address) -> Self {
+        Self { recipient, ..self }
+    }
+
+    /// settle with the users angstrom balances instead of their token
+    /// approvals.
+    pub fn use_internal(self, use_internal: bool) -> Self {
+        Self { use_internal, ..self }
+    }
+
+    pub fn hook_data(self, hook_data: Bytes) -> Self {
+        Self { hook_data, ..self }
+    }
+
+    /// the unsigned order.
+    pub fn build(self) -> Result<AllOrders, ClientError> {
+        let validity = self.validity.ok_or(ClientError::Missing("validity"))?;
+        let amount = self.amount.ok_or(ClientError::Missing("amount"))?;
+        let (asset_in, asset_out) = self.assets.ok_or(ClientError::Missing("assets"))?;
+        let min_price = self.min_price.ok_or(ClientError::Missing("min price"))?;
+        let max_fee = self.max_fee.ok_or(ClientError::Missing("max fee"))?;
+        if asset_in == asset_out {
+            return Err(ClientError::SameAsset(asset_in));
+        }
+
+        let zfo = zero_for_one(asset_in, asset_out);
+        let fee_in = asset0_as_input(max_fee, zfo, min_price);
+        let on_top = |fee: u128| if self.fee_on_top { fee } else { 0 };
+        let amount = match amount {
+            Amount::ExactIn(amount) => Amount::ExactIn(amount + on_top(fee_in)),
+            Amount::ExactOut(amount) => {
+                Amount::ExactOut(amount + on_top(asset0_as_output(max_fee, zfo, min_price)))
+            }
+            Amount::Partial { max_in, min_in } => {
+                let min_in = min_in.unwrap_or(fee_in);
+                let max_in = max_in + on_top(fee_in);
+                if min_in > max_in {
+                    return Err(ClientError::InvalidPartial { min: min_in, max: max_in });
+                }
+                Amount::Partial { max_in, min_in: Some(min_in) }
+            }
+        };
+        if let Amount::ExactIn(0) | Amount::ExactOut(0) | Amount::Partial { max_in: 0, .. } = amount
+        {
+            return Err(ClientError::ZeroAmount);
+        }
+
+        let min_price = *min_price;
+        let Self { recipient, use_internal, hook_data, .. } = self;
+        let order = match (validity, amount) {
+            (Validity::Standing { nonce, deadline }, Amount::Partial { max_in, min_in }) => {
+                AllOrders::PartialStanding(PartialStandingOrder {
+                    min_amount_in: min_in.unwrap_or_default(),
+                    max_amount_in: max_in,
+                    max_extra_fee_asset0: max_fee,
+                    min_price,
+                    use_internal,
+                    asset_in,
+                    asset_out,
+                    recipient,
+                    hook_data,
+                    nonce,
+                    deadline: deadline_u40(deadline)?,
+                    ..Default::default()
+                })
+            }
+            (Validity::Standing { nonce, deadline }, exact) => {
+                let (exact_in, amount) = exact.exact();
+                AllOrders::ExactStanding(ExactStandingOrder {
+                    exact_in,
+                    amount,
+                    max_extra_fee_asset0: max_fee,
+                    min_price,
+                    use_internal,
+                    asset_in,
+                    asset_out,
+                    recipient,
+                    hook_data,
+                    nonce,
+                    deadline: deadline_u40(deadline)?,
+                    ..Default::default()
+                })
+            }
+            (Validity::Flash { block }, Amount::Partial { max_in, min_in }) => {
+                AllOrders::PartialFlash(PartialFlashOrder {
+                    min_amount_in: min_in.unwrap_or_default(),
+                    max_amount_in: max_in,
+                    max_extra_fee_asset0: max_fee,
+                    min_price,
+                    use_internal,
+                    asset_in,
+                    asset_out,
+                    recipient,
+                    hook_data,
+                    valid_for_block: block,
+                    ..Default::default()
+                })
+            }
+            (Validity::Flash { block }, exact) => {
+                let (exact_in, amount) = exact.exact();
+                AllOrders::ExactFlash(ExactFlashOrder {
+                    exact_in,
+                    amount,
+                    max_extra_fee_asset0: max_fee,
+                    min_price,
+                    use_internal,
+                    asset_in,
+                    asset_out,
+                    recipient,
+                    hook_data,
+                    valid_for_block: block,
+                    ..Default::default()
+                })
+            }
+        };
+
+        Ok(order)
+    }
+
+    /// the order signed under `domain`.
+    pub async fn sign<S>(self, domain: &Eip712Domain, signer: &S) -> Result<AllOrders, ClientError>
+    where
+        S: Signer + Sync + ?Sized
+    {
+        sign_order(self.build()?, domain, signer).await
+    }
+
+    /// [`Self::sign`] for signers that hold their key locally.
+    pub fn sign_sync<S>(self, domain: &Eip712Domain, signer: &S) -> Result<AllOrders, ClientError>
+    where
+        S: SignerSync + Signer + ?Sized
+    {
+        sign_order_sync(self.build()?, domain, signer)
+    }
+}
+
+/// Builds a top of block order.
+///
+/// The block, quantities, assets and max gas have to be set.
+#[derive(Debug, Clone, Default)]
+pub struct TobOrderBuilder {
+    block:          Option<u64>,
+    quantities:     Option<(u128, u128)>,
+    assets:         Option<(Address, Address)>,
+    max_gas_asset0: Option<u128>,
+    recipient:      Address,
+    use_internal:   bool
+}
+
+impl TobOrderBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn block(self, block: u64) -> Self {
+        Self { block: Some(block), ..self }
+    }
+
+    /// swaps `quantity_in` of the input asset for `quantity_out` of the output
+    /// asset.
+    pub fn quantities(self, quantity_in: u128, quantity_out: u128) -> Self {
+        Self { quantities: Some((quantity_in, quantity_out)), ..self }
+    }
+
+    pub fn assets(self, asset_in: Address, asset_out: Address) -> Self {
+        Self { assets: Some((asset_in, asset_out)), ..self }
+    }
+
+    pub fn max_gas_asset0(self, max_gas: u128) -> Self {
+        Self { max_gas_asset0: Some(max_gas), ..self }
+    }
+
+    pub fn recipient(self, recipient: Address) -> Self {
+        Self { recipient, ..self }
+    }
+
+    pub fn use_internal(self, use_internal: bool) -> Self {
+        Self { use_internal, ..self }
+    }
+
+    /// the unsigned order.
+    pub fn build(self) -> Result<TopOfBlockOrder, ClientError> {
+        let valid_for_block = self.block.ok_or(ClientError::Missing("block"))?;
+        let (quantity_in, quantity_out) =
+            self.quantities.ok_or(ClientError::Missing("quantities"))?;
+        let (asset_in, asset_out) = self.assets.ok_or(ClientError::Missing("assets"))?;
+        let max_gas_asset0 = self.max_gas_asset0.ok_or(ClientError::Missing("max gas"))?;
+        if asset_in == asset_out {
+            return Err(ClientError::SameAsset(asset_in));
+        }
+        if quantity_in == 0 || quantity_out == 0 {
+            return Err(ClientError::ZeroAmount);
+        }
+
+        Ok(TopOfBlockOrder {
+            quantity_in,
+            quantity_out,
+            max_gas_asset0,
+            use_internal: self.use_internal,
+            asset_in,
+            asset_out,
+            recipient: self.recipient,
+            valid_for_block,
+            ..Default::default()
+        })
+    }
+
+    /// the order signed under `domain`.
+    pub async fn sign<S>(
+        self,
+        domain: &Eip712Domain,
+        signer: &S
+    ) -> Result<TopOfBlockOrder, ClientError>
+    where
+        S: Signer + Sync + ?Sized
+    {
+        sign_order(self.build()?, domain, signer).await
+    }
+
+    /// [`Self::sign`] for signers that hold their key locally.
+    pub fn sign_sync<S>(
+        self,
+        domain: &Eip712Domain,
+        signer: &S
+    ) -> Result<TopOfBlockOrder, ClientError>
+    where
+        S: SignerSync + Signer + ?Sized
+    {
+        sign_order_sync(self.build()?, domain, signer)
+    }
+}
+
+impl Amount {
+    /// whether an exact order is exact in, and its amount.
+    fn exact(self) -> (bool, u128) {
+        match self {
+            Self::ExactIn(amount) => (true, amount),
+            Self::ExactOut(amount) => (false, amount),
+            Self::Partial { max_in, .. } => (true, max_in)
+        }
+    }
+}
+
+fn deadline_u40(deadline: u64) -> Result<U40, ClientError> {
+    U40::try_from(deadline).map_err(|_| ClientError::DeadlineOverflow(deadline))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::local::PrivateKeySigner;
+    use angstrom_types::{
+        primitive::{ANGSTROM_DOMAIN, try_init_with_chain_id},
+        sol_bindings::RawPoolOrder
+    };
+
+    use super::*;
+
+    /// the domain the node checks signatures against.
+    fn node_domain() -> Eip712Domain {
+        let _ = try_init_with_chain_id(1);
+        ANGSTROM_DOMAIN.get().unwrap().clone()
+    }
+
+    fn assets() -> (Address, Address) {
+        (Address::with_last_byte(1), Address::with_last_byte(2))
+    }
+
+    #[test]
+    fn signed_orders_recover_to_the_signer() {
+        let domain = node_domain();
+        let signer = PrivateKeySigner::random();
+        let (asset0, asset1) = assets();
+
+        let order = UserOrderBuilder::new()
+            .standing(7, 1_000)
+            .partial(1_000)
+            .assets(asset1, asset0)
+            .min_price(Ray::generate_ray_decimal(1, 0))
+            .max_extra_fee_asset0(10)
+            .sign_sync(&domain, &signer)
+            .unwrap();
+        assert_eq!(order.from(), signer.address());
+        assert!(order.is_valid_signature());
+
+        // the node recovers someone else once the signed fields change
+        let AllOrders::PartialStanding(mut tampered) = order else {
+            panic!("expected a partial standing order")
+        };
+        tampered.max_amount_in += 1;
+        assert!(!tampered.is_valid_signature());
+
+        let tob = TobOrderBuilder::new()
+            .block(10)
+            .quantities(100, 90)
+            .assets(asset0, asset1)
+            .max_gas_asset0(1)
+            .sign_sync(&domain, &signer)
+            .unwrap();
+        assert_eq!(tob.from(), signer.address());
+        assert!(tob.is_valid_signature());
+    }
+
+    #[test]
+    fn fee_on_top_is_added_in_the_amounts_asset() {
+        let (asset0, asset1) = assets();
+        // 2 asset1 per asset0
+        let builder = UserOrderBuilder::new()
+            .flash(1)
+            .assets(asset1, asset0)
+            .min_price(Ray::generate_ray_decimal(5, 1))
+            .max_extra_fee_asset0(10)
+            .fee_on_top();
+
+        let AllOrders::ExactFlash(exact_in) = builder.clone().exact_in(100).build().unwrap() else {
+            panic!("expected an exact flash order")
+        };
+        assert!(exact_in.exact_in);
+        assert_eq!(exact_in.amount, 120);
+
+        let AllOrders::ExactFlash(exact_out) = builder.clone().exact_out(100).build().unwrap()
+        else {
+            panic!("expected an exact flash order")
+        };
+        assert!(!exact_out.exact_in);
+        assert_eq!(exact_out.amount, 110);
+
+        let AllOrders::PartialFlash(partial) = builder.partial(100).build().unwrap() else {
+            panic!("expected a partial flash order")
+        };
+        assert_eq!(partial.max_amount_in, 120);
+        assert_eq!(partial.min_amount_in, 20);
+    }
+
+    #[test]
+    fn rejects_incomplete_orders() {
+        let (asset0, _) = assets();
+
+        assert!(matches!(
+            UserOrderBuilder::new().exact_in(1).build(),
+            Err(ClientError::Missing("validity"))
+        ));
+        assert!(matches!(
+            UserOrderBuilder::new()
+                .standing(0, u64::MAX)
+                .exact_in(1)
+                .assets(asset0, asset0)
+                .min_price(Ray::ZERO)
+                .max_extra_fee_asset0(0)
+                .build(),
+            Err(ClientError::SameAsset(_))
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! Helpers for the `min_price` of orders.
+//!
+//! Angstrom prices are [`Ray`]s. Pool prices are always asset1 per asset0,
+//! while an orders `min_price` is the least amount of its output asset it
+//! accepts per unit of its input asset. Asset0 is the asset with the lower
+//! address.
+
+use alloy::primitives::{Address, U256};
+use angstrom_types::matching::{Ray, SqrtPriceX96};
+
+/// basis points in one.
+const BPS: u128 = 10_000;
+
+/// whether an order selling `asset_in` for `asset_out` sells asset0.
+pub fn zero_for_one(asset_in: Address, asset_out: Address) -> bool {
+    asset_in < asset_out
+}
+
+/// the pool price, asset1 per asset0, of a uniswap sqrt price.
+pub fn pool_price(sqrt_price: SqrtPriceX96) -> Ray {
+    Ray::from(sqrt_price)
+}
+
+/// the pool price as seen by an order trading in the given direction, output
+/// per input.
+pub fn order_price(pool_price: Ray, zero_for_one: bool) -> Ray {
+    if zero_for_one { pool_price } else { pool_price.inv_ray_round(false) }
+}
+
+/// lowers `price` by `slippage_bps` basis points.
+pub fn with_slippage(price: Ray, slippage_bps: u32) -> Ray {
+    let keep = BPS.saturating_sub(slippage_bps as u128);
+    price.mul_ray(Ray::generate_ray_decimal(keep, 4))
+}
+
+/// the min price of an order that trades in the given direction at most
+/// `slippage_bps` worse than the pool price.
+pub fn min_price(pool_price: Ray, zero_for_one: bool, slippage_bps: u32) -> Ray {
+    with_slippage(order_price(pool_price, zero_for_one), slippage_bps)
+}
+
+/// the min price of an order that wants at least `amount_out` for
+/// `amount_in`.
+pub fn from_amounts(amount_in: u128, amount_out: u128) -> Ray {
+    Ray::from_quantities(amount_out, amount_in, true)
+}
+
+/// an amount of the orders input asset in asset0, at its min price.
+pub fn input_as_asset0(amount_in: u128, zero_for_one: bool, min_price: Ray) -> u128 {
+    if zero_for_one {
+        amount_in
+    } else {
+        min_price
+            .mul_quantity(U256::from(amount_in))
+            .saturating_to()
+    }
+}
+
+/// an amount of asset0 in the orders input asset, at its min price.
+pub fn asset0_as_input(amount0: u128, zero_for_one: bool, min_price: Ray) -> u128 {
+    if zero_for_one { amount0 } else { min_price.inverse_quantity(amount0, true) }
+}
+
+/// an amount of asset0 in the orders output asset, at its min price.
+pub fn asset0_as_output(amount0: u128, zero_for_one: bool, min_price: Ray) -> u128 {
+    if zero_for_one { min_price.mul_quantity(U256::from(amount0)).saturating_to() } else { amount0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slippage_lowers_the_price() {
+        let price = Ray::generate_ray_decimal(2000, 0);
+
+        assert_eq!(with_slippage(price, 0), price);
+        assert_eq!(with_slippage(price, 50), Ray::generate_ray_decimal(1990, 0));
+        assert_eq!(with_slippage(price, 10_000), Ray::ZERO);
+    }
+
+    #[test]
+    fn converts_between_assets_at_the_min_price() {
+        // 2000 asset1 per asset0
+        let pool = Ray::generate_ray_decimal(2000, 0);
+
+        // selling asset0, the fee is already in the input asset
+        let zfo = min_price(pool, true, 0);
+        assert_eq!(asset0_as_input(10, true, zfo), 10);
+        assert_eq!(asset0_as_output(10, true, zfo), 20_000);
+        assert_eq!(input_as_asset0(10, true, zfo), 10);
+
+        // selling asset1, 2000 of it are worth one asset0
+        let ofz = min_price(pool, false, 0);
+        assert_eq!(asset0_as_input(10, false, ofz), 20_000);
+        assert_eq!(asset0_as_output(10, false, ofz), 10);
+        assert_eq!(input_as_asset0(20_000, false, ofz), 10);
+    }
+}
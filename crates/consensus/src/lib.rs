@@ -8,7 +8,8 @@ use std::{collections::HashSet, pin::Pin, time::Duration};
 
 use alloy::primitives::{Address, Bytes};
 use futures::{Stream, StreamExt};
-pub use leader_selection::AngstromValidator;
+pub use leader_selection::{AngstromValidator, WeightedRoundRobin};
+use rounds::TimingDecision;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{
     mpsc::{self, channel},
@@ -16,23 +17,62 @@ use tokio::sync::{
 };
 use tokio_stream::wrappers::ReceiverStream;
 
+/// kept free after solving for estimating gas, signing and submitting the
+/// bundle.
+const SUBMISSION_MARGIN: Duration = Duration::from_millis(1_500);
+/// every pool gets at least this long to solve, even if the wait leaves no
+/// room for it.
+const MIN_SOLVE_BUDGET: Duration = Duration::from_millis(250);
+/// share of the led rounds whose bundle may miss its block by default.
+const DEFAULT_MAX_MISS_RATE: f64 = 0.05;
+
+/// returned when setting a [`ConsensusTimingConfig`] that isn't valid.
+#[derive(Debug, Clone, Copy)]
+pub struct InvalidTimingConfig;
+
+impl std::fmt::Display for InvalidTimingConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(
+            "min wait duration must be less than the max wait duration and the max miss rate \
+             between 0 and 1"
+        )
+    }
+}
+
+impl std::error::Error for InvalidTimingConfig {}
+
 #[derive(Debug, Clone, Copy, clap::Args, Serialize, Deserialize)]
 pub struct ConsensusTimingConfig {
     #[clap(long, default_value_t = 8_000)]
     pub min_wait_duration_ms: u64,
     #[clap(long, default_value_t = 9_000)]
-    pub max_wait_duration_ms: u64
+    pub max_wait_duration_ms: u64,
+    /// the share of the rounds this node leads whose bundle may miss its
+    /// block, when learning how long to wait before the pre-proposal
+    #[clap(long, default_value_t = DEFAULT_MAX_MISS_RATE)]
+    #[serde(default = "default_max_miss_rate")]
+    pub max_miss_rate:        f64
 }
 
 impl Default for ConsensusTimingConfig {
     fn default() -> Self {
-        Self { min_wait_duration_ms: 8_000, max_wait_duration_ms: 9_000 }
+        Self {
+            min_wait_duration_ms: 8_000,
+            max_wait_duration_ms: 9_000,
+            max_miss_rate:        DEFAULT_MAX_MISS_RATE
+        }
     }
 }
 
+fn default_max_miss_rate() -> f64 {
+    DEFAULT_MAX_MISS_RATE
+}
+
 impl ConsensusTimingConfig {
     pub fn is_valid(&self) -> bool {
         self.min_wait_duration_ms < self.max_wait_duration_ms
+            && self.max_miss_rate > 0.0
+            && self.max_miss_rate < 1.0
     }
 
     pub const fn min_wait_time_ms(&self) -> Duration {
@@ -50,6 +90,24 @@ impl ConsensusTimingConfig {
     }
 }
 
+/// how long each pool has to solve when solving starts `into_slot` into a
+/// slot of `slot_duration`. This is what is left of the slot after the
+/// submission margin, so it follows the pre-proposal wait whether the operator
+/// set it or it was learned.
+pub fn solve_budget(slot_duration: Duration, into_slot: Duration) -> Duration {
+    slot_duration
+        .saturating_sub(into_slot + SUBMISSION_MARGIN)
+        .max(MIN_SOLVE_BUDGET)
+}
+
+/// The timing config along with what the timing controller made of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusTiming {
+    #[serde(flatten)]
+    pub config:     ConsensusTimingConfig,
+    pub controller: TimingDecision
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ConsensusDataWithBlock<T> {
     pub data:  T,
@@ -75,20 +133,39 @@ pub trait ConsensusHandle: Send + Sync + Clone + Unpin + 'static {
 
     fn timings(
         &self
-    ) -> impl Future<Output = eyre::Result<ConsensusDataWithBlock<ConsensusTimingConfig>>> + Send;
+    ) -> impl Future<Output = eyre::Result<ConsensusDataWithBlock<ConsensusTiming>>> + Send;
+
+    /// swaps the timing config used for the following rounds. Fails with
+    /// [`InvalidTimingConfig`] if the config isn't valid.
+    fn set_timings(
+        &self,
+        timings: ConsensusTimingConfig
+    ) -> impl Future<Output = eyre::Result<ConsensusDataWithBlock<ConsensusTiming>>> + Send;
 }
 
 #[derive(Clone)]
 pub struct ConsensusHandler(pub tokio::sync::mpsc::UnboundedSender<ConsensusRequest>);
 
 impl ConsensusHandle for ConsensusHandler {
-    async fn timings(&self) -> eyre::Result<ConsensusDataWithBlock<ConsensusTimingConfig>> {
+    async fn timings(&self) -> eyre::Result<ConsensusDataWithBlock<ConsensusTiming>> {
         let (tx, rx) = oneshot::channel();
         self.0.send(ConsensusRequest::Timing(tx))?;
 
         rx.await.map_err(Into::into)
     }
 
+    async fn set_timings(
+        &self,
+        timings: ConsensusTimingConfig
+    ) -> eyre::Result<ConsensusDataWithBlock<ConsensusTiming>> {
+        eyre::ensure!(timings.is_valid(), InvalidTimingConfig);
+
+        let (tx, rx) = oneshot::channel();
+        self.0.send(ConsensusRequest::SetTiming(timings, tx))?;
+
+        rx.await.map_err(Into::into)
+    }
+
     async fn is_round_closed(&self) -> eyre::Result<ConsensusDataWithBlock<bool>> {
         let (tx, rx) = oneshot::channel();
         self.0.send(ConsensusRequest::IsRoundClosed(tx))?;
@@ -142,7 +219,8 @@ impl ConsensusHandler {
 pub enum ConsensusRequest {
     CurrentLeader(oneshot::Sender<ConsensusDataWithBlock<Address>>),
     IsRoundClosed(oneshot::Sender<ConsensusDataWithBlock<bool>>),
-    Timing(oneshot::Sender<ConsensusDataWithBlock<ConsensusTimingConfig>>),
+    Timing(oneshot::Sender<ConsensusDataWithBlock<ConsensusTiming>>),
+    SetTiming(ConsensusTimingConfig, oneshot::Sender<ConsensusDataWithBlock<ConsensusTiming>>),
     CurrentConsensusState(oneshot::Sender<ConsensusDataWithBlock<HashSet<AngstromValidator>>>),
     SubscribeAttestations(mpsc::Sender<ConsensusSubscriptionData>),
     SubscribeRoundEventOrders(mpsc::Sender<ConsensusSubscriptionData>)
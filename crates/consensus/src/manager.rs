@@ -16,7 +16,8 @@ use angstrom_network::{StromMessage, StromNetworkHandle};
 use angstrom_types::{
     block_sync::BlockSyncConsumer,
     consensus::{
-        ConsensusRoundName, ConsensusRoundOrderHashes, StromConsensusEvent, SystemTimeSlotClock
+        ConsensusRoundName, ConsensusRoundOrderHashes, SlotClock, StromConsensusEvent,
+        SystemTimeSlotClock
     },
     contract_payloads::angstrom::UniswapAngstromRegistry,
     primitive::{AngstromMetaSigner, AngstromSigner, ChainExt},
@@ -36,20 +37,20 @@ use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
 use crate::{
     AngstromValidator, ConsensusDataWithBlock, ConsensusRequest, ConsensusSubscriptionData,
-    ConsensusSubscriptionRequestKind, ConsensusTimingConfig,
+    ConsensusSubscriptionRequestKind, ConsensusTiming, ConsensusTimingConfig,
     leader_selection::WeightedRoundRobin,
     rounds::{ConsensusMessage, RoundStateMachine, SharedRoundState}
 };
 
 const MODULE_NAME: &str = "Consensus";
 
-pub struct ConsensusManager<P, Matching, BlockSync, S: AngstromMetaSigner>
+pub struct ConsensusManager<P, Matching, BlockSync, S: AngstromMetaSigner, C = SystemTimeSlotClock>
 where
     P: Provider + Unpin + 'static
 {
     current_height:         BlockNumber,
     leader_selection:       WeightedRoundRobin,
-    consensus_round_state:  RoundStateMachine<P, Matching, S>,
+    consensus_round_state:  RoundStateMachine<P, Matching, S, C>,
     canonical_block_stream: BroadcastStream<CanonStateNotification>,
     strom_consensus_event:  UnboundedMeteredReceiver<StromConsensusEvent>,
     network:                StromNetworkHandle,
@@ -62,12 +63,13 @@ where
     broadcasted_messages: HashSet<StromConsensusEvent>
 }
 
-impl<P, Matching, BlockSync, S> ConsensusManager<P, Matching, BlockSync, S>
+impl<P, Matching, BlockSync, S, C> ConsensusManager<P, Matching, BlockSync, S, C>
 where
     P: Provider + Unpin + 'static,
     BlockSync: BlockSyncConsumer,
     Matching: MatchingEngineHandle,
-    S: AngstromMetaSigner
+    S: AngstromMetaSigner,
+    C: SlotClock
 {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -85,7 +87,7 @@ where
         rpc_rx: mpsc::UnboundedReceiver<ConsensusRequest>,
         state_updates: Option<mpsc::UnboundedSender<ConsensusRoundName>>,
         timing_config: ConsensusTimingConfig,
-        slot_clock: SystemTimeSlotClock
+        slot_clock: C
     ) -> Self {
         let ManagerNetworkDeps { network, canonical_block_stream, strom_consensus_event } = netdeps;
         let wrapped_broadcast_stream = BroadcastStream::new(canonical_block_stream);
@@ -124,6 +126,13 @@ where
         }
     }
 
+    /// seeds the jitter on when pre-proposals are sent, so that a run can be
+    /// replayed exactly. Takes effect from the next block.
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.consensus_round_state.seed_jitter(seed);
+        self
+    }
+
     fn on_blockchain_state(&mut self, notification: CanonStateNotification, waker: Waker) {
         tracing::info!("got new block_chain state");
         let new_block = notification.tip();
@@ -158,6 +167,13 @@ where
         }
     }
 
+    fn timing(&self) -> ConsensusTiming {
+        ConsensusTiming {
+            config:     self.consensus_round_state.timing(),
+            controller: self.consensus_round_state.timing_decision()
+        }
+    }
+
     fn handle_request(&mut self, request: ConsensusRequest) {
         match request {
             ConsensusRequest::CurrentLeader(tx) => {
@@ -182,10 +198,14 @@ where
             }
             ConsensusRequest::Timing(tx) => {
                 let block = self.current_height;
-                let _ = tx.send(ConsensusDataWithBlock {
-                    data: self.consensus_round_state.timing(),
-                    block
-                });
+                let _ = tx.send(ConsensusDataWithBlock { data: self.timing(), block });
+            }
+            ConsensusRequest::SetTiming(timing, tx) => {
+                tracing::info!(?timing, "updating consensus timings");
+                self.consensus_round_state.set_timing(timing);
+
+                let block = self.current_height;
+                let _ = tx.send(ConsensusDataWithBlock { data: self.timing(), block });
             }
             ConsensusRequest::IsRoundClosed(tx) => {
                 let block = self.current_height;
@@ -282,12 +302,13 @@ where
     async fn cleanup(mut self) {}
 }
 
-impl<P, Matching, BlockSync, S> Future for ConsensusManager<P, Matching, BlockSync, S>
+impl<P, Matching, BlockSync, S, C> Future for ConsensusManager<P, Matching, BlockSync, S, C>
 where
     P: Provider + Unpin + 'static,
     Matching: MatchingEngineHandle,
     BlockSync: BlockSyncConsumer,
-    S: AngstromMetaSigner
+    S: AngstromMetaSigner,
+    C: SlotClock + Unpin
 {
     type Output = ();
 
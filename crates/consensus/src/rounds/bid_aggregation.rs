@@ -1,7 +1,6 @@
 use std::{
     collections::HashSet,
-    task::{Context, Poll, Waker},
-    time::Instant
+    task::{Context, Poll, Waker}
 };
 
 use alloy::providers::Provider;
@@ -13,6 +12,7 @@ use angstrom_types::{
 };
 use futures::FutureExt;
 use matching_engine::MatchingEngineHandle;
+use tokio::time::Instant;
 
 use super::{
     ConsensusState, SharedRoundState, finalization::FinalizationState,
@@ -3,7 +3,8 @@ use std::{
     hash::Hash,
     pin::Pin,
     sync::Arc,
-    task::{Context, Poll}
+    task::{Context, Poll},
+    time::Duration
 };
 
 use alloy::{
@@ -27,6 +28,12 @@ use futures::{FutureExt, Stream, future::BoxFuture};
 use matching_engine::{MatchingEngineHandle, manager::MatchingEngineError};
 use order_pool::order_storage::OrderStorage;
 use preproposal_wait_trigger::{LastRoundInfo, PreProposalWaitTrigger};
+use timing_controller::{BundleOutcome, RoundClock, TimingController};
+pub use timing_controller::{RoundTiming, TimingDecision};
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    time::Instant
+};
 use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
 use crate::{AngstromValidator, ConsensusTimingConfig};
@@ -37,6 +44,7 @@ mod pre_proposal;
 mod pre_proposal_aggregation;
 mod preproposal_wait_trigger;
 mod proposal;
+mod timing_controller;
 
 type PollTransition<P, Matching, S> = Poll<Option<Box<dyn ConsensusState<P, Matching, S>>>>;
 
@@ -68,7 +76,7 @@ where
 }
 
 /// Holds and progresses the consensus state machine
-pub struct RoundStateMachine<P, Matching, S: AngstromMetaSigner>
+pub struct RoundStateMachine<P, Matching, S: AngstromMetaSigner, C = SystemTimeSlotClock>
 where
     P: Provider + Unpin + 'static
 {
@@ -76,20 +84,22 @@ where
     /// for consensus, on a new block we wait a duration of time before signing
     /// our pre-proposal. this is the time
     consensus_wait_duration: PreProposalWaitTrigger,
+    /// learns the wait from the history of rounds
+    timing_controller:       TimingController,
+    timing_decision:         TimingDecision,
+    round_clock:             RoundClock,
     shared_state:            SharedRoundState<P, Matching, S>,
-    slot_clock:              SystemTimeSlotClock
+    slot_clock:              C
 }
 
-impl<P, Matching, S> RoundStateMachine<P, Matching, S>
+impl<P, Matching, S, C> RoundStateMachine<P, Matching, S, C>
 where
     P: Provider + Unpin + 'static,
     Matching: MatchingEngineHandle,
-    S: AngstromMetaSigner
+    S: AngstromMetaSigner,
+    C: SlotClock
 {
-    pub fn new(
-        shared_state: SharedRoundState<P, Matching, S>,
-        slot_clock: SystemTimeSlotClock
-    ) -> Self {
+    pub fn new(mut shared_state: SharedRoundState<P, Matching, S>, slot_clock: C) -> Self {
         let mut consensus_wait_duration = PreProposalWaitTrigger::new(
             shared_state.order_storage.clone(),
             shared_state.consensus_config
@@ -98,11 +108,22 @@ where
         let next_slot_duration = slot_clock.duration_to_next_slot().unwrap();
         let elapsed_time = slot_clock.slot_duration() - next_slot_duration;
 
+        let current_state = Box::new(BidAggregationState::new(
+            consensus_wait_duration.update_for_new_round(None, elapsed_time)
+        ));
+        let timing_decision = TimingDecision {
+            trigger_ms: consensus_wait_duration.wait_duration().as_millis() as u64,
+            ..Default::default()
+        };
+
+        shared_state.start_slot(elapsed_time, slot_clock.slot_duration());
+
         Self {
-            current_state: Box::new(BidAggregationState::new(
-                consensus_wait_duration.update_for_new_round(None, elapsed_time)
-            )),
+            current_state,
+            round_clock: RoundClock::new(shared_state.block_height, elapsed_time),
             consensus_wait_duration,
+            timing_controller: TimingController::default(),
+            timing_decision,
             shared_state,
             slot_clock
         }
@@ -116,6 +137,23 @@ where
         self.shared_state.consensus_config
     }
 
+    /// the wait the timing controller chose for the current round.
+    pub fn timing_decision(&self) -> TimingDecision {
+        self.timing_decision.clone()
+    }
+
+    /// takes effect from the next round, the current wait isn't changed.
+    pub fn set_timing(&mut self, timing: ConsensusTimingConfig) {
+        self.shared_state.consensus_config = timing;
+        self.consensus_wait_duration.set_config(timing);
+    }
+
+    /// seeds the jitter added to the pre-proposal wait, so that it is the same
+    /// on every run. Takes effect from the next round.
+    pub fn seed_jitter(&mut self, seed: u64) {
+        self.consensus_wait_duration.seed_jitter(seed);
+    }
+
     pub fn is_auction_closed(&self) -> bool {
         self.current_state.name().is_closed()
     }
@@ -126,21 +164,60 @@ where
 
         // grab the last round info if we were the leader.
         let info = self.current_state.last_round_info();
+        let led = self.shared_state.i_am_leader();
 
         // reset before we got to proposal, we decay the round time to handle this case
         // as otherwise, can end up in a loop where we never submit and never
         // adjust time
-        if info.is_none() && self.shared_state.i_am_leader() {
+        if info.is_none() && led {
             self.consensus_wait_duration.reset_before_submission();
         }
 
+        let round_clock =
+            std::mem::replace(&mut self.round_clock, RoundClock::new(new_block, elapsed_time));
+        let round = round_clock.finish(info.as_ref(), led);
+        self.record_round_metrics(&round);
+        self.timing_controller.record(round);
+
+        // once there is enough history the learned wait replaces adjusting by
+        // the last led round
+        let mut decision = self
+            .timing_controller
+            .decide(&self.shared_state.consensus_config);
+        let info = if decision.learned {
+            self.consensus_wait_duration
+                .set_wait_duration(Duration::from_millis(decision.trigger_ms));
+            None
+        } else {
+            info
+        };
+
         self.shared_state.block_height = new_block;
         self.shared_state.round_leader = new_leader;
+        self.shared_state
+            .start_slot(elapsed_time, self.slot_clock.slot_duration());
 
         self.current_state = Box::new(BidAggregationState::new(
             self.consensus_wait_duration
                 .update_for_new_round(info, elapsed_time)
         ));
+
+        decision.trigger_ms = self.consensus_wait_duration.wait_duration().as_millis() as u64;
+        self.shared_state.metrics.set_pre_proposal_trigger(
+            decision.trigger_ms,
+            decision.learned,
+            decision.miss_rate
+        );
+        self.timing_decision = decision;
+    }
+
+    fn record_round_metrics(&self, round: &RoundTiming) {
+        let metrics = &self.shared_state.metrics;
+        metrics.set_round_phase_time("pre_proposal", round.pre_proposal_ms);
+        metrics.set_round_phase_time("aggregation", round.aggregation_ms);
+        if let Some(matching) = round.matching_ms {
+            metrics.set_round_phase_time("matching", matching);
+        }
     }
 
     pub fn handle_message(&mut self, event: StromConsensusEvent) {
@@ -149,17 +226,25 @@ where
     }
 }
 
-impl<P, Matching, S> Stream for RoundStateMachine<P, Matching, S>
+impl<P, Matching, S, C> Stream for RoundStateMachine<P, Matching, S, C>
 where
     P: Provider + Unpin + 'static,
     Matching: MatchingEngineHandle,
-    S: AngstromMetaSigner
+    S: AngstromMetaSigner,
+    C: SlotClock + Unpin
 {
     type Item = ConsensusMessage;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
+        while let Poll::Ready(Some(outcome)) = this.shared_state.outcome_rx.poll_recv(cx) {
+            this.shared_state
+                .metrics
+                .set_round_phase_time("submission", outcome.submission.as_millis() as u64);
+            this.timing_controller.record_outcome(outcome);
+        }
+
         if let Some(message) = this.shared_state.messages.pop_front() {
             return Poll::Ready(Some(message));
         }
@@ -171,6 +256,9 @@ where
             tracing::info!("transitioning to new round state");
             this.current_state = transitioned_state;
             let name = this.current_state.name();
+            let order_storage = &this.shared_state.order_storage;
+            this.round_clock
+                .on_transition(name, || order_storage.get_all_orders().total_orders());
             return Poll::Ready(Some(ConsensusMessage::StateChange(name)));
         }
 
@@ -185,12 +273,19 @@ pub struct SharedRoundState<P: Provider + Unpin + 'static, Matching, S: Angstrom
     round_leader:     Address,
     validators:       Vec<AngstromValidator>,
     order_storage:    Arc<OrderStorage>,
-    _metrics:         ConsensusMetricsWrapper,
+    metrics:          ConsensusMetricsWrapper,
     pool_registry:    UniswapAngstromRegistry,
     uniswap_pools:    SyncedUniswapPools,
     provider:         Arc<SubmissionHandler<P>>,
     messages:         VecDeque<ConsensusMessage>,
-    consensus_config: ConsensusTimingConfig
+    consensus_config: ConsensusTimingConfig,
+    /// when the slot of the current round started
+    slot_start:       Instant,
+    /// how long the slot of the current round lasts
+    slot_duration:    Duration,
+    /// whether the bundles of the led rounds landed, sent once known
+    outcome_tx:       UnboundedSender<BundleOutcome>,
+    outcome_rx:       UnboundedReceiver<BundleOutcome>
 }
 
 // contains shared impls
@@ -214,6 +309,7 @@ where
         matching_engine: Matching,
         consensus_config: ConsensusTimingConfig
     ) -> Self {
+        let (outcome_tx, outcome_rx) = unbounded_channel();
         Self {
             block_height,
             round_leader,
@@ -222,15 +318,28 @@ where
             pool_registry,
             uniswap_pools,
             signer,
-            _metrics: metrics,
+            metrics,
             matching_engine,
             messages: VecDeque::new(),
             provider: Arc::new(provider),
-            consensus_config
+            consensus_config,
+            slot_start: Instant::now(),
+            slot_duration: Duration::ZERO,
+            outcome_tx,
+            outcome_rx
         }
     }
 
+    /// the round's slot started `into_slot` ago.
+    fn start_slot(&mut self, into_slot: Duration, slot_duration: Duration) {
+        self.slot_start = Instant::now()
+            .checked_sub(into_slot)
+            .unwrap_or_else(Instant::now);
+        self.slot_duration = slot_duration;
+    }
+
     fn propagate_message(&mut self, message: ConsensusMessage) {
+        trace_round_orders(&message, self.block_height, &self.order_storage);
         self.messages.push_back(message);
     }
 
@@ -268,15 +377,26 @@ where
         // fetch
         let mut limit = Vec::new();
         let mut searcher = Vec::new();
+        let mut routes = Vec::new();
 
         for pre_proposal_agg in pre_proposal_aggregation {
             pre_proposal_agg.pre_proposals.into_iter().for_each(|pre| {
                 limit.extend(pre.limit);
                 searcher.extend(pre.searcher);
+                routes.extend(pre.routes);
             });
         }
 
-        let valid_limit = self.filter_quorum_orders(limit);
+        // legs are linked like the orders themselves are included, by quorum. The
+        // legs of a route that some nodes know but that misses the quorum are
+        // left out, as on their own they could be filled without the rest of it
+        let route_legs = routes.iter().flatten().copied().collect::<HashSet<_>>();
+        let mut routes = self.filter_quorum_orders(routes);
+        routes.sort_unstable();
+        let linked_legs = routes.iter().flatten().copied().collect::<HashSet<_>>();
+
+        let mut valid_limit = self.filter_quorum_orders(limit);
+        valid_limit.retain(|hash| linked_legs.contains(hash) || !route_legs.contains(hash));
         let valid_searcher = self.filter_quorum_orders(searcher);
         let orders = self
             .order_storage
@@ -311,8 +431,14 @@ where
             .collect();
 
         let pool_snapshots = self.fetch_pool_snapshot();
+        let solve_budget = crate::solve_budget(self.slot_duration, self.slot_start.elapsed());
         let matcher = self.matching_engine.clone();
-        async move { matcher.solve_pools(limit, searcher, pool_snapshots).await }.boxed()
+        async move {
+            matcher
+                .solve_pools(limit, searcher, pool_snapshots, routes, solve_budget)
+                .await
+        }
+        .boxed()
     }
 
     fn filter_quorum_orders<O: Hash + Eq + Clone>(&self, input: Vec<O>) -> Vec<O> {
@@ -481,6 +607,25 @@ impl ConsensusMessage {
     }
 }
 
+/// a span per order of the message, tagged with its hash, so the rounds an
+/// order made it into are part of its trace.
+fn trace_round_orders(message: &ConsensusMessage, block: BlockNumber, orders: &OrderStorage) {
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return;
+    }
+
+    let event = message.round_event();
+    for order_hash in message
+        .limit_order_hashes()
+        .into_iter()
+        .chain(message.searcher_order_hashes())
+    {
+        let span = tracing::debug_span!("consensus", %order_hash, block, ?event);
+        orders.set_trace_parent(&span, &order_hash);
+        span.in_scope(|| tracing::debug!("propagating order in round"));
+    }
+}
+
 impl From<PreProposal> for ConsensusMessage {
     fn from(value: PreProposal) -> Self {
         Self::PropagatePreProposal(value)
@@ -499,7 +644,7 @@ pub mod tests {
         collections::HashSet,
         sync::Arc,
         task::{Context, Poll},
-        time::{Duration, Instant}
+        time::Duration
     };
 
     use alloy::{
@@ -515,7 +660,7 @@ pub mod tests {
         },
         contract_payloads::angstrom::{AngstromPoolConfigStore, UniswapAngstromRegistry},
         primitive::{AngstromSigner, UniswapPoolRegistry},
-        submission::SubmissionHandler
+        submission::{SubmissionHandler, SubmitterToggles}
     };
     use dashmap::DashMap;
     use futures::{Stream, pin_mut};
@@ -526,6 +671,7 @@ pub mod tests {
             pre_proposal_agg::PreProposalAggregationBuilder, preproposal::PreproposalBuilder
         }
     };
+    use tokio::time::Instant;
     use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
     use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
@@ -584,8 +730,11 @@ pub mod tests {
             .unwrap()
             .into();
 
-        let provider =
-            SubmissionHandler { node_provider: querying_provider, submitters: vec![] };
+        let provider = SubmissionHandler {
+            node_provider: querying_provider,
+            submitters:    vec![],
+            toggles:       SubmitterToggles::default()
+        };
 
         let shared_state = SharedRoundState::new(
             1, // block height
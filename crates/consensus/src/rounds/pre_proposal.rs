@@ -1,7 +1,6 @@
 use std::{
     collections::HashSet,
-    task::{Context, Poll, Waker},
-    time::Instant
+    task::{Context, Poll, Waker}
 };
 
 use alloy::{primitives::BlockNumber, providers::Provider};
@@ -12,6 +11,7 @@ use angstrom_types::{
     primitive::AngstromMetaSigner
 };
 use matching_engine::MatchingEngineHandle;
+use tokio::time::Instant;
 
 use super::{ConsensusState, SharedRoundState};
 use crate::rounds::{
@@ -48,8 +48,18 @@ impl PreProposalState {
         Matching: MatchingEngineHandle
     {
         // generate my pre_proposal
-        let my_preproposal =
-            PreProposal::new(block_height, &handles.signer, handles.order_storage.get_all_orders());
+        let routes = handles
+            .order_storage
+            .routes()
+            .iter()
+            .map(|route| route.leg_hashes())
+            .collect();
+        let my_preproposal = PreProposal::new(
+            block_height,
+            &handles.signer,
+            handles.order_storage.get_all_orders(),
+            routes
+        );
 
         // propagate my pre_proposal
         handles.propagate_message(ConsensusMessage::PropagatePreProposal(my_preproposal.clone()));
@@ -1,7 +1,6 @@
 use std::{
     collections::HashSet,
-    task::{Context, Poll, Waker},
-    time::Instant
+    task::{Context, Poll, Waker}
 };
 
 use alloy::providers::Provider;
@@ -12,6 +11,7 @@ use angstrom_types::{
     primitive::AngstromMetaSigner
 };
 use matching_engine::MatchingEngineHandle;
+use tokio::time::Instant;
 
 use super::{ConsensusState, SharedRoundState};
 use crate::rounds::{finalization::FinalizationState, proposal::ProposalState};
@@ -1,12 +1,7 @@
-use std::{
-    future::Future,
-    sync::Arc,
-    task::Poll,
-    time::{Duration, Instant}
-};
+use std::{future::Future, sync::Arc, task::Poll, time::Duration};
 
-use rand::Rng;
-use tokio::time::{Interval, interval};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use tokio::time::{Instant, Interval, interval};
 
 use crate::{ConsensusTimingConfig, rounds::OrderStorage};
 
@@ -14,7 +9,7 @@ use crate::{ConsensusTimingConfig, rounds::OrderStorage};
 /// because its very low overhead to check
 const CHECK_INTERVAL: Duration = Duration::from_millis(1);
 /// How much to scale per order in the order pool
-const ORDER_SCALING: Duration = Duration::from_millis(10);
+pub(super) const ORDER_SCALING: Duration = Duration::from_millis(10);
 /// How close we want to be to the creation of the ethereum block
 const TARGET_SUBMISSION_TIME_POST_MAX: Duration = Duration::from_millis(200);
 /// The amount of the difference we scale by to reach
@@ -35,37 +30,38 @@ pub struct PreProposalWaitTrigger {
     order_storage:  Arc<OrderStorage>,
     /// Waker
     check_interval: Interval,
-    config:         ConsensusTimingConfig
-}
-
-impl Clone for PreProposalWaitTrigger {
-    fn clone(&self) -> Self {
-        let mut rng = rand::rng();
-        let jitter = Duration::from_millis(rng.random_range(3..=40));
-        Self {
-            wait_duration:  self.wait_duration + jitter,
-            start_instant:  Instant::now(),
-            order_storage:  self.order_storage.clone(),
-            check_interval: interval(CHECK_INTERVAL),
-            config:         self.config
-        }
-    }
+    config:         ConsensusTimingConfig,
+    /// where the per round jitter comes from
+    rng:            StdRng
 }
 
 impl PreProposalWaitTrigger {
     pub fn new(order_storage: Arc<OrderStorage>, config: ConsensusTimingConfig) -> Self {
-        let mut rng = rand::rng();
-        let jitter = Duration::from_millis(rng.random_range(3..=40));
+        let mut rng = StdRng::from_rng(&mut rand::rng());
+        let jitter = Self::jitter(&mut rng);
 
         Self {
             wait_duration: config.default_duration() + jitter,
             order_storage,
             start_instant: Instant::now(),
             check_interval: interval(CHECK_INTERVAL),
-            config
+            config,
+            rng
         }
     }
 
+    /// makes the jitter reproducible. This restarts the wait from the
+    /// configured default, so it is meant to be called before the first
+    /// round.
+    pub fn seed_jitter(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.wait_duration = self.config.default_duration() + Self::jitter(&mut self.rng);
+    }
+
+    fn jitter(rng: &mut StdRng) -> Duration {
+        Duration::from_millis(rng.random_range(3..=40))
+    }
+
     pub fn update_for_new_round(
         &mut self,
         info: Option<LastRoundInfo>,
@@ -75,10 +71,34 @@ impl PreProposalWaitTrigger {
             self.update_wait_duration_base(info);
         }
 
-        let mut this = self.clone();
-        this.wait_duration = this.wait_duration.saturating_sub(slot_elapsed_time);
+        let jitter = Self::jitter(&mut self.rng);
+        let wait_duration = (self.wait_duration + jitter).saturating_sub(slot_elapsed_time);
+
+        Self {
+            wait_duration,
+            start_instant: Instant::now(),
+            order_storage: self.order_storage.clone(),
+            check_interval: interval(CHECK_INTERVAL),
+            config: self.config,
+            rng: self.rng.clone()
+        }
+    }
+
+    pub fn set_config(&mut self, config: ConsensusTimingConfig) {
+        self.config = config;
+        self.wait_duration = self.sigmoid_clamp(self.wait_duration);
+    }
+
+    /// the wait before the order scaling and jitter, from the start of the
+    /// slot.
+    pub fn wait_duration(&self) -> Duration {
+        self.wait_duration
+    }
 
-        this
+    /// replaces the adjusted wait with one learned elsewhere.
+    pub fn set_wait_duration(&mut self, wait_duration: Duration) {
+        self.wait_duration =
+            wait_duration.clamp(self.config.min_wait_time_ms(), self.config.max_wait_time_ms());
     }
 
     pub fn reset_before_submission(&mut self) {
@@ -161,5 +181,7 @@ impl Future for PreProposalWaitTrigger {
 #[derive(Debug)]
 pub struct LastRoundInfo {
     /// the start of the round to submitting the bundle
-    pub time_to_complete: Duration
+    pub time_to_complete: Duration,
+    /// how long building the bundle took
+    pub matching:         Duration
 }
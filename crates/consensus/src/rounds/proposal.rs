@@ -1,10 +1,10 @@
 use std::{
     collections::HashSet,
     task::{Context, Poll, Waker},
-    time::{Duration, Instant}
+    time::Duration
 };
 
-use alloy::providers::Provider;
+use alloy::{primitives::B256, providers::Provider};
 use angstrom_types::{
     consensus::{ConsensusRoundName, PreProposalAggregation, Proposal, StromConsensusEvent},
     contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
@@ -14,9 +14,12 @@ use angstrom_types::{
 };
 use futures::{FutureExt, StreamExt, future::BoxFuture};
 use matching_engine::{MatchingEngineHandle, manager::MatchingEngineError};
+use tokio::time::Instant;
 
 use super::{ConsensusState, SharedRoundState};
-use crate::rounds::{ConsensusMessage, preproposal_wait_trigger::LastRoundInfo};
+use crate::rounds::{
+    ConsensusMessage, preproposal_wait_trigger::LastRoundInfo, timing_controller::BundleOutcome
+};
 
 type MatchingEngineFuture =
     BoxFuture<'static, Result<(Vec<PoolSolution>, BundleGasDetails), MatchingEngineError>>;
@@ -34,7 +37,8 @@ pub struct ProposalState {
     pre_proposal_aggs:      Vec<PreProposalAggregation>,
     proposal:               Option<Proposal>,
     last_round_info:        Option<LastRoundInfo>,
-    trigger_time:           Instant
+    trigger_time:           Instant,
+    matching_start:         Instant
 }
 
 impl ProposalState {
@@ -60,7 +64,8 @@ impl ProposalState {
             pre_proposal_aggs: pre_proposal_aggregation.into_iter().collect::<Vec<_>>(),
             submission_future: None,
             proposal: None,
-            trigger_time
+            trigger_time,
+            matching_start: Instant::now()
         }
     }
 
@@ -74,22 +79,28 @@ impl ProposalState {
         P: Provider + Unpin + 'static,
         Matching: MatchingEngineHandle
     {
+        let now = Instant::now();
         self.last_round_info = Some(LastRoundInfo {
-            time_to_complete: Instant::now().duration_since(self.trigger_time)
+            time_to_complete: now.duration_since(self.trigger_time),
+            matching:         now.duration_since(self.matching_start)
         });
 
         let provider = handles.provider.clone();
         let signer = handles.signer.clone();
         let target_block = handles.block_height + 1;
+        let round_block = handles.block_height;
+        let outcomes = handles.outcome_tx.clone();
 
         tracing::debug!("starting to build proposal");
 
+        let mut submitted_orders = Vec::new();
         let Ok(possible_bundle) = result.inspect_err(|e| {
             tracing::info!(err=%e,
                 "Failed to properly build proposal, THERE SHALL BE NO PROPOSAL THIS BLOCK :("
             )})
             .map(|(pool_solution, gas_info)| {
 
+                submitted_orders = filled_order_hashes(&pool_solution);
                 let proposal = Proposal::generate_proposal(
                     handles.block_height,
                     &handles.signer,
@@ -119,20 +130,26 @@ impl ProposalState {
         };
         handles.propagate_message(ConsensusMessage::PropagateEmptyBlockAttestation(attestation));
 
-        let submission_future = Box::pin(async move {
+        // the submission is traced as part of each order it fills
+        let submitted_orders = submitted_orders
+            .into_iter()
+            .map(|order_hash| (order_hash, handles.order_storage.trace_of(&order_hash)))
+            .collect::<Vec<_>>();
+        let submission = async move {
             let Ok(tx_hash) = provider
                 .submit_tx(signer, possible_bundle, target_block)
                 .await
             else {
                 tracing::error!("submission failed");
-                return false;
+                return (now.elapsed(), false);
             };
 
             let Some(tx_hash) = tx_hash else {
                 tracing::info!("submitted unlock attestation");
-                return true;
+                return (now.elapsed(), true);
             };
 
+            let submission = now.elapsed();
             tracing::info!("submitted bundle");
             provider
                 .watch_blocks()
@@ -143,13 +160,30 @@ impl ProposalState {
                 .next()
                 .await;
 
+            // a pending transaction is found by its hash too, only a receipt
+            // with a block means it landed
             let included = provider
-                .get_transaction_by_hash(tx_hash)
+                .get_transaction_receipt(tx_hash)
                 .await
-                .unwrap()
-                .is_some();
+                .ok()
+                .flatten()
+                .is_some_and(|receipt| receipt.block_number.is_some());
             tracing::info!(?included, "block tx result");
-            included
+            for (order_hash, trace) in submitted_orders {
+                let span = tracing::debug_span!("submission", %order_hash, %tx_hash, target_block, included);
+                if let Some(trace) = trace {
+                    trace.set_as_parent_of(&span);
+                }
+                span.in_scope(|| tracing::debug!("submitted order"));
+            }
+            (submission, included)
+        };
+        // the round is usually over once the outcome is in, so it's recorded
+        // for the round's block instead of the state
+        let submission_future = Box::pin(async move {
+            let (submission, landed) = submission.await;
+            let _ = outcomes.send(BundleOutcome { block: round_block, submission, landed });
+            landed
         });
 
         cx.waker().wake_by_ref();
@@ -159,6 +193,26 @@ impl ProposalState {
     }
 }
 
+/// the orders a bundle of the solutions executes.
+fn filled_order_hashes(solutions: &[PoolSolution]) -> Vec<B256> {
+    solutions
+        .iter()
+        .flat_map(|solution| {
+            solution
+                .searcher
+                .iter()
+                .map(|searcher| searcher.order_id.hash)
+                .chain(
+                    solution
+                        .limit
+                        .iter()
+                        .filter(|outcome| outcome.is_filled())
+                        .map(|outcome| outcome.id.hash)
+                )
+        })
+        .collect()
+}
+
 impl<P, Matching, S> ConsensusState<P, Matching, S> for ProposalState
 where
     P: Provider + Unpin + 'static,
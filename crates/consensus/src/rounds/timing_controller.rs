@@ -0,0 +1,347 @@
+use std::{collections::VecDeque, time::Duration};
+
+use angstrom_types::consensus::ConsensusRoundName;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::preproposal_wait_trigger::{LastRoundInfo, ORDER_SCALING};
+use crate::ConsensusTimingConfig;
+
+/// How many rounds of history the controller keeps.
+const HISTORY: usize = 128;
+/// How many led rounds, one of them landed, the history needs before the
+/// trigger is learned from it.
+const MIN_LED_ROUNDS: usize = 5;
+/// How much earlier to trigger for every miss over the target miss rate.
+const MISS_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The phases of a round. Matching, submission and whether the bundle landed
+/// are only known for the rounds this node led, the latter two once the
+/// [`BundleOutcome`] of the round is in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundTiming {
+    pub block:           u64,
+    /// time (ms) into the slot the pre-proposal was triggered at
+    pub trigger_ms:      u64,
+    /// the orders in the pool at the trigger
+    pub orders:          usize,
+    pub pre_proposal_ms: u64,
+    pub aggregation_ms:  u64,
+    pub matching_ms:     Option<u64>,
+    pub submission_ms:   Option<u64>,
+    pub landed:          Option<bool>
+}
+
+impl RoundTiming {
+    /// time (ms) from the trigger until the submission was done.
+    pub fn work_ms(&self) -> u64 {
+        self.pre_proposal_ms
+            + self.aggregation_ms
+            + self.matching_ms.unwrap_or_default()
+            + self.submission_ms.unwrap_or_default()
+    }
+
+    /// time (ms) into the slot the submission was done.
+    pub fn completion_ms(&self) -> u64 {
+        self.trigger_ms + self.work_ms()
+    }
+
+    /// the work that doesn't scale with the orders, as the trigger is already
+    /// moved earlier by [`ORDER_SCALING`] per order.
+    fn base_work_ms(&self) -> u64 {
+        self.work_ms()
+            .saturating_sub(ORDER_SCALING.as_millis() as u64 * self.orders as u64)
+    }
+
+    fn apply(&mut self, outcome: &BundleOutcome) {
+        self.submission_ms = Some(outcome.submission.as_millis() as u64);
+        self.landed = Some(outcome.landed);
+    }
+}
+
+/// Whether the bundle of a led round landed. It is only known a block after
+/// the submission, when the next round already started, so it is applied to
+/// the history of rounds once it is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleOutcome {
+    /// the block of the round that submitted the bundle
+    pub block:      u64,
+    /// how long submitting the bundle took
+    pub submission: Duration,
+    pub landed:     bool
+}
+
+/// When the controller wants the pre-proposal to trigger and what it based
+/// that on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimingDecision {
+    /// time (ms) into the slot to trigger at, before the order scaling and
+    /// jitter
+    pub trigger_ms:  u64,
+    /// whether the trigger is learned from the history. If not, it is only
+    /// adjusted by the last round this node led
+    pub learned:     bool,
+    /// share of the led rounds in the history whose bundle didn't land
+    pub miss_rate:   f64,
+    pub led_rounds:  usize,
+    /// the latest time (ms) into the slot a submission was done that still
+    /// landed
+    pub deadline_ms: Option<u64>,
+    /// the time (ms) from trigger to submission that all but the max miss
+    /// rate of the led rounds needed
+    pub work_ms:     Option<u64>,
+    pub history:     Vec<RoundTiming>
+}
+
+/// Learns when to trigger the pre-proposal from the history of rounds.
+///
+/// The later the trigger, the more orders make it into the round, but the
+/// more likely the bundle misses its block. The controller triggers as late
+/// as the landed rounds allow, minus the time from trigger to submission
+/// that all but the max miss rate of the led rounds fit into. While more
+/// rounds missed than that, it triggers earlier for every extra miss.
+#[derive(Debug, Default)]
+pub struct TimingController {
+    history:  VecDeque<RoundTiming>,
+    /// outcomes that came in before their round was recorded
+    outcomes: Vec<BundleOutcome>
+}
+
+impl TimingController {
+    pub fn record(&mut self, mut round: RoundTiming) {
+        if let Some(outcome) = self
+            .outcomes
+            .iter()
+            .find(|outcome| outcome.block == round.block)
+        {
+            round.apply(outcome);
+        }
+        self.outcomes.retain(|outcome| outcome.block > round.block);
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(round);
+    }
+
+    pub fn record_outcome(&mut self, outcome: BundleOutcome) {
+        match self
+            .history
+            .iter_mut()
+            .rev()
+            .find(|round| round.block == outcome.block)
+        {
+            Some(round) => round.apply(&outcome),
+            None => self.outcomes.push(outcome)
+        }
+    }
+
+    pub fn decide(&self, config: &ConsensusTimingConfig) -> TimingDecision {
+        let led = self
+            .history
+            .iter()
+            .filter(|round| round.landed.is_some())
+            .collect::<Vec<_>>();
+        let misses = led
+            .iter()
+            .filter(|round| round.landed == Some(false))
+            .count();
+        let miss_rate = if led.is_empty() { 0.0 } else { misses as f64 / led.len() as f64 };
+
+        let deadline_ms = led
+            .iter()
+            .filter(|round| round.landed == Some(true))
+            .map(|round| round.completion_ms())
+            .max();
+
+        let mut decision = TimingDecision {
+            miss_rate,
+            led_rounds: led.len(),
+            deadline_ms,
+            history: self.history.iter().copied().collect(),
+            ..Default::default()
+        };
+
+        let Some(deadline_ms) = deadline_ms else { return decision };
+        if led.len() < MIN_LED_ROUNDS {
+            return decision;
+        }
+
+        let mut work = led
+            .iter()
+            .map(|round| round.base_work_ms())
+            .collect::<Vec<_>>();
+        work.sort_unstable();
+        let quantile = ((work.len() - 1) as f64 * (1.0 - config.max_miss_rate)).ceil() as usize;
+        let work_ms = work[quantile.min(work.len() - 1)];
+
+        let allowed_misses = (config.max_miss_rate * led.len() as f64).floor() as usize;
+        let backoff = MISS_BACKOFF * misses.saturating_sub(allowed_misses) as u32;
+
+        let trigger = Duration::from_millis(deadline_ms.saturating_sub(work_ms))
+            .saturating_sub(backoff)
+            .clamp(config.min_wait_time_ms(), config.max_wait_time_ms());
+
+        decision.trigger_ms = trigger.as_millis() as u64;
+        decision.work_ms = Some(work_ms);
+        decision.learned = true;
+        decision
+    }
+}
+
+/// Times the phases of the current round.
+#[derive(Debug)]
+pub struct RoundClock {
+    block:        u64,
+    start:        Instant,
+    /// how far into the slot the round started
+    slot_offset:  Duration,
+    /// when the pre-proposal was triggered and the orders at that point
+    pre_proposal: Option<(Instant, usize)>,
+    aggregation:  Option<Instant>,
+    /// the proposal if this node leads, otherwise finalization
+    proposal:     Option<Instant>
+}
+
+impl RoundClock {
+    pub fn new(block: u64, slot_offset: Duration) -> Self {
+        Self {
+            block,
+            start: Instant::now(),
+            slot_offset,
+            pre_proposal: None,
+            aggregation: None,
+            proposal: None
+        }
+    }
+
+    pub fn on_transition(&mut self, name: ConsensusRoundName, orders: impl FnOnce() -> usize) {
+        let now = Instant::now();
+        match name {
+            ConsensusRoundName::PreProposal => self.pre_proposal = Some((now, orders())),
+            ConsensusRoundName::PreProposalAggregation => self.aggregation = Some(now),
+            ConsensusRoundName::Proposal | ConsensusRoundName::Finalization => {
+                self.proposal.get_or_insert(now);
+            }
+            ConsensusRoundName::BidAggregation => {}
+        }
+    }
+
+    /// the timing of the round, phases that weren't reached take no time.
+    pub fn finish(self, info: Option<&LastRoundInfo>, led: bool) -> RoundTiming {
+        let now = Instant::now();
+        let millis = |duration: Duration| duration.as_millis() as u64;
+
+        let (trigger, orders) = self.pre_proposal.unwrap_or((now, 0));
+        let aggregation = self.aggregation.unwrap_or(now).max(trigger);
+        let proposal = self.proposal.unwrap_or(now).max(aggregation);
+
+        RoundTiming {
+            block: self.block,
+            trigger_ms: millis(self.slot_offset + trigger.duration_since(self.start)),
+            orders,
+            pre_proposal_ms: millis(aggregation.duration_since(trigger)),
+            aggregation_ms: millis(proposal.duration_since(aggregation)),
+            matching_ms: info.map(|info| millis(info.matching)),
+            submission_ms: None,
+            // a led round that didn't submit missed, otherwise the outcome of the
+            // submission is recorded once it's in
+            landed: (led && info.is_none()).then_some(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn led_round(trigger_ms: u64, work_ms: u64, landed: bool) -> RoundTiming {
+        RoundTiming {
+            trigger_ms,
+            pre_proposal_ms: work_ms,
+            landed: Some(landed),
+            ..Default::default()
+        }
+    }
+
+    fn config() -> ConsensusTimingConfig {
+        ConsensusTimingConfig {
+            min_wait_duration_ms: 6_000,
+            max_wait_duration_ms: 10_000,
+            max_miss_rate:        0.2
+        }
+    }
+
+    #[test]
+    fn needs_enough_led_rounds() {
+        let mut controller = TimingController::default();
+        for _ in 0..MIN_LED_ROUNDS - 1 {
+            controller.record(led_round(8_000, 2_000, true));
+        }
+        // rounds led by others don't count
+        controller.record(RoundTiming { trigger_ms: 8_000, ..Default::default() });
+
+        assert!(!controller.decide(&config()).learned);
+
+        controller.record(led_round(8_000, 2_000, true));
+        let decision = controller.decide(&config());
+        assert!(decision.learned);
+        assert_eq!(decision.led_rounds, MIN_LED_ROUNDS);
+        assert_eq!(decision.trigger_ms, 8_000);
+    }
+
+    #[test]
+    fn triggers_as_late_as_the_deadline_allows() {
+        let mut controller = TimingController::default();
+        // landed up to 10.5s into the slot, usually needing 2s
+        controller.record(led_round(8_500, 2_000, true));
+        for _ in 0..8 {
+            controller.record(led_round(7_000, 2_000, true));
+        }
+        // one slow round is within the miss rate
+        controller.record(led_round(7_000, 4_000, false));
+
+        let decision = controller.decide(&config());
+        assert_eq!(decision.deadline_ms, Some(10_500));
+        assert_eq!(decision.work_ms, Some(2_000));
+        assert_eq!(decision.trigger_ms, 8_500);
+    }
+
+    #[test]
+    fn triggers_earlier_while_missing_too_often() {
+        let mut controller = TimingController::default();
+        for _ in 0..5 {
+            controller.record(led_round(8_000, 2_000, true));
+        }
+        for _ in 0..5 {
+            controller.record(led_round(8_000, 2_000, false));
+        }
+
+        let decision = controller.decide(&config());
+        assert_eq!(decision.miss_rate, 0.5);
+        // 2 misses are allowed, the other 3 move the trigger earlier
+        assert_eq!(decision.trigger_ms, 8_000 - 3 * MISS_BACKOFF.as_millis() as u64);
+    }
+
+    #[test]
+    fn applies_outcomes_to_their_round() {
+        let mut controller = TimingController::default();
+        let outcome =
+            |block, landed| BundleOutcome { block, submission: Duration::from_millis(300), landed };
+
+        // usually the outcome is in once the next round started
+        controller.record(RoundTiming { block: 1, ..Default::default() });
+        controller.record_outcome(outcome(1, true));
+        // but it can also come in before its round ended
+        controller.record_outcome(outcome(2, false));
+        controller.record(RoundTiming { block: 2, ..Default::default() });
+
+        let decision = controller.decide(&config());
+        assert_eq!(decision.led_rounds, 2);
+        assert_eq!(decision.miss_rate, 0.5);
+        assert_eq!(decision.history[0].landed, Some(true));
+        assert_eq!(decision.history[0].submission_ms, Some(300));
+        assert_eq!(decision.history[1].landed, Some(false));
+        assert!(controller.outcomes.is_empty());
+    }
+}
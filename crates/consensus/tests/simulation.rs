@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use testing_tools::simulation::{Byzantine, LinkConditions, Partition, Scenario};
+
+#[test]
+fn same_seed_replays_the_same_run() {
+    let lossy = LinkConditions {
+        min_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(300),
+        drop_rate: 0.05
+    };
+    let scenario = Scenario::new(7, 4, 4).with_link(lossy);
+
+    let first = scenario.clone().run();
+    let second = scenario.run();
+    assert_eq!(first, second);
+    assert!(first.safety_violations().is_empty(), "{:?}", first.safety_violations());
+
+    let other_seed = Scenario::new(8, 4, 4).with_link(lossy).run();
+    assert_ne!(first.messages, other_seed.messages);
+}
+
+#[test]
+fn honest_nodes_finalize_every_block() {
+    let reordering = LinkConditions {
+        min_delay: Duration::ZERO,
+        max_delay: Duration::from_millis(250),
+        drop_rate: 0.0
+    };
+    let report = Scenario::new(1, 5, 6).with_link(reordering).run();
+
+    assert!(report.safety_violations().is_empty(), "{:?}", report.safety_violations());
+    assert!(report.missed_blocks().is_empty(), "missed {:?}", report.missed_blocks());
+    assert!(
+        report
+            .blocks
+            .iter()
+            .all(|block| block.distinct_proposals() == 1)
+    );
+}
+
+#[test]
+fn isolated_node_only_misses_the_blocks_it_leads() {
+    let isolated = 3;
+    let report = Scenario::new(2, 4, 8)
+        .with_partition(Partition::new([isolated], Scenario::slot(2), Scenario::slot(5)))
+        .run();
+
+    assert!(report.safety_violations().is_empty(), "{:?}", report.safety_violations());
+    for missed in report.missed_blocks() {
+        let block = report
+            .blocks
+            .iter()
+            .find(|block| block.number == missed)
+            .unwrap();
+        assert_eq!(block.leader, isolated, "block {missed} was missed");
+    }
+
+    // rounds after the partition heals all complete
+    assert!(
+        report.blocks[5..]
+            .iter()
+            .all(|block| block.accepted.len() >= report.quorum)
+    );
+}
+
+#[test]
+fn byzantine_node_breaks_neither_safety_nor_liveness() {
+    for behaviour in [Byzantine::Silent, Byzantine::Equivocate, Byzantine::InvalidProposal] {
+        let report = Scenario::new(3, 4, 8).with_byzantine(0, behaviour).run();
+
+        assert!(
+            report.safety_violations().is_empty(),
+            "{behaviour:?}: {:?}",
+            report.safety_violations()
+        );
+        assert!(
+            report.missed_blocks().is_empty(),
+            "{behaviour:?}: missed {:?}",
+            report.missed_blocks()
+        );
+
+        if behaviour == Byzantine::InvalidProposal {
+            assert!(
+                report
+                    .blocks
+                    .iter()
+                    .filter(|block| block.leader == 0)
+                    .all(|block| block.accepted.is_empty())
+            );
+        }
+    }
+}
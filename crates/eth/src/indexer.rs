@@ -0,0 +1,335 @@
+//! Embedded index of every angstrom bundle that landed on chain. Each indexed
+//! block is appended to a json lines file so history survives restarts. On
+//! load later entries for a block override earlier ones, which is how reorgs
+//! are persisted. Once the file holds too many overridden or pruned entries
+//! it is rewritten with only the blocks that are still indexed.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc
+};
+
+use alloy::primitives::{Address, B256};
+use angstrom_types::{
+    contract_payloads::angstrom::{OrderFill, PoolClearing},
+    matching::Ray,
+    primitive::PoolId
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::json_lines::JsonLinesFile;
+
+/// A pool clearing of a bundle that landed on chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClearingRecord {
+    pub block_number: u64,
+    pub tx_hash:      B256,
+    pub pool_id:      PoolId,
+    #[serde(flatten)]
+    pub clearing:     PoolClearing
+}
+
+/// A fill of a users order that landed on chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillRecord {
+    pub block_number: u64,
+    pub tx_hash:      B256,
+    pub pool_id:      PoolId,
+    /// the uniform clearing price the order was filled at
+    pub ucp:          Ray,
+    #[serde(flatten)]
+    pub fill:         OrderFill
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedBlock {
+    block_number: u64,
+    clearings:    Vec<ClearingRecord>
+}
+
+#[derive(Debug, Default)]
+struct IndexerState {
+    blocks:     BTreeMap<u64, Vec<ClearingRecord>>,
+    /// user -> blocks the user had a fill in
+    user_fills: HashMap<Address, BTreeSet<u64>>
+}
+
+impl IndexerState {
+    fn insert(&mut self, block: IndexedBlock) {
+        self.remove(block.block_number);
+        if block.clearings.is_empty() {
+            return;
+        }
+
+        for fill in block.clearings.iter().flat_map(|c| &c.clearing.fills) {
+            self.user_fills
+                .entry(fill.user)
+                .or_default()
+                .insert(block.block_number);
+        }
+        self.blocks.insert(block.block_number, block.clearings);
+    }
+
+    fn remove(&mut self, block_number: u64) {
+        let Some(clearings) = self.blocks.remove(&block_number) else { return };
+
+        for fill in clearings.iter().flat_map(|c| &c.clearing.fills) {
+            if let Some(blocks) = self.user_fills.get_mut(&fill.user) {
+                blocks.remove(&block_number);
+                if blocks.is_empty() {
+                    self.user_fills.remove(&fill.user);
+                }
+            }
+        }
+    }
+
+    fn prune_before(&mut self, block_number: u64) {
+        let old = self
+            .blocks
+            .range(..block_number)
+            .map(|(block, _)| *block)
+            .collect::<Vec<_>>();
+        for block in old {
+            self.remove(block);
+        }
+    }
+}
+
+/// Cheaply cloneable handle to the bundle history index.
+#[derive(Debug, Clone)]
+pub struct BundleIndexer {
+    state:            Arc<RwLock<IndexerState>>,
+    /// where the index is persisted, in memory only if none
+    file:             Option<Arc<Mutex<JsonLinesFile>>>,
+    /// the amount of blocks of history to keep, everything is kept if none
+    retention_blocks: Option<u64>
+}
+
+impl BundleIndexer {
+    pub fn in_memory(retention_blocks: Option<u64>) -> Self {
+        Self { state: Default::default(), file: None, retention_blocks }
+    }
+
+    /// loads the index persisted at the path, creating it if it doesn't exist.
+    pub fn load(path: PathBuf, retention_blocks: Option<u64>) -> eyre::Result<Self> {
+        let mut state = IndexerState::default();
+        let file = JsonLinesFile::load(path, |block: IndexedBlock| state.insert(block))?;
+
+        let this = Self {
+            state: Arc::new(RwLock::new(state)),
+            file: Some(Arc::new(Mutex::new(file))),
+            retention_blocks
+        };
+        if let Some(tip) = this.latest_block() {
+            this.prune(tip);
+        }
+        this.compact_if_needed();
+
+        Ok(this)
+    }
+
+    /// sets the clearings of the block, overriding anything previously indexed
+    /// for it.
+    pub fn index_block(&self, block_number: u64, clearings: Vec<ClearingRecord>) {
+        if clearings.is_empty() && !self.state.read().blocks.contains_key(&block_number) {
+            return;
+        }
+
+        let block = IndexedBlock { block_number, clearings };
+        self.persist(&block);
+        self.state.write().insert(block);
+        self.prune(block_number);
+        self.compact_if_needed();
+    }
+
+    /// drops all indexed data for the reorged range.
+    pub fn reorg(&self, range: RangeInclusive<u64>) {
+        for block_number in range {
+            self.index_block(block_number, vec![]);
+        }
+    }
+
+    pub fn latest_block(&self) -> Option<u64> {
+        self.state
+            .read()
+            .blocks
+            .last_key_value()
+            .map(|(block, _)| *block)
+    }
+
+    /// all clearings of the pool in the inclusive block range, oldest first.
+    pub fn clearing_history(
+        &self,
+        pool_id: PoolId,
+        block_range: RangeInclusive<u64>
+    ) -> Vec<ClearingRecord> {
+        self.state
+            .read()
+            .blocks
+            .range(block_range)
+            .flat_map(|(_, clearings)| clearings)
+            .filter(|clearing| clearing.pool_id == pool_id)
+            .cloned()
+            .collect()
+    }
+
+    /// all fills of orders signed by the user, oldest first.
+    pub fn user_fills(&self, user: Address) -> Vec<FillRecord> {
+        let state = self.state.read();
+        let Some(blocks) = state.user_fills.get(&user) else { return vec![] };
+
+        blocks
+            .iter()
+            .filter_map(|block| state.blocks.get(block))
+            .flatten()
+            .flat_map(|record| {
+                record
+                    .clearing
+                    .fills
+                    .iter()
+                    .filter(|fill| fill.user == user)
+                    .map(|fill| FillRecord {
+                        block_number: record.block_number,
+                        tx_hash:      record.tx_hash,
+                        pool_id:      record.pool_id,
+                        ucp:          record.clearing.ucp,
+                        fill:         fill.clone()
+                    })
+            })
+            .collect()
+    }
+
+    fn prune(&self, tip: u64) {
+        let Some(retention) = self.retention_blocks else { return };
+        self.state
+            .write()
+            .prune_before(tip.saturating_sub(retention));
+    }
+
+    fn persist(&self, block: &IndexedBlock) {
+        let Some(file) = self.file.as_ref() else { return };
+        let mut file = file.lock();
+
+        if let Err(error) = file.append(std::slice::from_ref(block)) {
+            tracing::error!(%error, path = %file.path.display(), "failed to persist bundle index");
+        }
+    }
+
+    /// rewrites the file once it holds too many overridden or pruned blocks,
+    /// so it doesn't grow without bound and loading stays cheap.
+    fn compact_if_needed(&self) {
+        let Some(file) = self.file.as_ref() else { return };
+        let state = self.state.read();
+        let mut file = file.lock();
+        let blocks = state
+            .blocks
+            .iter()
+            .map(|(block_number, clearings)| IndexedBlock {
+                block_number: *block_number,
+                clearings:    clearings.clone()
+            });
+
+        if let Err(error) = file.compact_if_needed(state.blocks.len(), blocks) {
+            tracing::error!(%error, path = %file.path.display(), "failed to compact bundle index");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+    use crate::json_lines::COMPACTION_SLACK;
+
+    fn clearing(block_number: u64, user: Address) -> ClearingRecord {
+        ClearingRecord {
+            block_number,
+            tx_hash: B256::random(),
+            pool_id: PoolId::default(),
+            clearing: PoolClearing {
+                token0:               Address::ZERO,
+                token1:               Address::ZERO,
+                ucp:                  Ray::default(),
+                amm_zero_for_one:     false,
+                amm_swap_in_quantity: 0,
+                donation_total_t0:    0,
+                user_fees_t0:         0,
+                tob_reward_t0:        0,
+                fills:                vec![OrderFill {
+                    order_hash: B256::random(),
+                    user,
+                    is_tob: false,
+                    token_in: Address::ZERO,
+                    token_out: Address::ZERO,
+                    amount_in: 10,
+                    amount_out: 10,
+                    gas_fee_t0: 0,
+                    lp_fee_t0: 0
+                }]
+            }
+        }
+    }
+
+    #[test]
+    fn reorg_removes_fills() {
+        let user = address!("0x00000000000000000000000000000000000000aa");
+        let indexer = BundleIndexer::in_memory(None);
+        indexer.index_block(1, vec![clearing(1, user)]);
+        indexer.index_block(2, vec![clearing(2, user)]);
+
+        assert_eq!(indexer.user_fills(user).len(), 2);
+        assert_eq!(indexer.clearing_history(PoolId::default(), 0..=10).len(), 2);
+
+        indexer.reorg(2..=2);
+        assert_eq!(indexer.user_fills(user).len(), 1);
+        assert_eq!(indexer.latest_block(), Some(1));
+    }
+
+    #[test]
+    fn prunes_outside_of_retention() {
+        let user = address!("0x00000000000000000000000000000000000000aa");
+        let indexer = BundleIndexer::in_memory(Some(5));
+        indexer.index_block(1, vec![clearing(1, user)]);
+        indexer.index_block(10, vec![clearing(10, user)]);
+
+        assert_eq!(indexer.clearing_history(PoolId::default(), 0..=10).len(), 1);
+        assert_eq!(indexer.user_fills(user).len(), 1);
+    }
+
+    #[test]
+    fn reloads_persisted_index() {
+        let user = address!("0x00000000000000000000000000000000000000aa");
+        let path = std::env::temp_dir().join(format!("bundle-index-{}.jsonl", B256::random()));
+
+        let indexer = BundleIndexer::load(path.clone(), None).unwrap();
+        indexer.index_block(1, vec![clearing(1, user)]);
+        indexer.index_block(2, vec![clearing(2, user)]);
+        indexer.reorg(2..=2);
+
+        let reloaded = BundleIndexer::load(path.clone(), None).unwrap();
+        assert_eq!(reloaded.user_fills(user), indexer.user_fills(user));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn compacts_the_persisted_index() {
+        let user = address!("0x00000000000000000000000000000000000000aa");
+        let path = std::env::temp_dir().join(format!("bundle-index-{}.jsonl", B256::random()));
+
+        let indexer = BundleIndexer::load(path.clone(), Some(10)).unwrap();
+        for block in 0..(2 * COMPACTION_SLACK as u64) {
+            indexer.index_block(block, vec![clearing(block, user)]);
+        }
+
+        let entries = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert!(entries <= 11 + COMPACTION_SLACK);
+
+        let reloaded = BundleIndexer::load(path.clone(), Some(10)).unwrap();
+        assert_eq!(reloaded.user_fills(user), indexer.user_fills(user));
+        assert_eq!(reloaded.latest_block(), Some(2 * COMPACTION_SLACK as u64 - 1));
+        let _ = std::fs::remove_file(path);
+    }
+}
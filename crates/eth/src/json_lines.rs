@@ -0,0 +1,95 @@
+//! Json lines file that the bundle index and the rewards ledger are persisted
+//! to. Entries are appended as they come in and the file is rewritten with only
+//! the live entries once it holds too many stale ones.
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// how many more entries than live ones the file may hold before it is
+/// rewritten.
+pub(crate) const COMPACTION_SLACK: usize = 1024;
+
+#[derive(Debug)]
+pub(crate) struct JsonLinesFile {
+    pub(crate) path: PathBuf,
+    file:            File,
+    /// the entries in the file
+    entries:         usize
+}
+
+impl JsonLinesFile {
+    /// opens the file at the path, passing every entry in it to `on_entry`.
+    /// corrupt entries are skipped.
+    pub(crate) fn load<T: DeserializeOwned>(
+        path: PathBuf,
+        mut on_entry: impl FnMut(T)
+    ) -> eyre::Result<Self> {
+        let mut entries = 0;
+
+        if path.try_exists()? {
+            let reader = BufReader::new(File::open(&path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if line.is_empty() {
+                    continue;
+                }
+                entries += 1;
+                match serde_json::from_str(&line) {
+                    Ok(entry) => on_entry(entry),
+                    Err(error) => {
+                        tracing::warn!(%error, path = %path.display(), "skipping corrupt entry")
+                    }
+                }
+            }
+        }
+
+        Ok(Self::open(path, entries)?)
+    }
+
+    fn open(path: PathBuf, entries: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, file, entries })
+    }
+
+    pub(crate) fn append<T: Serialize>(&mut self, entries: &[T]) -> eyre::Result<()> {
+        let mut lines = String::new();
+        for entry in entries {
+            lines += &serde_json::to_string(entry)?;
+            lines.push('\n');
+        }
+        self.file.write_all(lines.as_bytes())?;
+        self.entries += entries.len();
+
+        Ok(())
+    }
+
+    /// rewrites the file with the live entries once it holds more than
+    /// [`COMPACTION_SLACK`] stale ones.
+    pub(crate) fn compact_if_needed<T: Serialize>(
+        &mut self,
+        live: usize,
+        entries: impl IntoIterator<Item = T>
+    ) -> eyre::Result<()> {
+        if self.entries <= live + COMPACTION_SLACK {
+            return Ok(());
+        }
+
+        let tmp = self.path.with_extension("compacting");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        let mut written = 0;
+        for entry in entries {
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.write_all(b"\n")?;
+            written += 1;
+        }
+        writer.into_inner()?.sync_all()?;
+        std::fs::rename(&tmp, &self.path)?;
+        *self = Self::open(self.path.clone(), written)?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
 pub mod handle;
+pub mod indexer;
+mod json_lines;
 pub mod manager;
+pub mod rewards;
+pub mod rpc_chain;
 pub mod telemetry;
@@ -7,7 +7,10 @@ use std::{
 
 use alloy::{
     consensus::Transaction,
-    primitives::{Address, B256, aliases::I24},
+    primitives::{
+        Address, B256,
+        aliases::{I24, U24}
+    },
     sol_types::{SolCall, SolEvent}
 };
 use angstrom_types::{
@@ -16,8 +19,10 @@ use angstrom_types::{
         angstrom::Angstrom::{PoolKey, executeCall},
         controller_v_1::ControllerV1::{NodeAdded, NodeRemoved, PoolConfigured, PoolRemoved}
     },
-    contract_payloads::angstrom::{AngPoolConfigEntry, AngstromBundle, AngstromPoolConfigStore},
-    primitive::ChainExt
+    contract_payloads::angstrom::{
+        AngPoolConfigEntry, AngstromBundle, AngstromPoolConfigStore, FillReceipt
+    },
+    primitive::{ChainExt, POOL_MANAGER_ADDRESS, PoolId}
 };
 use futures::Future;
 use futures_util::{FutureExt, StreamExt};
@@ -30,6 +35,8 @@ use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
 
 use crate::{
     handle::{EthCommand, EthHandle},
+    indexer::{BundleIndexer, ClearingRecord},
+    rewards::{LedgerEvent, RewardsLedger},
     telemetry::EthUpdaterSnapshot
 };
 
@@ -41,23 +48,27 @@ alloy::sol!(
 /// Listens for CanonStateNotifications and sends the appropriate updates to be
 /// executed by the order pool
 pub struct EthDataCleanser<Sync> {
-    pub(crate) angstrom_address:  Address,
+    pub(crate) angstrom_address: Address,
     pub(crate) periphery_address: Address,
     /// our command receiver
-    pub(crate) commander:         ReceiverStream<EthCommand>,
+    pub(crate) commander: ReceiverStream<EthCommand>,
     /// people listening to events
-    pub(crate) event_listeners:   Vec<UnboundedSender<EthEvent>>,
+    pub(crate) event_listeners: Vec<UnboundedSender<EthEvent>>,
     /// for rebroadcasting
-    pub(crate) cannon_sender:     tokio::sync::broadcast::Sender<CanonStateNotification>,
+    pub(crate) cannon_sender: tokio::sync::broadcast::Sender<CanonStateNotification>,
     /// Notifications for Canonical Block updates
     pub(crate) canonical_updates: BroadcastStream<CanonStateNotification>,
-    pub(crate) angstrom_tokens:   HashMap<Address, usize>,
+    pub(crate) angstrom_tokens: HashMap<Address, usize>,
     /// handles syncing of blocks.
-    block_sync:                   Sync,
+    block_sync: Sync,
     /// updated by periphery contract.
-    pub(crate) pool_store:        Arc<AngstromPoolConfigStore>,
+    pub(crate) pool_store: Arc<AngstromPoolConfigStore>,
     /// the set of currently active nodes.
-    pub(crate) node_set:          HashSet<Address>
+    pub(crate) node_set: HashSet<Address>,
+    /// history of the bundles that landed on chain
+    indexer: Option<BundleIndexer>,
+    /// rewards lps earned from bundle donations
+    rewards: Option<RewardsLedger>
 }
 
 impl<Sync> EthDataCleanser<Sync>
@@ -75,7 +86,9 @@ where
         pool_store: Arc<AngstromPoolConfigStore>,
         sync: Sync,
         node_set: HashSet<Address>,
-        event_listeners: Vec<UnboundedSender<EthEvent>>
+        event_listeners: Vec<UnboundedSender<EthEvent>>,
+        indexer: Option<BundleIndexer>,
+        rewards: Option<RewardsLedger>
     ) -> anyhow::Result<EthHandle> {
         let stream = ReceiverStream::new(rx);
         let (cannon_tx, _) = tokio::sync::broadcast::channel(1000);
@@ -90,7 +103,9 @@ where
             block_sync: sync,
             pool_store,
             node_set,
-            event_listeners
+            event_listeners,
+            indexer,
+            rewards
         };
         // ensure we broadcast node set. will allow for proper connections
         // on the network side
@@ -156,6 +171,22 @@ where
         let new_filled: HashSet<_> = self.fetch_filled_order(&new).collect();
 
         let difference: Vec<_> = old_filled.difference(&new_filled).copied().collect();
+        if let Some(indexer) = self.indexer.as_ref() {
+            indexer.reorg(reorg.clone());
+        }
+        // every block of the new chain replaces what was indexed for it, not just the
+        // tip
+        for block_number in new.block_numbers() {
+            self.index_bundles(block_number, self.landed_clearings(&new, block_number));
+        }
+        if let Some(rewards) = self.rewards.as_ref() {
+            let blocks = new
+                .block_numbers()
+                .into_iter()
+                .map(|block_number| (block_number, self.ledger_events(&new, block_number)))
+                .collect();
+            rewards.reorg(reorg.clone(), blocks);
+        }
         let reorged_orders = EthEvent::ReorgedOrders(difference, reorg);
 
         self.send_events(reorged_orders);
@@ -171,12 +202,34 @@ where
 
         let filled_orders = self.fetch_filled_order(&new).collect::<Vec<_>>();
         tracing::info!(?filled_orders, "filled orders found");
+        // a commit can carry several blocks, e.g. after the node fell behind
+        let mut fill_receipts = vec![];
+        for block_number in new.block_numbers() {
+            let clearings = self.landed_clearings(&new, block_number);
+            fill_receipts.extend(clearings.iter().flat_map(|record| {
+                record.clearing.fills.iter().map(|fill| FillReceipt {
+                    tx_hash: record.tx_hash,
+                    ucp:     record.clearing.ucp,
+                    fill:    fill.clone()
+                })
+            }));
+            self.index_bundles(block_number, clearings);
+        }
+        if let Some(rewards) = self.rewards.as_ref() {
+            let blocks = new
+                .block_numbers()
+                .into_iter()
+                .map(|block_number| (block_number, self.ledger_events(&new, block_number)))
+                .collect::<Vec<_>>();
+            rewards.record_blocks(blocks);
+        }
 
         let eoas = self.get_eoa(new.clone());
 
         let transitions = EthEvent::NewBlockTransitions {
             block_number: new.tip_number(),
             filled_orders,
+            fill_receipts,
             address_changeset: eoas
         };
         self.send_events(transitions);
@@ -253,6 +306,13 @@ where
                         hooks:       self.angstrom_address
                     };
 
+                    if let Some(rewards) = self.rewards.as_ref() {
+                        rewards.track_pool(
+                            PoolId::from(pool_key),
+                            PoolId::from(PoolKey { fee: U24::from(0x800000), ..pool_key })
+                        );
+                    }
+
                     self.pool_store.new_pool(asset0, asset1, entry);
                     *self.angstrom_tokens.entry(asset0).or_default() += 1;
                     *self.angstrom_tokens.entry(asset1).or_default() += 1;
@@ -266,23 +326,139 @@ where
         &'a self,
         chain: &'a impl ChainExt
     ) -> impl Iterator<Item = B256> + 'a {
+        // every block of the chain, not just the tip
+        chain
+            .block_numbers()
+            .into_iter()
+            .flat_map(move |block_number| {
+                chain
+                    .successful_block_transactions(block_number)
+                    .into_iter()
+                    .filter(|&tx| tx.to() == Some(self.angstrom_address))
+                    .filter_map(|transaction| {
+                        let input: &[u8] = transaction.input();
+                        let call = executeCall::abi_decode(input).ok()?;
+
+                        let mut input = call.encoded.as_ref();
+                        AngstromBundle::pade_decode(&mut input, None).ok()
+                    })
+                    .flat_map(move |bundle| {
+                        tracing::info!("found angstrom bundle that landed on chain!");
+                        bundle.get_order_hashes(block_number).collect::<Vec<_>>()
+                    })
+            })
+    }
+
+    fn index_bundles(&self, block_number: u64, clearings: Vec<ClearingRecord>) {
+        let Some(indexer) = self.indexer.as_ref() else { return };
+        indexer.index_block(block_number, clearings);
+    }
+
+    /// the pool clearings of all bundles that landed in the block.
+    fn landed_clearings(&self, chain: &impl ChainExt, block_number: u64) -> Vec<ClearingRecord> {
         chain
-            .successful_tip_transactions()
+            .successful_block_transactions(block_number)
+            .into_iter()
             .filter(|&tx| tx.to() == Some(self.angstrom_address))
-            .cloned()
             .filter_map(|transaction| {
-                let input: &[u8] = transaction.input();
-                let call = executeCall::abi_decode(input).ok()?;
+                let call = executeCall::abi_decode(transaction.input()).ok()?;
 
                 let mut input = call.encoded.as_ref();
-                AngstromBundle::pade_decode(&mut input, None).ok()
+                let bundle = AngstromBundle::pade_decode(&mut input, None).ok()?;
+                Some((*transaction.tx_hash(), bundle))
             })
-            .flat_map(move |bundle| {
-                tracing::info!("found angstrom bundle that landed on chain!");
+            .flat_map(|(tx_hash, bundle)| {
                 bundle
-                    .get_order_hashes(chain.tip_number())
+                    .pool_clearings(block_number, |t0, t1| {
+                        self.pool_store
+                            .get_entry(t0, t1)
+                            .map(|entry| entry.fee_in_e6)
+                            .unwrap_or_default()
+                    })
+                    .into_iter()
+                    .map(|clearing| ClearingRecord {
+                        block_number,
+                        tx_hash,
+                        pool_id: self.pool_id(clearing.token0, clearing.token1),
+                        clearing
+                    })
                     .collect::<Vec<_>>()
             })
+            .collect::<Vec<_>>()
+    }
+
+    /// the liquidity changes and rewards of the tracked pools in the block.
+    fn ledger_events(&self, chain: &impl ChainExt, block_number: u64) -> Vec<LedgerEvent> {
+        let Some(rewards) = self.rewards.as_ref() else { return vec![] };
+        let Some(pool_manager) = POOL_MANAGER_ADDRESS.get().copied() else { return vec![] };
+        let Some(block_hash) = chain.block_hash(block_number) else { return vec![] };
+        let receipts = chain.receipts_by_block_hash(block_hash).unwrap_or_default();
+
+        let mut events = vec![];
+        for (transaction, receipt) in chain
+            .block_transactions(block_number)
+            .into_iter()
+            .zip(receipts)
+        {
+            if !receipt.success {
+                continue;
+            }
+
+            // the bundle swaps before donating, so the logs go first
+            events.extend(
+                receipt
+                    .logs
+                    .iter()
+                    .filter(|log| log.address == pool_manager)
+                    .filter_map(LedgerEvent::from_pool_manager_log)
+            );
+
+            if transaction.to() != Some(self.angstrom_address) {
+                continue;
+            }
+            let Some(bundle) = executeCall::abi_decode(transaction.input())
+                .ok()
+                .and_then(|call| {
+                    AngstromBundle::pade_decode(&mut call.encoded.as_ref(), None).ok()
+                })
+            else {
+                continue;
+            };
+
+            events.extend(bundle.pool_updates.into_iter().map(|update| {
+                let pair = &bundle.pairs[update.pair_index as usize];
+                let asset0 = bundle.assets[pair.index0 as usize].addr;
+                let asset1 = bundle.assets[pair.index1 as usize].addr;
+                LedgerEvent::Rewards {
+                    pool_id: self.uniswap_pool_id(asset0, asset1),
+                    update:  update.rewards_update
+                }
+            }));
+        }
+
+        events.retain(|event| rewards.is_tracked(event.pool_id()));
+        events
+    }
+
+    fn pool_key(&self, asset0: Address, asset1: Address) -> PoolKey {
+        let entry = self.pool_store.get_entry(asset0, asset1);
+        PoolKey {
+            currency0:   asset0,
+            currency1:   asset1,
+            fee:         U24::from(entry.map(|e| e.fee_in_e6).unwrap_or_default()),
+            tickSpacing: I24::unchecked_from(entry.map(|e| e.tick_spacing).unwrap_or_default()),
+            hooks:       self.angstrom_address
+        }
+    }
+
+    fn pool_id(&self, asset0: Address, asset1: Address) -> PoolId {
+        PoolId::from(self.pool_key(asset0, asset1))
+    }
+
+    /// the id of the pool on the uniswap pool manager, which uses the dynamic
+    /// fee flag instead of the bundle fee.
+    fn uniswap_pool_id(&self, asset0: Address, asset1: Address) -> PoolId {
+        PoolId::from(PoolKey { fee: U24::from(0x800000), ..self.pool_key(asset0, asset1) })
     }
 
     /// fetches all eoa addresses touched
@@ -356,6 +532,8 @@ pub enum EthEvent {
     NewBlockTransitions {
         block_number:      u64,
         filled_orders:     Vec<B256>,
+        /// what every order that was filled in the block got
+        fill_receipts:     Vec<FillReceipt>,
         address_changeset: Vec<Address>
     },
     ReorgedOrders(Vec<B256>, RangeInclusive<u64>),
@@ -404,7 +582,9 @@ pub mod test {
         pub hash:         BlockHash,
         pub number:       BlockNumber,
         pub transactions: Vec<TransactionSigned>,
-        pub receipts:     Vec<&'a Receipt>
+        pub receipts:     Vec<&'a Receipt>,
+        /// the blocks before the tip, oldest first
+        pub parents:      Vec<(BlockNumber, Vec<TransactionSigned>)>
     }
 
     impl ChainExt for MockChain<'_> {
@@ -435,6 +615,33 @@ pub mod test {
         fn blocks_iter(&self) -> impl Iterator<Item = &RecoveredBlock<Block>> + '_ {
             vec![].into_iter()
         }
+
+        fn block_numbers(&self) -> Vec<BlockNumber> {
+            self.parents
+                .iter()
+                .map(|(number, _)| *number)
+                .chain(std::iter::once(self.number))
+                .collect()
+        }
+
+        fn block_hash(&self, number: BlockNumber) -> Option<BlockHash> {
+            self.block_numbers().contains(&number).then_some(self.hash)
+        }
+
+        fn block_transactions(&self, number: BlockNumber) -> Vec<&TransactionSigned> {
+            if number == self.number {
+                return self.transactions.iter().collect();
+            }
+            self.parents
+                .iter()
+                .find(|(parent, _)| *parent == number)
+                .map(|(_, transactions)| transactions.iter().collect())
+                .unwrap_or_default()
+        }
+
+        fn successful_block_transactions(&self, number: BlockNumber) -> Vec<&TransactionSigned> {
+            self.block_transactions(number)
+        }
     }
 
     fn setup_non_subscription_eth_manager(
@@ -453,7 +660,9 @@ pub mod test {
             canonical_updates: BroadcastStream::new(cannon_rx),
             block_sync:        GlobalBlockSync::new(1),
             cannon_sender:     tx,
-            pool_store:        Default::default()
+            pool_store:        Default::default(),
+            indexer:           None,
+            rewards:           None
         }
     }
 
@@ -649,6 +858,121 @@ pub mod test {
         assert!(received_reorg, "Should have received ReorgedOrders event");
     }
 
+    fn tob_bundle_transaction(angstrom_address: Address) -> TransactionSigned {
+        let order = ToBOrderBuilder::new()
+            .signing_key(Some(setup_signing_info()))
+            .build();
+        let order = OrderWithStorageData { order, ..Default::default() };
+        let pairs = vec![Pair {
+            index0:       0,
+            index1:       1,
+            store_index:  0,
+            price_1over0: U256::default()
+        }];
+        let assets = vec![
+            Asset { addr: order.asset_out, ..Default::default() },
+            Asset { addr: order.asset_in, ..Default::default() },
+        ];
+        let bundle = AngstromBundle::new(
+            assets,
+            pairs,
+            vec![],
+            vec![TopOfBlockOrder::of_max_gas(&order, 0)],
+            vec![]
+        );
+
+        let tx = TxLegacy {
+            to: TxKind::Call(angstrom_address),
+            input: executeCall::new((bundle.pade_encode().into(),))
+                .abi_encode()
+                .into(),
+            ..Default::default()
+        };
+        TransactionSigned::new_unhashed(tx.into(), Signature::test_signature())
+    }
+
+    #[test]
+    fn test_handle_reorg_indexes_every_new_block() {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let ang_addr = Address::random();
+        let mut eth = setup_non_subscription_eth_manager(Some(ang_addr));
+        let indexer = BundleIndexer::in_memory(None);
+        eth.indexer = Some(indexer.clone());
+
+        let old_chain =
+            Arc::new(MockChain { number: 95, hash: BlockHash::random(), ..Default::default() });
+        // the bundle landed in the block before the tip of the new chain
+        let new_chain = Arc::new(MockChain {
+            number: 95,
+            hash: BlockHash::random(),
+            parents: vec![(94, vec![tob_bundle_transaction(ang_addr)])],
+            ..Default::default()
+        });
+
+        eth.handle_reorg(old_chain, new_chain);
+
+        assert_eq!(indexer.latest_block(), Some(94));
+    }
+
+    #[test]
+    fn test_handle_commit_indexes_every_new_block() {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let ang_addr = Address::random();
+        let mut eth = setup_non_subscription_eth_manager(Some(ang_addr));
+        let indexer = BundleIndexer::in_memory(None);
+        eth.indexer = Some(indexer.clone());
+
+        let new_chain = Arc::new(MockChain {
+            number: 101,
+            hash: BlockHash::random(),
+            parents: vec![(100, vec![tob_bundle_transaction(ang_addr)])],
+            ..Default::default()
+        });
+
+        eth.handle_commit(new_chain);
+
+        assert_eq!(indexer.latest_block(), Some(100));
+    }
+
+    #[test]
+    fn test_handle_commit_has_receipts_of_every_new_block() {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let ang_addr = Address::random();
+        let mut eth = setup_non_subscription_eth_manager(Some(ang_addr));
+        let (parent_tx, tip_tx) =
+            (tob_bundle_transaction(ang_addr), tob_bundle_transaction(ang_addr));
+        let tx_hashes = [*parent_tx.tx_hash(), *tip_tx.tx_hash()];
+
+        let new_chain = Arc::new(MockChain {
+            number: 101,
+            hash: BlockHash::random(),
+            transactions: vec![tip_tx],
+            parents: vec![(100, vec![parent_tx])],
+            ..Default::default()
+        });
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        eth.event_listeners.push(tx);
+
+        eth.handle_commit(new_chain);
+
+        let Ok(EthEvent::NewBlockTransitions { filled_orders, fill_receipts, .. }) = rx.try_recv()
+        else {
+            panic!("Expected NewBlockTransitions event")
+        };
+        let receipt_txs = fill_receipts
+            .iter()
+            .map(|receipt| receipt.tx_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(receipt_txs, tx_hashes);
+        // the orders of both blocks are filled, each with its receipt
+        assert_eq!(filled_orders.len(), 2);
+        assert!(
+            fill_receipts
+                .iter()
+                .all(|receipt| filled_orders.contains(&receipt.fill.order_hash))
+        );
+    }
+
     #[test]
     fn test_handle_commit() {
         let ang_addr = Address::random();
@@ -666,7 +990,12 @@ pub mod test {
 
         // Verify new block transitions event was sent
         match rx.try_recv().expect("Should receive an event") {
-            EthEvent::NewBlockTransitions { block_number, filled_orders, address_changeset } => {
+            EthEvent::NewBlockTransitions {
+                block_number,
+                filled_orders,
+                address_changeset,
+                ..
+            } => {
                 assert_eq!(block_number, 100);
                 assert!(filled_orders.is_empty());
                 assert!(address_changeset.is_empty());
@@ -782,7 +1111,12 @@ pub mod test {
         eth.handle_commit(mock_chain);
 
         match rx.try_recv().expect("Should receive an event") {
-            EthEvent::NewBlockTransitions { block_number, filled_orders, address_changeset } => {
+            EthEvent::NewBlockTransitions {
+                block_number,
+                filled_orders,
+                address_changeset,
+                ..
+            } => {
                 assert_eq!(block_number, 100);
                 assert!(filled_orders.is_empty());
                 assert!(address_changeset.is_empty());
@@ -0,0 +1,833 @@
+//! Ledger of the rewards that every uniswap position in a angstrom pool earned
+//! from bundle donations. Positions are tracked from the `ModifyLiquidity`
+//! events of the pool manager and every `RewardsUpdate` of a landed bundle is
+//! replayed against them the same way the contract walks the initialized
+//! ticks. All events are appended to a json lines file and replayed on
+//! startup, liquidity that was added before the ledger started tracking a pool
+//! is backfilled from the pool manager logs with [`RewardsLedger::backfill`].
+//! The state of the pools is checkpointed every few blocks so that reorgs only
+//! replay the blocks after the fork.
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc
+};
+
+use alloy::{
+    primitives::{Address, B256, Log, U256},
+    providers::Provider,
+    rpc::types::Filter,
+    sol_types::SolEvent
+};
+use angstrom_types::{
+    contract_bindings::pool_manager::PoolManager::{Initialize, ModifyLiquidity, Swap},
+    contract_payloads::rewards::RewardsUpdate,
+    primitive::PoolId
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::json_lines::JsonLinesFile;
+
+/// the max amount of blocks that are fetched in one `eth_getLogs` call when
+/// backfilling.
+const BACKFILL_LOG_RANGE: u64 = 10_000;
+/// the min amount of blocks between two checkpoints of the pools.
+const CHECKPOINT_INTERVAL: u64 = 64;
+/// the amount of checkpoints that are kept, a change before the oldest one
+/// replays the whole history.
+const MAX_CHECKPOINTS: usize = 8;
+
+/// A uniswap v4 position. The owner is the address that modified the
+/// liquidity on the pool manager, e.g the position manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PositionKey {
+    pub owner:      Address,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub salt:       B256
+}
+
+impl PositionKey {
+    fn contains(&self, tick: i32) -> bool {
+        self.tick_lower <= tick && tick < self.tick_upper
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionReward {
+    pub block_number: u64,
+    /// rewards earned in the block, in token0
+    pub amount_t0:    u128
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionRewards {
+    pub uniswap_pool_id: PoolId,
+    pub position:        PositionKey,
+    /// the sum of all rewards in the requested range, in token0
+    pub total_t0:        u128,
+    /// the rewards for every block in the requested range the position earned
+    /// anything in, oldest first
+    pub rewards:         Vec<PositionReward>
+}
+
+/// Something that happened on chain that the ledger cares about. All pool ids
+/// are the ids of the pools on the uniswap pool manager.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerEvent {
+    ModifyLiquidity { pool_id: PoolId, position: PositionKey, liquidity_delta: i128 },
+    Initialize { pool_id: PoolId, tick: i32 },
+    Swap { pool_id: PoolId, tick: i32 },
+    Rewards { pool_id: PoolId, update: RewardsUpdate }
+}
+
+impl LedgerEvent {
+    pub fn pool_id(&self) -> PoolId {
+        match self {
+            Self::ModifyLiquidity { pool_id, .. }
+            | Self::Initialize { pool_id, .. }
+            | Self::Swap { pool_id, .. }
+            | Self::Rewards { pool_id, .. } => *pool_id
+        }
+    }
+
+    /// decodes a liquidity change, initialization or swap from a log of the
+    /// pool manager.
+    pub fn from_pool_manager_log(log: &Log) -> Option<Self> {
+        if let Ok(modify) = ModifyLiquidity::decode_log(log) {
+            Some(Self::ModifyLiquidity {
+                pool_id:         modify.id,
+                position:        PositionKey {
+                    owner:      modify.sender,
+                    tick_lower: modify.tickLower.as_i32(),
+                    tick_upper: modify.tickUpper.as_i32(),
+                    salt:       modify.salt
+                },
+                liquidity_delta: modify.liquidityDelta.as_i128()
+            })
+        } else if let Ok(swap) = Swap::decode_log(log) {
+            Some(Self::Swap { pool_id: swap.id, tick: swap.tick.as_i32() })
+        } else if let Ok(initialize) = Initialize::decode_log(log) {
+            Some(Self::Initialize { pool_id: initialize.id, tick: initialize.tick.as_i32() })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LedgerBlock {
+    block_number: u64,
+    events:       Vec<LedgerEvent>
+}
+
+#[derive(Debug, Clone, Default)]
+struct PoolLedger {
+    /// set by the initialization of the pool and every swap
+    current_tick: Option<i32>,
+    /// initialized tick -> liquidity net
+    ticks:        BTreeMap<i32, i128>,
+    positions:    HashMap<PositionKey, u128>
+}
+
+impl PoolLedger {
+    fn modify_liquidity(&mut self, position: PositionKey, liquidity_delta: i128) {
+        let liquidity = self.positions.entry(position).or_default();
+        *liquidity = liquidity.saturating_add_signed(liquidity_delta);
+        if *liquidity == 0 {
+            self.positions.remove(&position);
+        }
+
+        for (tick, delta) in
+            [(position.tick_lower, liquidity_delta), (position.tick_upper, -liquidity_delta)]
+        {
+            let net = self.ticks.entry(tick).or_default();
+            *net += delta;
+            if *net == 0 {
+                self.ticks.remove(&tick);
+            }
+        }
+    }
+
+    fn net(&self, tick: i32) -> i128 {
+        self.ticks.get(&tick).copied().unwrap_or_default()
+    }
+
+    /// splits the rewards into `(tick, liquidity, amount)` where the amount is
+    /// donated to the liquidity of the range that contains the tick.
+    fn donations(&self, update: &RewardsUpdate) -> Option<Vec<(i32, u128, u128)>> {
+        let current_tick = self.current_tick?;
+
+        match update {
+            RewardsUpdate::CurrentOnly { amount, expected_liquidity } => {
+                Some(vec![(current_tick, *expected_liquidity, *amount)])
+            }
+            RewardsUpdate::MultiTick { start_tick, start_liquidity, quantities, .. } => {
+                let (to_current, quantities) = quantities.split_last()?;
+                let start_tick = start_tick.as_i32();
+                let below = start_tick <= current_tick;
+
+                let mut donations = Vec::with_capacity(quantities.len() + 1);
+                let mut liquidity = *start_liquidity;
+                let mut tick = Some(start_tick);
+                let mut last_tick = start_tick;
+
+                for amount in quantities {
+                    let Some(reward_tick) = tick else {
+                        tracing::warn!(start_tick, "rewards update walks past known ticks");
+                        return None;
+                    };
+                    // coming from below the quantity goes to the range under the
+                    // tick, from above to the range that starts at it.
+                    let range_tick = if below { reward_tick - 1 } else { reward_tick };
+                    donations.push((range_tick, liquidity, *amount));
+
+                    let net = self.net(reward_tick);
+                    liquidity = liquidity.saturating_add_signed(if below { net } else { -net });
+                    last_tick = reward_tick;
+                    tick = if below {
+                        self.ticks.range(reward_tick + 1..).next()
+                    } else {
+                        self.ticks.range(..reward_tick).next_back()
+                    }
+                    .map(|(tick, _)| *tick);
+                }
+
+                let range_tick = if below { last_tick } else { last_tick - 1 };
+                donations.push((range_tick, liquidity, *to_current));
+
+                Some(donations)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LedgerState {
+    blocks:      BTreeMap<u64, Vec<LedgerEvent>>,
+    pools:       HashMap<PoolId, PoolLedger>,
+    /// (uniswap pool id, position) -> block -> rewards
+    accrued:     HashMap<(PoolId, PositionKey), BTreeMap<u64, u128>>,
+    /// block -> the pools once the block was applied
+    checkpoints: BTreeMap<u64, HashMap<PoolId, PoolLedger>>
+}
+
+impl LedgerState {
+    /// sets the events of the blocks. blocks past the latest one are applied on
+    /// top of the current state, anything else replays the history from the
+    /// first block of the batch once.
+    fn insert(&mut self, mut blocks: Vec<LedgerBlock>) {
+        blocks.sort_by_key(|block| block.block_number);
+        let appends = blocks.first().is_none_or(|first| {
+            self.blocks
+                .last_key_value()
+                .is_none_or(|(last, _)| *last < first.block_number)
+        }) && blocks
+            .windows(2)
+            .all(|pair| pair[0].block_number < pair[1].block_number);
+
+        for block in &blocks {
+            if block.events.is_empty() {
+                self.blocks.remove(&block.block_number);
+            } else {
+                self.blocks.insert(block.block_number, block.events.clone());
+            }
+        }
+
+        if appends {
+            for block in &blocks {
+                self.apply(block.block_number, &block.events);
+            }
+        } else if let Some(first) = blocks.first() {
+            self.replay_from(first.block_number);
+        }
+    }
+
+    /// the first block with an event of the pool.
+    fn first_block(&self, uniswap_pool_id: PoolId) -> Option<u64> {
+        self.blocks
+            .iter()
+            .find(|(_, events)| {
+                events
+                    .iter()
+                    .any(|event| event.pool_id() == uniswap_pool_id)
+            })
+            .map(|(block_number, _)| *block_number)
+    }
+
+    /// replays the blocks from the latest checkpoint before the fork, used when
+    /// the history from the fork on has been changed.
+    fn replay_from(&mut self, fork: u64) {
+        self.checkpoints
+            .retain(|block_number, _| *block_number < fork);
+        let start = match self.checkpoints.last_key_value() {
+            Some((block_number, pools)) => {
+                self.pools = pools.clone();
+                block_number + 1
+            }
+            None => {
+                self.pools.clear();
+                0
+            }
+        };
+        for rewards in self.accrued.values_mut() {
+            rewards.retain(|block_number, _| *block_number < start);
+        }
+        self.accrued.retain(|_, rewards| !rewards.is_empty());
+
+        let blocks = std::mem::take(&mut self.blocks);
+        for (block_number, events) in blocks.range(start..) {
+            self.apply(*block_number, events);
+        }
+        self.blocks = blocks;
+    }
+
+    fn apply(&mut self, block_number: u64, events: &[LedgerEvent]) {
+        for event in events {
+            match event {
+                LedgerEvent::ModifyLiquidity { pool_id, position, liquidity_delta } => {
+                    self.pools
+                        .entry(*pool_id)
+                        .or_default()
+                        .modify_liquidity(*position, *liquidity_delta);
+                }
+                LedgerEvent::Initialize { pool_id, tick } | LedgerEvent::Swap { pool_id, tick } => {
+                    self.pools.entry(*pool_id).or_default().current_tick = Some(*tick);
+                }
+                LedgerEvent::Rewards { pool_id, update } => {
+                    self.reward(block_number, *pool_id, update);
+                }
+            }
+        }
+        self.checkpoint(block_number);
+    }
+
+    fn checkpoint(&mut self, block_number: u64) {
+        let due = self
+            .checkpoints
+            .last_key_value()
+            .is_none_or(|(last, _)| last + CHECKPOINT_INTERVAL <= block_number);
+        if !due {
+            return;
+        }
+
+        self.checkpoints.insert(block_number, self.pools.clone());
+        while self.checkpoints.len() > MAX_CHECKPOINTS {
+            self.checkpoints.pop_first();
+        }
+    }
+
+    fn reward(&mut self, block_number: u64, pool_id: PoolId, update: &RewardsUpdate) {
+        let Some(pool) = self.pools.get(&pool_id) else { return };
+        let Some(donations) = pool.donations(update) else {
+            tracing::warn!(?pool_id, block_number, "unable to attribute rewards to positions");
+            return;
+        };
+
+        for (tick, liquidity, amount) in donations {
+            if liquidity == 0 || amount == 0 {
+                continue;
+            }
+
+            for (position, position_liquidity) in &pool.positions {
+                if !position.contains(tick) {
+                    continue;
+                }
+                let share = (U256::from(amount) * U256::from(*position_liquidity)
+                    / U256::from(liquidity))
+                .saturating_to::<u128>();
+
+                *self
+                    .accrued
+                    .entry((pool_id, *position))
+                    .or_default()
+                    .entry(block_number)
+                    .or_default() += share;
+            }
+        }
+    }
+}
+
+/// Cheaply cloneable handle to the rewards ledger.
+#[derive(Debug, Clone)]
+pub struct RewardsLedger {
+    state: Arc<RwLock<LedgerState>>,
+    /// angstrom pool id -> uniswap pool id of the pools that are tracked
+    pools: Arc<RwLock<HashMap<PoolId, PoolId>>>,
+    /// where the ledger is persisted, in memory only if none
+    file:  Option<Arc<Mutex<JsonLinesFile>>>
+}
+
+impl RewardsLedger {
+    pub fn in_memory() -> Self {
+        Self { state: Default::default(), pools: Default::default(), file: None }
+    }
+
+    /// loads the ledger persisted at the path, creating it if it doesn't exist.
+    pub fn load(path: PathBuf) -> eyre::Result<Self> {
+        let mut state = LedgerState::default();
+        let file = JsonLinesFile::load(path, |block: LedgerBlock| {
+            if block.events.is_empty() {
+                state.blocks.remove(&block.block_number);
+            } else {
+                state.blocks.insert(block.block_number, block.events);
+            }
+        })?;
+        state.replay_from(0);
+
+        let this = Self {
+            state: Arc::new(RwLock::new(state)),
+            pools: Default::default(),
+            file:  Some(Arc::new(Mutex::new(file)))
+        };
+        this.compact_if_needed();
+
+        Ok(this)
+    }
+
+    /// starts tracking the liquidity of the pool.
+    pub fn track_pool(&self, angstrom_pool_id: PoolId, uniswap_pool_id: PoolId) {
+        self.pools.write().insert(angstrom_pool_id, uniswap_pool_id);
+    }
+
+    pub fn is_tracked(&self, uniswap_pool_id: PoolId) -> bool {
+        self.pools.read().values().any(|id| *id == uniswap_pool_id)
+    }
+
+    /// sets the events of the block, overriding anything previously recorded
+    /// for it.
+    pub fn record_block(&self, block_number: u64, events: Vec<LedgerEvent>) {
+        self.record_blocks([(block_number, events)]);
+    }
+
+    /// sets the events of all the blocks, overriding anything previously
+    /// recorded for them.
+    pub fn record_blocks(&self, blocks: impl IntoIterator<Item = (u64, Vec<LedgerEvent>)>) {
+        let mut state = self.state.write();
+        let blocks = blocks
+            .into_iter()
+            .filter(|(block_number, events)| {
+                !events.is_empty() || state.blocks.contains_key(block_number)
+            })
+            .map(|(block_number, events)| LedgerBlock { block_number, events })
+            .collect::<Vec<_>>();
+        if blocks.is_empty() {
+            return;
+        }
+
+        self.persist(&blocks);
+        state.insert(blocks);
+        drop(state);
+        self.compact_if_needed();
+    }
+
+    /// drops all events of the reorged range and records the blocks of the new
+    /// chain in their place.
+    pub fn reorg(&self, range: RangeInclusive<u64>, new_blocks: Vec<(u64, Vec<LedgerEvent>)>) {
+        let mut blocks = range
+            .map(|block_number| (block_number, vec![]))
+            .collect::<BTreeMap<_, _>>();
+        blocks.extend(new_blocks);
+        self.record_blocks(blocks);
+    }
+
+    /// Fetches the liquidity changes and swaps of every tracked pool from the
+    /// pool manager logs up until the first block the ledger has recorded for
+    /// the pool. Without this, positions that were minted before the ledger
+    /// started tracking a pool wouldn't be credited any rewards. Rewards of
+    /// the backfilled blocks themselves aren't recovered.
+    pub async fn backfill<P: Provider>(
+        &self,
+        provider: &P,
+        pool_manager: Address,
+        from_block: u64,
+        to_block: u64
+    ) -> eyre::Result<()> {
+        let pools = self.pools.read().values().copied().collect::<Vec<_>>();
+        let mut logs = BTreeMap::<u64, Vec<(u64, LedgerEvent)>>::new();
+
+        for uniswap_pool_id in pools {
+            let end_block = self
+                .state
+                .read()
+                .first_block(uniswap_pool_id)
+                .map_or(to_block, |first| first.saturating_sub(1).min(to_block));
+
+            for from in (from_block..=end_block).step_by(BACKFILL_LOG_RANGE as usize) {
+                let filter = Filter::new()
+                    .address(pool_manager)
+                    .from_block(from)
+                    .to_block(std::cmp::min(from + BACKFILL_LOG_RANGE - 1, end_block))
+                    .event_signature(vec![
+                        ModifyLiquidity::SIGNATURE_HASH,
+                        Swap::SIGNATURE_HASH,
+                        Initialize::SIGNATURE_HASH,
+                    ])
+                    .topic1(uniswap_pool_id);
+
+                for log in provider.get_logs(&filter).await? {
+                    let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index)
+                    else {
+                        continue;
+                    };
+                    if let Some(event) = LedgerEvent::from_pool_manager_log(&log.inner) {
+                        logs.entry(block_number)
+                            .or_default()
+                            .push((log_index, event));
+                    }
+                }
+            }
+        }
+
+        self.record_backfill(logs);
+        Ok(())
+    }
+
+    /// adds the backfilled events, keyed by block and log index, to whatever
+    /// was recorded for the block already.
+    fn record_backfill(&self, logs: BTreeMap<u64, Vec<(u64, LedgerEvent)>>) {
+        let blocks = {
+            let state = self.state.read();
+            logs.into_iter()
+                .map(|(block_number, mut logs)| {
+                    logs.sort_by_key(|(log_index, _)| *log_index);
+                    let mut events = state.blocks.get(&block_number).cloned().unwrap_or_default();
+                    events.extend(logs.into_iter().map(|(_, event)| event));
+                    (block_number, events)
+                })
+                .collect::<Vec<_>>()
+        };
+        tracing::info!(blocks = blocks.len(), "backfilled rewards ledger");
+
+        self.record_blocks(blocks);
+    }
+
+    /// the rewards the position accrued in the inclusive block range. the pool
+    /// can be given as either the angstrom or the uniswap pool id.
+    pub fn position_rewards(
+        &self,
+        pool_id: PoolId,
+        position: PositionKey,
+        block_range: RangeInclusive<u64>
+    ) -> PositionRewards {
+        let uniswap_pool_id = self.pools.read().get(&pool_id).copied().unwrap_or(pool_id);
+
+        let rewards = self
+            .state
+            .read()
+            .accrued
+            .get(&(uniswap_pool_id, position))
+            .map(|blocks| {
+                blocks
+                    .range(block_range)
+                    .map(|(block_number, amount_t0)| PositionReward {
+                        block_number: *block_number,
+                        amount_t0:    *amount_t0
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        PositionRewards {
+            uniswap_pool_id,
+            position,
+            total_t0: rewards.iter().map(|reward| reward.amount_t0).sum(),
+            rewards
+        }
+    }
+
+    fn persist(&self, blocks: &[LedgerBlock]) {
+        let Some(file) = self.file.as_ref() else { return };
+        let mut file = file.lock();
+
+        if let Err(error) = file.append(blocks) {
+            tracing::error!(%error, path = %file.path.display(), "failed to persist rewards ledger");
+        }
+    }
+
+    /// rewrites the file once it holds too many overridden blocks.
+    fn compact_if_needed(&self) {
+        let Some(file) = self.file.as_ref() else { return };
+        let state = self.state.read();
+        let mut file = file.lock();
+        let blocks = state
+            .blocks
+            .iter()
+            .map(|(block_number, events)| LedgerBlock {
+                block_number: *block_number,
+                events:       events.clone()
+            });
+
+        if let Err(error) = file.compact_if_needed(state.blocks.len(), blocks) {
+            tracing::error!(%error, path = %file.path.display(), "failed to compact rewards ledger");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{U160, address, aliases::I24};
+
+    use super::*;
+    use crate::json_lines::COMPACTION_SLACK;
+
+    fn position(tick_lower: i32, tick_upper: i32) -> PositionKey {
+        PositionKey {
+            owner: address!("0x00000000000000000000000000000000000000aa"),
+            tick_lower,
+            tick_upper,
+            salt: B256::ZERO
+        }
+    }
+
+    fn mint(position: PositionKey, liquidity: i128) -> LedgerEvent {
+        LedgerEvent::ModifyLiquidity {
+            pool_id: PoolId::default(),
+            position,
+            liquidity_delta: liquidity
+        }
+    }
+
+    fn rewarded(ledger: &RewardsLedger, position: PositionKey) -> u128 {
+        ledger
+            .position_rewards(PoolId::default(), position, 0..=u64::MAX)
+            .total_t0
+    }
+
+    #[test]
+    fn current_only_is_split_pro_rata() {
+        let ledger = RewardsLedger::in_memory();
+        let (a, b, out_of_range) = (position(-10, 10), position(-20, 20), position(10, 20));
+        ledger.record_block(
+            1,
+            vec![
+                mint(a, 100),
+                mint(b, 300),
+                mint(out_of_range, 1_000),
+                LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 },
+            ]
+        );
+        ledger.record_block(
+            2,
+            vec![LedgerEvent::Rewards {
+                pool_id: PoolId::default(),
+                update:  RewardsUpdate::CurrentOnly {
+                    amount:             1_000,
+                    expected_liquidity: 400
+                }
+            }]
+        );
+
+        assert_eq!(rewarded(&ledger, a), 250);
+        assert_eq!(rewarded(&ledger, b), 750);
+        assert_eq!(rewarded(&ledger, out_of_range), 0);
+    }
+
+    #[test]
+    fn multi_tick_walks_ranges_from_below() {
+        let ledger = RewardsLedger::in_memory();
+        let (low, high) = (position(-20, 0), position(0, 20));
+        ledger.record_block(
+            1,
+            vec![
+                mint(low, 100),
+                mint(high, 200),
+                LedgerEvent::Swap { pool_id: PoolId::default(), tick: 5 },
+            ]
+        );
+        // 10 goes to [-20, 0) and 20 to [0, 20) where the current tick is
+        ledger.record_block(
+            2,
+            vec![LedgerEvent::Rewards {
+                pool_id: PoolId::default(),
+                update:  RewardsUpdate::MultiTick {
+                    start_tick:      I24::unchecked_from(0),
+                    start_liquidity: 100,
+                    quantities:      vec![10, 20],
+                    reward_checksum: U160::ZERO
+                }
+            }]
+        );
+
+        assert_eq!(rewarded(&ledger, low), 10);
+        assert_eq!(rewarded(&ledger, high), 20);
+    }
+
+    #[test]
+    fn reorg_drops_rewards() {
+        let ledger = RewardsLedger::in_memory();
+        let a = position(-10, 10);
+        ledger.record_block(
+            1,
+            vec![mint(a, 100), LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 }]
+        );
+        for block in 2..=3 {
+            ledger.record_block(
+                block,
+                vec![LedgerEvent::Rewards {
+                    pool_id: PoolId::default(),
+                    update:  RewardsUpdate::CurrentOnly {
+                        amount:             50,
+                        expected_liquidity: 100
+                    }
+                }]
+            );
+        }
+        assert_eq!(rewarded(&ledger, a), 100);
+
+        ledger.reorg(3..=3, vec![]);
+        assert_eq!(rewarded(&ledger, a), 50);
+        assert_eq!(ledger.position_rewards(PoolId::default(), a, 3..=3).rewards, vec![]);
+    }
+
+    fn current_only(amount: u128, expected_liquidity: u128) -> LedgerEvent {
+        LedgerEvent::Rewards {
+            pool_id: PoolId::default(),
+            update:  RewardsUpdate::CurrentOnly { amount, expected_liquidity }
+        }
+    }
+
+    #[test]
+    fn out_of_order_batch_is_replayed_in_order() {
+        let ledger = RewardsLedger::in_memory();
+        let a = position(-10, 10);
+        ledger.record_blocks([
+            (2, vec![current_only(50, 100)]),
+            (1, vec![mint(a, 100), LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 }])
+        ]);
+
+        assert_eq!(rewarded(&ledger, a), 50);
+    }
+
+    #[test]
+    fn reorg_replaces_range_with_new_blocks() {
+        let ledger = RewardsLedger::in_memory();
+        let a = position(-10, 10);
+        ledger.record_block(
+            1,
+            vec![mint(a, 100), LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 }]
+        );
+        ledger.record_blocks([(2, vec![current_only(50, 100)]), (3, vec![current_only(50, 100)])]);
+
+        ledger
+            .reorg(2..=4, vec![(3, vec![current_only(20, 100)]), (4, vec![current_only(5, 100)])]);
+
+        let rewards = ledger.position_rewards(PoolId::default(), a, 0..=u64::MAX);
+        assert_eq!(rewards.total_t0, 25);
+        assert_eq!(
+            rewards.rewards,
+            vec![
+                PositionReward { block_number: 3, amount_t0: 20 },
+                PositionReward { block_number: 4, amount_t0: 5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn backfill_credits_positions_minted_before_tracking() {
+        let ledger = RewardsLedger::in_memory();
+        ledger.track_pool(PoolId::default(), PoolId::default());
+        let a = position(-10, 10);
+        let other_pool = LedgerEvent::Swap { pool_id: PoolId::repeat_byte(1), tick: 7 };
+        ledger.record_block(5, vec![other_pool.clone()]);
+        ledger.record_block(10, vec![current_only(50, 100)]);
+        assert_eq!(rewarded(&ledger, a), 0);
+        assert_eq!(ledger.state.read().first_block(PoolId::default()), Some(10));
+
+        ledger.record_backfill(BTreeMap::from([(
+            5,
+            vec![(3, LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 }), (1, mint(a, 100))]
+        )]));
+
+        assert_eq!(rewarded(&ledger, a), 50);
+        assert_eq!(
+            ledger.state.read().blocks[&5],
+            vec![
+                other_pool,
+                mint(a, 100),
+                LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn initialize_seeds_the_current_tick() {
+        let ledger = RewardsLedger::in_memory();
+        let a = position(-10, 10);
+        ledger.record_block(
+            1,
+            vec![LedgerEvent::Initialize { pool_id: PoolId::default(), tick: 0 }, mint(a, 100)]
+        );
+        ledger.record_block(2, vec![current_only(50, 100)]);
+
+        assert_eq!(rewarded(&ledger, a), 50);
+    }
+
+    #[test]
+    fn reorg_replays_from_the_checkpoint_before_the_fork() {
+        let ledger = RewardsLedger::in_memory();
+        let (a, b) = (position(-10, 10), position(-20, 20));
+        ledger.record_block(
+            1,
+            vec![mint(a, 100), LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 }]
+        );
+        let last = 4 * CHECKPOINT_INTERVAL;
+        for block in 2..=last {
+            ledger.record_block(block, vec![current_only(10, 100)]);
+        }
+        assert!(ledger.state.read().checkpoints.len() > 1);
+
+        // b is minted in a block that was reorged in, after the latest checkpoint
+        let fork = last - 1;
+        ledger.reorg(
+            fork..=last,
+            vec![
+                (fork, vec![mint(b, 100), current_only(20, 200)]),
+                (last, vec![current_only(20, 200)]),
+            ]
+        );
+
+        let checkpoints = ledger
+            .state
+            .read()
+            .checkpoints
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        assert!(checkpoints.iter().all(|block| *block < fork));
+        assert_eq!(rewarded(&ledger, a), 10 * (fork - 2) as u128 + 20);
+        assert_eq!(rewarded(&ledger, b), 20);
+
+        // replaying the whole history ends up in the same place
+        let mut state = ledger.state.write();
+        let accrued = state.accrued.clone();
+        state.replay_from(0);
+        assert_eq!(state.accrued, accrued);
+    }
+
+    #[test]
+    fn compacts_the_persisted_ledger() {
+        let path = std::env::temp_dir().join(format!("rewards-ledger-{}.jsonl", B256::random()));
+        let a = position(-10, 10);
+
+        let ledger = RewardsLedger::load(path.clone()).unwrap();
+        ledger.record_block(
+            1,
+            vec![mint(a, 100), LedgerEvent::Swap { pool_id: PoolId::default(), tick: 0 }]
+        );
+        ledger.record_block(2, vec![current_only(50, 100)]);
+        // override the same block until the file has to be rewritten
+        for amount in 0..=COMPACTION_SLACK as u128 {
+            ledger.reorg(2..=2, vec![(2, vec![current_only(amount, 100)])]);
+        }
+
+        let entries = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert!(entries <= 2 + COMPACTION_SLACK);
+
+        let reloaded = RewardsLedger::load(path.clone()).unwrap();
+        assert_eq!(rewarded(&reloaded, a), COMPACTION_SLACK as u128);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,263 @@
+//! Canonical state notifications sourced from a external json-rpc node, for
+//! when angstrom runs as a sidecar. New heads are streamed over a websocket or
+//! ipc subscription and turned into the same [`CanonStateNotification`]s the
+//! embedded reth node produces, reorgs included.
+use std::{collections::VecDeque, sync::Arc};
+
+use alloy::{
+    eips::BlockId,
+    primitives::{B256, BlockNumber},
+    providers::Provider,
+    rpc::types::{Block, TransactionReceipt}
+};
+use eyre::OptionExt;
+use futures::StreamExt;
+use itertools::Itertools;
+use reth_primitives::{EthPrimitives, Receipt, RecoveredBlock, TransactionSigned};
+use reth_provider::{
+    CanonStateNotification, CanonStateNotifications, CanonStateSubscriptions, Chain,
+    ExecutionOutcome, NodePrimitivesProvider
+};
+use tokio::sync::broadcast;
+
+/// the amount of canonical blocks kept around to find where a reorg forked
+/// off.
+pub const REORG_WINDOW: usize = 64;
+
+#[derive(Debug, Clone)]
+struct CanonBlock {
+    number:      BlockNumber,
+    hash:        B256,
+    parent_hash: B256,
+    block:       RecoveredBlock<reth_primitives::Block>,
+    receipts:    Vec<Receipt>
+}
+
+impl CanonBlock {
+    fn new(block: Block, receipts: Vec<TransactionReceipt>) -> Self {
+        let number = block.header.number;
+        let hash = block.header.hash;
+        let parent_hash = block.header.parent_hash;
+
+        let recovered = block
+            .clone()
+            .into_consensus()
+            .map_transactions(|tx| tx.into_recovered());
+        let signers = recovered
+            .body
+            .transactions()
+            .map(|tx| tx.signer())
+            .collect_vec();
+        let block = block.into_consensus().map_transactions(|tx| {
+            let signed = tx.into_signed();
+            let sig = *signed.signature();
+            TransactionSigned::new_unhashed(signed.tx().clone().into(), sig)
+        });
+
+        let receipts = receipts
+            .into_iter()
+            .map(|receipt| {
+                let receipt = receipt.into_primitives_receipt();
+                Receipt {
+                    tx_type:             receipt.inner.tx_type(),
+                    success:             receipt.inner.status(),
+                    cumulative_gas_used: receipt.inner.cumulative_gas_used(),
+                    logs:                receipt.logs().to_vec()
+                }
+            })
+            .collect();
+
+        Self {
+            number,
+            hash,
+            parent_hash,
+            block: RecoveredBlock::new_unhashed(block, signers),
+            receipts
+        }
+    }
+}
+
+/// builds a chain out of consecutive blocks, oldest first.
+fn to_chain(blocks: impl IntoIterator<Item = CanonBlock>) -> Arc<Chain> {
+    let mut chain = Chain::default();
+    for block in blocks {
+        if chain.execution_outcome().first_block() == 0 {
+            chain.execution_outcome_mut().set_first_block(block.number);
+        }
+        chain.append_block(
+            block.block,
+            ExecutionOutcome::default().with_receipts(vec![block.receipts])
+        );
+    }
+
+    Arc::new(chain)
+}
+
+#[derive(Debug)]
+pub struct RpcCanonChain<P> {
+    provider:       Arc<P>,
+    canon_state_tx: broadcast::Sender<CanonStateNotification>
+}
+
+impl<P> Clone for RpcCanonChain<P> {
+    fn clone(&self) -> Self {
+        Self { provider: self.provider.clone(), canon_state_tx: self.canon_state_tx.clone() }
+    }
+}
+
+impl<P: Provider + 'static> RpcCanonChain<P> {
+    /// the provider needs to support subscriptions, so it should be connected
+    /// over websocket or ipc.
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider, canon_state_tx: broadcast::channel(1000).0 }
+    }
+
+    /// follows the head of the chain sending out a notification for every new
+    /// block. Only returns once the subscription has ended.
+    pub async fn run(self) -> eyre::Result<()> {
+        let mut heads = self.provider.subscribe_blocks().await?.into_stream();
+        let mut canonical = VecDeque::with_capacity(REORG_WINDOW);
+
+        while let Some(head) = heads.next().await {
+            if let Err(error) = self.on_new_head(&mut canonical, head.hash).await {
+                tracing::warn!(%error, number = head.number, "failed to process new head");
+            }
+        }
+
+        eyre::bail!("new heads subscription ended")
+    }
+
+    async fn on_new_head(
+        &self,
+        canonical: &mut VecDeque<CanonBlock>,
+        hash: B256
+    ) -> eyre::Result<()> {
+        if canonical.iter().any(|block| block.hash == hash) {
+            return Ok(());
+        }
+
+        // walk back from the new head until we hit a block we already know of. this
+        // also fills in any heads the subscription skipped.
+        let mut new = vec![self.fetch_block(hash).await?];
+        let fork = loop {
+            let parent = new.last().unwrap().parent_hash;
+            if let Some(fork) = canonical.iter().position(|block| block.hash == parent) {
+                break Some(fork);
+            }
+            if canonical.is_empty() || new.len() >= REORG_WINDOW {
+                break None;
+            }
+            new.push(self.fetch_block(parent).await?);
+        };
+        new.reverse();
+
+        let Some(fork) = fork else {
+            if !canonical.is_empty() {
+                tracing::warn!(?hash, "no common ancestor in the reorg window, following new head");
+            }
+            let head = new.pop().unwrap();
+            canonical.clear();
+            canonical.push_back(head.clone());
+            self.notify(CanonStateNotification::Commit { new: to_chain([head]) });
+
+            return Ok(());
+        };
+
+        let old = canonical.drain(fork + 1..).collect::<Vec<_>>();
+        canonical.extend(new.iter().cloned());
+        while canonical.len() > REORG_WINDOW {
+            canonical.pop_front();
+        }
+
+        if old.is_empty() {
+            for block in new {
+                self.notify(CanonStateNotification::Commit { new: to_chain([block]) });
+            }
+        } else {
+            tracing::info!(reorged = old.len(), ?hash, "reorg detected");
+            self.notify(CanonStateNotification::Reorg { old: to_chain(old), new: to_chain(new) });
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_block(&self, hash: B256) -> eyre::Result<CanonBlock> {
+        let block = self
+            .provider
+            .get_block_by_hash(hash)
+            .full()
+            .await?
+            .ok_or_eyre("block not found")?;
+        let receipts = self
+            .provider
+            .get_block_receipts(BlockId::hash(hash))
+            .await?
+            .ok_or_eyre("block receipts not found")?;
+
+        Ok(CanonBlock::new(block, receipts))
+    }
+
+    fn notify(&self, notification: CanonStateNotification) {
+        // there are no receivers until the modules have started up
+        let _ = self.canon_state_tx.send(notification);
+    }
+}
+
+impl<P: Provider + 'static> CanonStateSubscriptions for RpcCanonChain<P> {
+    fn subscribe_to_canonical_state(&self) -> CanonStateNotifications<Self::Primitives> {
+        self.canon_state_tx.subscribe()
+    }
+}
+
+impl<P: Provider + 'static> NodePrimitivesProvider for RpcCanonChain<P> {
+    type Primitives = EthPrimitives;
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        node_bindings::Anvil,
+        providers::{ProviderBuilder, ext::AnvilApi}
+    };
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn follows_anvil_through_reorgs() {
+        let anvil = Anvil::new().try_spawn().unwrap();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .connect(&anvil.ws_endpoint())
+                .await
+                .unwrap()
+        );
+
+        let chain = RpcCanonChain::new(provider.clone());
+        let mut notifications = chain.subscribe_to_canonical_state();
+        tokio::spawn(chain.run());
+        // give the subscription time to be installed
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        provider.anvil_mine(Some(1), None).await.unwrap();
+        let first = notifications.recv().await.unwrap();
+        assert!(matches!(first, CanonStateNotification::Commit { .. }));
+
+        let snapshot = provider.evm_snapshot().await.unwrap();
+        provider.anvil_mine(Some(1), None).await.unwrap();
+        let reorged = notifications.recv().await.unwrap().tip().hash();
+
+        provider.evm_revert(snapshot).await.unwrap();
+        provider
+            .evm_set_next_block_timestamp(first.tip().timestamp + 100)
+            .await
+            .unwrap();
+        provider.anvil_mine(Some(1), None).await.unwrap();
+
+        let CanonStateNotification::Reorg { old, new } = notifications.recv().await.unwrap() else {
+            panic!("expected a reorg")
+        };
+        assert_eq!(old.tip().hash(), reorged);
+        assert_eq!(old.tip().number, new.tip().number);
+        assert_ne!(new.tip().hash(), reorged);
+    }
+}
@@ -0,0 +1,451 @@
+//! When the simulation of a bundle fails, we don't want to lose the whole
+//! block over a single bad pool or order. The pools are bisected until the
+//! failing ones are found, then the orders of each failing pool are bisected
+//! the same way. The culprits are left out of the bundle and reported so the
+//! order pool can drop them.
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant
+};
+
+use alloy_primitives::{Address, B256};
+use angstrom_types::{
+    contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
+    orders::PoolSolution,
+    primitive::PoolId,
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder},
+    uni_structure::BaselinePoolState
+};
+use validation::bundle::BundleValidatorHandle;
+
+use crate::{book::BookOrder, build_book, solver::SolverPool, strategy::BinarySearchStrategy};
+
+/// bisecting is cut short after this many simulations, or once the round's
+/// deadline passes, so that it can't eat the whole round.
+pub const MAX_ISOLATION_SIMULATIONS: usize = 48;
+
+/// What had to be left out of a bundle for it to pass simulation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleFaults {
+    /// pools that fail even without their orders
+    pub pools:  Vec<PoolId>,
+    /// orders that made the simulation of their pool fail
+    pub orders: Vec<B256>
+}
+
+impl BundleFaults {
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty() && self.orders.is_empty()
+    }
+}
+
+/// Splits a set that is known to fail into halves until the failing items are
+/// found. Every group is simulated on its own, groups that pass are cleared
+/// and groups that fail are split again.
+#[derive(Debug)]
+pub struct Bisection<T> {
+    pending: Vec<Vec<T>>,
+    current: Option<Vec<T>>,
+    failing: Vec<T>
+}
+
+impl<T> Bisection<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        let mut this = Self { pending: vec![], current: None, failing: vec![] };
+        this.split(items);
+
+        this
+    }
+
+    fn split(&mut self, mut items: Vec<T>) {
+        if items.len() <= 1 {
+            self.failing.extend(items);
+            return;
+        }
+        let right = items.split_off(items.len() / 2);
+        self.pending.push(right);
+        self.pending.push(items);
+    }
+
+    /// the next group to simulate, its result has to be passed to
+    /// [`Bisection::report`].
+    pub fn next_group(&mut self) -> Option<&[T]> {
+        self.current = self.pending.pop();
+        self.current.as_deref()
+    }
+
+    pub fn report(&mut self, passed: bool) {
+        if let Some(group) = self.current.take() {
+            if !passed {
+                self.split(group);
+            }
+        }
+    }
+
+    pub fn into_failing(self) -> Vec<T> {
+        self.failing
+    }
+}
+
+/// no passing bundle was found within [`MAX_ISOLATION_SIMULATIONS`] or before
+/// the deadline.
+#[derive(Debug)]
+pub struct IsolationFailed;
+
+pub struct FaultIsolator<'a, V> {
+    validation:     &'a V,
+    solver:         &'a SolverPool,
+    limit:          &'a [BookOrder],
+    searchers:      &'a HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+    pool_snapshots: &'a HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+    deadline:       Instant,
+    simulations:    usize
+}
+
+impl<'a, V: BundleValidatorHandle> FaultIsolator<'a, V> {
+    pub fn new(
+        validation: &'a V,
+        solver: &'a SolverPool,
+        limit: &'a [BookOrder],
+        searchers: &'a HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+        pool_snapshots: &'a HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        deadline: Instant
+    ) -> Self {
+        Self { validation, solver, limit, searchers, pool_snapshots, deadline, simulations: 0 }
+    }
+
+    pub fn simulations(&self) -> usize {
+        self.simulations
+    }
+
+    /// finds a bundle that passes simulation by leaving out the pools and
+    /// orders that make it fail.
+    pub async fn isolate(
+        &mut self,
+        solutions: Vec<PoolSolution>
+    ) -> Result<(Vec<PoolSolution>, BundleGasDetails, BundleFaults), IsolationFailed> {
+        let mut pools = Bisection::new(solutions.clone());
+        while let Some(group) = pools.next_group() {
+            let passed = self.simulate(group.to_vec()).await?.is_some();
+            pools.report(passed);
+        }
+        let failing_pools = pools
+            .into_failing()
+            .into_iter()
+            .map(|solution| solution.id)
+            .collect::<HashSet<_>>();
+
+        let mut faults = BundleFaults::default();
+        let mut passing = solutions
+            .into_iter()
+            .filter(|solution| !failing_pools.contains(&solution.id))
+            .collect::<Vec<_>>();
+
+        for pool in failing_pools {
+            match self.isolate_orders(pool).await? {
+                Some((orders, solution)) => {
+                    faults.orders.extend(orders);
+                    passing.push(solution);
+                }
+                None => faults.pools.push(pool)
+            }
+        }
+
+        // nothing left to propose
+        if AngstromBundle::for_gas_finalization(
+            self.limit.to_vec(),
+            passing.clone(),
+            self.pool_snapshots
+        )
+        .is_err()
+        {
+            return Err(IsolationFailed);
+        }
+
+        // the pools pass on their own but can still fail together
+        let gas = self
+            .simulate(passing.clone())
+            .await?
+            .ok_or(IsolationFailed)?;
+
+        Ok((passing, gas, faults))
+    }
+
+    /// the orders of the pool that make it fail and the solution of the pool
+    /// without them. `None` if the pool fails without its orders as well.
+    async fn isolate_orders(
+        &mut self,
+        pool: PoolId
+    ) -> Result<Option<(Vec<B256>, PoolSolution)>, IsolationFailed> {
+        let mut orders = self
+            .limit
+            .iter()
+            .filter(|order| order.pool_id == pool)
+            .map(|order| order.order_id.hash)
+            .collect::<Vec<_>>();
+        orders.extend(self.searchers.get(&pool).map(|tob| tob.order_id.hash));
+
+        let Some(without_orders) = self.solve(pool, &orders).await else { return Ok(None) };
+        if self.simulate(vec![without_orders]).await?.is_none() {
+            return Ok(None);
+        }
+
+        let mut bisection = Bisection::new(orders.clone());
+        while let Some(group) = bisection.next_group() {
+            let group = group.iter().collect::<HashSet<_>>();
+            let excluded = orders
+                .iter()
+                .filter(|order| !group.contains(order))
+                .copied()
+                .collect::<Vec<_>>();
+
+            let passed = match self.solve(pool, &excluded).await {
+                Some(solution) => self.simulate(vec![solution]).await?.is_some(),
+                None => false
+            };
+            bisection.report(passed);
+        }
+        let culprits = bisection.into_failing();
+
+        let Some(solution) = self.solve(pool, &culprits).await else { return Ok(None) };
+        if self.simulate(vec![solution.clone()]).await?.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some((culprits, solution)))
+    }
+
+    /// solves the pool without the excluded orders. `None` if it couldn't be
+    /// solved before the deadline.
+    async fn solve(&self, pool: PoolId, excluded: &[B256]) -> Option<PoolSolution> {
+        let orders = self
+            .limit
+            .iter()
+            .filter(|order| order.pool_id == pool && !excluded.contains(&order.order_id.hash))
+            .cloned()
+            .collect();
+        let searcher = self
+            .searchers
+            .get(&pool)
+            .filter(|tob| !excluded.contains(&tob.order_id.hash))
+            .cloned();
+        let amm = self.pool_snapshots.get(&pool).map(|value| value.2.clone());
+        let book = build_book(pool, amm, orders);
+        let deadline = self.deadline;
+
+        self.solver
+            .run(move || BinarySearchStrategy::run_until(&book, searcher, Some(deadline)))
+            .await
+            .flatten()
+    }
+
+    /// `None` if the simulation of the bundle failed. A bundle that doesn't
+    /// fill anything can't fail.
+    async fn simulate(
+        &mut self,
+        solutions: Vec<PoolSolution>
+    ) -> Result<Option<BundleGasDetails>, IsolationFailed> {
+        let Ok(bundle) = AngstromBundle::for_gas_finalization(
+            self.limit.to_vec(),
+            solutions,
+            self.pool_snapshots
+        ) else {
+            return Ok(Some(BundleGasDetails::default()));
+        };
+
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if self.simulations >= MAX_ISOLATION_SIMULATIONS || remaining.is_zero() {
+            return Err(IsolationFailed);
+        }
+        self.simulations += 1;
+
+        tokio::time::timeout(remaining, self.validation.fetch_gas_for_bundle(bundle))
+            .await
+            .map(Result::ok)
+            .map_err(|_| IsolationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use alloy_primitives::U256;
+    use angstrom_types::{
+        matching::{Ray, SqrtPriceX96, uniswap::TickInfo},
+        primitive::{AngstromAddressConfig, AngstromSigner},
+        uni_structure::liquidity_base::BaselineLiquidity
+    };
+    use testing_tools::type_generator::orders::{
+        UserOrderBuilder, default_high_addr, default_low_addr
+    };
+
+    use super::*;
+    use crate::solver::SolverConfig;
+
+    /// reverts every bundle that pays out to the recipient.
+    #[derive(Clone)]
+    struct RevertsForRecipient(Address);
+
+    impl BundleValidatorHandle for RevertsForRecipient {
+        async fn fetch_gas_for_bundle(
+            &self,
+            bundle: AngstromBundle
+        ) -> eyre::Result<BundleGasDetails> {
+            if bundle
+                .user_orders
+                .iter()
+                .any(|order| order.recipient == Some(self.0))
+            {
+                eyre::bail!("bundle reverted");
+            }
+
+            Ok(BundleGasDetails::default())
+        }
+    }
+
+    /// a pool at tick 0 with a single position over ticks -1000 to 1000.
+    fn single_position_amm(liquidity: u128) -> BaselinePoolState {
+        const TICK_SPACING: i32 = 10;
+        let mut ticks = HashMap::new();
+        let mut bitmap: HashMap<i16, U256> = HashMap::new();
+        for (tick, liquidity_net) in [(-1000, liquidity as i128), (1000, -(liquidity as i128))] {
+            ticks.insert(
+                tick,
+                TickInfo { liquidity_gross: liquidity, liquidity_net, initialized: true }
+            );
+
+            let compressed = tick.div_euclid(TICK_SPACING);
+            *bitmap.entry((compressed >> 8) as i16).or_default() |=
+                U256::from(1) << (compressed & 0xff) as usize;
+        }
+
+        BaselinePoolState::new(
+            BaselineLiquidity::new(
+                TICK_SPACING,
+                0,
+                SqrtPriceX96::at_tick(0).unwrap(),
+                liquidity,
+                ticks,
+                bitmap
+            ),
+            1,
+            0
+        )
+    }
+
+    /// a bid for token0 that crosses the amm.
+    fn bid(pool: PoolId, amount: u128, recipient: Address) -> BookOrder {
+        UserOrderBuilder::new()
+            .kill_or_fill()
+            .partial()
+            .bid()
+            .amount(amount)
+            .bid_min_price(Ray::from(SqrtPriceX96::at_tick(500).unwrap()))
+            .recipient(recipient)
+            .signing_key(Some(AngstromSigner::random()))
+            .with_storage()
+            .bid()
+            .pool_id(pool)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn isolate_leaves_out_the_failing_order() {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let pool = PoolId::repeat_byte(1);
+        let bad_recipient = Address::repeat_byte(0xba);
+        let limit = vec![
+            bid(pool, 10u128.pow(15), Address::ZERO),
+            bid(pool, 2 * 10u128.pow(15), bad_recipient),
+            bid(pool, 3 * 10u128.pow(15), Address::ZERO),
+        ];
+        let bad_order = limit[1].order_id.hash;
+        let searchers = HashMap::new();
+        let pool_snapshots = HashMap::from([(
+            pool,
+            (*default_low_addr(), *default_high_addr(), single_position_amm(10u128.pow(18)), 0)
+        )]);
+        let validation = RevertsForRecipient(bad_recipient);
+        let solver = SolverPool::new(SolverConfig { threads: 1 });
+        let mut isolator = FaultIsolator::new(
+            &validation,
+            &solver,
+            &limit,
+            &searchers,
+            &pool_snapshots,
+            Instant::now() + Duration::from_secs(60)
+        );
+
+        let solution = isolator.solve(pool, &[]).await.unwrap();
+        assert!(solution.limit.iter().all(|outcome| outcome.is_filled()));
+        // the whole bundle fails
+        assert!(
+            isolator
+                .simulate(vec![solution.clone()])
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let (passing, _, faults) = isolator.isolate(vec![solution]).await.unwrap();
+        assert_eq!(faults, BundleFaults { pools: vec![], orders: vec![bad_order] });
+
+        // the pool is solved again without the order and the rest still fills
+        let [passing] = passing.as_slice() else { panic!("expected one pool, got {passing:?}") };
+        assert_eq!(passing.id, pool);
+        assert_eq!(passing.limit.len(), 2);
+        assert!(passing.limit.iter().all(|outcome| outcome.is_filled()));
+        assert!(isolator.simulations() <= MAX_ISOLATION_SIMULATIONS);
+    }
+
+    #[tokio::test]
+    async fn isolate_gives_up_once_the_round_is_over() {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let pool = PoolId::repeat_byte(1);
+        let bad_recipient = Address::repeat_byte(0xba);
+        let limit = vec![bid(pool, 10u128.pow(15), bad_recipient)];
+        let searchers = HashMap::new();
+        let pool_snapshots = HashMap::from([(
+            pool,
+            (*default_low_addr(), *default_high_addr(), single_position_amm(10u128.pow(18)), 0)
+        )]);
+        let validation = RevertsForRecipient(bad_recipient);
+        let solver = SolverPool::new(SolverConfig { threads: 1 });
+        let mut isolator = FaultIsolator::new(
+            &validation,
+            &solver,
+            &limit,
+            &searchers,
+            &pool_snapshots,
+            Instant::now()
+        );
+
+        let book = build_book(
+            pool,
+            Some(pool_snapshots[&pool].2.clone()),
+            limit.iter().cloned().collect()
+        );
+        let solution = BinarySearchStrategy::run(&book, None);
+        assert!(isolator.isolate(vec![solution]).await.is_err());
+        assert_eq!(isolator.simulations(), 0);
+    }
+
+    #[test]
+    fn bisection_finds_all_failing_items() {
+        let bad = [3, 6];
+        let mut bisection = Bisection::new((0..8).collect::<Vec<_>>());
+
+        let mut rounds = 0;
+        while let Some(group) = bisection.next_group() {
+            let passed = !group.iter().any(|item| bad.contains(item));
+            bisection.report(passed);
+            rounds += 1;
+        }
+
+        let mut failing = bisection.into_failing();
+        failing.sort();
+        assert_eq!(failing, bad);
+        assert!(rounds < 14);
+    }
+}
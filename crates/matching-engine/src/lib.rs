@@ -1,6 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration
+};
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, B256};
 use angstrom_types::{
     contract_payloads::angstrom::BundleGasDetails,
     orders::PoolSolution,
@@ -14,9 +17,11 @@ use book::{BookOrder, OrderBook};
 use futures_util::future::BoxFuture;
 
 pub mod book;
+pub mod isolation;
 pub mod manager;
 pub mod matcher;
 pub mod simulation;
+pub mod solver;
 pub mod strategy;
 
 pub use manager::MatchingManager;
@@ -24,11 +29,17 @@ pub use manager::MatchingManager;
 use crate::manager::MatchingEngineError;
 
 pub trait MatchingEngineHandle: Send + Sync + Clone + Unpin + 'static {
+    /// `routes` are the order hashes of the legs of each multi-hop route, the
+    /// legs of a route are either all filled or left out. `solve_budget` is
+    /// shared by every solve of the proposal, a pool that hasn't solved once it
+    /// runs out falls back to its top of block order.
     fn solve_pools(
         &self,
         limit: Vec<BookOrder>,
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
-        pools: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>
+        pools: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        routes: Vec<Vec<B256>>,
+        solve_budget: Duration
     ) -> BoxFuture<Result<(Vec<PoolSolution>, BundleGasDetails), MatchingEngineError>>;
 }
 
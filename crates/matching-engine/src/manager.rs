@@ -1,11 +1,13 @@
 use std::{
     collections::{HashMap, HashSet},
     pin::Pin,
-    sync::Arc
+    sync::Arc,
+    time::{Duration, Instant}
 };
 
 use alloy::hex;
-use alloy_primitives::Address;
+use alloy_primitives::{Address, B256};
+use angstrom_metrics::BundleBuildingMetricsWrapper;
 use angstrom_types::{
     contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
     matching::match_estimate_response::BundleEstimate,
@@ -17,12 +19,10 @@ use angstrom_types::{
 use futures::{Future, stream::FuturesUnordered};
 use futures_util::FutureExt;
 use reth_tasks::TaskSpawner;
-use tokio::{
-    sync::{
-        mpsc::{Receiver, Sender},
-        oneshot
-    },
-    task::JoinSet
+use tokio::sync::{
+    broadcast,
+    mpsc::{Receiver, Sender},
+    oneshot
 };
 use tracing::trace;
 use validation::bundle::BundleValidatorHandle;
@@ -31,6 +31,8 @@ use crate::{
     MatchingEngineHandle,
     book::{BookOrder, OrderBook},
     build_book,
+    isolation::{BundleFaults, FaultIsolator},
+    solver::{SolverConfig, SolverPool},
     strategy::BinarySearchStrategy
 };
 
@@ -47,6 +49,8 @@ pub enum MatcherCommand {
         Vec<BookOrder>,
         Vec<OrderWithStorageData<TopOfBlockOrder>>,
         HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        Vec<Vec<B256>>,
+        Duration,
         oneshot::Sender<Result<(Vec<PoolSolution>, BundleGasDetails), MatchingEngineError>>
     ),
     EstimateGasPerPool {
@@ -59,10 +63,17 @@ pub enum MatcherCommand {
 
 #[derive(Debug, Clone)]
 pub struct MatcherHandle {
-    pub sender: Sender<MatcherCommand>
+    pub sender: Sender<MatcherCommand>,
+    faults:     broadcast::Sender<BundleFaults>
 }
 
 impl MatcherHandle {
+    /// the pools and orders that were left out of a bundle because they made
+    /// its simulation fail.
+    pub fn subscribe_faults(&self) -> broadcast::Receiver<BundleFaults> {
+        self.faults.subscribe()
+    }
+
     async fn send(&self, cmd: MatcherCommand) {
         let _ = self.sender.send(cmd).await;
     }
@@ -78,14 +89,19 @@ impl MatchingEngineHandle for MatcherHandle {
         &self,
         limit: Vec<BookOrder>,
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
-        pools: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>
+        pools: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        routes: Vec<Vec<B256>>,
+        solve_budget: Duration
     ) -> futures_util::future::BoxFuture<
         Result<(Vec<PoolSolution>, BundleGasDetails), MatchingEngineError>
     > {
         Box::pin(async move {
             let (tx, rx) = oneshot::channel();
-            self.send_request(rx, MatcherCommand::BuildProposal(limit, searcher, pools, tx))
-                .await
+            self.send_request(
+                rx,
+                MatcherCommand::BuildProposal(limit, searcher, pools, routes, solve_budget, tx)
+            )
+            .await
         })
     }
 }
@@ -93,132 +109,278 @@ impl MatchingEngineHandle for MatcherHandle {
 pub struct MatchingManager<TP: TaskSpawner, V> {
     _futures:          FuturesUnordered<Pin<Box<dyn Future<Output = ()> + Sync + Send + 'static>>>,
     validation_handle: V,
+    faults:            broadcast::Sender<BundleFaults>,
+    solver:            SolverPool,
     _tp:               Arc<TP>
 }
 
 impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V> {
-    pub fn new(tp: TP, validation: V) -> Self {
+    pub fn new(tp: TP, validation: V, solver: SolverConfig) -> Self {
         Self {
             _futures:          FuturesUnordered::default(),
             validation_handle: validation,
+            faults:            broadcast::channel(16).0,
+            solver:            SolverPool::new(solver),
             _tp:               tp.into()
         }
     }
 
-    pub fn spawn(tp: TP, validation: V) -> MatcherHandle {
+    pub fn spawn(tp: TP, validation: V, solver: SolverConfig) -> MatcherHandle {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let (faults, _) = broadcast::channel(16);
         let tp = Arc::new(tp);
 
-        let fut = manager_thread(rx, tp.clone(), validation).boxed();
+        let fut = manager_thread(rx, tp.clone(), validation, faults.clone(), solver).boxed();
         tp.spawn_critical("matching_engine", fut);
 
-        MatcherHandle { sender: tx }
+        MatcherHandle { sender: tx, faults }
     }
 
     pub fn build_non_proposal_books(
         limit: Vec<BookOrder>,
         pool_snapshots: &HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>
     ) -> Vec<OrderBook> {
-        let book_sources = Self::orders_sorted_by_pool_id(limit);
-
-        book_sources
-            .into_iter()
-            .map(|(id, orders)| {
-                let amm = pool_snapshots.get(&id).map(|value| value.2.clone());
-                build_book(id, amm, orders)
-            })
-            .collect()
+        build_books(limit, pool_snapshots)
     }
 
     pub async fn build_proposal(
         &self,
-        limit: Vec<BookOrder>,
+        mut limit: Vec<BookOrder>,
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
-        pool_snapshots: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>
+        pool_snapshots: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        routes: Vec<Vec<B256>>,
+        solve_budget: Duration
     ) -> Result<(Vec<PoolSolution>, BundleGasDetails), MatchingEngineError> {
-        // Pull all the orders out of all the preproposals and build OrderPools out of
-        // them.  This is ugly and inefficient right now
-        let books = Self::build_non_proposal_books(limit.clone(), &pool_snapshots);
-
-        let searcher_orders: HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>> = searcher
-            .clone()
-            .into_iter()
-            .fold(HashMap::new(), |mut acc, order| {
-                // assert we are unique per pool
-                assert!(!acc.contains_key(&order.pool_id));
-                acc.entry(order.pool_id).or_insert(order);
-
-                acc
-            });
-
-        let mut solution_set = JoinSet::new();
-
-        if books.is_empty() {
-            for searcher in searcher_orders.values().cloned() {
-                let mut book = OrderBook::default();
-                book.id = searcher.pool_id;
-                solution_set
-                    .spawn_blocking(move || Some(BinarySearchStrategy::run(&book, Some(searcher))));
-            }
-        } else {
-            books.into_iter().for_each(|b| {
-                let searcher = searcher_orders.get(&b.id()).cloned();
-                // Using spawn-blocking here is not BAD but it might be suboptimal as it allows
-                // us to spawn many more tasks that the CPu has threads.  Better solution is a
-                // dedicated threadpool and some suggest the `rayon` crate.  This is probably
-                // not a problem while I'm testing, but leaving this note here as it may be
-                // important for future efficiency gains
-                solution_set.spawn_blocking(move || Some(BinarySearchStrategy::run(&b, searcher)));
-            });
+        let searcher_orders = searcher_orders_by_pool(searcher.clone());
+        // the budget is what is left of the round, every solve and the fault
+        // isolation share it however often the books are solved again
+        let deadline = Instant::now() + solve_budget;
+
+        loop {
+            let solutions = solve_with_routes(
+                &self.solver,
+                &mut limit,
+                &searcher_orders,
+                &pool_snapshots,
+                &routes,
+                deadline,
+                BinarySearchStrategy::run_until
+            )
+            .await;
+
+            // generate bundle without final gas known.
+            trace!("Building bundle for gas finalization");
+            let bundle = AngstromBundle::for_gas_finalization(
+                limit.clone(),
+                solutions.clone(),
+                &pool_snapshots
+            )
+            .map_err(|_| MatchingEngineError::NoOrdersFilled)?;
+
+            let gas_response = match self.validation_handle.fetch_gas_for_bundle(bundle).await {
+                Ok(gas_response) => gas_response,
+                Err(e) => {
+                    let proposal_snapshot =
+                        serde_json::to_vec(&(&limit, &searcher, &pool_snapshots)).unwrap();
+                    let hex = hex::encode(proposal_snapshot);
+                    tracing::error!(bad_bundle=%hex);
+
+                    let (solutions, gas_response) = self
+                        .isolate_faults(
+                            &limit,
+                            &searcher_orders,
+                            &pool_snapshots,
+                            solutions,
+                            deadline
+                        )
+                        .await
+                        .ok_or(MatchingEngineError::SimulationFailed(e))?;
+
+                    // leaving out a faulty order can leave a route half filled
+                    let broken = broken_routes(&routes, &solutions);
+                    if broken.is_empty() {
+                        return Ok((solutions, gas_response));
+                    }
+                    tracing::warn!(
+                        legs = broken.len(),
+                        "fault isolation split routes, solving again without them"
+                    );
+                    limit.retain(|order| !broken.contains(&order.order_id.hash));
+                    continue;
+                }
+            };
+
+            return Ok((solutions, gas_response));
         }
-        let mut solutions = Vec::new();
-        while let Some(res) = solution_set.join_next().await {
-            if let Ok(Some(r)) = res {
-                solutions.push(r);
+    }
+
+    /// leaves out the pools and orders that make the bundle fail so that the
+    /// rest of the block can still be proposed.
+    async fn isolate_faults(
+        &self,
+        limit: &[BookOrder],
+        searchers: &HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+        pool_snapshots: &HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        solutions: Vec<PoolSolution>,
+        deadline: Instant
+    ) -> Option<(Vec<PoolSolution>, BundleGasDetails)> {
+        let metrics = BundleBuildingMetricsWrapper::new();
+        let mut isolator = FaultIsolator::new(
+            &self.validation_handle,
+            &self.solver,
+            limit,
+            searchers,
+            pool_snapshots,
+            deadline
+        );
+
+        match isolator.isolate(solutions).await {
+            Ok((solutions, gas_response, faults)) => {
+                metrics.fault_isolation_finished(
+                    isolator.simulations(),
+                    Some((faults.pools.len(), faults.orders.len()))
+                );
+                tracing::warn!(
+                    pools = ?faults.pools,
+                    orders = ?faults.orders,
+                    "left faulty pools and orders out of the bundle"
+                );
+                let _ = self.faults.send(faults);
+
+                Some((solutions, gas_response))
+            }
+            Err(_) => {
+                metrics.fault_isolation_finished(isolator.simulations(), None);
+                tracing::error!(
+                    simulations = isolator.simulations(),
+                    "couldn't find a passing bundle"
+                );
+
+                None
             }
         }
+    }
 
-        // generate bundle without final gas known.
-        trace!("Building bundle for gas finalization");
-        let bundle =
-            AngstromBundle::for_gas_finalization(limit.clone(), solutions.clone(), &pool_snapshots)
-                .map_err(|_| MatchingEngineError::NoOrdersFilled)?;
+    pub fn orders_sorted_by_pool_id(limit: Vec<BookOrder>) -> HashMap<PoolId, HashSet<BookOrder>> {
+        orders_by_pool_id(limit)
+    }
+}
 
-        let gas_response = self
-            .validation_handle
-            .fetch_gas_for_bundle(bundle)
-            .await
-            .map_err(|e| {
-                let proposal_snapshot =
-                    serde_json::to_vec(&(limit, searcher, pool_snapshots)).unwrap();
-                let hex = hex::encode(proposal_snapshot);
-                tracing::error!(bad_bundle=%hex);
-                MatchingEngineError::SimulationFailed(e)
-            })?;
+/// solves the books until no route is filled on only some of its legs.
+/// Leaving the legs of such a route out moves the clearing prices, so the books
+/// are solved again until every route is either filled or left out. Every
+/// solve ends by the deadline.
+async fn solve_with_routes<F>(
+    solver: &SolverPool,
+    limit: &mut Vec<BookOrder>,
+    searcher_orders: &HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+    pool_snapshots: &HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+    routes: &[Vec<B256>],
+    deadline: Instant,
+    solve: F
+) -> Vec<PoolSolution>
+where
+    F: Fn(
+            &OrderBook,
+            Option<OrderWithStorageData<TopOfBlockOrder>>,
+            Option<Instant>
+        ) -> Option<PoolSolution>
+        + Clone
+        + Send
+        + 'static
+{
+    loop {
+        let books = build_books(limit.clone(), pool_snapshots);
+        let solutions = solver
+            .solve_with(books, searcher_orders, Some(deadline), solve.clone())
+            .await;
 
-        Ok((solutions, gas_response))
+        let broken = broken_routes(routes, &solutions);
+        if broken.is_empty() {
+            return solutions;
+        }
+        trace!(legs = broken.len(), "leaving out partially filled routes");
+        limit.retain(|order| !broken.contains(&order.order_id.hash));
     }
+}
 
-    pub fn orders_sorted_by_pool_id(limit: Vec<BookOrder>) -> HashMap<PoolId, HashSet<BookOrder>> {
-        limit.into_iter().fold(HashMap::new(), |mut acc, order| {
-            acc.entry(order.pool_id).or_default().insert(order);
-            acc
+/// the legs of the routes that are filled on some but not all of their legs.
+pub fn broken_routes(routes: &[Vec<B256>], solutions: &[PoolSolution]) -> HashSet<B256> {
+    let filled = solutions
+        .iter()
+        .flat_map(|solution| solution.limit.iter())
+        .filter(|outcome| outcome.is_filled())
+        .map(|outcome| outcome.id.hash)
+        .collect::<HashSet<_>>();
+
+    routes
+        .iter()
+        .filter(|legs| {
+            let filled_legs = legs.iter().filter(|leg| filled.contains(*leg)).count();
+            filled_legs > 0 && filled_legs < legs.len()
         })
-    }
+        .flatten()
+        .copied()
+        .collect()
+}
+
+fn orders_by_pool_id(limit: Vec<BookOrder>) -> HashMap<PoolId, HashSet<BookOrder>> {
+    limit.into_iter().fold(HashMap::new(), |mut acc, order| {
+        acc.entry(order.pool_id).or_default().insert(order);
+        acc
+    })
+}
+
+/// builds one book per pool that has limit orders against it.
+pub fn build_books(
+    limit: Vec<BookOrder>,
+    pool_snapshots: &HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>
+) -> Vec<OrderBook> {
+    orders_by_pool_id(limit)
+        .into_iter()
+        .map(|(id, orders)| {
+            let amm = pool_snapshots.get(&id).map(|value| value.2.clone());
+            build_book(id, amm, orders)
+        })
+        .collect()
+}
+
+/// there can only be a single top of block order per pool.
+pub fn searcher_orders_by_pool(
+    searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>
+) -> HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>> {
+    searcher.into_iter().fold(HashMap::new(), |mut acc, order| {
+        // assert we are unique per pool
+        assert!(!acc.contains_key(&order.pool_id));
+        acc.entry(order.pool_id).or_insert(order);
+
+        acc
+    })
 }
 
 pub async fn manager_thread<TP: TaskSpawner + 'static, V: BundleValidatorHandle>(
     mut input: Receiver<MatcherCommand>,
     tp: Arc<TP>,
-    validation_handle: V
+    validation_handle: V,
+    faults: broadcast::Sender<BundleFaults>,
+    solver: SolverConfig
 ) {
-    let manager =
-        MatchingManager { _futures: FuturesUnordered::default(), _tp: tp, validation_handle };
+    let manager = MatchingManager {
+        _futures: FuturesUnordered::default(),
+        _tp: tp,
+        validation_handle,
+        faults,
+        solver: SolverPool::new(solver)
+    };
 
     while let Some(c) = input.recv().await {
         match c {
-            MatcherCommand::BuildProposal(limit, searcher, snapshot, r) => {
-                let r = r.send(manager.build_proposal(limit, searcher, snapshot).await);
+            MatcherCommand::BuildProposal(limit, searcher, snapshot, routes, solve_budget, r) => {
+                let r = r.send(
+                    manager
+                        .build_proposal(limit, searcher, snapshot, routes, solve_budget)
+                        .await
+                );
                 if r.is_err() {
                     tracing::error!("failed to send built proposal back to caller");
                 }
@@ -229,3 +391,126 @@ pub async fn manager_thread<TP: TaskSpawner + 'static, V: BundleValidatorHandle>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::{
+        orders::{OrderFillState, OrderId, OrderOutcome},
+        primitive::{AngstromAddressConfig, AngstromSigner}
+    };
+    use testing_tools::type_generator::orders::UserOrderBuilder;
+
+    use super::*;
+
+    fn solution(outcomes: &[(B256, OrderFillState)]) -> PoolSolution {
+        PoolSolution {
+            limit: outcomes
+                .iter()
+                .map(|&(hash, outcome)| OrderOutcome {
+                    id: OrderId { hash, ..Default::default() },
+                    outcome
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_partially_filled_routes_are_broken() {
+        let [a, b, c, d, e] = [1, 2, 3, 4, 5].map(B256::with_last_byte);
+        let routes = vec![vec![a, b], vec![c, d], vec![e, B256::with_last_byte(6)]];
+        let solutions = vec![
+            solution(&[(a, OrderFillState::CompleteFill), (c, OrderFillState::CompleteFill)]),
+            solution(&[
+                (b, OrderFillState::CompleteFill),
+                (d, OrderFillState::Unfilled),
+                (e, OrderFillState::Unfilled)
+            ]),
+        ];
+
+        assert_eq!(broken_routes(&routes, &solutions), HashSet::from([c, d]));
+    }
+
+    fn order(pool: PoolId) -> BookOrder {
+        UserOrderBuilder::new()
+            .kill_or_fill()
+            .partial()
+            .bid()
+            .amount(10u128.pow(15))
+            .signing_key(Some(AngstromSigner::random()))
+            .with_storage()
+            .bid()
+            .pool_id(pool)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn solving_again_for_broken_routes_stays_within_the_budget() {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        const SOLVE_TIME: Duration = Duration::from_millis(200);
+        let budget = SOLVE_TIME * 5 / 2;
+
+        let [pool_a, pool_b, pool_c, pool_e] = [1, 2, 3, 4].map(PoolId::repeat_byte);
+        // `a` and `d` share a pool so that `d` only fills while `a` is there
+        let mut limit =
+            vec![order(pool_a), order(pool_a), order(pool_b), order(pool_c), order(pool_e)];
+        let [a, d, b, c, e] = [0, 1, 2, 3, 4].map(|i| limit[i].order_id.hash);
+        let routes = vec![vec![a, b], vec![c, d]];
+
+        // `b` never fills, so the first solve breaks the route of `a` and `b`
+        // and the second one, without `a`, the route of `c` and `d`.
+        let solve = move |book: &OrderBook,
+                          _: Option<OrderWithStorageData<TopOfBlockOrder>>,
+                          deadline: Option<Instant>| {
+            let done = Instant::now() + SOLVE_TIME;
+            let until = deadline.map_or(done, |deadline| deadline.min(done));
+            std::thread::sleep(until.saturating_duration_since(Instant::now()));
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return None;
+            }
+
+            let hashes = book
+                .all_orders_iter()
+                .map(|order| order.order_id.hash)
+                .collect::<HashSet<_>>();
+            let limit = hashes
+                .iter()
+                .map(|&hash| {
+                    let unfilled = hash == b || (hash == d && !hashes.contains(&a));
+                    OrderOutcome {
+                        id:      OrderId { hash, ..Default::default() },
+                        outcome: if unfilled {
+                            OrderFillState::Unfilled
+                        } else {
+                            OrderFillState::CompleteFill
+                        }
+                    }
+                })
+                .collect();
+            Some(PoolSolution { id: book.id(), limit, ..Default::default() })
+        };
+
+        let solver = SolverPool::new(SolverConfig { threads: 4 });
+        let start = Instant::now();
+        solve_with_routes(
+            &solver,
+            &mut limit,
+            &HashMap::new(),
+            &HashMap::new(),
+            &routes,
+            start + budget,
+            solve
+        )
+        .await;
+
+        // three solves of the books, the last one is cut short by the deadline
+        assert!(start.elapsed() < budget + SOLVE_TIME / 4, "took {:?}", start.elapsed());
+        assert_eq!(
+            limit
+                .iter()
+                .map(|order| order.order_id.hash)
+                .collect::<Vec<_>>(),
+            vec![e]
+        );
+    }
+}
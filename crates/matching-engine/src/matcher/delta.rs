@@ -1,6 +1,7 @@
 use std::{
     cmp::{Ordering, Reverse, max, min},
-    collections::HashSet
+    collections::HashSet,
+    time::Instant
 };
 
 use alloy_primitives::{I256, Sign, U256};
@@ -69,7 +70,9 @@ pub struct DeltaMatcher<'a> {
     /// If true, we solve for T0.  If false we solve for T1.
     solve_for_t0:       bool,
     /// changes if there is a tob or not
-    amm_start_location: Option<PoolSwapResult<'a>>
+    amm_start_location: Option<PoolSwapResult<'a>>,
+    /// the search for the clearing price gives up once this has passed
+    deadline:           Option<Instant>
 }
 
 impl<'a> DeltaMatcher<'a> {
@@ -102,7 +105,16 @@ impl<'a> DeltaMatcher<'a> {
             DeltaMatcherToB::None => book.amm().map(|book| book.noop())
         };
 
-        Self { book, amm_start_location, fee, solve_for_t0 }
+        Self { book, amm_start_location, fee, solve_for_t0, deadline: None }
+    }
+
+    pub fn with_deadline(self, deadline: Option<Instant>) -> Self {
+        Self { deadline, ..self }
+    }
+
+    pub fn past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
     }
 
     /// panics if there is no amm swap
@@ -678,6 +690,10 @@ impl<'a> DeltaMatcher<'a> {
             if diff <= U256_1 {
                 break;
             }
+            if self.past_deadline() {
+                debug!("solve deadline passed, giving up");
+                return None;
+            }
             // We're willing to kill orders if and only if we're at the end of our
             // iteration.  I believe that a distance of four will capture the last 2 cycles
             // of iteration
@@ -0,0 +1,303 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    panic::AssertUnwindSafe,
+    sync::Arc,
+    time::{Duration, Instant}
+};
+
+use angstrom_metrics::BundleBuildingMetricsWrapper;
+use angstrom_types::{
+    orders::PoolSolution,
+    primitive::PoolId,
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder},
+    uni_structure::BaselinePoolState
+};
+use futures::{StreamExt, stream::FuturesUnordered};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::sync::oneshot;
+
+use crate::{book::OrderBook, strategy::BinarySearchStrategy};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SolverConfig {
+    /// threads dedicated to solving books.
+    pub threads: usize
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            threads: std::thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1)
+        }
+    }
+}
+
+/// A bounded set of threads that books are solved on, so that a proposal
+/// never runs more solves at once than there are threads. A pool that hasn't
+/// solved once the budget runs out, or whose solve panicked, falls back to a
+/// solve of only its top of block order, or is left out if it has none.
+#[derive(Clone)]
+pub struct SolverPool {
+    threads: Arc<ThreadPool>
+}
+
+impl SolverPool {
+    pub fn new(config: SolverConfig) -> Self {
+        let threads = ThreadPoolBuilder::new()
+            .num_threads(config.threads)
+            .thread_name(|i| format!("solver-{i}"))
+            .build()
+            .expect("failed to build the solver thread pool");
+
+        Self { threads: Arc::new(threads) }
+    }
+
+    /// solves every book, pairing each with its pool's searcher order. Every
+    /// pool has until the deadline to solve, `None` for no deadline. When
+    /// there are no books, the searcher orders are solved on their own.
+    pub async fn solve(
+        &self,
+        books: Vec<OrderBook>,
+        searcher_orders: &HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+        deadline: Option<Instant>
+    ) -> Vec<PoolSolution> {
+        self.solve_with(books, searcher_orders, deadline, BinarySearchStrategy::run_until)
+            .await
+    }
+
+    pub(crate) async fn solve_with<F>(
+        &self,
+        books: Vec<OrderBook>,
+        searcher_orders: &HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+        deadline: Option<Instant>,
+        solve: F
+    ) -> Vec<PoolSolution>
+    where
+        F: Fn(
+                &OrderBook,
+                Option<OrderWithStorageData<TopOfBlockOrder>>,
+                Option<Instant>
+            ) -> Option<PoolSolution>
+            + Clone
+            + Send
+            + 'static
+    {
+        let books = if books.is_empty() {
+            searcher_orders
+                .keys()
+                .map(|id| {
+                    let mut book = OrderBook::default();
+                    book.id = *id;
+                    book
+                })
+                .collect()
+        } else {
+            books
+        };
+
+        let metrics = BundleBuildingMetricsWrapper::new();
+        let budget = deadline
+            .map_or(Duration::MAX, |deadline| deadline.saturating_duration_since(Instant::now()));
+        let past_deadline = move || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+        let mut solves = books
+            .into_iter()
+            .map(|book| {
+                let id = book.id();
+                let searcher = searcher_orders.get(&id).cloned();
+                let fallback = searcher
+                    .clone()
+                    .map(|searcher| (searcher, book.amm().cloned()));
+                let (tx, rx) = oneshot::channel();
+
+                let solve = solve.clone();
+                let solve_metrics = metrics.clone();
+                self.threads.spawn(move || {
+                    // the pool already fell back, don't hold up the books
+                    // queued behind it.
+                    if past_deadline() {
+                        return;
+                    }
+
+                    let start = Instant::now();
+                    // a panicking solve only loses its own book.
+                    let solution = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        solve(&book, searcher, deadline)
+                    }));
+                    solve_metrics.pool_solved(id, start.elapsed());
+
+                    if let Ok(Some(solution)) = solution {
+                        let _ = tx.send(solution);
+                    }
+                });
+
+                let metrics = metrics.clone();
+                async move {
+                    let timed_out = match tokio::time::timeout(budget, rx).await {
+                        Ok(Ok(solution)) => return Some(solution),
+                        // the solve gave up or panicked
+                        Ok(Err(_)) => past_deadline(),
+                        Err(_) => true
+                    };
+                    if timed_out {
+                        tracing::warn!(pool = ?id, ?budget, "pool missed its solve deadline");
+                        metrics.pool_solve_timed_out(id);
+                    } else {
+                        tracing::error!(pool = ?id, "pool solve failed");
+                    }
+
+                    let (searcher, amm) = fallback?;
+                    self.solve_top_of_block_only(id, amm, searcher).await
+                }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut solutions = Vec::new();
+        while let Some(res) = solves.next().await {
+            if let Some(solution) = res {
+                solutions.push(solution);
+            }
+        }
+
+        solutions
+    }
+
+    /// what a pool falls back to when its book couldn't be solved, a solve of
+    /// the amm with only the top of block order.
+    async fn solve_top_of_block_only(
+        &self,
+        id: PoolId,
+        amm: Option<BaselinePoolState>,
+        searcher: OrderWithStorageData<TopOfBlockOrder>
+    ) -> Option<PoolSolution> {
+        let book = OrderBook::new(id, amm, vec![], vec![], None);
+
+        self.run(move || BinarySearchStrategy::run(&book, Some(searcher)))
+            .await
+    }
+
+    /// runs a single solve on the solver threads. `None` if it panicked.
+    pub async fn run<T: Send + 'static>(
+        &self,
+        solve: impl FnOnce() -> T + Send + 'static
+    ) -> Option<T> {
+        let (tx, rx) = oneshot::channel();
+        self.threads.spawn(move || {
+            if let Ok(solution) = std::panic::catch_unwind(AssertUnwindSafe(solve)) {
+                let _ = tx.send(solution);
+            }
+        });
+
+        rx.await.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn searcher(id: PoolId) -> OrderWithStorageData<TopOfBlockOrder> {
+        let mut searcher = OrderWithStorageData::with_default(TopOfBlockOrder::default());
+        searcher.pool_id = id;
+        searcher
+    }
+
+    /// a solve of the pool with only its searcher order.
+    fn top_of_block_only(id: PoolId) -> PoolSolution {
+        let book = OrderBook::new(id, None, vec![], vec![], None);
+        BinarySearchStrategy::run(&book, Some(searcher(id)))
+    }
+
+    #[tokio::test]
+    async fn late_pools_fall_back_to_their_top_of_block_order() {
+        let pool = SolverPool::new(SolverConfig { threads: 1 });
+
+        // keep the only solver thread busy so the solve can't start in time,
+        // the fallback runs once it is free again.
+        pool.threads
+            .spawn(|| std::thread::sleep(Duration::from_millis(50)));
+
+        let id = PoolId::repeat_byte(1);
+        let searcher_orders = HashMap::from([(id, searcher(id))]);
+
+        let solutions = pool
+            .solve(vec![], &searcher_orders, Instant::now().checked_add(Duration::from_millis(10)))
+            .await;
+
+        assert_eq!(solutions, vec![top_of_block_only(id)]);
+    }
+
+    #[tokio::test]
+    async fn panicking_pools_fall_back_to_their_top_of_block_order() {
+        let pool = SolverPool::new(SolverConfig { threads: 1 });
+        let id = PoolId::repeat_byte(1);
+        let searcher_orders = HashMap::from([(id, searcher(id))]);
+
+        let solutions = pool
+            .solve_with(vec![], &searcher_orders, None, |_, _, _| panic!("solver bug"))
+            .await;
+
+        assert_eq!(solutions, vec![top_of_block_only(id)]);
+    }
+
+    #[tokio::test]
+    async fn timed_out_solves_free_their_thread() {
+        let pool = SolverPool::new(SolverConfig { threads: 1 });
+        let ids = [PoolId::repeat_byte(1), PoolId::repeat_byte(2)];
+        let searcher_orders = ids
+            .into_iter()
+            .map(|id| (id, searcher(id)))
+            .collect::<HashMap<_, _>>();
+
+        // every book takes until its deadline, like one too big to solve in
+        // time.
+        let started = Arc::new(AtomicUsize::new(0));
+        let counter = started.clone();
+        let slow = move |_: &OrderBook,
+                         _: Option<OrderWithStorageData<TopOfBlockOrder>>,
+                         deadline: Option<Instant>| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            while deadline.is_some_and(|deadline| Instant::now() < deadline) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            None
+        };
+        let solutions = pool
+            .solve_with(
+                vec![],
+                &searcher_orders,
+                Instant::now().checked_add(Duration::from_millis(20)),
+                slow
+            )
+            .await;
+        assert_eq!(solutions.len(), 2);
+
+        // the next round isn't starved by the books that missed their deadline
+        let id = ids[0];
+        let solved = move |book: &OrderBook,
+                           searcher: Option<OrderWithStorageData<TopOfBlockOrder>>,
+                           _: Option<Instant>| {
+            Some(PoolSolution { id: book.id(), searcher, reward_t0: 1, ..Default::default() })
+        };
+        let solutions = pool
+            .solve_with(vec![], &HashMap::from([(id, searcher(id))]), None, solved)
+            .await;
+        assert_eq!(
+            solutions,
+            vec![PoolSolution {
+                id,
+                searcher: Some(searcher(id)),
+                reward_t0: 1,
+                ..Default::default()
+            }]
+        );
+
+        // the book that was still queued once its deadline passed never ran
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use alloy::primitives::{I256, U160};
 use angstrom_types::{
     matching::SqrtPriceX96,
@@ -18,17 +20,39 @@ impl BinarySearchStrategy {
         matcher.solution(searcher)
     }
 
+    /// like [`Self::run`], but gives up once the deadline has passed so that
+    /// the thread is free for the next book. `None` if it gave up.
+    pub fn run_until(
+        book: &OrderBook,
+        searcher: Option<OrderWithStorageData<TopOfBlockOrder>>,
+        deadline: Option<Instant>
+    ) -> Option<PoolSolution> {
+        let mut matcher =
+            DeltaMatcher::new(book, searcher.clone().into(), false).with_deadline(deadline);
+        let solution = matcher.solution(searcher);
+
+        (!matcher.past_deadline()).then_some(solution)
+    }
+
     pub fn give_end_amm_state(
         book: &OrderBook,
         searcher: Option<OrderWithStorageData<TopOfBlockOrder>>
     ) -> (U160, i32, u128) {
+        Self::run_with_end_amm_state(book, searcher).1
+    }
+
+    /// solves the book and returns the solution along with the state the amm
+    /// will be in once the solution has been applied.
+    pub fn run_with_end_amm_state(
+        book: &OrderBook,
+        searcher: Option<OrderWithStorageData<TopOfBlockOrder>>
+    ) -> (PoolSolution, (U160, i32, u128)) {
         let snapshot = book.amm().unwrap();
 
         if book.is_empty_book() {
             return (
-                *snapshot.current_price(),
-                snapshot.current_tick(),
-                snapshot.current_liquidity()
+                PoolSolution { id: book.id(), searcher, ..Default::default() },
+                (*snapshot.current_price(), snapshot.current_tick(), snapshot.current_liquidity())
             );
         }
 
@@ -38,7 +62,7 @@ impl BinarySearchStrategy {
         // we have no book currently attached
         if solution.ucp.is_zero() {
             let amm = matcher.try_get_amm_location();
-            (*amm.end_price, amm.end_tick, amm.end_liquidity.liquidity())
+            (solution, (*amm.end_price, amm.end_tick, amm.end_liquidity.liquidity()))
         } else {
             // same flow as bundle building
             let post_tob_swap = matcher.try_get_amm_location();
@@ -73,7 +97,7 @@ impl BinarySearchStrategy {
                 .unwrap()
                 .clone();
 
-            (*res.end_price, res.end_tick, res.end_liquidity.liquidity())
+            (solution, (*res.end_price, res.end_tick, res.end_liquidity.liquidity()))
         }
     }
 }
@@ -1 +1,159 @@
+use std::{sync::OnceLock, time::Duration};
 
+use angstrom_types::primitive::PoolId;
+use prometheus::{HistogramVec, IntCounter, IntCounterVec};
+
+use crate::METRICS_ENABLED;
+
+#[derive(Clone)]
+struct BundleBuildingMetrics {
+    // bundles whose simulation failed and were bisected
+    fault_isolation_runs:        IntCounter,
+    // bisections that didn't find a passing bundle
+    fault_isolation_failures:    IntCounter,
+    // simulations run while bisecting
+    fault_isolation_simulations: IntCounter,
+    // pools excluded from a bundle
+    excluded_pools:              IntCounter,
+    // orders excluded from a bundle
+    excluded_orders:             IntCounter,
+    // time it took to solve a pool, in milliseconds
+    pool_solve_time:             HistogramVec,
+    // pools that didn't solve within their budget
+    pool_solve_timeouts:         IntCounterVec
+}
+
+impl Default for BundleBuildingMetrics {
+    fn default() -> Self {
+        let fault_isolation_runs = prometheus::register_int_counter!(
+            "bundle_fault_isolation_runs",
+            "bundles whose simulation failed and were bisected"
+        )
+        .unwrap();
+
+        let fault_isolation_failures = prometheus::register_int_counter!(
+            "bundle_fault_isolation_failures",
+            "bisections that didn't find a passing bundle"
+        )
+        .unwrap();
+
+        let fault_isolation_simulations = prometheus::register_int_counter!(
+            "bundle_fault_isolation_simulations",
+            "simulations run while bisecting"
+        )
+        .unwrap();
+
+        let excluded_pools = prometheus::register_int_counter!(
+            "bundle_excluded_pools",
+            "pools excluded from a bundle"
+        )
+        .unwrap();
+
+        let excluded_orders = prometheus::register_int_counter!(
+            "bundle_excluded_orders",
+            "orders excluded from a bundle"
+        )
+        .unwrap();
+
+        let pool_solve_time = prometheus::register_histogram_vec!(
+            "bundle_pool_solve_time",
+            "time it took to solve a pool, in milliseconds",
+            &["pool_id"],
+            prometheus::exponential_buckets(1.0, 2.0, 14).unwrap()
+        )
+        .unwrap();
+
+        let pool_solve_timeouts = prometheus::register_int_counter_vec!(
+            "bundle_pool_solve_timeouts",
+            "pools that didn't solve within their budget",
+            &["pool_id"]
+        )
+        .unwrap();
+
+        Self {
+            fault_isolation_runs,
+            fault_isolation_failures,
+            fault_isolation_simulations,
+            excluded_pools,
+            excluded_orders,
+            pool_solve_time,
+            pool_solve_timeouts
+        }
+    }
+}
+
+impl BundleBuildingMetrics {
+    pub fn fault_isolation_finished(&self, simulations: usize, excluded: Option<(usize, usize)>) {
+        self.fault_isolation_runs.inc();
+        self.fault_isolation_simulations.inc_by(simulations as u64);
+
+        match excluded {
+            Some((pools, orders)) => {
+                self.excluded_pools.inc_by(pools as u64);
+                self.excluded_orders.inc_by(orders as u64);
+            }
+            None => self.fault_isolation_failures.inc()
+        }
+    }
+
+    pub fn pool_solved(&self, pool: PoolId, elapsed: Duration) {
+        self.pool_solve_time
+            .with_label_values(&[&pool.to_string()])
+            .observe(elapsed.as_secs_f64() * 1000.0);
+    }
+
+    pub fn pool_solve_timed_out(&self, pool: PoolId) {
+        self.pool_solve_timeouts
+            .with_label_values(&[&pool.to_string()])
+            .inc();
+    }
+}
+
+static METRICS_INSTANCE: OnceLock<BundleBuildingMetricsWrapper> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct BundleBuildingMetricsWrapper(Option<BundleBuildingMetrics>);
+
+impl Default for BundleBuildingMetricsWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BundleBuildingMetricsWrapper {
+    pub fn new() -> Self {
+        METRICS_INSTANCE
+            .get_or_init(|| {
+                Self(
+                    METRICS_ENABLED
+                        .get()
+                        .copied()
+                        .unwrap_or_default()
+                        .then(BundleBuildingMetrics::default)
+                )
+            })
+            .clone()
+    }
+
+    /// records a bisection of a failed bundle. `excluded` is the amount of
+    /// pools and orders that were dropped, `None` if no passing bundle was
+    /// found.
+    pub fn fault_isolation_finished(&self, simulations: usize, excluded: Option<(usize, usize)>) {
+        if let Some(this) = self.0.as_ref() {
+            this.fault_isolation_finished(simulations, excluded)
+        }
+    }
+
+    /// records how long solving a pool took, even if it missed its budget.
+    pub fn pool_solved(&self, pool: PoolId, elapsed: Duration) {
+        if let Some(this) = self.0.as_ref() {
+            this.pool_solved(pool, elapsed)
+        }
+    }
+
+    pub fn pool_solve_timed_out(&self, pool: PoolId) {
+        if let Some(this) = self.0.as_ref() {
+            this.pool_solve_timed_out(pool)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, sync::OnceLock, time::Instant};
 
-use prometheus::{IntGauge, IntGaugeVec};
+use prometheus::{Gauge, IntGauge, IntGaugeVec};
 
 use crate::METRICS_ENABLED;
 
@@ -14,6 +14,14 @@ struct ConsensusMetrics {
     proposal_build_time_per_block: IntGaugeVec,
     // time (ms) it takes proposal verification per block
     proposal_verification_time_per_block: IntGaugeVec,
+    // time (ms) the phases of the last round took
+    round_phase_time: IntGaugeVec,
+    // time (ms) into the slot the next pre-proposal is triggered at
+    pre_proposal_trigger_time: IntGauge,
+    // whether the trigger is learned from the round history
+    pre_proposal_trigger_learned: IntGauge,
+    // share of the recently led rounds whose bundle didn't land
+    bundle_miss_rate: Gauge,
     // map of block numbers to their consensus start times
     block_consensus_start_times: HashMap<u64, Instant>
 }
@@ -45,11 +53,40 @@ impl Default for ConsensusMetrics {
         )
         .unwrap();
 
+        let round_phase_time = prometheus::register_int_gauge_vec!(
+            "consensus_round_phase_time",
+            "time (ms) the phases of the last round took",
+            &["phase"]
+        )
+        .unwrap();
+
+        let pre_proposal_trigger_time = prometheus::register_int_gauge!(
+            "consensus_pre_proposal_trigger_time",
+            "time (ms) into the slot the next pre-proposal is triggered at"
+        )
+        .unwrap();
+
+        let pre_proposal_trigger_learned = prometheus::register_int_gauge!(
+            "consensus_pre_proposal_trigger_learned",
+            "whether the trigger is learned from the round history"
+        )
+        .unwrap();
+
+        let bundle_miss_rate = prometheus::register_gauge!(
+            "consensus_bundle_miss_rate",
+            "share of the recently led rounds whose bundle didn't land"
+        )
+        .unwrap();
+
         Self {
             block_height,
             proposal_build_time_per_block,
             completion_time_per_block,
             proposal_verification_time_per_block,
+            round_phase_time,
+            pre_proposal_trigger_time,
+            pre_proposal_trigger_learned,
+            bundle_miss_rate,
             block_consensus_start_times: HashMap::default()
         }
     }
@@ -77,6 +114,19 @@ impl ConsensusMetrics {
             .set(time as i64);
     }
 
+    pub fn set_round_phase_time(&self, phase: &str, time: u64) {
+        self.round_phase_time
+            .get_metric_with_label_values(&[phase])
+            .unwrap()
+            .set(time as i64);
+    }
+
+    pub fn set_pre_proposal_trigger(&self, trigger_time: u64, learned: bool, miss_rate: f64) {
+        self.pre_proposal_trigger_time.set(trigger_time as i64);
+        self.pre_proposal_trigger_learned.set(learned as i64);
+        self.bundle_miss_rate.set(miss_rate);
+    }
+
     pub fn set_block_height(&mut self, block_number: u64) {
         self.block_height.set(block_number as i64);
         self.block_consensus_start_times
@@ -141,6 +191,18 @@ impl ConsensusMetricsWrapper {
         }
     }
 
+    pub fn set_round_phase_time(&self, phase: &str, time: u64) {
+        if let Some(this) = self.0.as_ref() {
+            this.set_round_phase_time(phase, time)
+        }
+    }
+
+    pub fn set_pre_proposal_trigger(&self, trigger_time: u64, learned: bool, miss_rate: f64) {
+        if let Some(this) = self.0.as_ref() {
+            this.set_pre_proposal_trigger(trigger_time, learned, miss_rate)
+        }
+    }
+
     pub fn set_block_height(&mut self, block_number: u64) {
         if let Some(this) = self.0.as_mut() {
             this.set_block_height(block_number)
@@ -4,6 +4,7 @@ use std::sync::OnceLock;
 pub use exporter::*;
 
 mod bundle_building;
+pub use bundle_building::*;
 
 pub mod validation;
 
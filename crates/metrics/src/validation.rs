@@ -170,6 +170,10 @@ impl ValidationMetricsInner {
         self.pending_verification.dec();
     }
 
+    fn pending(&self) -> i64 {
+        self.pending_verification.get()
+    }
+
     async fn handle_pending<'a, T>(
         &self,
         f: impl FnOnce() -> Pin<Box<dyn Future<Output = T> + Send + Sync + 'a>>
@@ -307,4 +311,10 @@ impl ValidationMetrics {
 
         f()
     }
+
+    /// the amount of orders currently queued for verification, summed over
+    /// every node in the process. `None` when metrics are disabled.
+    pub fn pending_verification(&self) -> Option<i64> {
+        self.0.as_ref().map(ValidationMetricsInner::pending)
+    }
 }
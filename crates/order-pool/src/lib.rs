@@ -6,6 +6,7 @@ mod order_indexer;
 pub mod order_storage;
 mod order_subscribers;
 pub mod order_tracker;
+pub mod route_book;
 pub mod telemetry;
 
 mod searcher;
@@ -15,8 +16,12 @@ use std::future::Future;
 
 use alloy::primitives::{Address, B256, FixedBytes};
 use angstrom_types::{
-    orders::{CancelOrderRequest, OrderLocation, OrderOrigin, OrderStatus},
-    primitive::OrderValidationError,
+    contract_payloads::angstrom::FillReceipt,
+    orders::{
+        CancelOrderRequest, OrderEvent, OrderId, OrderLocation, OrderOrigin, OrderStatus,
+        RoutedOrder, TriggerCondition
+    },
+    primitive::{OrderValidationError, PoolId},
     sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
 };
 pub use angstrom_utils::*;
@@ -27,7 +32,9 @@ use tokio_stream::wrappers::BroadcastStream;
 #[derive(Debug, Clone)]
 pub enum PoolManagerUpdate {
     NewOrder(OrderWithStorageData<AllOrders>),
-    FilledOrder(u64, OrderWithStorageData<AllOrders>),
+    /// the receipt is only missing if the bundle that filled the order couldn't
+    /// be decoded
+    FilledOrder(u64, OrderWithStorageData<AllOrders>, Option<FillReceipt>),
     UnfilledOrders(OrderWithStorageData<AllOrders>),
     CancelledOrder {
         is_tob:     bool,
@@ -35,16 +42,61 @@ pub enum PoolManagerUpdate {
         pool_id:    FixedBytes<32>,
         order_hash: B256
     },
-    ExpiredOrder(OrderWithStorageData<AllOrders>)
+    ExpiredOrder(OrderWithStorageData<AllOrders>),
+    /// the order was dropped from the pool by the operator
+    DroppedOrder(OrderWithStorageData<AllOrders>),
+    /// the order made the simulation of a bundle fail and was removed from the
+    /// pool
+    InvalidatedOrder(OrderWithStorageData<AllOrders>),
+    /// a valid conditional order that is held until the trigger is hit
+    ArmedOrder(TriggerCondition, OrderWithStorageData<AllOrders>),
+    /// the trigger of a conditional order was hit, the order is revalidated
+    /// and added to the book
+    TriggeredOrder(OrderWithStorageData<AllOrders>),
+    /// the user replaced a order with a new order of the same nonce
+    ReplacedOrder {
+        old: OrderWithStorageData<AllOrders>,
+        new: OrderWithStorageData<AllOrders>
+    },
+    /// the pool moved to a new block, the balances or approvals of these
+    /// addresses changed in it
+    NewBlock {
+        block_number:    u64,
+        address_changes: Vec<Address>
+    }
 }
 impl PoolManagerUpdate {
-    pub fn order_id(&self) -> B256 {
+    /// the order the update is about, `None` for block updates.
+    pub fn order_id(&self) -> Option<B256> {
         match self {
-            Self::NewOrder(o) => o.order_id.hash,
-            Self::FilledOrder(_, o) => o.order_id.hash,
-            Self::UnfilledOrders(o) => o.order_id.hash,
-            Self::CancelledOrder { order_hash, .. } => *order_hash,
-            Self::ExpiredOrder(o) => o.order_id.hash
+            Self::NewOrder(o) => Some(o.order_id.hash),
+            Self::FilledOrder(_, o, _) => Some(o.order_id.hash),
+            Self::UnfilledOrders(o) => Some(o.order_id.hash),
+            Self::CancelledOrder { order_hash, .. } => Some(*order_hash),
+            Self::ExpiredOrder(o) => Some(o.order_id.hash),
+            Self::DroppedOrder(o) => Some(o.order_id.hash),
+            Self::InvalidatedOrder(o) => Some(o.order_id.hash),
+            Self::ArmedOrder(_, o) => Some(o.order_id.hash),
+            Self::TriggeredOrder(o) => Some(o.order_id.hash),
+            Self::ReplacedOrder { old, .. } => Some(old.order_id.hash),
+            Self::NewBlock { .. } => None
+        }
+    }
+
+    /// the user the update is about, `None` for block updates.
+    pub fn user(&self) -> Option<Address> {
+        match self {
+            Self::NewOrder(o)
+            | Self::FilledOrder(_, o, _)
+            | Self::UnfilledOrders(o)
+            | Self::ExpiredOrder(o)
+            | Self::DroppedOrder(o)
+            | Self::InvalidatedOrder(o)
+            | Self::ArmedOrder(_, o)
+            | Self::TriggeredOrder(o)
+            | Self::ReplacedOrder { new: o, .. } => Some(o.from()),
+            Self::CancelledOrder { user, .. } => Some(*user),
+            Self::NewBlock { .. } => None
         }
     }
 
@@ -53,7 +105,10 @@ impl PoolManagerUpdate {
             self,
             PoolManagerUpdate::FilledOrder(..)
                 | PoolManagerUpdate::ExpiredOrder(..)
+                | PoolManagerUpdate::DroppedOrder(..)
+                | PoolManagerUpdate::InvalidatedOrder(..)
                 | PoolManagerUpdate::CancelledOrder { .. }
+                | PoolManagerUpdate::ReplacedOrder { .. }
         )
     }
 }
@@ -72,6 +127,36 @@ pub trait OrderPoolHandle: Send + Sync + Clone + Unpin + 'static {
 
     fn pending_orders(&self, sender: Address) -> impl Future<Output = Vec<AllOrders>> + Send;
 
+    /// replaces the pending standing order of the user that has the same
+    /// nonce as this order.
+    fn replace_order(
+        &self,
+        origin: OrderOrigin,
+        order: AllOrders
+    ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send;
+
+    /// a standing order that this node holds until the trigger is hit.
+    fn new_conditional_order(
+        &self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        trigger: TriggerCondition
+    ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send;
+
+    /// a multi-hop route whose legs are either all filled or none, resolves
+    /// to the route id once all legs are valid.
+    fn new_routed_order(
+        &self,
+        origin: OrderOrigin,
+        route: RoutedOrder
+    ) -> impl Future<Output = Result<B256, OrderValidationError>> + Send;
+
+    /// the conditional orders of the user that haven't been triggered yet.
+    fn conditional_orders(
+        &self,
+        sender: Address
+    ) -> impl Future<Output = Vec<(TriggerCondition, AllOrders)>> + Send;
+
     fn cancel_order(&self, req: CancelOrderRequest) -> impl Future<Output = bool> + Send;
 
     fn fetch_orders_from_pool(
@@ -84,4 +169,42 @@ pub trait OrderPoolHandle: Send + Sync + Clone + Unpin + 'static {
         &self,
         order_hash: B256
     ) -> impl Future<Output = Option<OrderStatus>> + Send;
+
+    fn fetch_order_history(&self, order_hash: B256)
+    -> impl Future<Output = Vec<OrderEvent>> + Send;
+}
+
+/// Selects the orders an operator acts on through the admin handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderFilter {
+    Hash(B256),
+    User(Address),
+    Pool(PoolId)
+}
+
+impl OrderFilter {
+    pub fn matches(&self, id: &OrderId) -> bool {
+        match self {
+            Self::Hash(hash) => id.hash == *hash,
+            Self::User(user) => id.address == *user,
+            Self::Pool(pool_id) => id.pool_id == *pool_id
+        }
+    }
+}
+
+/// Operator access to the order pool. Kept apart from [`OrderPoolHandle`] as
+/// it bypasses the rules that users are held to.
+pub trait OrderPoolAdminHandle: Send + Sync + Clone + Unpin + 'static {
+    /// all orders matching the filter, including parked orders.
+    fn admin_orders(
+        &self,
+        filter: OrderFilter
+    ) -> impl Future<Output = Vec<OrderWithStorageData<AllOrders>>> + Send;
+
+    /// removes the matching orders from the pool, returning their hashes.
+    fn drop_orders(&self, filter: OrderFilter) -> impl Future<Output = Vec<B256>> + Send;
+
+    /// revalidates all orders of the user against their current account state.
+    fn revalidate_user(&self, user: Address) -> impl Future<Output = bool> + Send;
 }
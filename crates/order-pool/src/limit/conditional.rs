@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{B256, FixedBytes};
+use angstrom_types::{
+    orders::{PriceObservation, TriggerCondition},
+    primitive::PoolId,
+    sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+type ArmedOrder = (TriggerCondition, OrderWithStorageData<AllOrders>);
+
+/// Valid conditional orders that are held until their trigger is hit. These
+/// aren't part of the book and aren't propagated to peers while armed. Orders
+/// are indexed by the pool of their trigger so an observation only checks the
+/// orders that watch its pool.
+#[serde_as]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ConditionalPool {
+    #[serde_as(as = "HashMap<DisplayFromStr, HashMap<DisplayFromStr, _>>")]
+    orders:       HashMap<PoolId, HashMap<FixedBytes<32>, ArmedOrder>>,
+    /// order hash to the pool of its trigger
+    #[serde_as(as = "HashMap<DisplayFromStr, DisplayFromStr>")]
+    hash_to_pool: HashMap<FixedBytes<32>, PoolId>
+}
+
+impl ConditionalPool {
+    pub fn new_order(&mut self, trigger: TriggerCondition, order: OrderWithStorageData<AllOrders>) {
+        let hash = order.order_hash();
+        // re-arming with a trigger on another pool
+        self.remove_order(hash);
+
+        self.hash_to_pool.insert(hash, trigger.pool_id);
+        self.orders
+            .entry(trigger.pool_id)
+            .or_default()
+            .insert(hash, (trigger, order));
+    }
+
+    fn get(&self, order_id: &B256) -> Option<&ArmedOrder> {
+        let pool = self.hash_to_pool.get(order_id)?;
+        self.orders.get(pool)?.get(order_id)
+    }
+
+    pub fn get_order(&self, order_id: B256) -> Option<OrderWithStorageData<AllOrders>> {
+        self.get(&order_id).map(|(_, order)| order.clone())
+    }
+
+    pub fn get_trigger(&self, order_id: B256) -> Option<TriggerCondition> {
+        self.get(&order_id).map(|(trigger, _)| *trigger)
+    }
+
+    pub fn remove_order(&mut self, order_id: B256) -> Option<OrderWithStorageData<AllOrders>> {
+        let pool = self.hash_to_pool.remove(&order_id)?;
+        let orders = self.orders.get_mut(&pool)?;
+        let (_, order) = orders.remove(&order_id)?;
+        if orders.is_empty() {
+            self.orders.remove(&pool);
+        }
+
+        Some(order)
+    }
+
+    pub fn cancel_order(&mut self, id: B256) -> bool {
+        let Some(pool) = self.hash_to_pool.get(&id) else { return false };
+        if let Some((_, order)) = self
+            .orders
+            .get_mut(pool)
+            .and_then(|orders| orders.get_mut(&id))
+        {
+            tracing::trace!(?order, "canceled conditional order");
+            order.cancel_requested = true;
+
+            return true;
+        }
+
+        false
+    }
+
+    fn remove_where(
+        &mut self,
+        f: impl Fn(&OrderWithStorageData<AllOrders>) -> bool
+    ) -> Vec<OrderWithStorageData<AllOrders>> {
+        let ids = self
+            .orders
+            .values()
+            .flatten()
+            .filter(|(_, (_, order))| f(order))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+
+        ids.into_iter()
+            .filter_map(|id| self.remove_order(id))
+            .collect()
+    }
+
+    pub fn remove_all_cancelled_orders(&mut self) -> Vec<OrderWithStorageData<AllOrders>> {
+        self.remove_where(|order| order.cancel_requested)
+    }
+
+    pub fn remove_pool(&mut self, pool_id: &PoolId) -> Vec<OrderWithStorageData<AllOrders>> {
+        self.remove_where(|order| order.pool_id == *pool_id)
+    }
+
+    /// removes and returns all orders that the observation triggers.
+    pub fn take_triggered(
+        &mut self,
+        observation: &PriceObservation
+    ) -> Vec<OrderWithStorageData<AllOrders>> {
+        let Some(orders) = self.orders.get(&observation.pool_id) else { return vec![] };
+        let ids = orders
+            .iter()
+            .filter(|(_, (trigger, order))| {
+                !order.cancel_requested && trigger.is_triggered(observation)
+            })
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+
+        ids.into_iter()
+            .filter_map(|id| self.remove_order(id))
+            .collect()
+    }
+
+    pub fn get_all_orders(&self) -> Vec<ArmedOrder> {
+        self.orders
+            .values()
+            .flat_map(|orders| orders.values().cloned())
+            .collect()
+    }
+}
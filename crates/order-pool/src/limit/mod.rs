@@ -2,13 +2,14 @@ use std::{collections::HashSet, fmt::Debug};
 
 use alloy::primitives::{B256, FixedBytes};
 use angstrom_types::{
-    orders::{OrderId, OrderStatus, UpdatedGas},
+    orders::{OrderId, OrderStatus, PriceObservation, TriggerCondition, UpdatedGas},
     primitive::{NewInitializedPool, PoolId},
     sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
 };
 
-use self::{composable::ComposableLimitPool, standard::LimitPool};
+use self::{composable::ComposableLimitPool, conditional::ConditionalPool, standard::LimitPool};
 mod composable;
+mod conditional;
 mod parked;
 mod pending;
 mod standard;
@@ -18,16 +19,20 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct LimitOrderPool {
     /// Sub-pool of all limit orders
-    limit_orders:      LimitPool,
+    limit_orders:       LimitPool,
     /// Sub-pool of all composable orders
-    composable_orders: ComposableLimitPool
+    composable_orders:  ComposableLimitPool,
+    /// Sub-pool of conditional orders waiting on their trigger
+    #[serde(default)]
+    conditional_orders: ConditionalPool
 }
 
 impl LimitOrderPool {
     pub fn new(ids: &[PoolId]) -> Self {
         Self {
-            composable_orders: ComposableLimitPool::new(ids),
-            limit_orders:      LimitPool::new(ids)
+            composable_orders:  ComposableLimitPool::new(ids),
+            limit_orders:       LimitPool::new(ids),
+            conditional_orders: ConditionalPool::default()
         }
     }
 
@@ -39,6 +44,7 @@ impl LimitOrderPool {
         self.limit_orders
             .get_order(id.pool_id, id.hash)
             .or_else(|| self.composable_orders.get_order(id.pool_id, id.hash))
+            .or_else(|| self.conditional_orders.get_order(id.hash))
     }
 
     pub fn remove_pool(&mut self, key: &PoolId) -> Vec<OrderWithStorageData<AllOrders>> {
@@ -62,20 +68,50 @@ impl LimitOrderPool {
                 .map(|pool| pool.get_all_orders().into_iter().collect::<Vec<_>>())
                 .unwrap_or_default()
         );
+        expired_orders.extend(self.conditional_orders.remove_pool(key));
 
         expired_orders
     }
 
     pub fn cancel_order(&mut self, id: &OrderId) -> bool {
-        self.limit_orders.cancel_order(id)
+        self.limit_orders.cancel_order(id) || self.conditional_orders.cancel_order(id.hash)
     }
 
     pub fn remove_all_cancelled_orders(&mut self) -> Vec<OrderWithStorageData<AllOrders>> {
-        self.limit_orders.remove_all_cancelled_orders()
+        let mut orders = self.limit_orders.remove_all_cancelled_orders();
+        orders.extend(self.conditional_orders.remove_all_cancelled_orders());
+        orders
     }
 
     pub fn get_order_status(&self, order_hash: B256) -> Option<OrderStatus> {
-        self.limit_orders.get_order_status(order_hash)
+        self.limit_orders.get_order_status(order_hash).or_else(|| {
+            self.conditional_orders
+                .get_trigger(order_hash)
+                .map(|trigger| OrderStatus::Armed { trigger })
+        })
+    }
+
+    pub fn add_conditional_order(
+        &mut self,
+        trigger: TriggerCondition,
+        order: OrderWithStorageData<AllOrders>
+    ) {
+        self.conditional_orders.new_order(trigger, order)
+    }
+
+    /// removes the conditional orders the observation triggers so they can be
+    /// added to the book.
+    pub fn take_triggered_orders(
+        &mut self,
+        observation: &PriceObservation
+    ) -> Vec<OrderWithStorageData<AllOrders>> {
+        self.conditional_orders.take_triggered(observation)
+    }
+
+    pub fn get_conditional_orders(
+        &self
+    ) -> Vec<(TriggerCondition, OrderWithStorageData<AllOrders>)> {
+        self.conditional_orders.get_all_orders()
     }
 
     pub fn add_composable_order(
@@ -96,6 +132,7 @@ impl LimitOrderPool {
         self.limit_orders
             .remove_order(id.pool_id, id.hash)
             .or_else(|| self.composable_orders.remove_order(id.pool_id, id.hash))
+            .or_else(|| self.conditional_orders.remove_order(id.hash))
     }
 
     pub fn remove_parked_order(&mut self, id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
@@ -148,6 +185,7 @@ impl LimitOrderPool {
     pub fn remove_invalid_order(&mut self, order_hash: B256) {
         self.composable_orders.remove_invalid_order(order_hash);
         self.limit_orders.remove_invalid_order(order_hash);
+        self.conditional_orders.remove_order(order_hash);
     }
 }
 
@@ -1,4 +1,5 @@
 use std::{
+    collections::{HashMap, HashSet},
     pin::Pin,
     sync::Arc,
     task::{Context, Poll}
@@ -6,13 +7,18 @@ use std::{
 
 use alloy::primitives::{Address, B256, BlockNumber, U256};
 use angstrom_types::{
-    orders::{OrderId, OrderLocation, OrderOrigin, OrderSet, OrderStatus},
-    primitive::{NewInitializedPool, PeerId, PoolId},
+    contract_payloads::angstrom::FillReceipt,
+    orders::{
+        OrderEvent, OrderId, OrderLocation, OrderOrigin, OrderSet, OrderStatus, PriceObservation,
+        RouteError, RoutedOrder, TriggerCondition
+    },
+    primitive::{NewInitializedPool, OrderValidationError, PeerId, PoolId},
     sol_bindings::{
-        RawPoolOrder,
+        RawPoolOrder, RespendAvoidanceMethod,
         grouped_orders::{AllOrders, OrderWithStorageData},
         rpc_orders::TopOfBlockOrder
-    }
+    },
+    trace_context::TraceContext
 };
 use futures_util::{Stream, StreamExt};
 use tokio::sync::oneshot::Sender;
@@ -20,7 +26,7 @@ use tracing::error;
 use validation::order::{OrderValidationResults, OrderValidatorHandle};
 
 use crate::{
-    PoolManagerUpdate,
+    OrderFilter, PoolManagerUpdate,
     order_storage::OrderStorage,
     order_subscribers::OrderSubscriptionTracker,
     order_tracker::OrderTracker,
@@ -35,6 +41,16 @@ pub(crate) struct InnerCancelOrderRequest {
     pub deadline: U256
 }
 
+/// a route whose legs are still being validated.
+struct PendingRoute {
+    route:     RoutedOrder,
+    /// the legs that haven't been validated yet
+    remaining: HashSet<B256>,
+    /// the first leg that turned out to be invalid
+    failed:    Option<B256>,
+    sender:    Option<Sender<Result<B256, OrderValidationError>>>
+}
+
 pub struct OrderIndexer<V: OrderValidatorHandle> {
     /// order storage
     pub(crate) order_storage: Arc<OrderStorage>,
@@ -47,7 +63,18 @@ pub struct OrderIndexer<V: OrderValidatorHandle> {
     pub(crate) validator:     OrderValidator<V>,
     /// List of subscribers for order validation result
     /// order
-    pub(crate) subscribers:   OrderSubscriptionTracker
+    pub(crate) subscribers:   OrderSubscriptionTracker,
+    /// receipts of the orders filled in the block that is being transitioned
+    /// to
+    pub(crate) fill_receipts: HashMap<B256, FillReceipt>,
+    /// addresses whose balances changed in the block that is being
+    /// transitioned to, announced once the validator has moved to it
+    changed_addresses:        Vec<Address>,
+    /// routes by id, that are held back until all of their legs are valid
+    pending_routes:           HashMap<B256, PendingRoute>,
+    /// the legs of the pending routes that are still validated, to the id of
+    /// their route
+    pending_legs:             HashMap<B256, B256>
 }
 
 impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
@@ -62,7 +89,11 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             order_tracker: OrderTracker::default(),
             block_number,
             validator: OrderValidator::new(validator),
-            subscribers: OrderSubscriptionTracker::new(orders_subscriber_tx)
+            subscribers: OrderSubscriptionTracker::new(orders_subscriber_tx),
+            fill_receipts: HashMap::default(),
+            changed_addresses: Vec::new(),
+            pending_routes: HashMap::default(),
+            pending_legs: HashMap::default()
         }
     }
 
@@ -85,8 +116,27 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         self.order_storage.get_orders_by_pool(pool_id, location)
     }
 
+    /// the current status of the order. cancelled orders are only removed from
+    /// storage after the block, so terminal statuses we recorded take priority
+    /// over what storage says.
     pub fn order_status(&self, order_hash: B256) -> Option<OrderStatus> {
-        self.order_storage.fetch_status_of_order(order_hash)
+        let last_status = self.order_tracker.last_status(&order_hash);
+        if let Some(status) = last_status.clone().filter(OrderStatus::is_terminal) {
+            return Some(status);
+        }
+
+        self.order_storage
+            .fetch_status_of_order(order_hash)
+            .or(last_status)
+    }
+
+    pub fn order_history(&self, order_hash: B256) -> Vec<OrderEvent> {
+        self.order_tracker.order_history(&order_hash)
+    }
+
+    fn record_status(&mut self, order_hash: B256, status: OrderStatus) {
+        self.order_tracker
+            .record_status(order_hash, self.block_number, status);
     }
 
     fn is_seen_invalid(&self, order_hash: &B256) -> bool {
@@ -117,6 +167,349 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         self.new_order(Some(peer_id), origin, order, None)
     }
 
+    /// A standing order that is held by this node until the trigger is hit.
+    pub fn new_conditional_order(
+        &mut self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        trigger: TriggerCondition,
+        validation_tx: tokio::sync::oneshot::Sender<OrderValidationResults>
+    ) {
+        let hash = order.order_hash();
+        if !matches!(order, AllOrders::ExactStanding(_) | AllOrders::PartialStanding(_)) {
+            let _ = validation_tx.send(OrderValidationResults::Invalid {
+                hash,
+                error: OrderValidationError::ConditionalOrderNotStanding
+            });
+            return;
+        }
+
+        // orders we already know of go through the regular flow and get rejected there
+        if !self.order_tracker.is_duplicate(&hash) && !self.order_tracker.is_validating(&hash) {
+            self.order_tracker.arm_order(hash, trigger);
+        }
+        self.new_order(None, origin, order, Some(validation_tx))
+    }
+
+    /// Releases the conditional orders that the observation triggers. They are
+    /// revalidated before they are added to the book and propagated.
+    pub fn on_price_observation(&mut self, observation: PriceObservation) {
+        for order in self.order_storage.take_triggered_orders(&observation) {
+            let hash = order.order_hash();
+            self.order_tracker.disarm_order(&hash);
+            self.record_status(hash, OrderStatus::Triggered);
+            self.subscribers
+                .notify_order_subscribers(PoolManagerUpdate::TriggeredOrder(order.clone()));
+
+            self.order_tracker.start_validating(hash);
+            self.validator
+                .validate_order(OrderOrigin::ReValidation, order.order);
+        }
+    }
+
+    pub fn conditional_orders(
+        &self,
+        address: Address
+    ) -> Vec<(TriggerCondition, OrderWithStorageData<AllOrders>)> {
+        self.order_storage
+            .get_conditional_orders()
+            .into_iter()
+            .filter(|(_, order)| order.from() == address)
+            .collect()
+    }
+
+    /// holds the valid conditional order until its trigger is hit.
+    fn arm_order(
+        &mut self,
+        trigger: TriggerCondition,
+        valid: OrderWithStorageData<AllOrders>
+    ) -> PoolInnerEvent {
+        let hash = valid.order_hash();
+        self.subscribers
+            .try_notify_validation_subscribers(&hash, OrderValidationResults::Valid(valid.clone()));
+        self.order_tracker
+            .new_valid_order(&hash, valid.from(), valid.order_id);
+        self.record_status(hash, OrderStatus::Armed { trigger });
+        self.subscribers
+            .notify_order_subscribers(PoolManagerUpdate::ArmedOrder(trigger, valid.clone()));
+        self.order_storage.add_conditional_order(trigger, valid);
+
+        PoolInnerEvent::None
+    }
+
+    pub fn new_rpc_route(
+        &mut self,
+        origin: OrderOrigin,
+        route: RoutedOrder,
+        sender: Sender<Result<B256, OrderValidationError>>
+    ) {
+        self.new_route(None, origin, route, Some(sender))
+    }
+
+    pub fn new_network_route(&mut self, peer_id: PeerId, route: RoutedOrder) {
+        self.new_route(Some(peer_id), OrderOrigin::External, route, None)
+    }
+
+    /// Validates the legs of a multi-hop route. The route is only linked, and
+    /// propagated, once all of its legs are valid. If a leg is invalid the
+    /// others are removed again, as they can't be filled on their own.
+    fn new_route(
+        &mut self,
+        peer_id: Option<PeerId>,
+        origin: OrderOrigin,
+        route: RoutedOrder,
+        sender: Option<Sender<Result<B256, OrderValidationError>>>
+    ) {
+        let route_id = route.route_id();
+        let _span = tracing::error_span!("order_indexer", %route_id, ?peer_id).entered();
+        if let Err(error) = self.check_new_route(&route, &route_id) {
+            tracing::debug!(%error, "route invalid");
+            if let Some(sender) = sender {
+                let _ = sender.send(Err(error));
+            }
+            return;
+        }
+
+        // the legs are handed to validation before the route is pending, as from then
+        // on they are turned away when submitted on their own
+        tracing::debug!(legs = route.legs.len(), "validating new route");
+        for leg in route.legs.clone() {
+            self.new_order(peer_id, origin, leg, None);
+        }
+
+        let remaining = route.leg_hashes().into_iter().collect::<HashSet<_>>();
+        self.pending_legs
+            .extend(remaining.iter().map(|leg| (*leg, route_id)));
+        self.pending_routes
+            .insert(route_id, PendingRoute { route, remaining, failed: None, sender });
+    }
+
+    /// the routes whose legs are all valid.
+    pub fn routes(&self) -> Vec<RoutedOrder> {
+        self.order_storage.routes()
+    }
+
+    /// the trace of the order, if the traces are exported.
+    pub fn trace_of(&self, order_hash: &B256) -> Option<TraceContext> {
+        self.order_storage.trace_of(order_hash)
+    }
+
+    fn check_new_route(
+        &self,
+        route: &RoutedOrder,
+        route_id: &B256
+    ) -> Result<(), OrderValidationError> {
+        route.validate()?;
+        if self.pending_routes.contains_key(route_id) || self.order_storage.has_route(route_id) {
+            return Err(OrderValidationError::DuplicateOrder);
+        }
+
+        // orders that are already in the pool could be filled without the rest of the
+        // route, so the legs have to be new.
+        let known_leg = route.legs.iter().any(|leg| {
+            let hash = leg.order_hash();
+            self.order_tracker.is_duplicate(&hash)
+                || self.order_tracker.is_validating(&hash)
+                || self.order_tracker.is_valid_cancel(&hash, leg.from())
+        });
+        if known_leg {
+            return Err(OrderValidationError::DuplicateOrder);
+        }
+
+        Ok(())
+    }
+
+    fn is_pending_route_leg(&self, hash: &B256) -> bool {
+        self.pending_legs.contains_key(hash)
+    }
+
+    /// whether the order is a leg of a pending or linked route.
+    fn is_route_leg(&self, hash: &B256) -> bool {
+        self.is_pending_route_leg(hash) || self.order_storage.is_route_leg(hash)
+    }
+
+    /// records the validation result of a leg of a pending route. Returns the
+    /// route once all of its legs are valid.
+    fn resolve_route_leg(&mut self, leg: B256, valid: bool) -> Option<RoutedOrder> {
+        let route_id = self.pending_legs.remove(&leg)?;
+        let pending = self.pending_routes.get_mut(&route_id)?;
+
+        pending.remaining.remove(&leg);
+        if !valid && pending.failed.is_none() {
+            pending.failed = Some(leg);
+            if let Some(sender) = pending.sender.take() {
+                let _ = sender.send(Err(RouteError::InvalidLeg { leg }.into()));
+            }
+        }
+        if !pending.remaining.is_empty() {
+            return None;
+        }
+
+        let PendingRoute { route, failed, sender, .. } = self.pending_routes.remove(&route_id)?;
+        if let Some(leg) = failed {
+            let ids = route
+                .leg_hashes()
+                .iter()
+                .filter_map(|hash| self.order_tracker.order_hash_to_order_id.get(hash).copied())
+                .collect::<Vec<_>>();
+            let removed = self.remove_orders(
+                &ids,
+                OrderStatus::Invalid { error: RouteError::InvalidLeg { leg }.into() }
+            );
+            self.subscribers.notify_expired_orders(&removed);
+            return None;
+        }
+
+        self.order_storage.add_route(route.clone());
+        if let Some(sender) = sender {
+            let _ = sender.send(Ok(route_id));
+        }
+
+        Some(route)
+    }
+
+    /// drops the routes that lost a leg to a fill, cancel or expiry, together
+    /// with the legs they have left as those can't be filled on their own.
+    fn prune_routes(&mut self) {
+        for route in self.order_storage.routes() {
+            let legs = route.leg_hashes();
+            // legs that are being revalidated are only out of storage for now
+            if legs
+                .iter()
+                .any(|hash| self.order_tracker.is_validating(hash))
+            {
+                continue;
+            }
+
+            let live = legs
+                .iter()
+                .filter_map(|hash| self.order_tracker.order_hash_to_order_id.get(hash).copied())
+                .filter(|id| self.order_storage.get_order_from_id(id).is_some())
+                .collect::<Vec<_>>();
+            if live.len() == route.legs.len() {
+                continue;
+            }
+
+            self.order_storage.remove_route(&route.route_id());
+            let removed = self.remove_orders(
+                &live,
+                OrderStatus::Invalid { error: RouteError::IncompleteRoute.into() }
+            );
+            self.subscribers.notify_expired_orders(&removed);
+        }
+    }
+
+    pub fn replace_rpc_order(
+        &mut self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        validation_tx: tokio::sync::oneshot::Sender<OrderValidationResults>
+    ) {
+        self.replace_order(None, origin, order, Some(validation_tx))
+    }
+
+    pub fn replace_network_order(&mut self, peer_id: PeerId, order: AllOrders) {
+        self.replace_order(Some(peer_id), OrderOrigin::External, order, None)
+    }
+
+    /// Replaces the users pending order that has the same nonce. The old order
+    /// stays in the pool until the new one is validated, and is only removed
+    /// if the new one is valid.
+    fn replace_order(
+        &mut self,
+        peer_id: Option<PeerId>,
+        origin: OrderOrigin,
+        order: AllOrders,
+        validation_res_sub: Option<Sender<OrderValidationResults>>
+    ) {
+        let hash = order.order_hash();
+        let user = order.from();
+        // let the regular flow deal with orders we have already seen
+        if self.order_tracker.is_duplicate(&hash)
+            || self.order_tracker.is_validating(&hash)
+            || self.order_tracker.is_valid_cancel(&hash, user)
+        {
+            return self.new_order(peer_id, origin, order, validation_res_sub);
+        }
+
+        let replaced = match order.respend_avoidance_strategy() {
+            nonce @ RespendAvoidanceMethod::Nonce(_) => self
+                .pending_orders_for_address(user)
+                .into_iter()
+                .find(|pending| {
+                    pending.respend_avoidance_strategy() == nonce && pending.order_hash() != hash
+                })
+                .map(|pending| pending.order_hash()),
+            RespendAvoidanceMethod::Block(_) => None
+        };
+
+        let error = match replaced {
+            // peers might not have seen the order it replaces yet
+            None if peer_id.is_some() => {
+                return self.new_order(peer_id, origin, order, validation_res_sub);
+            }
+            None => OrderValidationError::NoOrderToReplace,
+            Some(old) if self.order_tracker.start_replacement(user, hash, old) => {
+                // frees up the nonce so the new order doesn't conflict with the one it
+                // replaces
+                self.validator.cancel_order(user, old);
+                return self.new_order(peer_id, origin, order, validation_res_sub);
+            }
+            Some(_) => OrderValidationError::ReplacementRateLimited
+        };
+
+        if let Some(validation_tx) = validation_res_sub {
+            let _ = validation_tx.send(OrderValidationResults::Invalid { hash, error });
+        }
+    }
+
+    /// puts the order back into the users account state after the order that
+    /// was going to replace it turned out to be invalid. The order stays in the
+    /// book while it is revalidated.
+    fn restore_replaced_order(&mut self, hash: B256) {
+        let Some(id) = self.order_tracker.order_hash_to_order_id.get(&hash) else { return };
+        let Some(order) = self.order_storage.get_order_from_id(id) else { return };
+
+        self.order_tracker.start_restoring(hash);
+        self.order_tracker.start_validating(hash);
+        self.validator
+            .validate_order(OrderOrigin::ReValidation, order.order);
+    }
+
+    /// swaps the revalidated replaced order in for the copy that stayed in the
+    /// book.
+    fn finish_restoring(&mut self, valid: OrderWithStorageData<AllOrders>) -> PoolInnerEvent {
+        // the copy in the book is revalidated with the rest of the pool on the
+        // new block
+        if valid.valid_block != self.block_number {
+            return PoolInnerEvent::None;
+        }
+        // filled or cancelled while it was revalidated
+        if self
+            .order_storage
+            .remove_order_from_id(&valid.order_id)
+            .is_none()
+        {
+            return PoolInnerEvent::None;
+        }
+
+        self.record_status(valid.order_hash(), Self::validated_status(&valid));
+        if let Err(e) = self.insert_order(valid) {
+            tracing::error!(%e, "failed to insert restored order");
+        }
+
+        PoolInnerEvent::None
+    }
+
+    fn validated_status(valid: &OrderWithStorageData<AllOrders>) -> OrderStatus {
+        match valid.is_currently_valid {
+            Some(ref error) => OrderStatus::try_from_err(error).unwrap_or_else(|_| {
+                OrderStatus::Invalid { error: OrderValidationError::StateError(error.clone()) }
+            }),
+            None => OrderStatus::Pending
+        }
+    }
+
     pub fn cancel_order(&mut self, request: &angstrom_types::orders::CancelOrderRequest) -> bool {
         // ensure validity
         if !request.is_valid() {
@@ -149,6 +542,7 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                         .validate_order(OrderOrigin::ReValidation, order.order);
                 });
 
+            self.record_status(request.order_id, OrderStatus::Cancelled);
             self.subscribers
                 .notify_order_subscribers(PoolManagerUpdate::CancelledOrder {
                     is_tob,
@@ -170,10 +564,25 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         validation_res_sub: Option<Sender<OrderValidationResults>>
     ) {
         let hash = order.order_hash();
+        // validation is spawned in this span, making it the parent of the
+        // orders validation
+        let _span = tracing::error_span!("order_indexer", order_hash = %hash, ?peer_id).entered();
         if let Some(validation_tx) = validation_res_sub {
             self.subscribers.subscribe_to_order(hash, validation_tx);
         }
 
+        // on its own a leg could be filled without the rest of its route
+        if self.is_route_leg(&hash) {
+            self.subscribers.notify_validation_subscribers(
+                &hash,
+                OrderValidationResults::Invalid {
+                    hash,
+                    error: RouteError::LegOfRoute { leg: hash }.into()
+                }
+            );
+            return;
+        }
+
         // if the order has been canceled, we just notify the validation subscribers
         // that its a cancelled order
         if self.order_tracker.is_valid_cancel(&hash, order.from()) {
@@ -186,6 +595,7 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                     error: angstrom_types::primitive::OrderValidationError::CancelledOrder
                 }
             );
+            self.record_status(hash, OrderStatus::Cancelled);
             self.order_storage.log_cancel_order(&order);
             return;
         }
@@ -207,10 +617,98 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             return;
         }
         self.order_tracker.start_validating(hash);
+        // so that consensus and the submission of the order join its trace
+        self.order_storage.record_trace(hash);
 
+        tracing::debug!(?origin, "validating new order");
         self.validator.validate_order(origin, order);
     }
 
+    /// all orders matching the filter, including parked orders.
+    pub fn admin_orders(&self, filter: &OrderFilter) -> Vec<OrderWithStorageData<AllOrders>> {
+        self.order_tracker
+            .order_ids_matching(filter)
+            .iter()
+            .filter_map(|id| self.order_storage.get_order_from_id(id))
+            .collect()
+    }
+
+    /// removes the matching orders without the user cancelling them. The
+    /// orders are still valid, so peers that have them keep them.
+    pub fn drop_orders(&mut self, filter: &OrderFilter) -> Vec<B256> {
+        let ids = self.order_tracker.order_ids_matching(filter);
+        let dropped = self.remove_orders(&ids, OrderStatus::Dropped);
+
+        dropped
+            .into_iter()
+            .map(|order| {
+                let hash = order.order_hash();
+                // peers still gossip the order, don't take it back in until it expires
+                self.order_tracker.ban_order(order.order_id);
+                self.subscribers
+                    .notify_order_subscribers(PoolManagerUpdate::DroppedOrder(order));
+                hash
+            })
+            .collect()
+    }
+
+    /// removes the orders that made the simulation of a bundle fail, so they
+    /// can't take down the next block as well.
+    pub fn invalidate_orders(&mut self, hashes: &[B256]) -> Vec<B256> {
+        let ids = hashes
+            .iter()
+            .filter_map(|hash| self.order_tracker.order_hash_to_order_id.get(hash).copied())
+            .collect::<Vec<_>>();
+
+        let removed = self.remove_orders(
+            &ids,
+            OrderStatus::Invalid { error: OrderValidationError::FailedBundleSimulation }
+        );
+
+        removed
+            .into_iter()
+            .map(|order| {
+                let hash = order.order_hash();
+                // keep it out until it expires, not only for the rest of the block
+                self.order_tracker.ban_order(order.order_id);
+                self.subscribers
+                    .notify_order_subscribers(PoolManagerUpdate::InvalidatedOrder(order));
+                hash
+            })
+            .collect()
+    }
+
+    fn remove_orders(
+        &mut self,
+        ids: &[OrderId],
+        status: OrderStatus
+    ) -> Vec<OrderWithStorageData<AllOrders>> {
+        let orders = ids
+            .iter()
+            .filter_map(|id| self.order_storage.remove_order_from_id(id))
+            .collect::<Vec<_>>();
+        self.order_tracker.handle_pool_removed(&orders);
+
+        for order in &orders {
+            self.validator
+                .cancel_order(order.from(), order.order_hash());
+            self.record_status(order.order_hash(), status.clone());
+        }
+
+        orders
+    }
+
+    /// revalidates all orders of the user, returns false if the user has no
+    /// orders in the pool.
+    pub fn revalidate_user(&mut self, user: Address) -> bool {
+        if self.pending_orders_for_address(user).is_empty() {
+            return false;
+        }
+        self.eoa_state_change(&[user]);
+
+        true
+    }
+
     fn eoa_state_change(&mut self, eoas: &[Address]) {
         self.order_tracker.eoa_state_changes(
             eoas,
@@ -233,6 +731,11 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             .reorg(orders)
             .into_iter()
             .for_each(|order| {
+                self.order_tracker.record_status(
+                    order.order_hash(),
+                    self.block_number,
+                    OrderStatus::Reorged
+                );
                 self.subscribers
                     .notify_order_subscribers(PoolManagerUpdate::UnfilledOrders(order.clone()));
                 self.validator
@@ -250,13 +753,20 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             .order_tracker
             .filled_orders(orders, &self.order_storage)
             .inspect(|order| {
+                let receipt = self.fill_receipts.remove(&order.order_hash());
                 self.subscribers
                     .notify_order_subscribers(PoolManagerUpdate::FilledOrder(
                         block_number,
-                        order.clone()
+                        order.clone(),
+                        receipt
                     ));
             })
-            .collect();
+            .collect::<Vec<_>>();
+
+        for order in &filled_orders {
+            self.order_tracker
+                .record_status(order.order_hash(), block_number, OrderStatus::Filled);
+        }
 
         self.order_storage
             .add_filled_orders(block_number, filled_orders);
@@ -271,9 +781,20 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         match res {
             OrderValidationResults::Valid(valid) => {
                 let hash = valid.order_hash();
+                let _span = tracing::error_span!("order_indexer", order_hash = %hash).entered();
+                tracing::debug!(is_valid = valid.is_currently_valid.is_none(), "order validated");
                 self.order_tracker.stop_validating(&hash);
+                if self.order_tracker.take_restoring(&hash) {
+                    return Ok(self.finish_restoring(valid));
+                }
+                let replaces = self.order_tracker.take_replacement(&hash);
 
                 if valid.valid_block != self.block_number {
+                    if let Some(old) = replaces {
+                        self.restore_replaced_order(old);
+                    }
+                    // the order is revalidated against the new block, so it isn't terminally
+                    // invalid and no status is recorded for it
                     self.subscribers.try_notify_validation_subscribers(
                         &hash,
                         OrderValidationResults::Invalid {
@@ -283,13 +804,20 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                         }
                     );
 
+                    self.resolve_route_leg(hash, false);
+
                     let peers = self.order_tracker.invalid_verification(hash);
                     return Ok(PoolInnerEvent::BadOrderMessages(peers));
                 }
+                if let Some(trigger) = self.order_tracker.armed_trigger(&hash) {
+                    return Ok(self.arm_order(trigger, valid));
+                }
                 self.subscribers
                     .notify_order_subscribers(PoolManagerUpdate::NewOrder(valid.clone()));
 
                 // check to see if the transaction is parked.
+                self.record_status(hash, Self::validated_status(&valid));
+
                 if let Some(ref error) = valid.is_currently_valid {
                     self.subscribers.try_notify_validation_subscribers(
                         &hash,
@@ -324,22 +852,65 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                     .collect::<Vec<_>>();
 
                 for order in invalided_orders {
+                    self.record_status(
+                        order.order_hash(),
+                        OrderStatus::ParkedByNonce { invalidated_by: hash }
+                    );
                     self.order_tracker.start_validating(order.order_hash());
                     self.validator
                         .validate_order(OrderOrigin::ReValidation, order.order);
                 }
 
+                let replaced = replaces.and_then(|old| {
+                    self.order_tracker.remove_replaced_order(
+                        valid.from(),
+                        &old,
+                        &self.order_storage
+                    )
+                });
+                if let Some(old) = &replaced {
+                    self.record_status(old.order_hash(), OrderStatus::Replaced { by: hash });
+                    self.subscribers
+                        .notify_order_subscribers(PoolManagerUpdate::ReplacedOrder {
+                            old: old.clone(),
+                            new: valid.clone()
+                        });
+                }
+
+                let is_currently_valid = valid.is_currently_valid.is_none();
                 if let Err(e) = self.insert_order(valid) {
                     tracing::error!(%e, "failed to insert valid order");
                 }
 
+                // legs are only propagated together with their route
+                if self.is_pending_route_leg(&hash) {
+                    return Ok(self
+                        .resolve_route_leg(hash, is_currently_valid)
+                        .map_or(PoolInnerEvent::None, PoolInnerEvent::RoutePropagation));
+                }
+                if self.order_storage.route_of(&hash).is_some() {
+                    return Ok(PoolInnerEvent::None);
+                }
+
+                if replaced.is_some() {
+                    return Ok(PoolInnerEvent::ReplacementPropagation(to_propagate));
+                }
                 Ok(PoolInnerEvent::Propagation(to_propagate))
             }
-            this @ OrderValidationResults::Invalid { hash, .. } => {
+            OrderValidationResults::Invalid { hash, error } => {
+                tracing::debug!(order_hash = %hash, %error, "order invalid");
                 self.order_tracker.stop_validating(&hash);
-                self.subscribers
-                    .try_notify_validation_subscribers(&hash, this);
+                self.order_tracker.take_restoring(&hash);
+                if let Some(old) = self.order_tracker.take_replacement(&hash) {
+                    self.restore_replaced_order(old);
+                }
+                self.record_status(hash, OrderStatus::Invalid { error: error.clone() });
+                self.subscribers.try_notify_validation_subscribers(
+                    &hash,
+                    OrderValidationResults::Invalid { hash, error }
+                );
                 self.order_storage.remove_invalid_order(hash);
+                self.resolve_route_leg(hash, false);
                 let peers = self.order_tracker.invalid_verification(hash);
                 Ok(PoolInnerEvent::BadOrderMessages(peers))
             }
@@ -400,9 +971,14 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         &mut self,
         block_number: BlockNumber,
         completed_orders: Vec<B256>,
+        fill_receipts: Vec<FillReceipt>,
         address_changes: Vec<Address>
     ) {
         tracing::info!(%block_number, "starting transition to new block processing");
+        self.fill_receipts = fill_receipts
+            .into_iter()
+            .map(|receipt| (receipt.fill.order_hash, receipt))
+            .collect();
         self.validator
             .on_new_block(block_number, completed_orders, address_changes);
     }
@@ -436,14 +1012,21 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         telemetry_recorder::telemetry_event!(OrderPoolSnapshot::from((block_number, &*self)));
         // clear the invalid orders as they could of become valid.
         self.order_tracker.clear_invalid();
+        self.order_tracker.prune_banned_orders(block_number);
+        self.order_tracker.clear_replacements();
         // deal with filled orders
         self.filled_orders(block_number, &completed_orders);
+        self.fill_receipts.clear();
 
         self.purge_cancelled();
         let expired_orders = self
             .order_tracker
             .remove_expired_orders(block_number, &self.order_storage);
+        for order in &expired_orders {
+            self.record_status(order.order_hash(), OrderStatus::Expired);
+        }
         self.subscribers.notify_expired_orders(&expired_orders);
+        self.prune_routes();
 
         // deal with changed orders
         self.eoa_state_change(&address_changes);
@@ -454,6 +1037,7 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
 
         completed_orders.extend(expired_orders.into_iter().map(|o| o.order_id.hash));
 
+        self.changed_addresses = address_changes.clone();
         self.validator.notify_validation_on_changes(
             block_number,
             completed_orders,
@@ -485,6 +1069,9 @@ where
                     }
                 }
                 OrderValidatorRes::TransitionComplete => {
+                    let changed_addresses = std::mem::take(&mut self.changed_addresses);
+                    self.subscribers
+                        .notify_new_block(self.block_number, changed_addresses);
                     validated.push(PoolInnerEvent::HasTransitionedToNewBlock(self.block_number));
                 }
             }
@@ -496,6 +1083,10 @@ where
 
 pub enum PoolInnerEvent {
     Propagation(AllOrders),
+    /// a order that replaced a order with the same nonce
+    ReplacementPropagation(AllOrders),
+    /// a multi-hop route whose legs are all valid
+    RoutePropagation(RoutedOrder),
     BadOrderMessages(Vec<PeerId>),
     HasTransitionedToNewBlock(u64),
     None
@@ -525,9 +1116,13 @@ mod tests {
     use alloy::{primitives::U256, signers::local::PrivateKeySigner};
     use angstrom_types::{
         contract_bindings::angstrom::Angstrom::PoolKey,
+        contract_payloads::angstrom::OrderFill,
         matching::Ray,
-        orders::OrderId,
-        primitive::{AngstromAddressConfig, AngstromSigner, OrderValidationError},
+        orders::{OrderId, TriggerDirection, TriggerPrice, guaranteed_amount_out},
+        primitive::{
+            AngstromAddressConfig, AngstromSigner, OrderValidationError,
+            UserAccountVerificationError
+        },
         sol_bindings::RespendAvoidanceMethod
     };
     use testing_tools::{
@@ -608,6 +1203,76 @@ mod tests {
         if validity.is_standing { builder.standing() } else { builder.kill_or_fill() }.build()
     }
 
+    /// adds a pool to the indexer, returns its key and id.
+    fn setup_test_pool(indexer: &mut OrderIndexer<MockValidator>) -> (PoolKey, PoolId) {
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+
+        (pool_key, pool_id)
+    }
+
+    /// the order validated at block 1 with the nonce.
+    fn validated_order(
+        order: &AllOrders,
+        pool_id: PoolId,
+        nonce: u64
+    ) -> OrderWithStorageData<AllOrders> {
+        OrderWithStorageData {
+            order: order.clone(),
+            cancel_requested: false,
+            order_id: OrderId {
+                address: order.from(),
+                reuse_avoidance: RespendAvoidanceMethod::Nonce(nonce),
+                hash: order.order_hash(),
+                pool_id,
+                location: OrderLocation::Limit,
+                deadline: order.deadline(),
+                flash_block: None
+            },
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: None,
+            is_valid: true,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        }
+    }
+
+    /// submits the order and hands its validation to the indexer, returns its
+    /// status.
+    fn submit_validated(
+        indexer: &mut OrderIndexer<MockValidator>,
+        valid: OrderWithStorageData<AllOrders>
+    ) -> Option<OrderStatus> {
+        let hash = valid.order_hash();
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, valid.order.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid))
+            .unwrap();
+
+        indexer.order_status(hash)
+    }
+
+    fn statuses(indexer: &OrderIndexer<MockValidator>, order_hash: B256) -> Vec<OrderStatus> {
+        indexer
+            .order_history(order_hash)
+            .into_iter()
+            .map(|event| event.status)
+            .collect()
+    }
+
     #[tokio::test]
     async fn test_expired_orders_handling() {
         let mut indexer = setup_test_indexer();
@@ -778,8 +1443,10 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_network_order_handling() {
-        let mut indexer = setup_test_indexer();
+    async fn test_fill_receipts_reach_subscribers() {
+        let (tx, mut updates) = broadcast::channel(100);
+        let order_storage = Arc::new(OrderStorage::new(&PoolConfig::default()));
+        let mut indexer = OrderIndexer::new(MockValidator::default(), order_storage, 1, tx);
         let from = Address::random();
         let pool_key = PoolKey {
             currency0: Address::random(),
@@ -787,37 +1454,115 @@ mod tests {
             ..Default::default()
         };
         let pool_id = PoolId::from(pool_key);
-
-        let validity = OrderValidity {
-            valid_until: Some(U256::from(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-                    + 3600
-            )),
-            flash_block: None,
-            is_standing: true
-        };
-        let order = create_test_order(from, pool_key, Some(validity), None);
         indexer.new_pool(NewInitializedPool {
             currency_out: pool_key.currency0,
             currency_in:  pool_key.currency1,
             id:           pool_id
         });
 
-        let peer_id = PeerId::random();
-
-        // Submit network order
-        indexer.new_network_order(peer_id, OrderOrigin::External, order.clone());
+        let order = create_test_order(from, pool_key, None, None);
         let order_hash = order.order_hash();
-
-        // Verify peer tracking
-        assert!(
-            indexer
-                .order_tracker
-                .order_hash_to_peer_id
-                .contains_key(&order_hash)
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                cancel_requested: false,
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: None,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+
+        let receipt = FillReceipt {
+            tx_hash: B256::random(),
+            ucp:     Ray::from(U256::from(7)),
+            fill:    OrderFill {
+                order_hash,
+                user: from,
+                is_tob: false,
+                token_in: pool_key.currency0,
+                token_out: pool_key.currency1,
+                amount_in: 900,
+                amount_out: 850,
+                gas_fee_t0: 10,
+                lp_fee_t0: 0
+            }
+        };
+        indexer.start_new_block_processing(2, vec![order_hash], vec![receipt.clone()], vec![from]);
+        indexer.finish_new_block_processing(2, vec![order_hash], vec![from]);
+
+        let filled = std::iter::from_fn(|| updates.try_recv().ok())
+            .find_map(|update| match update {
+                PoolManagerUpdate::FilledOrder(block_number, filled, receipt) => {
+                    Some((block_number, filled.order_hash(), receipt))
+                }
+                _ => None
+            })
+            .expect("filled order notification");
+        assert_eq!(filled, (2, order_hash, Some(receipt)));
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Filled));
+        let filled = indexer.order_history(order_hash).pop().unwrap();
+        assert_eq!((filled.block_number, filled.status), (2, OrderStatus::Filled));
+        // the receipts only live for the block they were landed in
+        assert!(indexer.fill_receipts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_network_order_handling() {
+        let mut indexer = setup_test_indexer();
+        let from = Address::random();
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+
+        let validity = OrderValidity {
+            valid_until: Some(U256::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + 3600
+            )),
+            flash_block: None,
+            is_standing: true
+        };
+        let order = create_test_order(from, pool_key, Some(validity), None);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+
+        let peer_id = PeerId::random();
+
+        // Submit network order
+        indexer.new_network_order(peer_id, OrderOrigin::External, order.clone());
+        let order_hash = order.order_hash();
+
+        // Verify peer tracking
+        assert!(
+            indexer
+                .order_tracker
+                .order_hash_to_peer_id
+                .contains_key(&order_hash)
         );
         assert_eq!(indexer.order_tracker.order_hash_to_peer_id[&order_hash], vec![peer_id]);
 
@@ -1140,6 +1885,69 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_order_history_after_cancel() {
+        let mut indexer = setup_test_indexer();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+
+        let order = create_test_order(from, pool_key, None, Some(signer.clone()));
+        let order_hash = order.order_hash();
+        assert_eq!(indexer.order_status(order_hash), None);
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                cancel_requested: false,
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: None,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Pending));
+
+        let cancel_request =
+            angstrom_types::orders::CancelOrderRequest::new(from, order_hash, &signer);
+        assert!(indexer.cancel_order(&cancel_request));
+
+        // the order left the pool, but we still know what happened to it
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Cancelled));
+        let history = indexer
+            .order_history(order_hash)
+            .into_iter()
+            .map(|event| event.status)
+            .collect::<Vec<_>>();
+        assert_eq!(history, vec![OrderStatus::Pending, OrderStatus::Cancelled]);
+    }
+
     #[tokio::test]
     async fn test_duplicate_order_rejection() {
         let from = Address::random();
@@ -1221,4 +2029,801 @@ mod tests {
 
         // assert!(!indexer.order_tracker.or)
     }
+
+    #[tokio::test]
+    async fn test_replace_order_by_nonce() {
+        init_tracing();
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let (tx, mut updates) = broadcast::channel(100);
+        let order_storage = Arc::new(OrderStorage::new(&PoolConfig::default()));
+        let mut indexer = OrderIndexer::new(MockValidator::default(), order_storage, 1, tx);
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+
+        let build = |amount: u128| {
+            UserOrderBuilder::new()
+                .standing()
+                .asset_in(pool_key.currency0)
+                .asset_out(pool_key.currency1)
+                .amount(amount)
+                .min_price(Ray::from(U256::from(1)))
+                .nonce(1)
+                .signing_key(Some(signer.clone()))
+                .recipient(from)
+                .build()
+        };
+        let valid = |order: &AllOrders| {
+            OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                cancel_requested: false,
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order.order_hash(),
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: None,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            })
+        };
+
+        let old = build(900);
+        let new = build(1000);
+        let (old_hash, new_hash) = (old.order_hash(), new.order_hash());
+
+        // nothing to replace yet
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.replace_rpc_order(OrderOrigin::Local, new.clone(), tx);
+        assert!(matches!(
+            rx.await,
+            Ok(OrderValidationResults::Invalid {
+                error: OrderValidationError::NoOrderToReplace,
+                ..
+            })
+        ));
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, old.clone(), tx);
+        indexer.handle_validated_order(valid(&old)).unwrap();
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.replace_rpc_order(OrderOrigin::Local, new.clone(), tx);
+        // the old order stays until the new one is valid
+        assert!(
+            indexer
+                .order_tracker
+                .order_hash_to_order_id
+                .contains_key(&old_hash)
+        );
+
+        let event = indexer.handle_validated_order(valid(&new)).unwrap();
+        assert!(matches!(event, PoolInnerEvent::ReplacementPropagation(_)));
+        assert!(
+            !indexer
+                .order_tracker
+                .order_hash_to_order_id
+                .contains_key(&old_hash)
+        );
+        assert_eq!(indexer.order_status(old_hash), Some(OrderStatus::Replaced { by: new_hash }));
+        assert_eq!(indexer.order_status(new_hash), Some(OrderStatus::Pending));
+
+        let replaced = std::iter::from_fn(|| updates.try_recv().ok())
+            .find(|update| matches!(update, PoolManagerUpdate::ReplacedOrder { .. }));
+        let Some(PoolManagerUpdate::ReplacedOrder { old, new }) = replaced else {
+            panic!("expected a replaced order update")
+        };
+        assert_eq!(old.order_hash(), old_hash);
+        assert_eq!(new.order_hash(), new_hash);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_replacement_keeps_old_order() {
+        let mut indexer = setup_test_indexer();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+
+        let build = |amount: u128| {
+            UserOrderBuilder::new()
+                .standing()
+                .asset_in(pool_key.currency0)
+                .asset_out(pool_key.currency1)
+                .amount(amount)
+                .min_price(Ray::from(U256::from(1)))
+                .nonce(1)
+                .signing_key(Some(signer.clone()))
+                .recipient(from)
+                .build()
+        };
+        let valid = |order: &AllOrders| OrderWithStorageData {
+            order: order.clone(),
+            cancel_requested: false,
+            order_id: OrderId {
+                address: from,
+                reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                hash: order.order_hash(),
+                pool_id,
+                location: OrderLocation::Limit,
+                deadline: None,
+                flash_block: None
+            },
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: None,
+            is_valid: true,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        };
+        let book_hashes = |indexer: &OrderIndexer<MockValidator>| {
+            indexer
+                .order_storage
+                .get_all_orders_with_parked()
+                .into_all_orders()
+                .into_iter()
+                .map(|o| o.order_hash())
+                .collect::<Vec<_>>()
+        };
+
+        let old = build(900);
+        let new = build(1000);
+        let (old_hash, new_hash) = (old.order_hash(), new.order_hash());
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, old.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid(&old)))
+            .unwrap();
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.replace_rpc_order(OrderOrigin::Local, new.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Invalid {
+                hash:  new_hash,
+                error: OrderValidationError::InvalidSignature
+            })
+            .unwrap();
+
+        // the old order is revalidated without leaving the book
+        assert!(indexer.order_tracker.is_validating(&old_hash));
+        assert_eq!(book_hashes(&indexer), vec![old_hash]);
+
+        let event = indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid(&old)))
+            .unwrap();
+        assert!(matches!(event, PoolInnerEvent::None));
+        assert_eq!(book_hashes(&indexer), vec![old_hash]);
+        assert_eq!(indexer.order_status(old_hash), Some(OrderStatus::Pending));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_order_is_held_until_triggered() {
+        let mut indexer = setup_test_indexer();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+        let validity = OrderValidity { is_standing: true, ..Default::default() };
+        let order = create_test_order(from, pool_key, Some(validity), Some(signer));
+        let order_hash = order.order_hash();
+        let valid = OrderWithStorageData {
+            order: order.clone(),
+            cancel_requested: false,
+            order_id: OrderId {
+                address: from,
+                reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                hash: order_hash,
+                pool_id,
+                location: OrderLocation::Limit,
+                deadline: None,
+                flash_block: None
+            },
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: None,
+            is_valid: true,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        };
+        let trigger = TriggerCondition {
+            pool_id,
+            price: TriggerPrice::Ucp(Ray::from(U256::from(100))),
+            direction: TriggerDirection::Below
+        };
+        let observation = |ucp: u64| PriceObservation {
+            block: 1,
+            pool_id,
+            amm_sqrt_price: Default::default(),
+            ucp: Some(Ray::from(U256::from(ucp)))
+        };
+
+        // flash orders can't be conditional
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let flash = create_test_order(from, pool_key, None, None);
+        indexer.new_conditional_order(OrderOrigin::Local, flash, trigger, tx);
+        assert!(matches!(
+            rx.await,
+            Ok(OrderValidationResults::Invalid {
+                error: OrderValidationError::ConditionalOrderNotStanding,
+                ..
+            })
+        ));
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_conditional_order(OrderOrigin::Local, order.clone(), trigger, tx);
+        let event = indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid.clone()))
+            .unwrap();
+        assert!(matches!(event, PoolInnerEvent::None));
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Armed { trigger }));
+        assert!(indexer.get_all_orders().limit.is_empty());
+        assert_eq!(indexer.conditional_orders(from).len(), 1);
+
+        indexer.on_price_observation(observation(150));
+        assert_eq!(indexer.conditional_orders(from).len(), 1);
+
+        indexer.on_price_observation(observation(90));
+        assert!(indexer.conditional_orders(from).is_empty());
+        assert!(indexer.order_tracker.is_validating(&order_hash));
+
+        // once revalidated it goes into the book like any other order
+        let event = indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid))
+            .unwrap();
+        assert!(matches!(event, PoolInnerEvent::Propagation(_)));
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Pending));
+        let history = indexer
+            .order_history(order_hash)
+            .into_iter()
+            .map(|event| event.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            history,
+            vec![OrderStatus::Armed { trigger }, OrderStatus::Triggered, OrderStatus::Pending]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drop_orders_by_user() {
+        init_tracing();
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let (tx, mut updates) = broadcast::channel(100);
+        let order_storage = Arc::new(OrderStorage::new(&PoolConfig::default()));
+        let mut indexer = OrderIndexer::new(MockValidator::default(), order_storage, 1, tx);
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+        let validity = OrderValidity { is_standing: true, ..Default::default() };
+        let order = create_test_order(from, pool_key, Some(validity), Some(signer));
+        let order_hash = order.order_hash();
+        let deadline = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600;
+        let valid = OrderWithStorageData {
+            order: order.clone(),
+            cancel_requested: false,
+            order_id: OrderId {
+                address: from,
+                reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                hash: order_hash,
+                pool_id,
+                location: OrderLocation::Limit,
+                deadline: Some(U256::from(deadline)),
+                flash_block: None
+            },
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: None,
+            is_valid: true,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        };
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid))
+            .unwrap();
+
+        assert!(
+            indexer
+                .admin_orders(&OrderFilter::User(Address::random()))
+                .is_empty()
+        );
+        assert_eq!(indexer.admin_orders(&OrderFilter::Pool(pool_id)).len(), 1);
+
+        assert_eq!(indexer.drop_orders(&OrderFilter::User(from)), vec![order_hash]);
+        assert!(
+            indexer
+                .admin_orders(&OrderFilter::Hash(order_hash))
+                .is_empty()
+        );
+        assert!(indexer.get_all_orders().limit.is_empty());
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Dropped));
+        assert!(!indexer.revalidate_user(from));
+
+        // peers gossiping the order can't bring it back, also in later blocks
+        assert!(indexer.order_tracker.is_banned(&order_hash));
+        indexer.start_new_block_processing(2, vec![], vec![], vec![]);
+        indexer.finish_new_block_processing(2, vec![], vec![]);
+        assert!(indexer.order_tracker.is_banned(&order_hash));
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order, tx);
+        assert!(matches!(
+            rx.await.unwrap(),
+            OrderValidationResults::Invalid { error: OrderValidationError::DuplicateOrder, .. }
+        ));
+        assert!(!indexer.order_tracker.is_validating(&order_hash));
+        let removals = std::iter::from_fn(|| updates.try_recv().ok())
+            .filter(|update| {
+                matches!(
+                    update,
+                    PoolManagerUpdate::DroppedOrder(_) | PoolManagerUpdate::ExpiredOrder(_)
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            removals.as_slice(),
+            [PoolManagerUpdate::DroppedOrder(dropped)] if dropped.order_hash() == order_hash
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalidated_orders_are_not_reported_as_expired() {
+        init_tracing();
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let (tx, mut updates) = broadcast::channel(100);
+        let order_storage = Arc::new(OrderStorage::new(&PoolConfig::default()));
+        let mut indexer = OrderIndexer::new(MockValidator::default(), order_storage, 1, tx);
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key);
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+        let validity = OrderValidity { is_standing: true, ..Default::default() };
+        let order = create_test_order(from, pool_key, Some(validity), Some(signer));
+        let order_hash = order.order_hash();
+        let valid = OrderWithStorageData {
+            order: order.clone(),
+            cancel_requested: false,
+            order_id: OrderId {
+                address: from,
+                reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                hash: order_hash,
+                pool_id,
+                location: OrderLocation::Limit,
+                deadline: Some(U256::from(deadline)),
+                flash_block: None
+            },
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: None,
+            is_valid: true,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        };
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid))
+            .unwrap();
+
+        // unknown hashes are skipped
+        assert_eq!(indexer.invalidate_orders(&[B256::random(), order_hash]), vec![order_hash]);
+        assert!(indexer.get_all_orders().limit.is_empty());
+        assert_eq!(
+            indexer.order_status(order_hash),
+            Some(OrderStatus::Invalid { error: OrderValidationError::FailedBundleSimulation })
+        );
+
+        // peers gossiping the order can't bring it back, also in later blocks
+        assert!(indexer.order_tracker.is_banned(&order_hash));
+        indexer.start_new_block_processing(2, vec![], vec![], vec![]);
+        indexer.finish_new_block_processing(2, vec![], vec![]);
+        assert!(indexer.order_tracker.is_banned(&order_hash));
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order, tx);
+        assert!(matches!(
+            rx.await.unwrap(),
+            OrderValidationResults::Invalid { error: OrderValidationError::DuplicateOrder, .. }
+        ));
+        let removals = std::iter::from_fn(|| updates.try_recv().ok())
+            .filter(|update| {
+                matches!(
+                    update,
+                    PoolManagerUpdate::InvalidatedOrder(_) | PoolManagerUpdate::ExpiredOrder(_)
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            removals.as_slice(),
+            [PoolManagerUpdate::InvalidatedOrder(invalid)] if invalid.order_hash() == order_hash
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_route_is_linked_once_all_legs_are_valid() {
+        let mut indexer = setup_test_indexer();
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+
+        let [token_a, token_b, token_c] = [(); 3].map(|_| Address::random());
+        let pool = |currency0: Address, currency1: Address| {
+            let pool_id = PoolId::from(PoolKey { currency0, currency1, ..Default::default() });
+            indexer.new_pool(NewInitializedPool {
+                currency_out: currency0,
+                currency_in:  currency1,
+                id:           pool_id
+            });
+            pool_id
+        };
+        let (pool_ab, pool_bc) = (pool(token_a, token_b), pool(token_b, token_c));
+
+        let leg = |asset_in: Address, asset_out: Address, amount: u128, nonce: u64| {
+            UserOrderBuilder::new()
+                .standing()
+                .exact()
+                .exact_in(true)
+                .asset_in(asset_in)
+                .asset_out(asset_out)
+                .amount(amount)
+                .min_price(Ray::scale_to_ray(U256::from(1)))
+                .nonce(nonce)
+                .signing_key(Some(signer.clone()))
+                .recipient(from)
+                .build()
+        };
+        let route = |amount: u128, nonce: u64| {
+            let first = leg(token_a, token_b, amount, nonce);
+            let second = leg(token_b, token_c, guaranteed_amount_out(&first), nonce + 1);
+            RoutedOrder { legs: vec![first, second], min_amount_out: 0 }
+        };
+        let valid = |order: &AllOrders, pool_id: PoolId, nonce: u64| {
+            OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                cancel_requested: false,
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(nonce),
+                    hash: order.order_hash(),
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: false,
+                is_currently_valid: None,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            })
+        };
+
+        let linked = route(1000, 1);
+        let [first, second] = [linked.legs[0].clone(), linked.legs[1].clone()];
+        let (tx, mut rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_route(OrderOrigin::Local, linked.clone(), tx);
+        assert!(indexer.order_tracker.is_validating(&first.order_hash()));
+        assert!(indexer.order_tracker.is_validating(&second.order_hash()));
+
+        // the first leg alone isn't propagated
+        let event = indexer
+            .handle_validated_order(valid(&first, pool_ab, 1))
+            .unwrap();
+        assert!(matches!(event, PoolInnerEvent::None));
+        assert!(rx.try_recv().is_err());
+
+        let event = indexer
+            .handle_validated_order(valid(&second, pool_bc, 2))
+            .unwrap();
+        let PoolInnerEvent::RoutePropagation(propagated) = event else {
+            panic!("expected the route to be propagated")
+        };
+        assert_eq!(propagated, linked);
+        assert_eq!(rx.await.unwrap(), Ok(linked.route_id()));
+        assert_eq!(indexer.order_storage.route_of(&first.order_hash()), Some(linked.clone()));
+        assert_eq!(indexer.get_all_orders().limit.len(), 2);
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_route(OrderOrigin::Local, linked, tx);
+        assert_eq!(rx.await.unwrap(), Err(OrderValidationError::DuplicateOrder));
+
+        // legs aren't taken on their own, neither once linked nor while pending
+        let assert_leg_of_route = |result: OrderValidationResults, leg: &AllOrders| {
+            let OrderValidationResults::Invalid { error, .. } = result else {
+                panic!("expected the leg to be rejected")
+            };
+            let expected =
+                OrderValidationError::from(RouteError::LegOfRoute { leg: leg.order_hash() });
+            assert_eq!(error, expected);
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::External, first.clone(), tx);
+        assert_leg_of_route(rx.await.unwrap(), &first);
+
+        // an invalid leg takes the valid one with it
+        let broken = route(2000, 3);
+        let [first, second] = [broken.legs[0].clone(), broken.legs[1].clone()];
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_route(OrderOrigin::Local, broken, tx);
+
+        let (leg_tx, leg_rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::External, second.clone(), leg_tx);
+        assert_leg_of_route(leg_rx.await.unwrap(), &second);
+        indexer
+            .handle_validated_order(valid(&first, pool_ab, 3))
+            .unwrap();
+        indexer
+            .handle_validated_order(OrderValidationResults::Invalid {
+                hash:  second.order_hash(),
+                error: OrderValidationError::InvalidSignature
+            })
+            .unwrap();
+
+        let error = OrderValidationError::from(RouteError::InvalidLeg { leg: second.order_hash() });
+        assert_eq!(rx.await.unwrap(), Err(error.clone()));
+        assert_eq!(indexer.order_status(first.order_hash()), Some(OrderStatus::Invalid { error }));
+        assert_eq!(indexer.get_all_orders().limit.len(), 2);
+        assert!(
+            indexer
+                .order_storage
+                .route_of(&first.order_hash())
+                .is_none()
+        );
+
+        // every leg is validated on its own, so the user needs to hold the asset in
+        // the middle of the route too. A leg they can't cover is parked, which
+        // fails the route
+        let unfunded = route(3000, 5);
+        let [first, second] = [unfunded.legs[0].clone(), unfunded.legs[1].clone()];
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_route(OrderOrigin::Local, unfunded, tx);
+        indexer
+            .handle_validated_order(valid(&first, pool_ab, 5))
+            .unwrap();
+
+        let OrderValidationResults::Valid(mut parked) = valid(&second, pool_bc, 6) else {
+            unreachable!()
+        };
+        parked.is_currently_valid = Some(UserAccountVerificationError::InsufficientBalance {
+            order_hash: second.order_hash(),
+            token_in:   token_b,
+            amount:     second.amount()
+        });
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(parked))
+            .unwrap();
+
+        let error = OrderValidationError::from(RouteError::InvalidLeg { leg: second.order_hash() });
+        assert_eq!(rx.await.unwrap(), Err(error));
+        assert_eq!(indexer.get_all_orders().limit.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_status_of_blocked_order() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, pool_id) = setup_test_pool(&mut indexer);
+        let order = create_test_order(Address::random(), pool_key, None, None);
+
+        let mut valid = validated_order(&order, pool_id, 1);
+        valid.is_currently_valid = Some(UserAccountVerificationError::InsufficientBalance {
+            order_hash: order.order_hash(),
+            token_in:   pool_key.currency0,
+            amount:     100
+        });
+
+        let blocked = OrderStatus::Blocked {
+            token:           pool_key.currency0,
+            approval_needed: 0,
+            balance_needed:  100
+        };
+        assert_eq!(submit_validated(&mut indexer, valid), Some(blocked.clone()));
+        assert_eq!(statuses(&indexer, order.order_hash()), vec![blocked]);
+    }
+
+    #[tokio::test]
+    async fn test_status_of_order_missing_gas() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, pool_id) = setup_test_pool(&mut indexer);
+        let order = create_test_order(Address::random(), pool_key, None, None);
+
+        let mut valid = validated_order(&order, pool_id, 1);
+        valid.is_currently_valid =
+            Some(UserAccountVerificationError::NotEnoughGas { needed_gas: 20, set_gas: 10 });
+
+        let missing_gas = OrderStatus::MissingGas { needed: 20, max_set: 10 };
+        assert_eq!(submit_validated(&mut indexer, valid), Some(missing_gas.clone()));
+        assert_eq!(statuses(&indexer, order.order_hash()), vec![missing_gas]);
+    }
+
+    #[tokio::test]
+    async fn test_status_of_order_parked_by_nonce() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, pool_id) = setup_test_pool(&mut indexer);
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+        let parked = create_test_order(from, pool_key, None, Some(signer.clone()));
+        let standing = OrderValidity { is_standing: true, ..Default::default() };
+        let parking = create_test_order(from, pool_key, Some(standing), Some(signer));
+
+        submit_validated(&mut indexer, validated_order(&parked, pool_id, 1));
+        let mut valid = validated_order(&parking, pool_id, 1);
+        valid.invalidates = vec![parked.order_hash()];
+        submit_validated(&mut indexer, valid);
+
+        let parked_by_nonce = OrderStatus::ParkedByNonce { invalidated_by: parking.order_hash() };
+        assert_eq!(indexer.order_status(parked.order_hash()), Some(parked_by_nonce.clone()));
+        assert_eq!(
+            statuses(&indexer, parked.order_hash()),
+            vec![OrderStatus::Pending, parked_by_nonce]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_of_reorged_order() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, pool_id) = setup_test_pool(&mut indexer);
+        let from = Address::random();
+        let order = create_test_order(from, pool_key, None, None);
+        let order_hash = order.order_hash();
+
+        submit_validated(&mut indexer, validated_order(&order, pool_id, 1));
+        indexer.start_new_block_processing(2, vec![order_hash], vec![], vec![from]);
+        indexer.finish_new_block_processing(2, vec![order_hash], vec![from]);
+        indexer.reorg(vec![order_hash]);
+
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Reorged));
+        assert_eq!(
+            statuses(&indexer, order_hash),
+            vec![OrderStatus::Pending, OrderStatus::Filled, OrderStatus::Reorged]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_of_expired_order() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, pool_id) = setup_test_pool(&mut indexer);
+        let from = Address::random();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let validity = OrderValidity {
+            valid_until: Some(U256::from(now + 1)),
+            flash_block: None,
+            is_standing: true
+        };
+        let order = create_test_order(from, pool_key, Some(validity), None);
+        let order_hash = order.order_hash();
+
+        submit_validated(&mut indexer, validated_order(&order, pool_id, 1));
+        // the deadline is within the next block
+        indexer.start_new_block_processing(2, vec![], vec![], vec![]);
+        indexer.finish_new_block_processing(2, vec![], vec![]);
+
+        assert_eq!(indexer.order_status(order_hash), Some(OrderStatus::Expired));
+        assert_eq!(
+            statuses(&indexer, order_hash),
+            vec![OrderStatus::Pending, OrderStatus::Expired]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_of_invalid_order() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, _) = setup_test_pool(&mut indexer);
+        let order = create_test_order(Address::random(), pool_key, None, None);
+        let order_hash = order.order_hash();
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order, tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Invalid {
+                hash:  order_hash,
+                error: OrderValidationError::NotEnoughGas
+            })
+            .unwrap();
+
+        let invalid = OrderStatus::Invalid { error: OrderValidationError::NotEnoughGas };
+        assert_eq!(indexer.order_status(order_hash), Some(invalid.clone()));
+        assert_eq!(statuses(&indexer, order_hash), vec![invalid]);
+    }
+
+    #[tokio::test]
+    async fn test_validation_against_a_stale_block_is_not_terminal() {
+        let mut indexer = setup_test_indexer();
+        let (pool_key, pool_id) = setup_test_pool(&mut indexer);
+        let order = create_test_order(Address::random(), pool_key, None, None);
+        let order_hash = order.order_hash();
+
+        // the pool moved on while the order was being validated
+        let mut valid = validated_order(&order, pool_id, 1);
+        valid.valid_block = 0;
+        let status = submit_validated(&mut indexer, valid);
+
+        assert!(!status.is_some_and(|status| status.is_terminal()));
+        assert!(statuses(&indexer, order_hash).is_empty());
+
+        // once validated against the current block it is pending like any other order
+        indexer.start_new_block_processing(2, vec![], vec![], vec![]);
+        indexer.finish_new_block_processing(2, vec![], vec![]);
+        let mut valid = validated_order(&order, pool_id, 1);
+        valid.valid_block = 2;
+        assert_eq!(submit_validated(&mut indexer, valid), Some(OrderStatus::Pending));
+    }
 }
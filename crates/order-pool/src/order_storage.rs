@@ -9,12 +9,16 @@ use std::{
 use alloy::primitives::{B256, BlockNumber, FixedBytes};
 use angstrom_metrics::OrderStorageMetricsWrapper;
 use angstrom_types::{
-    orders::{OrderId, OrderLocation, OrderSet, OrderStatus, UpdatedGas},
+    orders::{
+        OrderId, OrderLocation, OrderSet, OrderStatus, PriceObservation, RoutedOrder,
+        TriggerCondition, UpdatedGas
+    },
     primitive::{NewInitializedPool, PoolId},
     sol_bindings::{
         grouped_orders::{AllOrders, OrderWithStorageData},
         rpc_orders::TopOfBlockOrder
-    }
+    },
+    trace_context::{OrderTraces, TraceContext}
 };
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
@@ -23,6 +27,7 @@ use crate::{
     PoolConfig,
     finalization_pool::FinalizationPool,
     limit::{LimitOrderPool, LimitPoolError},
+    route_book::RouteBook,
     searcher::{SearcherPool, SearcherPoolError}
 };
 
@@ -30,15 +35,20 @@ use crate::{
 #[serde_as]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OrderStorage {
-    pub limit_orders:                Arc<Mutex<LimitOrderPool>>,
-    pub searcher_orders:             Arc<Mutex<SearcherPool>>,
+    pub limit_orders: Arc<Mutex<LimitOrderPool>>,
+    pub searcher_orders: Arc<Mutex<SearcherPool>>,
     pub pending_finalization_orders: Arc<Mutex<FinalizationPool>>,
     /// we store filled order hashes until they are expired time wise to ensure
     /// we don't waste processing power in the validator.
     #[serde_as(as = "Arc<Mutex<HashMap<DisplayFromStr, _>>>")]
-    pub filled_orders:               Arc<Mutex<HashMap<B256, SystemTime>>>,
+    pub filled_orders: Arc<Mutex<HashMap<B256, SystemTime>>>,
+    #[serde(default)]
+    pub routes: Arc<Mutex<RouteBook>>,
+    /// the trace of the orders, only recorded when the traces are exported.
+    #[serde(skip)]
+    pub traces: Arc<Mutex<OrderTraces>>,
     #[serde(skip)]
-    pub metrics:                     OrderStorageMetricsWrapper
+    pub metrics: OrderStorageMetricsWrapper
 }
 
 impl OrderStorage {
@@ -53,6 +63,8 @@ impl OrderStorage {
             limit_orders,
             searcher_orders,
             pending_finalization_orders,
+            routes: Arc::new(Mutex::new(RouteBook::default())),
+            traces: Arc::new(Mutex::new(OrderTraces::default())),
             metrics: OrderStorageMetricsWrapper::new()
         }
     }
@@ -63,12 +75,16 @@ impl OrderStorage {
         let pending_finalization_orders =
             Arc::new(Mutex::new(self.pending_finalization_orders.lock().unwrap().clone()));
         let filled_orders = Arc::new(Mutex::new(self.filled_orders.lock().unwrap().clone()));
+        let routes = Arc::new(Mutex::new(self.routes.lock().unwrap().clone()));
+        let traces = Arc::new(Mutex::new(self.traces.lock().unwrap().clone()));
 
         Self {
             limit_orders,
             pending_finalization_orders,
             searcher_orders,
             filled_orders,
+            routes,
+            traces,
             metrics: OrderStorageMetricsWrapper::new()
         }
     }
@@ -300,6 +316,92 @@ impl OrderStorage {
         Ok(())
     }
 
+    pub fn add_conditional_order(
+        &self,
+        trigger: TriggerCondition,
+        order: OrderWithStorageData<AllOrders>
+    ) {
+        self.limit_orders
+            .lock()
+            .expect("lock poisoned")
+            .add_conditional_order(trigger, order);
+    }
+
+    pub fn take_triggered_orders(
+        &self,
+        observation: &PriceObservation
+    ) -> Vec<OrderWithStorageData<AllOrders>> {
+        self.limit_orders
+            .lock()
+            .expect("lock poisoned")
+            .take_triggered_orders(observation)
+    }
+
+    pub fn get_conditional_orders(
+        &self
+    ) -> Vec<(TriggerCondition, OrderWithStorageData<AllOrders>)> {
+        self.limit_orders
+            .lock()
+            .expect("lock poisoned")
+            .get_conditional_orders()
+    }
+
+    /// links the legs of a route, they have to be in the pool already.
+    pub fn add_route(&self, route: RoutedOrder) -> bool {
+        self.routes.lock().expect("lock poisoned").insert(route)
+    }
+
+    pub fn remove_route(&self, route_id: &B256) -> Option<RoutedOrder> {
+        self.routes.lock().expect("lock poisoned").remove(route_id)
+    }
+
+    pub fn has_route(&self, route_id: &B256) -> bool {
+        self.routes
+            .lock()
+            .expect("lock poisoned")
+            .contains(route_id)
+    }
+
+    pub fn is_route_leg(&self, leg: &B256) -> bool {
+        self.routes.lock().expect("lock poisoned").contains_leg(leg)
+    }
+
+    pub fn route_of(&self, leg: &B256) -> Option<RoutedOrder> {
+        self.routes
+            .lock()
+            .expect("lock poisoned")
+            .route_of(leg)
+            .cloned()
+    }
+
+    pub fn routes(&self) -> Vec<RoutedOrder> {
+        self.routes.lock().expect("lock poisoned").routes()
+    }
+
+    /// records the current span as the trace of the order.
+    pub fn record_trace(&self, order_hash: B256) {
+        self.traces
+            .lock()
+            .expect("lock poisoned")
+            .record_current(order_hash);
+    }
+
+    pub fn trace_of(&self, order_hash: &B256) -> Option<TraceContext> {
+        self.traces
+            .lock()
+            .expect("lock poisoned")
+            .get(order_hash)
+            .cloned()
+    }
+
+    /// makes the span part of the trace of the order, if it has one.
+    pub fn set_trace_parent(&self, span: &tracing::Span, order_hash: &B256) {
+        self.traces
+            .lock()
+            .expect("lock poisoned")
+            .set_parent_of(span, order_hash);
+    }
+
     pub fn add_new_searcher_order(
         &self,
         order: OrderWithStorageData<TopOfBlockOrder>
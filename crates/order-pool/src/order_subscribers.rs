@@ -1,6 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
-use alloy::primitives::B256;
+use alloy::primitives::{Address, B256};
 use angstrom_types::sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData};
 use tokio::sync::oneshot::Sender;
 use validation::order::OrderValidationResults;
@@ -40,19 +40,28 @@ impl OrderSubscriptionTracker {
     pub fn notify_order_subscribers(&mut self, update: PoolManagerUpdate) {
         // if we insert the order and its not a new one, and this isn't the
         // last notification for a order, return
-        let id = update.order_id();
-        if self.order_notification_tracker.contains(&id) && !update.last_notification_for_order() {
-            return;
-        }
+        if let Some(id) = update.order_id() {
+            if self.order_notification_tracker.contains(&id)
+                && !update.last_notification_for_order()
+            {
+                return;
+            }
 
-        // will never be seen again
-        if update.last_notification_for_order() {
-            self.order_notification_tracker.remove(&id);
+            // will never be seen again
+            if update.last_notification_for_order() {
+                self.order_notification_tracker.remove(&id);
+            }
         }
 
         let _ = self.orders_subscriber_tx.send(update);
     }
 
+    pub fn notify_new_block(&mut self, block_number: u64, address_changes: Vec<Address>) {
+        let _ = self
+            .orders_subscriber_tx
+            .send(PoolManagerUpdate::NewBlock { block_number, address_changes });
+    }
+
     pub fn notify_expired_orders(&mut self, orders: &[OrderWithStorageData<AllOrders>]) {
         for order in orders {
             self.order_notification_tracker.remove(&order.order_id.hash);
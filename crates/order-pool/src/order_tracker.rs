@@ -1,11 +1,11 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     time::{Duration, SystemTime, UNIX_EPOCH}
 };
 
 use alloy::primitives::{Address, B256, U256};
 use angstrom_types::{
-    orders::{OrderId, OrderLocation},
+    orders::{OrderEvent, OrderId, OrderLocation, OrderStatus, TriggerCondition},
     primitive::{PeerId, PoolId},
     sol_bindings::{ext::grouped_orders::AllOrders, grouped_orders::OrderWithStorageData}
 };
@@ -13,13 +13,21 @@ use serde_with::{DisplayFromStr, serde_as};
 use validation::order::OrderValidatorHandle;
 
 use crate::{
-    order_indexer::InnerCancelOrderRequest, order_storage::OrderStorage, validator::OrderValidator
+    OrderFilter, order_indexer::InnerCancelOrderRequest, order_storage::OrderStorage,
+    validator::OrderValidator
 };
 
 /// This is used to remove validated orders. During validation
 /// the same check wil be ran but with more accuracy
 const ETH_BLOCK_TIME: Duration = Duration::from_secs(12);
 const MAX_NEW_ORDER_DELAY_PROPAGATION: u64 = 7000;
+/// The max amount of status changes kept per order.
+const MAX_ORDER_HISTORY_EVENTS: usize = 32;
+/// The max amount of orders we keep a history for. The oldest ones are dropped
+/// first.
+const MAX_ORDER_HISTORIES: usize = 100_000;
+/// The max amount of orders a user can replace per block.
+const MAX_REPLACEMENTS_PER_BLOCK: usize = 8;
 
 /// Used as a storage of order hashes to order ids of validated and pending
 /// validation orders.
@@ -40,7 +48,32 @@ pub struct OrderTracker {
     /// Used to protect against late order propagation
     #[serde_as(as = "HashMap<DisplayFromStr, _>")]
     pub(super) cancelled_orders:       HashMap<B256, InnerCancelOrderRequest>,
-    pub(super) is_validating:          HashSet<B256>
+    pub(super) is_validating:          HashSet<B256>,
+    /// status changes of orders, also kept after the order left the pool
+    #[serde(skip)]
+    pub(super) order_history:          HashMap<B256, VecDeque<OrderEvent>>,
+    /// orders with a history, oldest first
+    #[serde(skip)]
+    pub(super) history_queue:          VecDeque<B256>,
+    /// the amount of orders each user replaced this block
+    #[serde(skip)]
+    pub(super) replacements:           HashMap<Address, usize>,
+    /// orders that are being validated as a replacement, to the order they
+    /// replace
+    #[serde(skip)]
+    pub(super) pending_replacements:   HashMap<B256, B256>,
+    /// replaced orders that are revalidated after their replacement turned
+    /// out to be invalid
+    #[serde(skip)]
+    pub(super) restoring_orders:       HashSet<B256>,
+    /// the triggers of conditional orders that haven't been triggered yet
+    #[serde(skip)]
+    pub(super) armed_orders:           HashMap<B256, TriggerCondition>,
+    /// orders that were taken out of the pool for good. unlike the seen
+    /// invalid orders these are kept until the order expires, so that gossip or
+    /// a resubmission doesn't bring them back
+    #[serde(skip)]
+    pub(super) banned_orders:          HashMap<B256, OrderId>
 }
 
 impl OrderTracker {
@@ -53,6 +86,55 @@ impl OrderTracker {
         self.seen_invalid_orders.clear()
     }
 
+    /// starts replacing the order, returning false if the user hit the
+    /// replacement limit for this block.
+    pub fn start_replacement(&mut self, user: Address, new: B256, old: B256) -> bool {
+        let replacements = self.replacements.entry(user).or_default();
+        if *replacements >= MAX_REPLACEMENTS_PER_BLOCK {
+            return false;
+        }
+        *replacements += 1;
+        self.pending_replacements.insert(new, old);
+
+        true
+    }
+
+    /// the order that the validated order replaces, if it is a replacement.
+    pub fn take_replacement(&mut self, new: &B256) -> Option<B256> {
+        self.pending_replacements.remove(new)
+    }
+
+    pub fn start_restoring(&mut self, order: B256) {
+        let _ = self.restoring_orders.insert(order);
+    }
+
+    /// whether the validated order is a replaced order that is being restored.
+    pub fn take_restoring(&mut self, order: &B256) -> bool {
+        self.restoring_orders.remove(order)
+    }
+
+    pub fn clear_replacements(&mut self) {
+        self.replacements.clear();
+    }
+
+    /// removes the order that got replaced from the pool.
+    pub fn remove_replaced_order(
+        &mut self,
+        user: Address,
+        hash: &B256,
+        storage: &OrderStorage
+    ) -> Option<OrderWithStorageData<AllOrders>> {
+        let id = self.order_hash_to_order_id.remove(hash)?;
+        if let Some(orders) = self.address_to_orders.get_mut(&user) {
+            orders.remove(&id);
+        }
+
+        match id.location {
+            OrderLocation::Limit => storage.remove_limit_order(&id),
+            OrderLocation::Searcher => storage.remove_searcher_order(&id)
+        }
+    }
+
     #[inline(always)]
     pub fn is_seen_invalid(&self, order_hash: &B256) -> bool {
         self.seen_invalid_orders.contains(order_hash)
@@ -81,7 +163,100 @@ impl OrderTracker {
     }
 
     pub fn is_duplicate(&self, hash: &B256) -> bool {
-        self.order_hash_to_order_id.contains_key(hash) || self.seen_invalid_orders.contains(hash)
+        self.order_hash_to_order_id.contains_key(hash)
+            || self.seen_invalid_orders.contains(hash)
+            || self.banned_orders.contains_key(hash)
+    }
+
+    /// keeps the order out of the pool until it expires.
+    pub fn ban_order(&mut self, id: OrderId) {
+        self.banned_orders.insert(id.hash, id);
+    }
+
+    pub fn is_banned(&self, hash: &B256) -> bool {
+        self.banned_orders.contains_key(hash)
+    }
+
+    /// forgets the banned orders that can't be included anymore.
+    pub fn prune_banned_orders(&mut self, block_number: u64) {
+        let now = U256::from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        );
+        self.banned_orders.retain(|_, id| {
+            id.deadline.is_some_and(|deadline| deadline > now)
+                || id.flash_block.is_some_and(|block| block > block_number)
+        });
+    }
+
+    /// holds the order until its trigger is hit.
+    pub fn arm_order(&mut self, hash: B256, trigger: TriggerCondition) {
+        self.armed_orders.insert(hash, trigger);
+    }
+
+    pub fn armed_trigger(&self, hash: &B256) -> Option<TriggerCondition> {
+        self.armed_orders.get(hash).copied()
+    }
+
+    pub fn disarm_order(&mut self, hash: &B256) -> Option<TriggerCondition> {
+        self.armed_orders.remove(hash)
+    }
+
+    /// records the new status of the order, ignoring it if the status didn't
+    /// change.
+    pub fn record_status(&mut self, hash: B256, block_number: u64, status: OrderStatus) {
+        if status.is_terminal() {
+            self.armed_orders.remove(&hash);
+        }
+
+        if !self.order_history.contains_key(&hash) {
+            if self.history_queue.len() >= MAX_ORDER_HISTORIES {
+                let oldest = self
+                    .history_queue
+                    .pop_front()
+                    .expect("history queue is full");
+                self.order_history.remove(&oldest);
+            }
+            self.history_queue.push_back(hash);
+        }
+
+        let history = self.order_history.entry(hash).or_default();
+        if history.back().is_some_and(|event| event.status == status) {
+            return;
+        }
+        if history.len() >= MAX_ORDER_HISTORY_EVENTS {
+            history.pop_front();
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        history.push_back(OrderEvent { block_number, timestamp, status });
+    }
+
+    pub fn order_history(&self, hash: &B256) -> Vec<OrderEvent> {
+        self.order_history
+            .get(hash)
+            .map(|history| history.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn last_status(&self, hash: &B256) -> Option<OrderStatus> {
+        self.order_history
+            .get(hash)
+            .and_then(|history| history.back())
+            .map(|event| event.status.clone())
+    }
+
+    pub fn order_ids_matching(&self, filter: &OrderFilter) -> Vec<OrderId> {
+        self.order_hash_to_order_id
+            .values()
+            .filter(|id| filter.matches(id))
+            .copied()
+            .collect()
     }
 
     pub fn handle_pool_removed(&mut self, txes: &[OrderWithStorageData<AllOrders>]) {
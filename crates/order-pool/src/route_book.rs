@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use alloy::primitives::B256;
+use angstrom_types::orders::RoutedOrder;
+use serde::{Deserialize, Serialize};
+
+/// The multi-hop routes whose legs are all valid and in the pool. The legs
+/// themselves are stored like any other order, this only keeps the link
+/// between them so that matching fills all legs of a route or none.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteBook {
+    routes: HashMap<B256, RoutedOrder>,
+    /// leg hash to the id of the route it belongs to
+    legs:   HashMap<B256, B256>
+}
+
+impl RouteBook {
+    /// returns false if the route or one of its legs is already in the book.
+    pub fn insert(&mut self, route: RoutedOrder) -> bool {
+        let route_id = route.route_id();
+        let legs = route.leg_hashes();
+        if self.routes.contains_key(&route_id) || legs.iter().any(|leg| self.legs.contains_key(leg))
+        {
+            return false;
+        }
+
+        self.legs
+            .extend(legs.into_iter().map(|leg| (leg, route_id)));
+        self.routes.insert(route_id, route);
+
+        true
+    }
+
+    pub fn remove(&mut self, route_id: &B256) -> Option<RoutedOrder> {
+        let route = self.routes.remove(route_id)?;
+        for leg in route.leg_hashes() {
+            self.legs.remove(&leg);
+        }
+
+        Some(route)
+    }
+
+    pub fn contains(&self, route_id: &B256) -> bool {
+        self.routes.contains_key(route_id)
+    }
+
+    pub fn contains_leg(&self, leg: &B256) -> bool {
+        self.legs.contains_key(leg)
+    }
+
+    pub fn route_of(&self, leg: &B256) -> Option<&RoutedOrder> {
+        self.routes.get(self.legs.get(leg)?)
+    }
+
+    /// all routes, ordered by their id so that every node hands them to
+    /// matching the same way.
+    pub fn routes(&self) -> Vec<RoutedOrder> {
+        let mut routes = self.routes.iter().collect::<Vec<_>>();
+        routes.sort_unstable_by_key(|(route_id, _)| **route_id);
+
+        routes.into_iter().map(|(_, route)| route.clone()).collect()
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use alloy::primitives::{Address, B256};
 use angstrom_types::{orders::OrderOrigin, sol_bindings::grouped_orders::AllOrders};
 use futures_util::{Future, FutureExt, Stream, StreamExt, stream::FuturesUnordered};
-use tracing::info;
+use tracing::{Instrument, info};
 use validation::order::{OrderValidationResults, OrderValidatorHandle};
 
 type ValidationFuture = Pin<Box<dyn Future<Output = OrderValidationResults> + Send + Sync>>;
@@ -137,8 +137,11 @@ where
         match self {
             Self::RegularProcessing { remaining_futures, validator } => {
                 let val = validator.clone();
-                remaining_futures
-                    .push(Box::pin(async move { val.validate_order(origin, order).await }))
+                // keeps the span of the order, so its validation is traced under it
+                remaining_futures.push(Box::pin(
+                    async move { val.validate_order(origin, order).await }
+                        .instrument(tracing::Span::current())
+                ))
             }
             Self::WaitingForStorageCleanup { waiting_for_new_block, .. } => {
                 waiting_for_new_block.push_back((origin, order));
@@ -0,0 +1,5 @@
+mod orders;
+mod quoting;
+
+pub use orders::*;
+pub use quoting::*;
@@ -1,10 +1,12 @@
 use std::collections::HashSet;
 
 use alloy_primitives::{Address, B256, U256};
-use angstrom_amm_quoter::Slot0Update;
 use angstrom_types::{
-    orders::{CancelOrderRequest, OrderLocation},
-    primitive::PoolId,
+    orders::{
+        CancelOrderRequest, OrderEvent, OrderLocation, RouteHop, RoutedOrder, TriggerCondition
+    },
+    primitive::{PoolId, TokenBalance},
+    quoting::{BookDepth, OrderQuote, Slot0Update},
     sol_bindings::grouped_orders::AllOrders
 };
 use futures::StreamExt;
@@ -14,7 +16,9 @@ use jsonrpsee::{
 };
 use serde::Deserialize;
 
-use crate::types::{CallResult, OrderSubscriptionFilter, OrderSubscriptionKind, PendingOrder};
+use crate::types::{
+    CallResult, ConditionalOrder, OrderSubscriptionFilter, OrderSubscriptionKind, PendingOrder
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GasEstimateResponse {
@@ -30,6 +34,37 @@ pub trait OrderApi {
     #[method(name = "sendOrder")]
     async fn send_order(&self, order: AllOrders) -> RpcResult<CallResult>;
 
+    /// Submit a order that replaces the pending order of the same user and
+    /// nonce. The old order is only removed once the new one is valid.
+    #[method(name = "replaceOrder")]
+    async fn replace_order(&self, order: AllOrders) -> RpcResult<CallResult>;
+
+    /// Submit a standing order that the node holds until the trigger is hit,
+    /// after which it is added to the book. The trigger isn't signed and only
+    /// known to this node.
+    #[method(name = "sendConditionalOrder")]
+    async fn send_conditional_order(
+        &self,
+        order: AllOrders,
+        trigger: TriggerCondition
+    ) -> RpcResult<CallResult>;
+
+    /// Submit a multi-hop route of exact in orders signed by the same user.
+    /// The legs are only matched together, either all of them fill or none.
+    /// Every leg needs the balance and approval of the asset it spends, also
+    /// the ones in the middle of the route.
+    #[method(name = "sendRoutedOrder")]
+    async fn send_routed_order(&self, route: RoutedOrder) -> RpcResult<CallResult>;
+
+    /// The pools a route from `token_in` to `token_out` goes through, fewest
+    /// hops first.
+    #[method(name = "findRoute")]
+    async fn find_route(&self, token_in: Address, token_out: Address) -> RpcResult<Vec<RouteHop>>;
+
+    /// The conditional orders of the user that haven't been triggered yet.
+    #[method(name = "conditionalOrders")]
+    async fn conditional_orders(&self, from: Address) -> RpcResult<Vec<ConditionalOrder>>;
+
     #[method(name = "pendingOrder")]
     async fn pending_order(&self, from: Address) -> RpcResult<Vec<PendingOrder>>;
 
@@ -45,12 +80,37 @@ pub trait OrderApi {
         token_1: Address
     ) -> RpcResult<Result<(U256, u64), String>>;
 
+    /// Simulates the order against the current book and amm without submitting
+    /// it, returning the expected fill, clearing price and price impact.
+    #[method(name = "quoteOrder")]
+    async fn quote_order(&self, order: AllOrders) -> RpcResult<OrderQuote>;
+
+    /// Aggregated bid / ask depth of the pools book and amm, grouped into price
+    /// levels of `tick_grouping` ticks.
+    #[method(name = "bookDepth")]
+    async fn book_depth(
+        &self,
+        pool_id: PoolId,
+        tick_grouping: i32,
+        levels: Option<usize>
+    ) -> RpcResult<BookDepth>;
+
     #[method(name = "orderStatus")]
     async fn order_status(&self, order_hash: B256) -> RpcResult<CallResult>;
 
+    /// Every status change of the order, oldest first. Kept for a bounded
+    /// amount of events and orders.
+    #[method(name = "orderHistory")]
+    async fn order_history(&self, order_hash: B256) -> RpcResult<Vec<OrderEvent>>;
+
     #[method(name = "validNonce")]
     async fn valid_nonce(&self, user: Address) -> RpcResult<u64>;
 
+    /// The users wallet, approval and angstrom balances of the tokens, along
+    /// with how much of them their pending orders committed.
+    #[method(name = "balances")]
+    async fn balances(&self, user: Address, tokens: Vec<Address>) -> RpcResult<Vec<TokenBalance>>;
+
     #[method(name = "ordersByPair")]
     async fn orders_by_pool_id(
         &self,
@@ -65,6 +125,31 @@ pub trait OrderApi {
     )]
     async fn subscribe_amm(&self, pools: HashSet<PoolId>) -> jsonrpsee::core::SubscriptionResult;
 
+    #[subscription(
+        name = "subscribeBookDepth",
+        unsubscribe = "unsubscribeBookDepth",
+        item = BookDepth
+    )]
+    async fn subscribe_book_depth(
+        &self,
+        pool_id: PoolId,
+        tick_grouping: i32,
+        levels: Option<usize>
+    ) -> jsonrpsee::core::SubscriptionResult;
+
+    /// The balances of the tokens whenever they change, starting with the
+    /// current ones.
+    #[subscription(
+        name = "subscribeBalances",
+        unsubscribe = "unsubscribeBalances",
+        item = Vec<TokenBalance>
+    )]
+    async fn subscribe_balances(
+        &self,
+        user: Address,
+        tokens: Vec<Address>
+    ) -> jsonrpsee::core::SubscriptionResult;
+
     #[subscription(
         name = "subscribeOrders",
         unsubscribe = "unsubscribeOrders",
@@ -0,0 +1,5 @@
+//! The order and quoting apis of the `angstrom` namespace and the types they
+//! use, without the node behind them.
+
+pub mod api;
+pub mod types;
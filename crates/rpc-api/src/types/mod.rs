@@ -1,7 +1,10 @@
 pub mod quoting;
 pub mod subscriptions;
 use alloy_primitives::FixedBytes;
-use angstrom_types::{primitive::OrderValidationError, sol_bindings::grouped_orders::AllOrders};
+use angstrom_types::{
+    orders::TriggerCondition, primitive::OrderValidationError,
+    sol_bindings::grouped_orders::AllOrders
+};
 pub use quoting::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -14,6 +17,21 @@ pub struct PendingOrder {
     pub order:    AllOrders
 }
 
+/// a conditional order that is waiting on its trigger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct ConditionalOrder {
+    pub order_id: FixedBytes<32>,
+    pub trigger:  TriggerCondition,
+    pub order:    AllOrders
+}
+
+/// a submitter of bundles and whether it is used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct SubmitterStatus {
+    pub name:    String,
+    pub enabled: bool
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct CallResult {
     pub is_success: bool,
@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
 use alloy_primitives::{Address, B256, FixedBytes};
-use angstrom_types::{consensus::*, sol_bindings::grouped_orders::AllOrders};
+use angstrom_types::{
+    consensus::*, contract_payloads::angstrom::FillReceipt, orders::TriggerCondition,
+    sol_bindings::grouped_orders::AllOrders
+};
 use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
@@ -42,7 +45,17 @@ pub enum OrderSubscriptionKind {
     /// Any new cancelled orders
     CancelledOrders,
     /// Orders that expire.
-    ExpiredOrders
+    ExpiredOrders,
+    /// Orders that the operator dropped from the pool
+    DroppedOrders,
+    /// Orders that made the simulation of a bundle fail
+    InvalidatedOrders,
+    /// Orders that were replaced by a order with the same nonce
+    ReplacedOrders,
+    /// Conditional orders that are held until their trigger is hit
+    ArmedOrders,
+    /// Conditional orders whose trigger was hit
+    TriggeredOrders
 }
 
 impl OrderSubscriptionKind {
@@ -75,8 +88,22 @@ pub enum OrderSubscriptionFilter {
 #[serde(rename_all = "camelCase")]
 pub enum OrderSubscriptionResult {
     NewOrder(AllOrders),
-    FilledOrder(u64, AllOrders),
+    /// the block the order was filled in, the order and what it got out of the
+    /// fill
+    FilledOrder(u64, AllOrders, Option<FillReceipt>),
     UnfilledOrder(AllOrders),
     CancelledOrder(B256),
-    ExpiredOrder(AllOrders)
+    ExpiredOrder(AllOrders),
+    DroppedOrder(B256),
+    InvalidatedOrder(B256),
+    /// the hash of the replaced order and the order that replaced it
+    ReplacedOrder {
+        old: B256,
+        new: AllOrders
+    },
+    ArmedOrder {
+        trigger: TriggerCondition,
+        order:   AllOrders
+    },
+    TriggeredOrder(AllOrders)
 }
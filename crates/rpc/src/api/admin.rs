@@ -0,0 +1,59 @@
+use alloy_primitives::{Address, B256};
+use angstrom_network::StromPeerInfo;
+use angstrom_types::{
+    primitive::PeerId,
+    sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
+};
+use consensus::{ConsensusDataWithBlock, ConsensusTiming, ConsensusTimingConfig};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use order_pool::OrderFilter;
+
+use crate::types::SubmitterStatus;
+
+/// Operator only api, this is served on its own port and should never be
+/// exposed publicly.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+#[async_trait::async_trait]
+pub trait AdminApi {
+    /// the orders in the pool matching the filter, including parked orders.
+    #[method(name = "orders")]
+    async fn orders(&self, filter: OrderFilter) -> RpcResult<Vec<OrderWithStorageData<AllOrders>>>;
+
+    /// removes the orders matching the filter from the pool.
+    #[method(name = "dropOrders")]
+    async fn drop_orders(&self, filter: OrderFilter) -> RpcResult<Vec<B256>>;
+
+    /// revalidates all orders of the user against their current state.
+    #[method(name = "revalidateUser")]
+    async fn revalidate_user(&self, user: Address) -> RpcResult<bool>;
+
+    #[method(name = "peers")]
+    async fn peers(&self) -> RpcResult<Vec<StromPeerInfo>>;
+
+    #[method(name = "banPeer")]
+    async fn ban_peer(&self, peer_id: PeerId) -> RpcResult<()>;
+
+    #[method(name = "unbanPeer")]
+    async fn unban_peer(&self, peer_id: PeerId) -> RpcResult<()>;
+
+    /// the telemetry snapshot of the block, defaults to the latest block.
+    #[method(name = "blockLog")]
+    async fn block_log(&self, block: Option<u64>) -> RpcResult<Option<serde_json::Value>>;
+
+    #[method(name = "submitters")]
+    async fn submitters(&self) -> RpcResult<Vec<SubmitterStatus>>;
+
+    #[method(name = "setSubmitterEnabled")]
+    async fn set_submitter_enabled(&self, name: String, enabled: bool) -> RpcResult<()>;
+
+    #[method(name = "consensusTiming")]
+    async fn consensus_timing(&self) -> RpcResult<ConsensusDataWithBlock<ConsensusTiming>>;
+
+    /// takes effect from the next round.
+    #[method(name = "setConsensusTiming")]
+    async fn set_consensus_timing(
+        &self,
+        timing: ConsensusTimingConfig
+    ) -> RpcResult<ConsensusDataWithBlock<ConsensusTiming>>;
+}
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 use alloy_primitives::Address;
-use consensus::{AngstromValidator, ConsensusDataWithBlock, ConsensusTimingConfig};
+use consensus::{AngstromValidator, ConsensusDataWithBlock, ConsensusTiming};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "consensus"))]
@@ -18,8 +18,10 @@ pub trait ConsensusApi {
     #[method(name = "getCurrentLeader")]
     async fn get_current_leader(&self) -> RpcResult<ConsensusDataWithBlock<Address>>;
 
+    /// the timing config along with when the pre-proposal triggers and the
+    /// round history that was learned from.
     #[method(name = "getTiming")]
-    async fn get_timing(&self) -> RpcResult<ConsensusDataWithBlock<ConsensusTimingConfig>>;
+    async fn get_timing(&self) -> RpcResult<ConsensusDataWithBlock<ConsensusTiming>>;
 
     #[method(name = "isRoundClosed")]
     async fn is_round_closed(&self) -> RpcResult<ConsensusDataWithBlock<bool>>;
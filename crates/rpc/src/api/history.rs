@@ -0,0 +1,37 @@
+use alloy_primitives::Address;
+use angstrom_eth::{
+    indexer::{ClearingRecord, FillRecord},
+    rewards::{PositionKey, PositionRewards}
+};
+use angstrom_types::primitive::PoolId;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "angstrom"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "angstrom"))]
+#[async_trait::async_trait]
+pub trait HistoryApi {
+    /// The clearing of the pool for every bundle that landed in the inclusive
+    /// block range.
+    #[method(name = "clearingHistory")]
+    async fn clearing_history(
+        &self,
+        pool_id: PoolId,
+        from_block: u64,
+        to_block: u64
+    ) -> RpcResult<Vec<ClearingRecord>>;
+
+    /// All fills of orders signed by the user.
+    #[method(name = "userFills")]
+    async fn user_fills(&self, user: Address) -> RpcResult<Vec<FillRecord>>;
+
+    /// The rewards the position accrued from bundle donations in the inclusive
+    /// block range. The pool can be either the angstrom or the uniswap pool id.
+    #[method(name = "positionRewards")]
+    async fn position_rewards(
+        &self,
+        pool_id: PoolId,
+        position: PositionKey,
+        from_block: u64,
+        to_block: u64
+    ) -> RpcResult<PositionRewards>;
+}
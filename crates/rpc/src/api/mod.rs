@@ -1,7 +1,8 @@
+mod admin;
 mod consensus;
-mod orders;
-mod quoting;
+mod history;
 
+pub use admin::*;
+pub use angstrom_rpc_api::api::*;
 pub use consensus::*;
-pub use orders::*;
-pub use quoting::*;
+pub use history::*;
@@ -0,0 +1,232 @@
+use alloy_primitives::{Address, B256};
+use angstrom_network::{StromNetworkHandle, StromPeerInfo};
+use angstrom_types::{
+    primitive::PeerId,
+    sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData},
+    submission::SubmitterToggles
+};
+use consensus::{
+    ConsensusDataWithBlock, ConsensusHandle, ConsensusTiming, ConsensusTimingConfig,
+    InvalidTimingConfig
+};
+use jsonrpsee::core::RpcResult;
+use order_pool::{OrderFilter, OrderPoolAdminHandle};
+
+use crate::{
+    api::AdminApiServer,
+    impls::{invalid_params_rpc_err, rpc_err},
+    types::SubmitterStatus
+};
+
+pub struct AdminApi<OrderPool, Consensus> {
+    pool:       OrderPool,
+    consensus:  Consensus,
+    network:    StromNetworkHandle,
+    submitters: SubmitterToggles
+}
+
+impl<OrderPool, Consensus> AdminApi<OrderPool, Consensus> {
+    pub fn new(
+        pool: OrderPool,
+        consensus: Consensus,
+        network: StromNetworkHandle,
+        submitters: SubmitterToggles
+    ) -> Self {
+        Self { pool, consensus, network, submitters }
+    }
+}
+
+#[async_trait::async_trait]
+impl<OrderPool, Consensus> AdminApiServer for AdminApi<OrderPool, Consensus>
+where
+    OrderPool: OrderPoolAdminHandle,
+    Consensus: ConsensusHandle
+{
+    async fn orders(&self, filter: OrderFilter) -> RpcResult<Vec<OrderWithStorageData<AllOrders>>> {
+        Ok(self.pool.admin_orders(filter).await)
+    }
+
+    async fn drop_orders(&self, filter: OrderFilter) -> RpcResult<Vec<B256>> {
+        Ok(self.pool.drop_orders(filter).await)
+    }
+
+    async fn revalidate_user(&self, user: Address) -> RpcResult<bool> {
+        Ok(self.pool.revalidate_user(user).await)
+    }
+
+    async fn peers(&self) -> RpcResult<Vec<StromPeerInfo>> {
+        self.network
+            .peers()
+            .await
+            .map_err(|e| internal_rpc_err(e.to_string()))
+    }
+
+    async fn ban_peer(&self, peer_id: PeerId) -> RpcResult<()> {
+        self.network.ban_peer(peer_id);
+        Ok(())
+    }
+
+    async fn unban_peer(&self, peer_id: PeerId) -> RpcResult<()> {
+        self.network.unban_peer(peer_id);
+        Ok(())
+    }
+
+    async fn block_log(&self, block: Option<u64>) -> RpcResult<Option<serde_json::Value>> {
+        telemetry_recorder::request_block_log(block)
+            .await
+            .map_err(|e| internal_rpc_err(e.to_string()))
+    }
+
+    async fn submitters(&self) -> RpcResult<Vec<SubmitterStatus>> {
+        Ok(self
+            .submitters
+            .all()
+            .into_iter()
+            .map(|(name, enabled)| SubmitterStatus { name: name.to_string(), enabled })
+            .collect())
+    }
+
+    async fn set_submitter_enabled(&self, name: String, enabled: bool) -> RpcResult<()> {
+        if !self.submitters.set_enabled(&name, enabled) {
+            return Err(invalid_params_rpc_err(format!("unknown submitter {name}")));
+        }
+        tracing::info!(%name, enabled, "toggled submitter");
+
+        Ok(())
+    }
+
+    async fn consensus_timing(&self) -> RpcResult<ConsensusDataWithBlock<ConsensusTiming>> {
+        self.consensus
+            .timings()
+            .await
+            .map_err(|e| internal_rpc_err(e.to_string()))
+    }
+
+    async fn set_consensus_timing(
+        &self,
+        timing: ConsensusTimingConfig
+    ) -> RpcResult<ConsensusDataWithBlock<ConsensusTiming>> {
+        self.consensus.set_timings(timing).await.map_err(|e| {
+            if e.is::<InvalidTimingConfig>() {
+                invalid_params_rpc_err(e.to_string())
+            } else {
+                internal_rpc_err(e.to_string())
+            }
+        })
+    }
+}
+
+fn internal_rpc_err(msg: impl Into<String>) -> jsonrpsee::types::ErrorObjectOwned {
+    rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, msg, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future, future::Future};
+
+    use angstrom_network::StromNetworkHandleMsg;
+    use consensus::{ConsensusHandler, ConsensusRequest, rounds::TimingDecision};
+    use jsonrpsee::types::error::INVALID_PARAMS_CODE;
+    use reth_metrics::common::mpsc::UnboundedMeteredSender;
+    use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct MockOrderPool;
+
+    impl OrderPoolAdminHandle for MockOrderPool {
+        fn admin_orders(
+            &self,
+            _: OrderFilter
+        ) -> impl Future<Output = Vec<OrderWithStorageData<AllOrders>>> + Send {
+            future::ready(vec![])
+        }
+
+        fn drop_orders(&self, _: OrderFilter) -> impl Future<Output = Vec<B256>> + Send {
+            future::ready(vec![])
+        }
+
+        fn revalidate_user(&self, _: Address) -> impl Future<Output = bool> + Send {
+            future::ready(false)
+        }
+    }
+
+    fn setup_admin_api() -> (
+        AdminApi<MockOrderPool, ConsensusHandler>,
+        UnboundedReceiver<ConsensusRequest>,
+        UnboundedReceiver<StromNetworkHandleMsg>
+    ) {
+        let (consensus_tx, consensus_rx) = unbounded_channel();
+        let (network_tx, network_rx) = unbounded_channel();
+        let network = StromNetworkHandle::new(
+            Default::default(),
+            UnboundedMeteredSender::new(network_tx, "admin test")
+        );
+        let api = AdminApi::new(
+            MockOrderPool,
+            ConsensusHandler(consensus_tx),
+            network,
+            SubmitterToggles::default()
+        );
+
+        (api, consensus_rx, network_rx)
+    }
+
+    #[tokio::test]
+    async fn test_set_consensus_timing() {
+        let (api, mut consensus_rx, _) = setup_admin_api();
+
+        let invalid = ConsensusTimingConfig {
+            min_wait_duration_ms: 9_000,
+            max_wait_duration_ms: 8_000,
+            ..Default::default()
+        };
+        let err = api.set_consensus_timing(invalid).await.unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS_CODE);
+        // never reaches consensus
+        assert!(consensus_rx.try_recv().is_err());
+
+        let valid = ConsensusTimingConfig { min_wait_duration_ms: 7_000, ..Default::default() };
+        tokio::spawn(async move {
+            let Some(ConsensusRequest::SetTiming(config, tx)) = consensus_rx.recv().await else {
+                panic!("expected the timing to be set")
+            };
+            let _ = tx.send(ConsensusDataWithBlock {
+                data:  ConsensusTiming { config, controller: TimingDecision::default() },
+                block: 1
+            });
+        });
+        let timing = api.set_consensus_timing(valid).await.unwrap();
+        assert_eq!(timing.data.config.min_wait_duration_ms, 7_000);
+    }
+
+    #[tokio::test]
+    async fn test_set_unknown_submitter() {
+        let (api, ..) = setup_admin_api();
+
+        let err = api
+            .set_submitter_enabled("nope".to_string(), false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS_CODE);
+        assert!(api.submitters().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ban_and_unban_peer() {
+        let (api, _, mut network_rx) = setup_admin_api();
+        let peer = PeerId::random();
+
+        api.ban_peer(peer).await.unwrap();
+        assert!(
+            matches!(network_rx.try_recv(), Ok(StromNetworkHandleMsg::BanPeer(p)) if p == peer)
+        );
+
+        api.unban_peer(peer).await.unwrap();
+        assert!(matches!(
+            network_rx.try_recv(),
+            Ok(StromNetworkHandleMsg::UnbanPeer(p)) if p == peer
+        ));
+    }
+}
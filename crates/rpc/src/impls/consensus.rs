@@ -1,9 +1,7 @@
 use std::collections::HashSet;
 
 use alloy_primitives::Address;
-use consensus::{
-    AngstromValidator, ConsensusDataWithBlock, ConsensusHandle, ConsensusTimingConfig
-};
+use consensus::{AngstromValidator, ConsensusDataWithBlock, ConsensusHandle, ConsensusTiming};
 use futures::StreamExt;
 use jsonrpsee::{
     PendingSubscriptionSink, SubscriptionMessage,
@@ -39,7 +37,7 @@ where
             .map_err(|_| ErrorObjectOwned::from(ErrorCode::from(-1)))?)
     }
 
-    async fn get_timing(&self) -> RpcResult<ConsensusDataWithBlock<ConsensusTimingConfig>> {
+    async fn get_timing(&self) -> RpcResult<ConsensusDataWithBlock<ConsensusTiming>> {
         Ok(self
             .consensus
             .timings()
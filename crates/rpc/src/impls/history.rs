@@ -0,0 +1,73 @@
+use alloy_primitives::Address;
+use angstrom_eth::{
+    indexer::{BundleIndexer, ClearingRecord, FillRecord},
+    rewards::{PositionKey, PositionRewards, RewardsLedger}
+};
+use angstrom_types::primitive::PoolId;
+use jsonrpsee::core::RpcResult;
+
+use crate::{api::HistoryApiServer, impls::invalid_params_rpc_err};
+
+/// the max amount of blocks that can be queried at once.
+pub const MAX_HISTORY_BLOCK_RANGE: u64 = 50_000;
+
+pub struct HistoryApi {
+    indexer: BundleIndexer,
+    rewards: RewardsLedger
+}
+
+impl HistoryApi {
+    pub fn new(indexer: BundleIndexer, rewards: RewardsLedger) -> Self {
+        Self { indexer, rewards }
+    }
+}
+
+fn validate_block_range(from_block: u64, to_block: u64) -> RpcResult<()> {
+    if from_block > to_block {
+        return Err(invalid_params_rpc_err("fromBlock is after toBlock"));
+    }
+    if to_block - from_block > MAX_HISTORY_BLOCK_RANGE {
+        return Err(invalid_params_rpc_err(format!(
+            "block range is larger than {MAX_HISTORY_BLOCK_RANGE} blocks"
+        )));
+    }
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl HistoryApiServer for HistoryApi {
+    async fn clearing_history(
+        &self,
+        pool_id: PoolId,
+        from_block: u64,
+        to_block: u64
+    ) -> RpcResult<Vec<ClearingRecord>> {
+        validate_block_range(from_block, to_block)?;
+
+        Ok(self
+            .indexer
+            .clearing_history(pool_id, from_block..=to_block))
+    }
+
+    async fn user_fills(&self, user: Address) -> RpcResult<Vec<FillRecord>> {
+        Ok(self.indexer.user_fills(user))
+    }
+
+    async fn position_rewards(
+        &self,
+        pool_id: PoolId,
+        position: PositionKey,
+        from_block: u64,
+        to_block: u64
+    ) -> RpcResult<PositionRewards> {
+        validate_block_range(from_block, to_block)?;
+        if position.tick_lower >= position.tick_upper {
+            return Err(invalid_params_rpc_err("tickLower must be below tickUpper"));
+        }
+
+        Ok(self
+            .rewards
+            .position_rewards(pool_id, position, from_block..=to_block))
+    }
+}
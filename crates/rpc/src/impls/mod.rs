@@ -1,7 +1,11 @@
+mod admin;
 mod consensus;
+mod history;
 mod orders;
 mod quoting;
 
+pub use admin::*;
 pub use consensus::*;
+pub use history::*;
 pub use orders::*;
 pub use quoting::*;
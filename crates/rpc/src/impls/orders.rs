@@ -1,23 +1,30 @@
 use std::collections::HashSet;
 
 use alloy_primitives::{Address, B256, U256};
-use angstrom_amm_quoter::AngstromBookQuoter;
+use angstrom_amm_quoter::{
+    AngstromBookQuoter, BookDepth, BookDepthRequest, DEFAULT_DEPTH_LEVELS, OrderQuote
+};
 use angstrom_types::{
-    orders::{CancelOrderRequest, OrderLocation, OrderOrigin, OrderStatus},
-    primitive::PoolId,
+    orders::{
+        CancelOrderRequest, MAX_ROUTE_HOPS, OrderEvent, OrderLocation, OrderOrigin, OrderStatus,
+        RouteError, RouteHop, RoutedOrder, TriggerCondition
+    },
+    primitive::{PoolId, TokenBalance},
     sol_bindings::{RawPoolOrder, grouped_orders::AllOrders}
 };
 use futures::StreamExt;
 use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage, core::RpcResult};
 use order_pool::{OrderPoolHandle, PoolManagerUpdate};
 use reth_tasks::TaskSpawner;
-use validation::order::OrderValidatorHandle;
+use tokio_stream::wrappers::BroadcastStream;
+use tracing::Instrument;
+use validation::order::{MAX_BALANCE_TOKENS, OrderValidatorHandle};
 
 use crate::{
     api::OrderApiServer,
     types::{
-        CallResult, OrderSubscriptionFilter, OrderSubscriptionKind, OrderSubscriptionResult,
-        PendingOrder
+        CallResult, ConditionalOrder, OrderSubscriptionFilter, OrderSubscriptionKind,
+        OrderSubscriptionResult, PendingOrder
     }
 };
 
@@ -49,12 +56,82 @@ where
     Validator: OrderValidatorHandle
 {
     async fn send_order(&self, order: AllOrders) -> RpcResult<CallResult> {
-        match self.pool.new_order(OrderOrigin::External, order).await {
+        let span = tracing::error_span!("rpc", order_hash = %order.order_hash());
+        match self
+            .pool
+            .new_order(OrderOrigin::External, order)
+            .instrument(span)
+            .await
+        {
+            Ok(v) => Ok(CallResult::from_success(v)),
+            Err(e) => Ok(e.into())
+        }
+    }
+
+    async fn replace_order(&self, order: AllOrders) -> RpcResult<CallResult> {
+        let span = tracing::error_span!("rpc", order_hash = %order.order_hash());
+        match self
+            .pool
+            .replace_order(OrderOrigin::External, order)
+            .instrument(span)
+            .await
+        {
+            Ok(v) => Ok(CallResult::from_success(v)),
+            Err(e) => Ok(e.into())
+        }
+    }
+
+    async fn send_conditional_order(
+        &self,
+        order: AllOrders,
+        trigger: TriggerCondition
+    ) -> RpcResult<CallResult> {
+        let span = tracing::error_span!("rpc", order_hash = %order.order_hash());
+        match self
+            .pool
+            .new_conditional_order(OrderOrigin::External, order, trigger)
+            .instrument(span)
+            .await
+        {
+            Ok(v) => Ok(CallResult::from_success(v)),
+            Err(e) => Ok(e.into())
+        }
+    }
+
+    async fn send_routed_order(&self, route: RoutedOrder) -> RpcResult<CallResult> {
+        let span = tracing::error_span!("rpc", route_id = %route.route_id());
+        match self
+            .pool
+            .new_routed_order(OrderOrigin::External, route)
+            .instrument(span)
+            .await
+        {
             Ok(v) => Ok(CallResult::from_success(v)),
             Err(e) => Ok(e.into())
         }
     }
 
+    async fn find_route(&self, token_in: Address, token_out: Address) -> RpcResult<Vec<RouteHop>> {
+        self.validator
+            .find_route(token_in, token_out, MAX_ROUTE_HOPS)
+            .await
+            .ok_or_else(|| invalid_params_rpc_err(RouteError::NoRoute.to_string()))
+    }
+
+    async fn conditional_orders(&self, from: Address) -> RpcResult<Vec<ConditionalOrder>> {
+        Ok(self
+            .pool
+            .conditional_orders(from)
+            .await
+            .into_iter()
+            .map(|(trigger, order)| ConditionalOrder {
+                order_id: order.order_hash(),
+                trigger,
+                order
+            })
+            .collect())
+    }
+
     async fn pending_order(&self, from: Address) -> RpcResult<Vec<PendingOrder>> {
         Ok(self
             .pool
@@ -66,7 +143,8 @@ where
     }
 
     async fn cancel_order(&self, request: CancelOrderRequest) -> RpcResult<bool> {
-        Ok(self.pool.cancel_order(request).await)
+        let span = tracing::error_span!("rpc", order_hash = %request.order_id);
+        Ok(self.pool.cancel_order(request).instrument(span).await)
     }
 
     async fn estimate_gas(
@@ -83,6 +161,48 @@ where
         Ok(res)
     }
 
+    async fn quote_order(&self, order: AllOrders) -> RpcResult<OrderQuote> {
+        if order.is_tob() {
+            return Err(invalid_params_rpc_err("only book orders can be quoted"));
+        }
+
+        let (mut token_0, mut token_1) = (order.token_in(), order.token_out());
+        if token_0 > token_1 {
+            std::mem::swap(&mut token_0, &mut token_1);
+        }
+        let (gas_token_0, _) = self
+            .validator
+            .estimate_gas(true, order.use_internal(), token_0, token_1)
+            .await
+            .map_err(OrderApiError::GasEstimationError)?;
+
+        self.amm_quoter
+            .quote_order(order, gas_token_0)
+            .await
+            .map_err(|e| invalid_params_rpc_err(e.to_string()))
+    }
+
+    async fn book_depth(
+        &self,
+        pool_id: PoolId,
+        tick_grouping: i32,
+        levels: Option<usize>
+    ) -> RpcResult<BookDepth> {
+        let request = BookDepthRequest {
+            pool_id,
+            tick_grouping,
+            levels: levels.unwrap_or(DEFAULT_DEPTH_LEVELS)
+        };
+        request
+            .validate()
+            .map_err(|e| invalid_params_rpc_err(e.to_string()))?;
+
+        self.amm_quoter
+            .book_depth(request)
+            .await
+            .map_err(|e| invalid_params_rpc_err(e.to_string()))
+    }
+
     async fn order_status(&self, order_hash: B256) -> RpcResult<CallResult> {
         let status = self
             .pool
@@ -92,10 +212,23 @@ where
         Ok(CallResult::from_success(status))
     }
 
+    async fn order_history(&self, order_hash: B256) -> RpcResult<Vec<OrderEvent>> {
+        Ok(self.pool.fetch_order_history(order_hash).await)
+    }
+
     async fn valid_nonce(&self, user: Address) -> RpcResult<u64> {
         Ok(self.validator.valid_nonce_for_user(user).await)
     }
 
+    async fn balances(&self, user: Address, tokens: Vec<Address>) -> RpcResult<Vec<TokenBalance>> {
+        check_balance_tokens(&tokens)?;
+
+        self.validator
+            .balances(user, tokens)
+            .await
+            .map_err(|e| rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, e, None))
+    }
+
     async fn orders_by_pool_id(
         &self,
         pool_id: PoolId,
@@ -134,6 +267,108 @@ where
         Ok(())
     }
 
+    async fn subscribe_book_depth(
+        &self,
+        pending: PendingSubscriptionSink,
+        pool_id: PoolId,
+        tick_grouping: i32,
+        levels: Option<usize>
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let request = BookDepthRequest {
+            pool_id,
+            tick_grouping,
+            levels: levels.unwrap_or(DEFAULT_DEPTH_LEVELS)
+        };
+        if let Err(e) = request.validate() {
+            pending.reject(invalid_params_rpc_err(e.to_string())).await;
+            return Ok(());
+        }
+
+        let sink = pending.accept().await?;
+        let mut subscription = self.amm_quoter.subscribe_to_book_depth(request).await;
+
+        self.task_spawner.spawn(Box::pin(async move {
+            while let Some(depth) = subscription.next().await {
+                if sink.is_closed() {
+                    break;
+                }
+
+                match SubscriptionMessage::new(sink.method_name(), sink.subscription_id(), &depth) {
+                    Ok(message) => {
+                        if sink.send(message).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to serialize subscription message: {:?}", e);
+                    }
+                }
+            }
+        }));
+
+        Ok(())
+    }
+
+    async fn subscribe_balances(
+        &self,
+        pending: PendingSubscriptionSink,
+        user: Address,
+        tokens: Vec<Address>
+    ) -> jsonrpsee::core::SubscriptionResult {
+        if let Err(e) = check_balance_tokens(&tokens) {
+            pending.reject(e).await;
+            return Ok(());
+        }
+
+        let sink = pending.accept().await?;
+        let mut updates = self.pool.subscribe_orders();
+        let validator = self.validator.clone();
+
+        self.task_spawner.spawn(Box::pin(async move {
+            let mut last_sent = None;
+            let mut orders_changed = false;
+
+            loop {
+                match validator.balances(user, tokens.clone()).await {
+                    Ok(balances) if last_sent.as_ref() != Some(&balances) => {
+                        match SubscriptionMessage::new(
+                            sink.method_name(),
+                            sink.subscription_id(),
+                            &balances
+                        ) {
+                            Ok(message) => {
+                                if sink.send(message).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!(
+                                    "Failed to serialize subscription message: {:?}",
+                                    e
+                                );
+                            }
+                        }
+                        last_sent = Some(balances);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!(%user, "failed to fetch balances: {e}");
+                    }
+                }
+
+                if !next_balance_change(&mut updates, user, &mut orders_changed).await {
+                    return;
+                }
+
+                if sink.is_closed() {
+                    break;
+                }
+            }
+        }));
+
+        Ok(())
+    }
+
     async fn subscribe_orders(
         &self,
         pending: PendingSubscriptionSink,
@@ -195,6 +430,45 @@ impl From<OrderApiError> for jsonrpsee::types::ErrorObjectOwned {
     }
 }
 
+fn check_balance_tokens(tokens: &[Address]) -> RpcResult<()> {
+    if tokens.len() > MAX_BALANCE_TOKENS {
+        return Err(invalid_params_rpc_err(format!(
+            "at most {MAX_BALANCE_TOKENS} tokens can be requested at once"
+        )));
+    }
+
+    Ok(())
+}
+
+/// waits for the next update that can change the balances of the user, false
+/// once there are no more updates. The fills of the users orders only show up
+/// in the balances once their block is processed, so the block after any
+/// update of their orders counts as well.
+async fn next_balance_change(
+    updates: &mut BroadcastStream<PoolManagerUpdate>,
+    user: Address,
+    orders_changed: &mut bool
+) -> bool {
+    loop {
+        match updates.next().await {
+            Some(Ok(PoolManagerUpdate::NewBlock { address_changes, .. })) => {
+                if std::mem::take(orders_changed) || address_changes.contains(&user) {
+                    return true;
+                }
+            }
+            Some(Ok(update)) => {
+                if update.user() == Some(user) {
+                    *orders_changed = true;
+                    return true;
+                }
+            }
+            // missed updates, refetch to be safe
+            Some(Err(_)) => return true,
+            None => return false
+        }
+    }
+}
+
 pub fn invalid_params_rpc_err(msg: impl Into<String>) -> jsonrpsee::types::ErrorObjectOwned {
     rpc_err(jsonrpsee::types::error::INVALID_PARAMS_CODE, msg, None)
 }
@@ -254,11 +528,11 @@ impl OrderFilterMatching for PoolManagerUpdate {
             {
                 Some(OrderSubscriptionResult::NewOrder(order.order))
             }
-            PoolManagerUpdate::FilledOrder(block, order)
+            PoolManagerUpdate::FilledOrder(block, order, receipt)
                 if kind.contains(&OrderSubscriptionKind::FilledOrders)
                     && matches_all_filters(filter, order.pool_id, order.from(), order.is_tob()) =>
             {
-                Some(OrderSubscriptionResult::FilledOrder(block, order.order))
+                Some(OrderSubscriptionResult::FilledOrder(block, order.order, receipt))
             }
             PoolManagerUpdate::UnfilledOrders(order)
                 if kind.contains(&OrderSubscriptionKind::UnfilledOrders)
@@ -278,6 +552,39 @@ impl OrderFilterMatching for PoolManagerUpdate {
             {
                 Some(OrderSubscriptionResult::ExpiredOrder(order.order))
             }
+            PoolManagerUpdate::DroppedOrder(order)
+                if kind.contains(&OrderSubscriptionKind::DroppedOrders)
+                    && matches_all_filters(filter, order.pool_id, order.from(), order.is_tob()) =>
+            {
+                Some(OrderSubscriptionResult::DroppedOrder(order.order_hash()))
+            }
+            PoolManagerUpdate::InvalidatedOrder(order)
+                if kind.contains(&OrderSubscriptionKind::InvalidatedOrders)
+                    && matches_all_filters(filter, order.pool_id, order.from(), order.is_tob()) =>
+            {
+                Some(OrderSubscriptionResult::InvalidatedOrder(order.order_hash()))
+            }
+            PoolManagerUpdate::ArmedOrder(trigger, order)
+                if kind.contains(&OrderSubscriptionKind::ArmedOrders)
+                    && matches_all_filters(filter, order.pool_id, order.from(), order.is_tob()) =>
+            {
+                Some(OrderSubscriptionResult::ArmedOrder { trigger, order: order.order })
+            }
+            PoolManagerUpdate::TriggeredOrder(order)
+                if kind.contains(&OrderSubscriptionKind::TriggeredOrders)
+                    && matches_all_filters(filter, order.pool_id, order.from(), order.is_tob()) =>
+            {
+                Some(OrderSubscriptionResult::TriggeredOrder(order.order))
+            }
+            PoolManagerUpdate::ReplacedOrder { old, new }
+                if kind.contains(&OrderSubscriptionKind::ReplacedOrders)
+                    && matches_all_filters(filter, new.pool_id, new.from(), new.is_tob()) =>
+            {
+                Some(OrderSubscriptionResult::ReplacedOrder {
+                    old: old.order_hash(),
+                    new: new.order
+                })
+            }
             _ => None
         }
     }
@@ -291,15 +598,17 @@ mod tests {
     use angstrom_amm_quoter::QuoterHandle;
     use angstrom_network::pool_manager::OrderCommand;
     use angstrom_types::{
+        contract_payloads::angstrom::{FillReceipt, OrderFill},
+        matching::Ray,
         orders::{OrderOrigin, OrderStatus},
         primitive::OrderValidationError,
-        sol_bindings::grouped_orders::AllOrders
+        sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
     };
     use futures::FutureExt;
     use order_pool::PoolManagerUpdate;
     use reth_tasks::TokioTaskExecutor;
     use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
-    use tokio_stream::wrappers::BroadcastStream;
+    use tracing::Span;
     use validation::order::{GasEstimationFuture, ValidationFuture};
 
     use super::*;
@@ -349,6 +658,126 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_quote_order_rejects_tob() {
+        let (_handle, api) = setup_order_api();
+
+        assert!(api.quote_order(create_tob_order()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_book_depth_rejects_invalid_grouping() {
+        let (_handle, api) = setup_order_api();
+
+        assert!(api.book_depth(PoolId::default(), 0, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_route_without_route() {
+        let (_handle, api) = setup_order_api();
+
+        assert!(
+            api.find_route(Address::with_last_byte(1), Address::with_last_byte(2))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_balances_one_per_token() {
+        let (_handle, api) = setup_order_api();
+        let tokens = vec![Address::with_last_byte(1), Address::with_last_byte(2)];
+
+        let balances = api
+            .balances(Address::ZERO, tokens.clone())
+            .await
+            .expect("to not throw error");
+        assert_eq!(balances.into_iter().map(|b| b.token).collect::<Vec<_>>(), tokens);
+    }
+
+    #[tokio::test]
+    async fn test_balances_reject_too_many_tokens() {
+        let (_handle, api) = setup_order_api();
+        let tokens = (0..=MAX_BALANCE_TOKENS as u64)
+            .map(|i| Address::left_padding_from(&i.to_be_bytes()))
+            .collect();
+
+        assert!(api.balances(Address::ZERO, tokens).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_balances_are_refetched_on_updates_of_the_user() {
+        let user = Address::with_last_byte(7);
+        let other = Address::with_last_byte(8);
+        let new_block =
+            |address_changes| PoolManagerUpdate::NewBlock { block_number: 1, address_changes };
+        let cancelled = |user| PoolManagerUpdate::CancelledOrder {
+            is_tob: false,
+            user,
+            pool_id: PoolId::default(),
+            order_hash: B256::ZERO
+        };
+
+        let (tx, rx) = tokio::sync::broadcast::channel(16);
+        let mut updates = BroadcastStream::new(rx);
+        let mut orders_changed = false;
+
+        // the block changed the users balances
+        tx.send(new_block(vec![other])).unwrap();
+        tx.send(new_block(vec![user])).unwrap();
+        assert!(next_balance_change(&mut updates, user, &mut orders_changed).await);
+        assert!(!orders_changed);
+
+        // the users order changed what is committed
+        tx.send(cancelled(other)).unwrap();
+        tx.send(cancelled(user)).unwrap();
+        assert!(next_balance_change(&mut updates, user, &mut orders_changed).await);
+        assert!(orders_changed);
+
+        // so the next block is refetched for, as it may have filled their orders
+        tx.send(new_block(vec![other])).unwrap();
+        assert!(next_balance_change(&mut updates, user, &mut orders_changed).await);
+        assert!(!orders_changed);
+
+        // but not the block after it
+        tx.send(new_block(vec![other])).unwrap();
+        drop(tx);
+        assert!(!next_balance_change(&mut updates, user, &mut orders_changed).await);
+    }
+
+    #[test]
+    fn test_filled_order_subscription_carries_receipt() {
+        let order = OrderWithStorageData { order: create_standing_order(), ..Default::default() };
+        let receipt = FillReceipt {
+            tx_hash: B256::with_last_byte(1),
+            ucp:     Ray::from(U256::from(7)),
+            fill:    OrderFill {
+                order_hash: order.order_hash(),
+                user:       Address::with_last_byte(2),
+                is_tob:     false,
+                token_in:   Address::with_last_byte(3),
+                token_out:  Address::with_last_byte(4),
+                amount_in:  900,
+                amount_out: 850,
+                gas_fee_t0: 10,
+                lp_fee_t0:  0
+            }
+        };
+
+        let result = PoolManagerUpdate::FilledOrder(2, order.clone(), Some(receipt.clone()))
+            .filter_out_order(
+                &HashSet::from([OrderSubscriptionKind::FilledOrders]),
+                &HashSet::new()
+            )
+            .expect("filled orders are subscribed to");
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            serde_json::json!({
+                "filledOrder": [2, order.order, receipt]
+            })
+        );
+    }
+
     fn setup_order_api() -> (
         OrderApiTestHandle,
         OrderApi<MockOrderPoolHandle, TokioTaskExecutor, MockValidator, QuoterHandle>
@@ -395,11 +824,58 @@ mod tests {
             let (tx, _) = tokio::sync::oneshot::channel();
             let _ = self
                 .sender
-                .send(OrderCommand::NewOrder(origin, order, tx))
+                .send(OrderCommand::NewOrder(origin, order, tx, Span::none()))
+                .is_ok();
+            future::ready(Ok(FixedBytes::<32>::default()))
+        }
+
+        fn replace_order(
+            &self,
+            origin: OrderOrigin,
+            order: AllOrders
+        ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send {
+            let (tx, _) = tokio::sync::oneshot::channel();
+            let _ = self
+                .sender
+                .send(OrderCommand::ReplaceOrder(origin, order, tx, Span::none()))
+                .is_ok();
+            future::ready(Ok(FixedBytes::<32>::default()))
+        }
+
+        fn new_conditional_order(
+            &self,
+            origin: OrderOrigin,
+            order: AllOrders,
+            trigger: TriggerCondition
+        ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send {
+            let (tx, _) = tokio::sync::oneshot::channel();
+            let _ = self
+                .sender
+                .send(OrderCommand::ConditionalOrder(origin, order, trigger, tx, Span::none()))
+                .is_ok();
+            future::ready(Ok(FixedBytes::<32>::default()))
+        }
+
+        fn new_routed_order(
+            &self,
+            origin: OrderOrigin,
+            route: RoutedOrder
+        ) -> impl Future<Output = Result<FixedBytes<32>, OrderValidationError>> + Send {
+            let (tx, _) = tokio::sync::oneshot::channel();
+            let _ = self
+                .sender
+                .send(OrderCommand::RoutedOrder(origin, route, tx, Span::none()))
                 .is_ok();
             future::ready(Ok(FixedBytes::<32>::default()))
         }
 
+        fn conditional_orders(
+            &self,
+            _: Address
+        ) -> impl Future<Output = Vec<(TriggerCondition, AllOrders)>> + Send {
+            future::ready(vec![])
+        }
+
         fn subscribe_orders(&self) -> BroadcastStream<PoolManagerUpdate> {
             unimplemented!("Not needed for this test")
         }
@@ -422,6 +898,10 @@ mod tests {
         fn fetch_order_status(&self, _: B256) -> impl Future<Output = Option<OrderStatus>> + Send {
             future::ready(None)
         }
+
+        fn fetch_order_history(&self, _: B256) -> impl Future<Output = Vec<OrderEvent>> + Send {
+            future::ready(vec![])
+        }
     }
 
     #[derive(Debug, Clone)]
@@ -458,5 +938,26 @@ mod tests {
         }
 
         fn cancel_order(&self, _: Address, _: B256) {}
+
+        fn balances(
+            &self,
+            _user: Address,
+            tokens: Vec<Address>
+        ) -> validation::order::BalancesFuture {
+            let balances = tokens
+                .into_iter()
+                .map(|token| TokenBalance { token, ..Default::default() })
+                .collect();
+            Box::pin(future::ready(Ok(balances)))
+        }
+
+        fn find_route(
+            &self,
+            _token_in: Address,
+            _token_out: Address,
+            _max_hops: usize
+        ) -> validation::order::RouteFuture {
+            Box::pin(future::ready(None))
+        }
     }
 }
@@ -1,5 +1,6 @@
 pub mod api;
 pub mod impls;
-pub mod types;
+pub mod rate_limit;
 
+pub use angstrom_rpc_api::types;
 pub use impls::*;
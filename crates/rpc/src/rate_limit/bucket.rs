@@ -0,0 +1,295 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv6Addr},
+    time::{Duration, Instant}
+};
+
+use parking_lot::Mutex;
+
+use super::{RateLimitConfig, RateLimitTier};
+
+/// the max amount of clients that are tracked, the least recently charged one
+/// is dropped to make room for a new one.
+const MAX_TRACKED_CLIENTS: usize = 100_000;
+
+/// Who the tokens are charged to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    ApiKey(String),
+    /// ipv6 clients are charged by their /64, as that is what a single host
+    /// usually gets.
+    Ip(IpAddr),
+    /// the ip isn't known, so the connection is limited on its own.
+    Connection(usize)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBucket {
+    tokens:      f64,
+    last_refill: Instant
+}
+
+impl TokenBucket {
+    pub fn new(tier: &RateLimitTier, now: Instant) -> Self {
+        Self { tokens: tier.burst as f64, last_refill: now }
+    }
+
+    fn refill(&mut self, tier: &RateLimitTier, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * tier.requests_per_second as f64).min(tier.burst as f64);
+        self.last_refill = now;
+    }
+
+    /// takes the tokens, or returns how long until there are enough of them.
+    pub fn try_take(
+        &mut self,
+        cost: u32,
+        tier: &RateLimitTier,
+        now: Instant
+    ) -> Result<(), Duration> {
+        self.refill(tier, now);
+
+        let cost = cost as f64;
+        if cost > tier.burst as f64 {
+            // can never be served, no point in retrying
+            return Err(Duration::MAX);
+        }
+
+        if self.tokens < cost {
+            let missing = cost - self.tokens;
+            return Err(Duration::from_secs_f64(missing / tier.requests_per_second.max(1) as f64));
+        }
+        self.tokens -= cost;
+
+        Ok(())
+    }
+}
+
+impl ClientKey {
+    /// the key of a client connecting from the ip.
+    pub fn ip(ip: IpAddr) -> Self {
+        match ip.to_canonical() {
+            IpAddr::V6(ip) => {
+                Self::Ip(IpAddr::V6(Ipv6Addr::from_bits(ip.to_bits() & !(u64::MAX as u128))))
+            }
+            ip => Self::Ip(ip)
+        }
+    }
+}
+
+/// Why a request was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitError {
+    RateLimited { retry_after: Duration },
+    BatchTooLarge { size: usize, max: usize },
+    UnknownApiKey
+}
+
+/// The buckets of the clients, at most [`MAX_TRACKED_CLIENTS`] of them.
+#[derive(Debug, Default)]
+struct Buckets {
+    /// the bucket of every client along with when it was last charged
+    clients: HashMap<ClientKey, (TokenBucket, u64)>,
+    /// the clients by when they were last charged, least recent first
+    by_use:  BTreeMap<u64, ClientKey>,
+    /// counts up on every charge
+    uses:    u64
+}
+
+impl Buckets {
+    /// the bucket of the client, a new client takes the place of the least
+    /// recently charged one if there is no room.
+    fn bucket(&mut self, key: ClientKey, tier: &RateLimitTier, now: Instant) -> &mut TokenBucket {
+        self.uses += 1;
+        let last_use = self.uses;
+
+        if let Some((_, previous_use)) = self.clients.get(&key) {
+            self.by_use.remove(previous_use);
+        } else if self.clients.len() >= MAX_TRACKED_CLIENTS {
+            let (_, oldest) = self.by_use.pop_first().expect("every client has a use");
+            self.clients.remove(&oldest);
+        }
+        self.by_use.insert(last_use, key.clone());
+
+        let (bucket, used) = self
+            .clients
+            .entry(key)
+            .or_insert_with(|| (TokenBucket::new(tier, now), last_use));
+        *used = last_use;
+
+        bucket
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    config:  RateLimitConfig,
+    buckets: Mutex<Buckets>
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, buckets: Mutex::new(Buckets::default()) }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// resolves who to charge and the limits that apply to them.
+    pub fn resolve(
+        &self,
+        identity: Option<&super::ClientIdentity>,
+        connection: usize
+    ) -> Result<(ClientKey, RateLimitTier), RateLimitError> {
+        let identity = identity.cloned().unwrap_or_default();
+        if let Some(api_key) = identity.api_key {
+            let tier = self
+                .config
+                .api_key_tier(&api_key)
+                .ok_or(RateLimitError::UnknownApiKey)?;
+            return Ok((ClientKey::ApiKey(api_key), tier));
+        }
+
+        let key = identity
+            .ip
+            .map(ClientKey::ip)
+            .unwrap_or(ClientKey::Connection(connection));
+
+        Ok((key, self.config.per_ip))
+    }
+
+    /// charges the client for a call with the given amount of items.
+    pub fn check(
+        &self,
+        key: ClientKey,
+        tier: &RateLimitTier,
+        items: usize
+    ) -> Result<(), RateLimitError> {
+        Self::check_size(tier, items)?;
+        self.charge(key, tier, items)
+    }
+
+    pub fn check_size(tier: &RateLimitTier, items: usize) -> Result<(), RateLimitError> {
+        if items > tier.max_batch_size {
+            return Err(RateLimitError::BatchTooLarge { size: items, max: tier.max_batch_size });
+        }
+
+        Ok(())
+    }
+
+    /// takes `cost` tokens from the bucket of the client.
+    pub fn charge(
+        &self,
+        key: ClientKey,
+        tier: &RateLimitTier,
+        cost: usize
+    ) -> Result<(), RateLimitError> {
+        let now = Instant::now();
+        let cost = u32::try_from(cost.max(1)).unwrap_or(u32::MAX);
+        self.buckets
+            .lock()
+            .bucket(key, tier, now)
+            .try_take(cost, tier, now)
+            .map_err(|retry_after| RateLimitError::RateLimited { retry_after })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let tier = RateLimitTier {
+            requests_per_second: 10,
+            burst:               20,
+            max_batch_size:      5
+        };
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(&tier, start);
+
+        assert!(bucket.try_take(20, &tier, start).is_ok());
+        let retry = bucket.try_take(5, &tier, start).unwrap_err();
+        assert_eq!(retry, Duration::from_millis(500));
+
+        assert!(
+            bucket
+                .try_take(5, &tier, start + Duration::from_millis(500))
+                .is_ok()
+        );
+        assert_eq!(bucket.try_take(21, &tier, start), Err(Duration::MAX));
+    }
+
+    #[test]
+    fn evicts_the_least_recently_charged_client() {
+        // never refills, so the drained client stays drained
+        let tier = RateLimitTier {
+            requests_per_second: 0,
+            burst:               1,
+            max_batch_size:      1
+        };
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let drained = ClientKey::ApiKey("drained".into());
+        limiter.charge(drained.clone(), &tier, 1).unwrap();
+        for i in 0..MAX_TRACKED_CLIENTS - 1 {
+            limiter.charge(ClientKey::Connection(i), &tier, 1).unwrap();
+        }
+        // still being charged, so it isn't the one that makes room
+        assert!(limiter.charge(drained.clone(), &tier, 1).is_err());
+
+        limiter
+            .charge(ClientKey::Ip([1, 1, 1, 1].into()), &tier, 1)
+            .unwrap();
+        let buckets = limiter.buckets.lock();
+        assert_eq!(buckets.clients.len(), MAX_TRACKED_CLIENTS);
+        assert!(buckets.clients.contains_key(&drained));
+        assert!(!buckets.clients.contains_key(&ClientKey::Connection(0)));
+        assert_eq!(buckets.by_use.len(), MAX_TRACKED_CLIENTS);
+    }
+
+    #[test]
+    fn ipv6_clients_are_charged_by_their_subnet() {
+        let ip = |ip: &str| ClientKey::ip(ip.parse().unwrap());
+
+        assert_eq!(ip("2001:db8:1:2:aaaa::1"), ip("2001:db8:1:2:bbbb::2"));
+        assert_ne!(ip("2001:db8:1:2::1"), ip("2001:db8:1:3::1"));
+        assert_eq!(ip("::ffff:1.2.3.4"), ClientKey::Ip([1, 2, 3, 4].into()));
+        assert_ne!(ip("1.2.3.4"), ip("1.2.3.5"));
+    }
+
+    #[test]
+    fn api_keys_get_their_own_tier() {
+        let market_maker = RateLimitTier {
+            requests_per_second: 1,
+            burst:               1,
+            max_batch_size:      100
+        };
+        let config = RateLimitConfig {
+            tiers: HashMap::from([("mm".to_string(), market_maker)]),
+            api_keys: HashMap::from([("key".to_string(), "mm".to_string())]),
+            ..Default::default()
+        };
+        let limiter = RateLimiter::new(config);
+
+        let identity = super::super::ClientIdentity { api_key: Some("key".into()), ip: None };
+        let (key, tier) = limiter.resolve(Some(&identity), 0).unwrap();
+        assert_eq!(tier, market_maker);
+        assert!(limiter.check(key.clone(), &tier, 1).is_ok());
+        assert!(matches!(
+            limiter.check(key.clone(), &tier, 1),
+            Err(RateLimitError::RateLimited { .. })
+        ));
+        assert_eq!(
+            limiter.check(key, &tier, 101),
+            Err(RateLimitError::BatchTooLarge { size: 101, max: 100 })
+        );
+
+        let unknown = super::super::ClientIdentity { api_key: Some("other".into()), ip: None };
+        assert_eq!(limiter.resolve(Some(&unknown), 0), Err(RateLimitError::UnknownApiKey));
+        assert_eq!(limiter.resolve(None, 3).unwrap().0, ClientKey::Connection(3));
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashSet,
+    net::IpAddr,
+    sync::Arc,
+    task::{Context, Poll}
+};
+
+use http::{HeaderMap, Request};
+use tower::{Layer, Service};
+
+/// header clients send their api key in.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Who sent a request, taken from the connection and the http headers of the
+/// request (or of the websocket upgrade) and handed to the rpc middleware
+/// through the request extensions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientIdentity {
+    pub api_key: Option<String>,
+    pub ip:      Option<IpAddr>
+}
+
+impl ClientIdentity {
+    /// The client is the peer of the connection, unless the peer is one of
+    /// the trusted proxies. Then it is taken from the forwarding headers the
+    /// proxies set, as anyone else could put whatever they want in them.
+    pub fn from_request(
+        headers: &HeaderMap,
+        peer: Option<IpAddr>,
+        trusted_proxies: &HashSet<IpAddr>
+    ) -> Self {
+        let api_key = headers
+            .get(API_KEY_HEADER)
+            .and_then(|key| key.to_str().ok())
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty());
+
+        let ip = match peer {
+            Some(peer) if trusted_proxies.contains(&peer) => {
+                forwarded_client(headers, trusted_proxies).or(Some(peer))
+            }
+            peer => peer
+        };
+
+        Self { api_key, ip }
+    }
+}
+
+/// Every proxy appends the address it got the request from to
+/// x-forwarded-for, so the entries left of the ones our proxies added could
+/// be made up by the client. The client is the right-most entry that isn't a
+/// trusted proxy.
+fn forwarded_client(headers: &HeaderMap, trusted_proxies: &HashSet<IpAddr>) -> Option<IpAddr> {
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>();
+
+    if forwarded.is_empty() {
+        return headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|ip| ip.trim().parse().ok());
+    }
+
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !trusted_proxies.contains(ip))
+        .or(forwarded.first())
+        .copied()
+}
+
+/// Http middleware that tags every request with its [`ClientIdentity`].
+#[derive(Debug, Clone, Default)]
+pub struct ClientIdentityLayer {
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+    peer:            Option<IpAddr>
+}
+
+impl ClientIdentityLayer {
+    pub fn new(trusted_proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        Self {
+            trusted_proxies: Arc::new(trusted_proxies.into_iter().collect()),
+            peer:            None
+        }
+    }
+
+    /// the layer for a single connection with the given peer.
+    pub fn for_peer(&self, peer: IpAddr) -> Self {
+        Self { trusted_proxies: self.trusted_proxies.clone(), peer: Some(peer) }
+    }
+}
+
+impl<S> Layer<S> for ClientIdentityLayer {
+    type Service = ClientIdentityService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientIdentityService {
+            inner,
+            trusted_proxies: self.trusted_proxies.clone(),
+            peer: self.peer
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientIdentityService<S> {
+    inner:           S,
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+    peer:            Option<IpAddr>
+}
+
+impl<S, B> Service<Request<B>> for ClientIdentityService<S>
+where
+    S: Service<Request<B>>
+{
+    type Error = S::Error;
+    type Future = S::Future;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        let identity =
+            ClientIdentity::from_request(req.headers(), self.peer, &self.trusted_proxies);
+        req.extensions_mut().insert(identity);
+
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    fn headers(forwarded_for: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_str(forwarded_for).unwrap());
+        headers
+    }
+
+    #[test]
+    fn forwarding_headers_are_only_read_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let peer: IpAddr = "1.1.1.1".parse().unwrap();
+        let trusted = HashSet::from([proxy]);
+        let headers = headers("6.6.6.6, 2.2.2.2");
+
+        // anyone can set the header, it only counts when our proxy did
+        let direct = ClientIdentity::from_request(&headers, Some(peer), &trusted);
+        assert_eq!(direct.ip, Some(peer));
+
+        // the client can't pick its own ip by prepending to the header
+        let proxied = ClientIdentity::from_request(&headers, Some(proxy), &trusted);
+        assert_eq!(proxied.ip, "2.2.2.2".parse().ok());
+
+        assert_eq!(ClientIdentity::from_request(&headers, None, &trusted).ip, None);
+    }
+}
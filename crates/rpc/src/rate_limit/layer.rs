@@ -0,0 +1,216 @@
+use std::{future::Future, sync::Arc};
+
+use futures::future::{Either, ready};
+use jsonrpsee::{
+    MethodResponse,
+    core::{
+        middleware::{Batch, BatchEntry, Notification, RpcServiceT},
+        server::BatchResponseBuilder
+    },
+    server::ConnectionId,
+    types::{ErrorObjectOwned, Request}
+};
+use serde_json::json;
+use tower::Layer;
+
+use super::{
+    BATCH_TOO_LARGE_CODE, ClientIdentity, ClientKey, RATE_LIMITED_CODE, RateLimitConfig,
+    RateLimitError, RateLimitTier, RateLimiter, UNKNOWN_API_KEY_CODE
+};
+
+/// only our own namespace is limited, the eth namespace of the node has its
+/// own limits.
+const LIMITED_NAMESPACE: &str = "angstrom_";
+
+/// Rpc middleware that charges every `angstrom_*` call against the token
+/// bucket of its client.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>
+}
+
+impl RateLimitLayer {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { limiter: Arc::new(RateLimiter::new(config)) }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit { inner, limiter: self.limiter.clone() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimit<S> {
+    inner:   S,
+    limiter: Arc<RateLimiter>
+}
+
+impl<S> RateLimit<S> {
+    fn resolve(&self, request: &Request<'_>) -> Result<(ClientKey, RateLimitTier), RateLimitError> {
+        let connection = request
+            .extensions()
+            .get::<ConnectionId>()
+            .map(|id| id.0)
+            .unwrap_or_default();
+
+        self.limiter
+            .resolve(request.extensions().get::<ClientIdentity>(), connection)
+    }
+
+    /// charges the client for the given amount of items.
+    fn check(&self, request: &Request<'_>, items: usize) -> Result<(), RateLimitError> {
+        let (key, tier) = self.resolve(request)?;
+        self.limiter.check(key, &tier, items)
+    }
+
+    /// the whole batch is charged at once so that a client can't get around
+    /// the limits by wrapping its calls in a json-rpc batch. All calls of a
+    /// batch come from the same connection.
+    fn check_batch(&self, calls: &[&Request<'_>]) -> Result<(), RateLimitError> {
+        let Some(first) = calls.first() else { return Ok(()) };
+        // the server caps batches as well, but not every server we run in lets
+        // us configure that
+        let max_calls = self.limiter.config().max_rpc_batch_size as usize;
+        if calls.len() > max_calls {
+            return Err(RateLimitError::BatchTooLarge { size: calls.len(), max: max_calls });
+        }
+
+        let (key, tier) = self.resolve(first)?;
+
+        let mut total = 0;
+        for items in calls.iter().filter_map(|call| request_items(call)) {
+            RateLimiter::check_size(&tier, items)?;
+            total += items;
+        }
+        if total == 0 {
+            return Ok(());
+        }
+
+        self.limiter.charge(key, &tier, total)
+    }
+}
+
+/// the amount of items in a call. The multi calls of the order api take the
+/// items as their first param, everything else is a single item.
+fn request_items(request: &Request<'_>) -> Option<usize> {
+    if !request.method_name().starts_with(LIMITED_NAMESPACE) {
+        return None;
+    }
+
+    let items = request
+        .params()
+        .parse::<Vec<serde_json::Value>>()
+        .ok()
+        .and_then(|params| {
+            params
+                .first()
+                .and_then(|first| first.as_array().map(Vec::len))
+        })
+        .unwrap_or(1);
+
+    Some(items)
+}
+
+/// answers every call of the batch with the error, notifications don't get
+/// an answer.
+fn reject_batch(batch: Batch<'_>, err: RateLimitError) -> MethodResponse {
+    let mut responses = BatchResponseBuilder::new_with_limit(usize::MAX);
+    for entry in batch {
+        let response = match entry {
+            Ok(BatchEntry::Call(request)) => {
+                MethodResponse::error(request.id().into_owned(), err.clone())
+            }
+            Ok(BatchEntry::Notification(_)) => continue,
+            Err(invalid) => {
+                let (error, id) = invalid.into_parts();
+                MethodResponse::error(id, error)
+            }
+        };
+        if let Err(too_large) = responses.append(response) {
+            return too_large;
+        }
+    }
+
+    MethodResponse::from_batch(responses.finish())
+}
+
+impl From<RateLimitError> for ErrorObjectOwned {
+    fn from(err: RateLimitError) -> Self {
+        match err {
+            RateLimitError::RateLimited { retry_after } => ErrorObjectOwned::owned(
+                RATE_LIMITED_CODE,
+                "rate limit exceeded",
+                Some(json!({ "retryAfterMs": u64::try_from(retry_after.as_millis()).ok() }))
+            ),
+            RateLimitError::BatchTooLarge { size, max } => ErrorObjectOwned::owned(
+                BATCH_TOO_LARGE_CODE,
+                format!("batch of {size} items exceeds the limit"),
+                Some(json!({ "maxBatchSize": max }))
+            ),
+            RateLimitError::UnknownApiKey => {
+                ErrorObjectOwned::owned(UNKNOWN_API_KEY_CODE, "unknown api key", None::<()>)
+            }
+        }
+    }
+}
+
+impl<S> RpcServiceT for RateLimit<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+            NotificationResponse = MethodResponse
+        > + Send
+        + Sync
+        + Clone
+        + 'static
+{
+    type BatchResponse = MethodResponse;
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = MethodResponse;
+
+    fn call<'a>(
+        &self,
+        request: Request<'a>
+    ) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+        let rejected = request_items(&request).and_then(|items| self.check(&request, items).err());
+
+        match rejected {
+            Some(err) => {
+                tracing::debug!(method = request.method_name(), ?err, "rejected rpc call");
+                Either::Left(ready(MethodResponse::error(request.id().into_owned(), err)))
+            }
+            None => Either::Right(self.inner.call(request))
+        }
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+        let calls = batch
+            .iter()
+            .filter_map(|entry| match entry {
+                Ok(BatchEntry::Call(request)) => Some(request),
+                _ => None
+            })
+            .collect::<Vec<_>>();
+        let rejected = self.check_batch(&calls).err();
+
+        match rejected {
+            Some(err) => {
+                tracing::debug!(?err, "rejected rpc batch");
+                Either::Left(ready(reject_batch(batch, err)))
+            }
+            None => Either::Right(self.inner.batch(batch))
+        }
+    }
+
+    fn notification<'a>(
+        &self,
+        n: Notification<'a>
+    ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+        self.inner.notification(n)
+    }
+}
@@ -0,0 +1,116 @@
+//! Per client rate limiting of the `angstrom_*` methods.
+//!
+//! Clients are identified by their api key if they send one in the
+//! [`API_KEY_HEADER`], otherwise by their ip. The ip is the peer of the
+//! connection, or taken from the forwarding headers if the peer is one of the
+//! configured trusted proxies. Every client has a token bucket, single calls
+//! cost one token and the multi calls cost one token per item.
+mod bucket;
+mod identity;
+mod layer;
+mod server;
+
+use std::{collections::HashMap, net::IpAddr};
+
+pub use bucket::*;
+pub use identity::*;
+pub use layer::*;
+use serde::{Deserialize, Serialize};
+pub use server::*;
+
+/// the request was rejected as the client ran out of tokens
+pub const RATE_LIMITED_CODE: i32 = -32005;
+/// a multi call had more items than the tier of the client allows
+pub const BATCH_TOO_LARGE_CODE: i32 = -32006;
+/// the api key isn't known to the node
+pub const UNKNOWN_API_KEY_CODE: i32 = -32007;
+
+/// The limits of a class of clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitTier {
+    /// tokens that are refilled every second
+    pub requests_per_second: u32,
+    /// max tokens a client can save up
+    pub burst:               u32,
+    /// max items in a single multi call
+    pub max_batch_size:      usize
+}
+
+impl Default for RateLimitTier {
+    fn default() -> Self {
+        Self { requests_per_second: 20, burst: 100, max_batch_size: 50 }
+    }
+}
+
+/// Loaded from toml, e.g.
+///
+/// ```toml
+/// max_subscriptions_per_connection = 16
+/// trusted_proxies = ["10.0.0.1"]
+///
+/// [per_ip]
+/// requests_per_second = 20
+/// burst = 100
+/// max_batch_size = 50
+///
+/// [tiers.market_maker]
+/// requests_per_second = 500
+/// burst = 2000
+/// max_batch_size = 500
+///
+/// [api_keys]
+/// "some-secret-key" = "market_maker"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// the limits of clients without an api key, per ip
+    pub per_ip: RateLimitTier,
+    /// named tiers that api keys are assigned to
+    pub tiers: HashMap<String, RateLimitTier>,
+    /// api key to the name of its tier
+    pub api_keys: HashMap<String, String>,
+    pub max_subscriptions_per_connection: u32,
+    /// max calls in a single json-rpc batch
+    pub max_rpc_batch_size: u32,
+    /// proxies whose forwarding headers are used to find the client
+    pub trusted_proxies: Vec<IpAddr>
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_ip: RateLimitTier::default(),
+            tiers: HashMap::new(),
+            api_keys: HashMap::new(),
+            max_subscriptions_per_connection: 16,
+            max_rpc_batch_size: 50,
+            trusted_proxies: vec![]
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn load(path: impl AsRef<std::path::Path>) -> eyre::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&contents)?;
+
+        if let Some((key, tier)) = config
+            .api_keys
+            .iter()
+            .find(|(_, tier)| !config.tiers.contains_key(*tier))
+        {
+            eyre::bail!("api key {key} is assigned to the unknown tier {tier}");
+        }
+
+        Ok(config)
+    }
+
+    /// the tier of the api key, `None` if the key is unknown.
+    pub fn api_key_tier(&self, api_key: &str) -> Option<RateLimitTier> {
+        self.api_keys
+            .get(api_key)
+            .and_then(|tier| self.tiers.get(tier))
+            .copied()
+    }
+}
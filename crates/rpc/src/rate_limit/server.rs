@@ -0,0 +1,62 @@
+use std::net::SocketAddr;
+
+use jsonrpsee::{
+    Methods,
+    server::{
+        BatchRequestConfig, RpcServiceBuilder, Server, ServerHandle, serve_with_graceful_shutdown,
+        stop_channel
+    }
+};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tower::Layer;
+
+use super::{ClientIdentityLayer, RateLimitConfig, RateLimitLayer};
+
+/// Serves the methods with the rate limits applied. The connections are
+/// accepted here instead of by [`Server::start`] so that every request can be
+/// tagged with the peer of its connection.
+pub async fn serve_rate_limited(
+    addr: impl ToSocketAddrs,
+    methods: impl Into<Methods>,
+    config: RateLimitConfig
+) -> eyre::Result<(SocketAddr, ServerHandle)> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+
+    let identity = ClientIdentityLayer::new(config.trusted_proxies.iter().copied());
+    let service_builder = Server::builder()
+        .max_subscriptions_per_connection(config.max_subscriptions_per_connection)
+        .set_batch_request_config(BatchRequestConfig::Limit(config.max_rpc_batch_size))
+        .set_rpc_middleware(RpcServiceBuilder::new().layer(RateLimitLayer::new(config)))
+        .to_service_builder();
+    let methods = methods.into();
+    let (stop_handle, server_handle) = stop_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = tokio::select! {
+                conn = listener.accept() => match conn {
+                    Ok(conn) => conn,
+                    Err(error) => {
+                        tracing::warn!(%error, "failed to accept rpc connection");
+                        continue
+                    }
+                },
+                _ = stop_handle.clone().shutdown() => break
+            };
+
+            let service = identity.for_peer(peer.ip()).layer(
+                service_builder
+                    .clone()
+                    .build(methods.clone(), stop_handle.clone())
+            );
+            tokio::spawn(serve_with_graceful_shutdown(
+                stream,
+                service,
+                stop_handle.clone().shutdown()
+            ));
+        }
+    });
+
+    Ok((local_addr, server_handle))
+}
@@ -14,9 +14,10 @@ use angstrom_types::{
 };
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
 
 pub static TELEMETRY_SENDER: OnceLock<UnboundedSender<TelemetryMessage>> = OnceLock::new();
+pub static TELEMETRY_REQUESTS: OnceLock<UnboundedSender<TelemetryRequest>> = OnceLock::new();
 
 #[macro_export]
 macro_rules! telemetry_event {
@@ -101,6 +102,27 @@ pub enum TelemetryMessage {
     }
 }
 
+/// On demand requests to the telemetry task. Kept apart from
+/// [`TelemetryMessage`] as these carry a response channel.
+#[derive(Debug)]
+pub enum TelemetryRequest {
+    /// the json encoded block log of the given block, or the latest block
+    BlockLog { blocknum: Option<u64>, tx: oneshot::Sender<Option<serde_json::Value>> }
+}
+
+/// Fetches a block log snapshot from the telemetry task. Returns `None` if the
+/// block isn't cached.
+pub async fn request_block_log(blocknum: Option<u64>) -> eyre::Result<Option<serde_json::Value>> {
+    let handle = TELEMETRY_REQUESTS
+        .get()
+        .ok_or_else(|| eyre::eyre!("telemetry is not enabled"))?;
+
+    let (tx, rx) = oneshot::channel();
+    handle.send(TelemetryRequest::BlockLog { blocknum, tx })?;
+
+    Ok(rx.await?)
+}
+
 impl TelemetryMessage {
     pub fn try_get_timestamp(&self) -> chrono::DateTime<Utc> {
         match self {
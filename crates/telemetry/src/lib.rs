@@ -22,7 +22,9 @@ use order_pool::telemetry::OrderPoolSnapshot;
 use outputs::TelemetryOutput;
 use reth_tasks::shutdown::GracefulShutdown;
 use serde::{Deserialize, Serialize};
-use telemetry_recorder::{TELEMETRY_SENDER, TelemetryMessage};
+use telemetry_recorder::{
+    TELEMETRY_REQUESTS, TELEMETRY_SENDER, TelemetryMessage, TelemetryRequest
+};
 use tokio::sync::mpsc::UnboundedReceiver;
 use tracing::warn;
 use validation::telemetry::ValidationSnapshot;
@@ -69,6 +71,7 @@ impl NodeConstants {
 
 pub struct Telemetry {
     rx:                  UnboundedReceiver<TelemetryMessage>,
+    requests:            UnboundedReceiver<TelemetryRequest>,
     node_consts:         NodeConstants,
     block_cache:         HashMap<u64, BlockLog>,
     outputs:             Vec<Box<dyn TelemetryOutput + Send + 'static>>,
@@ -80,6 +83,7 @@ pub struct Telemetry {
 impl Telemetry {
     pub fn new(
         rx: UnboundedReceiver<TelemetryMessage>,
+        requests: UnboundedReceiver<TelemetryRequest>,
         node_address: Address,
         guard: GracefulShutdown,
         outputs: Vec<Box<dyn TelemetryOutput + Send + 'static>>
@@ -94,6 +98,7 @@ impl Telemetry {
         };
         Self {
             rx,
+            requests,
             node_consts,
             block_cache: HashMap::new(),
             outputs,
@@ -170,6 +175,23 @@ impl Telemetry {
             warn!(blocknum, "got a error for a block that doesn't exist");
         }
     }
+
+    fn on_request(&mut self, request: TelemetryRequest) {
+        match request {
+            TelemetryRequest::BlockLog { blocknum, tx } => {
+                let blocknum = blocknum.or_else(|| self.block_cache.keys().copied().max());
+                let log = blocknum
+                    .and_then(|blocknum| self.block_cache.get(&blocknum))
+                    .map(|block| {
+                        let mut block = block.clone();
+                        block.set_node_constants(self.node_consts.clone());
+                        serde_json::to_value(block).unwrap()
+                    });
+
+                let _ = tx.send(log);
+            }
+        }
+    }
 }
 
 impl Future for Telemetry {
@@ -224,6 +246,10 @@ impl Future for Telemetry {
             }
         }
 
+        while let Poll::Ready(Some(request)) = self.requests.poll_recv(cx) {
+            self.on_request(request);
+        }
+
         while let Poll::Ready(Some(_)) = self.pending_submissions.poll_next_unpin(cx) {}
 
         // We want to be careful here as we want to ensure that all readings have been
@@ -269,6 +295,8 @@ impl Future for Telemetry {
 pub async fn init_telemetry(node_address: Address, shutdown_handle: GracefulShutdown) {
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
     let _ = TELEMETRY_SENDER.set(tx);
+    let (request_tx, request_rx) = tokio::sync::mpsc::unbounded_channel();
+    let _ = TELEMETRY_REQUESTS.set(request_tx);
     let handles =
         vec![Box::new(S3Storage::new().await.unwrap()) as Box<dyn TelemetryOutput + Send>];
 
@@ -279,6 +307,6 @@ pub async fn init_telemetry(node_address: Address, shutdown_handle: GracefulShut
             .build()
             .unwrap();
 
-        rt.block_on(Telemetry::new(rx, node_address, shutdown_handle, handles))
+        rt.block_on(Telemetry::new(rx, request_rx, node_address, shutdown_handle, handles))
     });
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use alloy::{
     primitives::{Address, B256, BlockNumber, keccak256},
     signers::Signature
@@ -19,8 +21,11 @@ pub struct PreProposal {
     pub source:       PeerId,
     pub limit:        Vec<B256>,
     pub searcher:     Vec<B256>,
-    /// The signature is over the ethereum height as well as the limit and
-    /// searcher sets
+    /// the legs of the routes among the limit orders, which can only be filled
+    /// together
+    pub routes:       Vec<Vec<B256>>,
+    /// The signature is over the ethereum height as well as the limit,
+    /// searcher and route sets
     pub signature:    Signature
 }
 
@@ -31,7 +36,8 @@ impl Default for PreProposal {
             block_height: Default::default(),
             source:       Default::default(),
             limit:        Default::default(),
-            searcher:     Default::default()
+            searcher:     Default::default(),
+            routes:       Default::default()
         }
     }
 }
@@ -48,27 +54,54 @@ impl PreProposal {
         limit: Vec<B256>,
         searcher: Vec<B256>
     ) -> Self {
-        let payload = Self::serialize_payload(&ethereum_height, &limit, &searcher);
+        Self::generate_pre_proposal_with_routes(ethereum_height, sk, limit, searcher, vec![])
+    }
+
+    pub fn generate_pre_proposal_with_routes<S: AngstromMetaSigner>(
+        ethereum_height: BlockNumber,
+        sk: &AngstromSigner<S>,
+        limit: Vec<B256>,
+        searcher: Vec<B256>,
+        routes: Vec<Vec<B256>>
+    ) -> Self {
+        let payload = Self::serialize_payload(&ethereum_height, &limit, &searcher, &routes);
         let signature = Self::sign_payload(sk, payload);
 
-        Self { limit, source: sk.id(), searcher, block_height: ethereum_height, signature }
+        Self { limit, source: sk.id(), searcher, routes, block_height: ethereum_height, signature }
     }
 
+    /// `routes` are the legs of the known routes, only the ones whose legs are
+    /// all among the limit orders are included.
     pub fn new<S: AngstromMetaSigner>(
         ethereum_height: u64,
         sk: &AngstromSigner<S>,
-        orders: OrderSet<AllOrders, TopOfBlockOrder>
+        orders: OrderSet<AllOrders, TopOfBlockOrder>,
+        routes: Vec<Vec<B256>>
     ) -> Self {
         let OrderSet { limit, searcher } = orders;
         let limit_orders = limit.len();
         let searcher_orders = searcher.len();
         tracing::info!(%limit_orders,%searcher_orders, %ethereum_height,"building my pre_proposal");
-        let limit_hashes = limit.into_iter().map(|order| order.order_hash()).collect();
+        let limit_hashes = limit
+            .into_iter()
+            .map(|order| order.order_hash())
+            .collect::<Vec<_>>();
         let searcher_hashes = searcher
             .into_iter()
             .map(|order| order.order_hash())
             .collect();
-        Self::generate_pre_proposal(ethereum_height, sk, limit_hashes, searcher_hashes)
+        let known = limit_hashes.iter().collect::<HashSet<_>>();
+        let routes = routes
+            .into_iter()
+            .filter(|legs| legs.iter().all(|leg| known.contains(leg)))
+            .collect();
+        Self::generate_pre_proposal_with_routes(
+            ethereum_height,
+            sk,
+            limit_hashes,
+            searcher_hashes,
+            routes
+        )
     }
 
     pub fn recover_address(&self) -> Option<Address> {
@@ -87,16 +120,27 @@ impl PreProposal {
         source == self.source && &self.block_height == block_height
     }
 
-    fn serialize_payload(block_height: &BlockNumber, limit: &[B256], searcher: &[B256]) -> Vec<u8> {
+    fn serialize_payload(
+        block_height: &BlockNumber,
+        limit: &[B256],
+        searcher: &[B256],
+        routes: &[Vec<B256>]
+    ) -> Vec<u8> {
         let mut buf = Vec::new();
         buf.extend(bincode::serialize(block_height).unwrap());
         buf.extend(bincode::serialize(limit).unwrap());
         buf.extend(bincode::serialize(searcher).unwrap());
+        buf.extend(bincode::serialize(routes).unwrap());
         buf
     }
 
     fn payload(&self) -> Bytes {
-        Bytes::from(Self::serialize_payload(&self.block_height, &self.limit, &self.searcher))
+        Bytes::from(Self::serialize_payload(
+            &self.block_height,
+            &self.limit,
+            &self.searcher,
+            &self.routes
+        ))
     }
 
     pub fn searcher_order_hashes(&self) -> Vec<B256> {
@@ -111,6 +155,8 @@ impl PreProposal {
 #[cfg(test)]
 mod tests {
 
+    use alloy::primitives::B256;
+
     use super::PreProposal;
     use crate::primitive::AngstromSigner;
 
@@ -134,4 +180,21 @@ mod tests {
 
         assert!(preproposal.is_valid(&ethereum_height), "Unable to validate self");
     }
+
+    #[test]
+    fn routes_are_signed() {
+        let sk = AngstromSigner::random();
+        let legs = vec![B256::with_last_byte(1), B256::with_last_byte(2)];
+        let mut preproposal = PreProposal::generate_pre_proposal_with_routes(
+            100,
+            &sk,
+            legs.clone(),
+            vec![],
+            vec![legs]
+        );
+        assert!(preproposal.is_valid(&100));
+
+        preproposal.routes.clear();
+        assert!(!preproposal.is_valid(&100));
+    }
 }
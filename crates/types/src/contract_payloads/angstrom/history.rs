@@ -0,0 +1,163 @@
+use alloy::primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+
+use super::{AngstromBundle, OrderQuantities, lp_user_donation};
+use crate::matching::{Ray, get_quantities_at_price};
+
+/// A order that was filled in a bundle that landed on chain.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub order_hash: B256,
+    pub user:       Address,
+    pub is_tob:     bool,
+    pub token_in:   Address,
+    pub token_out:  Address,
+    pub amount_in:  u128,
+    pub amount_out: u128,
+    /// the gas fee charged to the order in token0
+    pub gas_fee_t0: u128,
+    /// the fee charged by the pool in token0. always zero for tob orders
+    pub lp_fee_t0:  u128
+}
+
+/// What a order got out of the bundle that filled it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FillReceipt {
+    /// the transaction of the bundle that filled the order
+    pub tx_hash: B256,
+    /// the uniform clearing price the order was filled at in ray format (t1 /
+    /// t0)
+    pub ucp:     Ray,
+    #[serde(flatten)]
+    pub fill:    OrderFill
+}
+
+/// Everything that happened to a single pool in a bundle that landed on chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolClearing {
+    pub token0:               Address,
+    pub token1:               Address,
+    /// uniform clearing price in ray format (t1 / t0)
+    pub ucp:                  Ray,
+    /// the direction of the net amm swap
+    pub amm_zero_for_one:     bool,
+    /// the amount swapped into the amm
+    pub amm_swap_in_quantity: u128,
+    /// total token0 donated to the lps of the pool
+    pub donation_total_t0:    u128,
+    /// total pool fees paid by the user orders
+    pub user_fees_t0:         u128,
+    /// the part of the donation that didn't come from user fees. this is the
+    /// tob reward plus any left over from matching the book
+    pub tob_reward_t0:        u128,
+    pub fills:                Vec<OrderFill>
+}
+
+impl AngstromBundle {
+    /// Breaks the bundle down into what happened in each pool. `pool_fee`
+    /// returns the bundle fee (in e6) of the pool for the given token0 and
+    /// token1. The block number is the block that this bundle was executed at.
+    pub fn pool_clearings(
+        &self,
+        block_number: u64,
+        pool_fee: impl Fn(Address, Address) -> u32
+    ) -> Vec<PoolClearing> {
+        self.pairs
+            .iter()
+            .enumerate()
+            .map(|(pair_index, pair)| {
+                let token0 = self.assets[pair.index0 as usize].addr;
+                let token1 = self.assets[pair.index1 as usize].addr;
+                let ucp = Ray::from(pair.price_1over0);
+                let fee = pool_fee(token0, token1) as u128;
+
+                let tob_fills = self
+                    .top_of_block_orders
+                    .iter()
+                    .filter(|order| order.pairs_index as usize == pair_index)
+                    .map(|order| {
+                        let (token_in, token_out) =
+                            if order.zero_for_1 { (token0, token1) } else { (token1, token0) };
+                        OrderFill {
+                            order_hash: order.order_hash(&self.pairs, &self.assets, block_number),
+                            user: order.user_address(&self.pairs, &self.assets, block_number),
+                            is_tob: true,
+                            token_in,
+                            token_out,
+                            amount_in: order.quantity_in,
+                            amount_out: order.quantity_out,
+                            gas_fee_t0: order.gas_used_asset_0,
+                            lp_fee_t0: 0
+                        }
+                    });
+
+                let user_fills = self
+                    .user_orders
+                    .iter()
+                    .filter(|order| order.pair_index as usize == pair_index)
+                    .map(|order| {
+                        let is_bid = !order.zero_for_one;
+                        let fill_amount = match order.order_quantities {
+                            OrderQuantities::Exact { quantity } => quantity,
+                            OrderQuantities::Partial { filled_quantity, .. } => filled_quantity
+                        };
+                        let gas = order.extra_fee_asset0;
+                        let (t1, t0_net, t0_fee) = get_quantities_at_price(
+                            is_bid,
+                            order.exact_in,
+                            fill_amount,
+                            gas,
+                            fee,
+                            ucp
+                        );
+                        // same accounting as when the bundle is built
+                        let (token_in, token_out, amount_in, amount_out) = if is_bid {
+                            (token1, token0, t1, t0_net)
+                        } else {
+                            (token0, token1, t0_net + t0_fee + gas, t1)
+                        };
+
+                        OrderFill {
+                            order_hash: order.order_hash(&self.pairs, &self.assets, block_number),
+                            user: order.recover_signer(&self.pairs, &self.assets, block_number),
+                            is_tob: false,
+                            token_in,
+                            token_out,
+                            amount_in,
+                            amount_out,
+                            gas_fee_t0: gas,
+                            lp_fee_t0: t0_fee
+                        }
+                    });
+
+                let fills = tob_fills.chain(user_fills).collect::<Vec<_>>();
+                let user_fees_t0 = fills.iter().map(|fill| fill.lp_fee_t0).sum::<u128>();
+
+                let pool_update = self
+                    .pool_updates
+                    .iter()
+                    .find(|update| update.pair_index as usize == pair_index);
+                let donation_total_t0 = pool_update
+                    .map(|update| update.rewards_update.quantities().iter().sum::<u128>())
+                    .unwrap_or_default();
+                let user_donation = lp_user_donation(user_fees_t0);
+
+                PoolClearing {
+                    token0,
+                    token1,
+                    ucp,
+                    amm_zero_for_one: pool_update
+                        .map(|update| update.zero_for_one)
+                        .unwrap_or_default(),
+                    amm_swap_in_quantity: pool_update
+                        .map(|update| update.swap_in_quantity)
+                        .unwrap_or_default(),
+                    donation_total_t0,
+                    user_fees_t0,
+                    tob_reward_t0: donation_total_t0.saturating_sub(user_donation),
+                    fills
+                }
+            })
+            .collect()
+    }
+}
@@ -41,12 +41,22 @@ use crate::{
     uni_structure::{BaselinePoolState, donation::DonationCalculation}
 };
 
+mod history;
 mod order;
 mod tob;
+pub use history::{FillReceipt, OrderFill, PoolClearing};
 pub use order::{OrderQuantities, StandingValidation, UserOrder};
 pub use tob::*;
 
-const LP_DONATION_SPLIT: f64 = 0.75;
+/// the part of the user fees that is donated to the lps, in percent.
+const LP_DONATION_SPLIT_PCT: u128 = 75;
+/// the part of the user fees donated to the lps. integer math so that it is
+/// exact for any amount and the same wherever it is recomputed.
+fn lp_user_donation(user_fees_t0: u128) -> u128 {
+    // split before multiplying so large fees can't overflow
+    user_fees_t0 / 100 * LP_DONATION_SPLIT_PCT + user_fees_t0 % 100 * LP_DONATION_SPLIT_PCT / 100
+}
+
 // We set high for ticks
 const BASE_GAS_FOR_POOL: usize = 350_000;
 const BASE_EST_FOR_USER: usize = 90_000;
@@ -619,7 +629,7 @@ impl AngstromBundle {
         };
 
         // add user donation split
-        let total_lp_user_donate = (total_user_fees as f64 * LP_DONATION_SPLIT) as u128;
+        let total_lp_user_donate = lp_user_donation(total_user_fees);
         let save_amount = total_user_fees - total_lp_user_donate;
 
         // We then use `post_tob_price` as the start price for our book swap, just as
@@ -1287,13 +1297,23 @@ impl UniswapAngstromRegistry {
 
 #[cfg(test)]
 mod test {
-    use super::AngstromBundle;
+    use super::{AngstromBundle, lp_user_donation};
 
     #[test]
     fn can_be_constructed() {
         let _result = AngstromBundle::new(vec![], vec![], vec![], vec![], vec![]);
     }
 
+    #[test]
+    fn lp_user_donation_is_exact() {
+        assert_eq!(lp_user_donation(0), 0);
+        assert_eq!(lp_user_donation(3), 2);
+        assert_eq!(lp_user_donation(400), 300);
+        // beyond what a f64 can represent exactly
+        assert_eq!(lp_user_donation(4 * 10u128.pow(30) + 1), 3 * 10u128.pow(30));
+        assert_eq!(lp_user_donation(u128::MAX), u128::MAX / 100 * 75 + u128::MAX % 100 * 75 / 100);
+    }
+
     #[test]
     fn decode_tob_angstrom_bundle() {
         let bundle: [u8; 376] = [
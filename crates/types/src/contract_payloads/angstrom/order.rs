@@ -455,14 +455,7 @@ impl UserOrder {
             ),
             AllOrders::TOB(_) => panic!("tob found in limit orders")
         };
-        let hook_bytes = match &order.order {
-            AllOrders::PartialFlash(p_o) => p_o.hook_data.clone(),
-            AllOrders::PartialStanding(p_o) => p_o.hook_data.clone(),
-            AllOrders::ExactFlash(o) => o.hook_data.clone(),
-            AllOrders::ExactStanding(o) => o.hook_data.clone(),
-            AllOrders::TOB(_) => panic!("tob found in limit orders")
-        };
-        let hook_data = if hook_bytes.is_empty() { None } else { Some(hook_bytes) };
+        let hook_data = order.order.hook_data().cloned();
 
         let recipient = (!recipient.is_zero()).then_some(recipient);
         let gas_used: u128 = (order.priority_data.gas).to();
@@ -524,14 +517,7 @@ impl UserOrder {
             ),
             AllOrders::TOB(_) => panic!("tob found in limit orders")
         };
-        let hook_bytes = match &order.order {
-            AllOrders::PartialFlash(p_o) => p_o.hook_data.clone(),
-            AllOrders::PartialStanding(p_o) => p_o.hook_data.clone(),
-            AllOrders::ExactFlash(o) => o.hook_data.clone(),
-            AllOrders::ExactStanding(o) => o.hook_data.clone(),
-            AllOrders::TOB(_) => panic!("tob found in limit orders")
-        };
-        let hook_data = if hook_bytes.is_empty() { None } else { Some(hook_bytes) };
+        let hook_data = order.order.hook_data().cloned();
         let sig = order.order_signature().unwrap();
         let signature = Signature::from(sig);
 
@@ -9,11 +9,14 @@ pub mod matching;
 pub mod orders;
 pub mod pair_with_price;
 pub mod primitive;
+pub mod quoting;
 pub mod reth_db_provider;
 pub mod reth_db_wrapper;
+pub mod rpc_db_wrapper;
 pub mod sol_bindings;
 pub mod submission;
 pub mod testnet;
+pub mod trace_context;
 pub mod uni_structure;
 
 pub use pade::*;
@@ -1,5 +1,7 @@
 mod fillstate;
 mod origin;
+mod route;
+mod trigger;
 use alloy::primitives::{Address, B256, Signature};
 pub mod orderpool;
 
@@ -8,7 +10,9 @@ pub use fillstate::*;
 pub use orderpool::*;
 pub use origin::*;
 use pade::{PadeDecode, PadeEncode};
+pub use route::*;
 use serde::{Deserialize, Serialize};
+pub use trigger::*;
 
 pub type BookID = u128;
 pub type OrderID = u128;
@@ -220,15 +224,21 @@ impl CancelOrderRequest {
         order_id: B256,
         signer: &AngstromSigner<S>
     ) -> Self {
-        let payload = format!("canceling order: {order_id:?} for user: {user_address:?}");
+        let payload = Self::signing_message(user_address, order_id);
         let signature = signer.sign_message_sync(payload.as_bytes()).unwrap();
         let encoded: Bytes = signature.pade_encode().into();
 
         Self { signature: encoded, user_address, order_id }
     }
 
+    /// the message the user signs to cancel the order, for signers that can't
+    /// sign synchronously.
+    pub fn signing_message(user_address: Address, order_id: B256) -> String {
+        format!("canceling order: {order_id:?} for user: {user_address:?}")
+    }
+
     fn signing_payload(&self) -> String {
-        format!("canceling order: {:?} for user: {:?}", self.order_id, self.user_address)
+        Self::signing_message(self.user_address, self.order_id)
     }
 
     pub fn is_valid(&self) -> bool {
@@ -7,20 +7,82 @@ use thiserror::Error;
 mod updated_gas;
 pub use updated_gas::*;
 
+use super::TriggerCondition;
 use crate::{
-    primitive::{PoolId, UserAccountVerificationError},
+    primitive::{OrderValidationError, PoolId, UserAccountVerificationError},
     sol_bindings::{RawPoolOrder, ext::RespendAvoidanceMethod}
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum OrderStatus {
+    /// the order was included in a bundle that landed on chain, the block is
+    /// the one of the event in the history of the order
     Filled,
     Pending,
-    Blocked { token: Address, approval_needed: u128, balance_needed: u128 },
-    MissingGas { needed: u128, max_set: u128 },
+    Blocked {
+        token:           Address,
+        approval_needed: u128,
+        balance_needed:  u128
+    },
+    MissingGas {
+        needed:  u128,
+        max_set: u128
+    },
+    /// a order of the same user with the same nonce took its place. the order
+    /// is revalidated and stays parked until it is valid again
+    ParkedByNonce {
+        invalidated_by: B256
+    },
+    /// the block the order was filled in got reorged out. the order is
+    /// revalidated and put back into the pool if still valid
+    Reorged,
+    /// the user replaced the order with a new order of the same nonce
+    Replaced {
+        by: B256
+    },
+    /// a conditional order that is held until its trigger is hit
+    Armed {
+        trigger: TriggerCondition
+    },
+    /// the trigger of the conditional order was hit and the order was added to
+    /// the book
+    Triggered,
+    Cancelled,
+    Expired,
+    /// the node operator dropped the order from the pool
+    Dropped,
+    Invalid {
+        error: OrderValidationError
+    },
     OrderNotFound
 }
 
+impl OrderStatus {
+    /// if the order won't change status anymore, unless it is reorged or
+    /// revalidated.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Filled
+                | Self::Replaced { .. }
+                | Self::Cancelled
+                | Self::Expired
+                | Self::Dropped
+                | Self::Invalid { .. }
+        )
+    }
+}
+
+/// A change in status of a order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrderEvent {
+    /// the block the pool was at when the status changed
+    pub block_number: u64,
+    /// unix timestamp in milliseconds
+    pub timestamp:    u64,
+    pub status:       OrderStatus
+}
+
 impl OrderStatus {
     pub fn try_from_err(error: &UserAccountVerificationError) -> eyre::Result<Self> {
         match error {
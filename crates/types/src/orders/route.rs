@@ -0,0 +1,243 @@
+//! Multi-hop orders. A route swaps A for C through a pool that doesn't exist
+//! directly by linking per-pool orders (A->B, B->C). Every leg is a regular
+//! signed order of the user, the route only links them so that matching fills
+//! all of them or none.
+//!
+//! Each leg is validated on its own, so the user has to hold and have approved
+//! the assets in the middle of the route (B) too. A leg they can't cover is
+//! parked like any other order, which fails the whole route.
+use alloy::primitives::{Address, B256, keccak256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    matching::Ray,
+    primitive::PoolId,
+    sol_bindings::{RawPoolOrder, grouped_orders::AllOrders}
+};
+
+/// the most legs a route can have.
+pub const MAX_ROUTE_HOPS: usize = 3;
+
+/// a hop of a route through the pools of angstrom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteHop {
+    pub pool_id:   PoolId,
+    pub token_in:  Address,
+    pub token_out: Address
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutedOrder {
+    /// the orders of the route, in the order the assets are swapped
+    pub legs:           Vec<AllOrders>,
+    /// the least amount of the last asset the user accepts for the first
+    pub min_amount_out: u128
+}
+
+impl RoutedOrder {
+    /// the id of the route, derived from the hashes of its legs.
+    pub fn route_id(&self) -> B256 {
+        let hashes = self
+            .leg_hashes()
+            .into_iter()
+            .flat_map(|hash| hash.0)
+            .collect::<Vec<_>>();
+        keccak256(hashes)
+    }
+
+    pub fn leg_hashes(&self) -> Vec<B256> {
+        self.legs.iter().map(|leg| leg.order_hash()).collect()
+    }
+
+    pub fn user(&self) -> Option<Address> {
+        self.legs.first().map(|leg| leg.from())
+    }
+
+    pub fn token_in(&self) -> Option<Address> {
+        self.legs.first().map(|leg| leg.token_in())
+    }
+
+    pub fn token_out(&self) -> Option<Address> {
+        self.legs.last().map(|leg| leg.token_out())
+    }
+
+    /// checks that the legs chain into each other. Each leg has to be covered
+    /// by the least the leg before it can output, so that the route can't
+    /// leave the user short in the middle.
+    pub fn validate(&self) -> Result<(), RouteError> {
+        if self.legs.len() < 2 {
+            return Err(RouteError::TooFewLegs);
+        }
+        if self.legs.len() > MAX_ROUTE_HOPS {
+            return Err(RouteError::TooManyHops { max: MAX_ROUTE_HOPS });
+        }
+
+        let user = self.legs[0].from();
+        let mut tokens = vec![self.legs[0].token_in()];
+        let mut previous: Option<&AllOrders> = None;
+
+        for leg in &self.legs {
+            let hash = leg.order_hash();
+            if !is_exact_in(leg) {
+                return Err(RouteError::LegNotExactIn { leg: hash });
+            }
+            if leg.from() != user {
+                return Err(RouteError::MixedSigners { leg: hash });
+            }
+            if let Some(previous) = previous {
+                if leg.token_in() != previous.token_out() {
+                    return Err(RouteError::BrokenChain { leg: hash });
+                }
+                if leg.amount() > guaranteed_amount_out(previous) {
+                    return Err(RouteError::LegExceedsPreviousOut { leg: hash });
+                }
+            }
+            if tokens.contains(&leg.token_out()) {
+                return Err(RouteError::RepeatedToken { token: leg.token_out() });
+            }
+            tokens.push(leg.token_out());
+            previous = Some(leg);
+        }
+
+        if guaranteed_amount_out(&self.legs[self.legs.len() - 1]) < self.min_amount_out {
+            return Err(RouteError::MinAmountOutNotCovered);
+        }
+
+        Ok(())
+    }
+}
+
+/// only orders that fill completely and spend exactly what they say can be
+/// chained.
+fn is_exact_in(order: &AllOrders) -> bool {
+    match order {
+        AllOrders::ExactStanding(o) => o.exact_in,
+        AllOrders::ExactFlash(o) => o.exact_in,
+        _ => false
+    }
+}
+
+/// the least an exact in order outputs at its limit price, after the max gas
+/// it pays in t0.
+pub fn guaranteed_amount_out(order: &AllOrders) -> u128 {
+    let price = Ray::from(order.limit_price());
+    let gas = order.max_gas_token_0();
+
+    if order.is_bid() {
+        // t1 in, gas is taken out of the t0 that comes out
+        price.quantity(order.amount(), false).saturating_sub(gas)
+    } else {
+        // t0 in, gas is taken out of the t0 that goes in
+        price.quantity(order.amount().saturating_sub(gas), false)
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RouteError {
+    #[error("a route needs at least two legs")]
+    TooFewLegs,
+    #[error("a route can have at most {max} legs")]
+    TooManyHops { max: usize },
+    #[error("leg {leg:?} isn't an exact in order")]
+    LegNotExactIn { leg: B256 },
+    #[error("leg {leg:?} isn't signed by the same user as the first leg")]
+    MixedSigners { leg: B256 },
+    #[error("leg {leg:?} doesn't spend the asset the leg before it buys")]
+    BrokenChain { leg: B256 },
+    #[error("the route passes through {token:?} twice")]
+    RepeatedToken { token: Address },
+    #[error("leg {leg:?} spends more than the leg before it is guaranteed to output")]
+    LegExceedsPreviousOut { leg: B256 },
+    #[error("the last leg isn't guaranteed to output the min amount out")]
+    MinAmountOutNotCovered,
+    #[error("no route between the assets")]
+    NoRoute,
+    #[error("leg {leg:?} is invalid")]
+    InvalidLeg { leg: B256 },
+    #[error("order {leg:?} is a leg of a route and is only taken together with it")]
+    LegOfRoute { leg: B256 },
+    #[error("a leg of the route was filled, cancelled or expired without the others")]
+    IncompleteRoute
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+    use crate::sol_bindings::rpc_orders::{ExactFlashOrder, OrderMeta, PartialFlashOrder};
+
+    const A: Address = Address::repeat_byte(1);
+    const B: Address = Address::repeat_byte(2);
+    const C: Address = Address::repeat_byte(3);
+
+    fn leg(from: Address, asset_in: Address, asset_out: Address, amount: u128) -> AllOrders {
+        // a price of 1 in both directions
+        AllOrders::ExactFlash(ExactFlashOrder {
+            exact_in: true,
+            amount,
+            min_price: U256::from(10).pow(U256::from(27)),
+            asset_in,
+            asset_out,
+            meta: OrderMeta { from, ..Default::default() },
+            ..Default::default()
+        })
+    }
+
+    fn route(legs: Vec<AllOrders>, min_amount_out: u128) -> RoutedOrder {
+        RoutedOrder { legs, min_amount_out }
+    }
+
+    #[test]
+    fn chained_legs_are_valid() {
+        let user = Address::random();
+        let route = route(vec![leg(user, A, B, 100), leg(user, B, C, 100)], 100);
+
+        assert_eq!(route.validate(), Ok(()));
+        assert_eq!(route.token_in(), Some(A));
+        assert_eq!(route.token_out(), Some(C));
+    }
+
+    #[test]
+    fn route_id_depends_on_the_legs() {
+        let user = Address::random();
+        let a = route(vec![leg(user, A, B, 100), leg(user, B, C, 100)], 100);
+        let b = route(vec![leg(user, A, B, 100), leg(user, B, C, 90)], 90);
+
+        assert_eq!(a.route_id(), a.clone().route_id());
+        assert_ne!(a.route_id(), b.route_id());
+    }
+
+    #[test]
+    fn rejects_legs_that_dont_chain() {
+        let user = Address::random();
+
+        assert_eq!(route(vec![leg(user, A, B, 100)], 0).validate(), Err(RouteError::TooFewLegs));
+
+        let broken = route(vec![leg(user, A, B, 100), leg(user, C, A, 100)], 0);
+        assert!(matches!(broken.validate(), Err(RouteError::BrokenChain { .. })));
+
+        let circular = route(vec![leg(user, A, B, 100), leg(user, B, A, 100)], 0);
+        assert_eq!(circular.validate(), Err(RouteError::RepeatedToken { token: A }));
+
+        let other_user = route(vec![leg(user, A, B, 100), leg(Address::random(), B, C, 100)], 0);
+        assert!(matches!(other_user.validate(), Err(RouteError::MixedSigners { .. })));
+
+        let overspends = route(vec![leg(user, A, B, 100), leg(user, B, C, 101)], 0);
+        assert!(matches!(overspends.validate(), Err(RouteError::LegExceedsPreviousOut { .. })));
+
+        let short = route(vec![leg(user, A, B, 100), leg(user, B, C, 100)], 101);
+        assert_eq!(short.validate(), Err(RouteError::MinAmountOutNotCovered));
+
+        let partial = AllOrders::PartialFlash(PartialFlashOrder {
+            asset_in: B,
+            asset_out: C,
+            meta: OrderMeta { from: user, ..Default::default() },
+            ..Default::default()
+        });
+        let partial = route(vec![leg(user, A, B, 100), partial], 0);
+        assert!(matches!(partial.validate(), Err(RouteError::LegNotExactIn { .. })));
+    }
+}
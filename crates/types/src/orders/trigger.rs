@@ -0,0 +1,106 @@
+//! Conditions for conditional (stop-loss / take-profit) standing orders. The
+//! condition isn't part of the signed order, the node that received the order
+//! holds it until the pools price crosses the trigger and only then adds it to
+//! the book.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    matching::{Ray, SqrtPriceX96},
+    primitive::PoolId
+};
+
+/// the price of the pool that the trigger watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TriggerPrice {
+    /// the current sqrt price of the amm
+    AmmSqrtPrice(SqrtPriceX96),
+    /// the uniform clearing price of the book
+    Ucp(Ray)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TriggerDirection {
+    /// triggers once the price is at or above the trigger price
+    Above,
+    /// triggers once the price is at or below the trigger price
+    Below
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerCondition {
+    pub pool_id:   PoolId,
+    pub price:     TriggerPrice,
+    pub direction: TriggerDirection
+}
+
+impl TriggerCondition {
+    pub fn is_triggered(&self, observation: &PriceObservation) -> bool {
+        if observation.pool_id != self.pool_id {
+            return false;
+        }
+
+        let ordering = match self.price {
+            TriggerPrice::AmmSqrtPrice(price) => observation.amm_sqrt_price.cmp(&price),
+            // no ucp if nothing in the book crosses
+            TriggerPrice::Ucp(price) => match observation.ucp {
+                Some(ucp) => ucp.cmp(&price),
+                None => return false
+            }
+        };
+
+        match self.direction {
+            TriggerDirection::Above => ordering.is_ge(),
+            TriggerDirection::Below => ordering.is_le()
+        }
+    }
+}
+
+/// the prices of a pool at a point in time, as seen by the quoter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceObservation {
+    pub block:          u64,
+    pub pool_id:        PoolId,
+    pub amm_sqrt_price: SqrtPriceX96,
+    pub ucp:            Option<Ray>
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+
+    fn observation(pool_id: PoolId, ucp: Option<u64>) -> PriceObservation {
+        PriceObservation {
+            block: 1,
+            pool_id,
+            amm_sqrt_price: SqrtPriceX96::at_tick(0).unwrap(),
+            ucp: ucp.map(|ucp| Ray::from(U256::from(ucp)))
+        }
+    }
+
+    #[test]
+    fn triggers_on_crossing() {
+        let pool_id = PoolId::random();
+        let stop_loss = TriggerCondition {
+            pool_id,
+            price: TriggerPrice::Ucp(Ray::from(U256::from(100))),
+            direction: TriggerDirection::Below
+        };
+
+        assert!(!stop_loss.is_triggered(&observation(pool_id, None)));
+        assert!(!stop_loss.is_triggered(&observation(pool_id, Some(101))));
+        assert!(stop_loss.is_triggered(&observation(pool_id, Some(100))));
+        assert!(!stop_loss.is_triggered(&observation(PoolId::random(), Some(50))));
+
+        let take_profit = TriggerCondition {
+            pool_id,
+            price: TriggerPrice::AmmSqrtPrice(SqrtPriceX96::at_tick(-10).unwrap()),
+            direction: TriggerDirection::Above
+        };
+        assert!(take_profit.is_triggered(&observation(pool_id, None)));
+    }
+}
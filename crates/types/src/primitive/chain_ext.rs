@@ -19,6 +19,11 @@ pub trait ChainExt {
     fn successful_tip_transactions(&self) -> impl Iterator<Item = &TransactionSigned> + '_;
     fn reorged_range(&self, new: impl ChainExt) -> Option<RangeInclusive<u64>>;
     fn blocks_iter(&self) -> impl Iterator<Item = &RecoveredBlock<Block>> + '_;
+    /// the numbers of all blocks of the chain, oldest first.
+    fn block_numbers(&self) -> Vec<BlockNumber>;
+    fn block_hash(&self, number: BlockNumber) -> Option<BlockHash>;
+    fn block_transactions(&self, number: BlockNumber) -> Vec<&TransactionSigned>;
+    fn successful_block_transactions(&self, number: BlockNumber) -> Vec<&TransactionSigned>;
 }
 
 impl ChainExt for Chain {
@@ -47,6 +52,32 @@ impl ChainExt for Chain {
         self.tip().body().transactions()
     }
 
+    fn block_numbers(&self) -> Vec<BlockNumber> {
+        self.blocks().keys().copied().collect()
+    }
+
+    fn block_hash(&self, number: BlockNumber) -> Option<BlockHash> {
+        self.blocks().get(&number).map(|block| block.hash())
+    }
+
+    fn block_transactions(&self, number: BlockNumber) -> Vec<&TransactionSigned> {
+        self.blocks()
+            .get(&number)
+            .map(|block| block.body().transactions().collect())
+            .unwrap_or_default()
+    }
+
+    fn successful_block_transactions(&self, number: BlockNumber) -> Vec<&TransactionSigned> {
+        let Some(execution) = self.execution_outcome_at_block(number) else { return vec![] };
+        let receipts = execution.receipts.last().cloned().unwrap_or_default();
+
+        self.block_transactions(number)
+            .into_iter()
+            .zip(receipts)
+            .filter_map(|(tx, receipt)| receipt.success.then_some(tx))
+            .collect()
+    }
+
     fn reorged_range(&self, new: impl ChainExt) -> Option<RangeInclusive<u64>> {
         let tip = new.tip_number();
         // search 150 blocks back;
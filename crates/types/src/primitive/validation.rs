@@ -1,8 +1,8 @@
-use alloy::primitives::{Address, B256};
+use alloy::primitives::{Address, B256, U256};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::primitive::PoolId;
+use crate::{orders::RouteError, primitive::PoolId};
 
 #[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize, PartialEq, Eq)]
 pub enum OrderValidationError {
@@ -34,6 +34,20 @@ pub enum OrderValidationError {
     PriceOutOfPoolBounds,
     #[error("order was cancelled")]
     CancelledOrder,
+    #[error("no pending order with the same nonce to replace")]
+    NoOrderToReplace,
+    #[error("replaced too many orders this block, try again next block")]
+    ReplacementRateLimited,
+    #[error("only standing orders can be conditional")]
+    ConditionalOrderNotStanding,
+    #[error("order hook {hook:?} reverted or returned an invalid value in simulation")]
+    HookReverted { hook: Address },
+    #[error("order hook used {gas_used} gas which is over the cap of {gas_cap}")]
+    HookGasExceeded { gas_used: u64, gas_cap: u64 },
+    #[error("order made the bundle simulation fail")]
+    FailedBundleSimulation,
+    #[error(transparent)]
+    InvalidRoute(#[from] RouteError),
     #[error("{err}")]
     Unknown { err: String }
 }
@@ -53,8 +67,8 @@ pub enum UserAccountVerificationError {
          {requested_block}."
     )]
     BadBlock { next_block: u64, requested_block: u64 },
-    #[error("currently hooks are not supported. this field should be empty bytes")]
-    NonEmptyHook,
+    #[error("hook data must start with the 20 byte hook address")]
+    InvalidHookData,
     #[error("could not fetch, error - {err}")]
     CouldNotFetch { err: String },
     #[error("{order_hash:?} insufficient approval amounts. token {token_in:?} needs {amount} more")]
@@ -86,3 +100,19 @@ pub struct UserOrderPoolInfo {
     pub is_bid:  bool,
     pub pool_id: PoolId
 }
+
+/// A users funds of a token as the order validator sees them. The committed
+/// amounts are what the users pending orders will spend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenBalance {
+    pub token: Address,
+    /// the balance in the users wallet
+    pub balance: U256,
+    /// how much angstrom is approved to transfer out of the wallet
+    pub approval: U256,
+    /// the users internal balance held by angstrom
+    pub angstrom_balance: U256,
+    pub committed_balance: U256,
+    pub committed_approval: U256,
+    pub committed_angstrom_balance: U256
+}
@@ -0,0 +1,127 @@
+//! What the quoter hands out over the rpc, kept here so that clients of the
+//! rpc don't depend on the quoter itself.
+
+use alloy::primitives::{U160, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{orders::OrderFillState, primitive::PoolId, sol_bindings::Ray};
+
+/// the max amount of price levels that can be requested per side of the book.
+pub const MAX_DEPTH_LEVELS: usize = 500;
+/// the amount of price levels returned per side if none are specified.
+pub const DEFAULT_DEPTH_LEVELS: usize = 50;
+/// the most ticks a single price level can group, a level this wide covers
+/// the whole tick range.
+pub const MAX_TICK_GROUPING: i32 = 887_272;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct Slot0Update {
+    /// there will be 120 updates per block or per 100ms
+    pub seq_id:           u16,
+    /// in case of block lag on node
+    pub current_block:    u64,
+    pub angstrom_pool_id: PoolId,
+    pub uni_pool_id:      PoolId,
+
+    pub sqrt_price_x96: U160,
+    pub liquidity:      u128,
+    pub tick:           i32
+}
+
+/// The expected outcome of a order if it was submitted now.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderQuote {
+    pub current_block:        u64,
+    pub angstrom_pool_id:     PoolId,
+    /// how the order would be filled given the current book.
+    pub fill_state:           OrderFillState,
+    /// the amount of the order that would be filled, in the order's specified
+    /// quantity
+    pub fill_amount:          u128,
+    /// uniform clearing price of the book in ray format (t1 / t0), zero if the
+    /// book doesn't cross.
+    pub ucp:                  Ray,
+    /// the gas fee that would be charged to the order, in token0
+    pub gas_token_0:          U256,
+    pub start_sqrt_price_x96: U160,
+    pub end_sqrt_price_x96:   U160,
+    /// the amount of ticks the amm moves by. every tick is roughly a 1 bps
+    /// change in price
+    pub amm_price_impact:     i32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BookDepthRequest {
+    pub pool_id:       PoolId,
+    /// the amount of ticks that are grouped into a single price level.
+    pub tick_grouping: i32,
+    /// the amount of price levels to return per side of the book
+    pub levels:        usize
+}
+
+impl BookDepthRequest {
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.tick_grouping <= 0 || self.tick_grouping > MAX_TICK_GROUPING {
+            eyre::bail!("tick grouping must be between 1 and {MAX_TICK_GROUPING}");
+        }
+        if self.levels == 0 || self.levels > MAX_DEPTH_LEVELS {
+            eyre::bail!("levels must be between 1 and {MAX_DEPTH_LEVELS}");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    /// the lowest tick of this price level.
+    pub tick:          i32,
+    /// the price at the lowest tick of this level in ray format (t1 / t0)
+    pub price:         Ray,
+    /// amount of token0 resting in limit orders in this level
+    pub book_quantity: u128,
+    /// amount of token0 the amm will trade to move through this level
+    pub amm_quantity:  u128
+}
+
+/// Aggregated depth of a pool. Bids are sorted from the highest price down and
+/// asks from the lowest price up. All quantities are denominated in token0.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookDepth {
+    pub current_block:    u64,
+    pub angstrom_pool_id: PoolId,
+    pub tick_grouping:    i32,
+    pub current_tick:     i32,
+    pub bids:             Vec<DepthLevel>,
+    pub asks:             Vec<DepthLevel>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_request() {
+        let request = BookDepthRequest {
+            pool_id:       PoolId::default(),
+            tick_grouping: 10,
+            levels:        20
+        };
+        assert!(request.validate().is_ok());
+        assert!(
+            BookDepthRequest { tick_grouping: 0, ..request }
+                .validate()
+                .is_err()
+        );
+        assert!(
+            BookDepthRequest { tick_grouping: MAX_TICK_GROUPING + 1, ..request }
+                .validate()
+                .is_err()
+        );
+        assert!(
+            BookDepthRequest { levels: MAX_DEPTH_LEVELS + 1, ..request }
+                .validate()
+                .is_err()
+        );
+    }
+}
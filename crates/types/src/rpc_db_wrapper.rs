@@ -0,0 +1,368 @@
+//! [`revm::DatabaseRef`] backed by a external json-rpc node. Used instead of
+//! [`RethDbWrapper`](crate::reth_db_wrapper::RethDbWrapper) when angstrom runs
+//! as a sidecar. Accounts are loaded with `eth_getProof` and storage with
+//! `eth_getStorageAt`, both pinned to the block set through [`SetBlock`].
+//! Loaded state is cached until the block changes.
+use std::{
+    future::{Future, IntoFuture},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU64, Ordering}
+    }
+};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::{Address, B256, BlockNumber, KECCAK256_EMPTY, U256},
+    providers::Provider
+};
+use dashmap::DashMap;
+use reth_chainspec::ChainInfo;
+use reth_provider::{BlockHashReader, BlockNumReader, ProviderError, ProviderResult};
+use revm::state::AccountInfo;
+use revm_bytecode::Bytecode;
+use tokio::runtime::{Runtime, RuntimeFlavor};
+
+use crate::reth_db_wrapper::{DBError, SetBlock};
+
+/// the amount of blocks below the current block that cached block hashes are
+/// kept for. anything newer can still be reorged.
+const BLOCK_HASH_REORG_DEPTH: u64 = 64;
+
+/// drives the requests made from a current thread runtime, which can't be
+/// blocked in place.
+static REQUEST_RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("rpc-db")
+        .enable_all()
+        .build()
+        .expect("failed to build the rpc db runtime")
+});
+
+#[derive(Debug, Default)]
+struct RpcDbCache {
+    accounts:     DashMap<Address, Option<AccountInfo>>,
+    storage:      DashMap<(Address, U256), U256>,
+    /// code never changes for a hash so this is kept across blocks
+    code:         DashMap<B256, Bytecode>,
+    block_hashes: DashMap<u64, B256>
+}
+
+pub struct RpcDbWrapper<P> {
+    provider: Arc<P>,
+    block:    Arc<AtomicU64>,
+    cache:    Arc<RpcDbCache>,
+    /// used to drive requests when called from outside of the runtime
+    runtime:  tokio::runtime::Handle
+}
+
+impl<P> Clone for RpcDbWrapper<P> {
+    fn clone(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            block:    self.block.clone(),
+            cache:    self.cache.clone(),
+            runtime:  self.runtime.clone()
+        }
+    }
+}
+
+impl<P: Provider + 'static> RpcDbWrapper<P> {
+    /// needs to be created from within a tokio runtime.
+    pub fn new(provider: Arc<P>, block: u64) -> Self {
+        Self {
+            provider,
+            block: Arc::new(block.into()),
+            cache: Default::default(),
+            runtime: tokio::runtime::Handle::current()
+        }
+    }
+
+    fn block(&self) -> u64 {
+        self.block.load(Ordering::Relaxed)
+    }
+
+    fn block_on<F>(&self, f: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send
+    {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(f))
+            }
+            // block_in_place panics on a current thread runtime and blocking it would stall
+            // whatever it drives, so the request is driven from a thread outside of it
+            Ok(_) => std::thread::scope(|scope| {
+                scope
+                    .spawn(|| REQUEST_RUNTIME.block_on(f))
+                    .join()
+                    .expect("rpc db request panicked")
+            }),
+            Err(_) => self.runtime.block_on(f)
+        }
+    }
+
+    fn fetch_account(&self, address: Address, block: u64) -> Result<Option<AccountInfo>, DBError> {
+        let proof = self.block_on(
+            self.provider
+                .get_proof(address, vec![])
+                .block_id(block.into())
+                .into_future()
+        )?;
+
+        let has_code = proof.code_hash != KECCAK256_EMPTY && proof.code_hash != B256::ZERO;
+        if !has_code && proof.nonce == 0 && proof.balance.is_zero() {
+            return Ok(None);
+        }
+
+        let code = if has_code {
+            match self.cache.code.get(&proof.code_hash) {
+                Some(code) => code.clone(),
+                None => {
+                    let bytes = self.block_on(
+                        self.provider
+                            .get_code_at(address)
+                            .block_id(block.into())
+                            .into_future()
+                    )?;
+                    let code = Bytecode::new_raw(bytes);
+                    self.cache.code.insert(proof.code_hash, code.clone());
+                    code
+                }
+            }
+        } else {
+            Bytecode::default()
+        };
+
+        Ok(Some(AccountInfo {
+            code_hash: if has_code { proof.code_hash } else { KECCAK256_EMPTY },
+            balance:   proof.balance,
+            nonce:     proof.nonce,
+            code:      Some(code)
+        }))
+    }
+
+    fn fetch_block_hash(&self, number: u64) -> Result<Option<B256>, DBError> {
+        if let Some(hash) = self.cache.block_hashes.get(&number) {
+            return Ok(Some(*hash));
+        }
+
+        let block = self.block_on(
+            self.provider
+                .get_block_by_number(BlockNumberOrTag::Number(number))
+                .into_future()
+        )?;
+        let Some(hash) = block.map(|block| block.header.hash) else { return Ok(None) };
+        self.cache.block_hashes.insert(number, hash);
+
+        Ok(Some(hash))
+    }
+}
+
+impl<P: Provider + 'static> SetBlock for RpcDbWrapper<P> {
+    fn set_block(&self, block: u64) {
+        if self.block.swap(block, Ordering::Relaxed) == block {
+            return;
+        }
+
+        self.cache.accounts.clear();
+        self.cache.storage.clear();
+        self.cache
+            .block_hashes
+            .retain(|number, _| number + BLOCK_HASH_REORG_DEPTH < block);
+    }
+}
+
+impl<P: Provider + 'static> revm::DatabaseRef for RpcDbWrapper<P> {
+    type Error = DBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.cache.accounts.get(&address) {
+            return Ok(account.clone());
+        }
+
+        let block = self.block();
+        let account = self.fetch_account(address, block)?;
+        // don't cache state of a block we are no longer on
+        if self.block() == block {
+            self.cache.accounts.insert(address, account.clone());
+        }
+
+        Ok(account)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.cache
+            .code
+            .get(&code_hash)
+            .map(|code| code.clone())
+            .ok_or_else(|| DBError::String(format!("code for {code_hash} was never loaded")))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.cache.storage.get(&(address, index)) {
+            return Ok(*value);
+        }
+
+        let block = self.block();
+        let value = self.block_on(
+            self.provider
+                .get_storage_at(address, index)
+                .block_id(block.into())
+                .into_future()
+        )?;
+        if self.block() == block {
+            self.cache.storage.insert((address, index), value);
+        }
+
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        Ok(self.fetch_block_hash(number)?.unwrap_or_default())
+    }
+}
+
+impl<P: Provider + 'static> BlockHashReader for RpcDbWrapper<P> {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.fetch_block_hash(number)
+            .map_err(|_| ProviderError::HeaderNotFound(number.into()))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber
+    ) -> ProviderResult<Vec<B256>> {
+        (start..end)
+            .map(|number| {
+                self.block_hash(number)?
+                    .ok_or(ProviderError::HeaderNotFound(number.into()))
+            })
+            .collect()
+    }
+}
+
+impl<P: Provider + 'static> BlockNumReader for RpcDbWrapper<P> {
+    fn chain_info(&self) -> ProviderResult<ChainInfo> {
+        let block = self
+            .block_on(self.provider.get_block(BlockId::latest()).into_future())
+            .ok()
+            .flatten()
+            .ok_or(ProviderError::BestBlockNotFound)?;
+
+        Ok(ChainInfo { best_hash: block.header.hash, best_number: block.header.number })
+    }
+
+    fn best_block_number(&self) -> ProviderResult<BlockNumber> {
+        self.block_on(self.provider.get_block_number().into_future())
+            .map_err(|_| ProviderError::BestBlockNotFound)
+    }
+
+    fn last_block_number(&self) -> ProviderResult<BlockNumber> {
+        self.best_block_number()
+    }
+
+    fn block_number(&self, hash: B256) -> ProviderResult<Option<BlockNumber>> {
+        self.block_on(self.provider.get_block_by_hash(hash).into_future())
+            .map(|block| block.map(|block| block.header.number))
+            .map_err(|_| ProviderError::BlockHashNotFound(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        node_bindings::Anvil,
+        primitives::{bytes, keccak256},
+        providers::{ProviderBuilder, ext::AnvilApi}
+    };
+    use revm::DatabaseRef;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reads_state_of_the_set_block_from_anvil() {
+        let anvil = Anvil::new().try_spawn().unwrap();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .connect(&anvil.endpoint())
+                .await
+                .unwrap()
+        );
+
+        let contract = Address::with_last_byte(0xaa);
+        let code = bytes!("0x60006000f3");
+        let slot = U256::from(3);
+        provider
+            .anvil_set_code(contract, code.clone())
+            .await
+            .unwrap();
+        provider
+            .anvil_set_balance(contract, U256::from(1_000))
+            .await
+            .unwrap();
+        provider
+            .anvil_set_storage_at(contract, slot, B256::with_last_byte(1))
+            .await
+            .unwrap();
+        provider.anvil_mine(Some(1), None).await.unwrap();
+        let first = provider.get_block_number().await.unwrap();
+
+        provider
+            .anvil_set_storage_at(contract, slot, B256::with_last_byte(2))
+            .await
+            .unwrap();
+        provider.anvil_mine(Some(1), None).await.unwrap();
+        let second = provider.get_block_number().await.unwrap();
+
+        let db = RpcDbWrapper::new(provider.clone(), first);
+        let account = db.basic_ref(contract).unwrap().expect("account exists");
+        assert_eq!(account.balance, U256::from(1_000));
+        assert_eq!(account.code_hash, keccak256(&code));
+        assert_eq!(
+            db.code_by_hash_ref(account.code_hash)
+                .unwrap()
+                .original_bytes(),
+            code
+        );
+        assert_eq!(db.basic_ref(Address::with_last_byte(0xbb)).unwrap(), None);
+        assert_eq!(db.storage_ref(contract, slot).unwrap(), U256::from(1));
+
+        db.set_block(second);
+        assert_eq!(db.storage_ref(contract, slot).unwrap(), U256::from(2));
+
+        let block = provider
+            .get_block_by_number(BlockNumberOrTag::Number(first))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(db.block_hash_ref(first).unwrap(), block.header.hash);
+        assert_eq!(db.best_block_number().unwrap(), second);
+        assert_eq!(db.block_number(block.header.hash).unwrap(), Some(first));
+    }
+
+    #[tokio::test]
+    async fn reads_state_from_a_current_thread_runtime() {
+        let anvil = Anvil::new().try_spawn().unwrap();
+        let setup = ProviderBuilder::new()
+            .connect(&anvil.endpoint())
+            .await
+            .unwrap();
+        let account = Address::with_last_byte(0xaa);
+        setup
+            .anvil_set_balance(account, U256::from(1_000))
+            .await
+            .unwrap();
+        setup.anvil_mine(Some(1), None).await.unwrap();
+        let block = setup.get_block_number().await.unwrap();
+
+        let provider = Arc::new(ProviderBuilder::new().connect_http(anvil.endpoint_url()));
+        let db = RpcDbWrapper::new(provider, block);
+        let account = db.basic_ref(account).unwrap().expect("account exists");
+        assert_eq!(account.balance, U256::from(1_000));
+        assert_eq!(db.best_block_number().unwrap(), block);
+    }
+}
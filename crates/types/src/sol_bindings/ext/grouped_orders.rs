@@ -1,14 +1,14 @@
 use std::{hash::Hash, ops::Deref};
 
 use alloy::{
-    primitives::{Address, FixedBytes, TxHash, U256},
+    primitives::{Address, Bytes, FixedBytes, TxHash, U256},
     signers::Signature
 };
 use alloy_primitives::B256;
 use pade::PadeDecode;
 use serde::{Deserialize, Serialize};
 
-use super::{GenerateFlippedOrder, RawPoolOrder, RespendAvoidanceMethod};
+use super::{GenerateFlippedOrder, RawPoolOrder, RespendAvoidanceMethod, hooks::OrderHook};
 use crate::{
     matching::Ray,
     orders::{OrderId, OrderLocation, OrderPriorityData},
@@ -54,6 +54,19 @@ impl AllOrders {
             Self::TOB(_) => false
         }
     }
+
+    /// the raw hook data of the order, none if the order has no hook set.
+    pub fn hook_data(&self) -> Option<&Bytes> {
+        let hook_data = match self {
+            Self::ExactStanding(p) => &p.hook_data,
+            Self::PartialStanding(p) => &p.hook_data,
+            Self::ExactFlash(p) => &p.hook_data,
+            Self::PartialFlash(p) => &p.hook_data,
+            Self::TOB(_) => return None
+        };
+
+        (!hook_data.is_empty()).then_some(hook_data)
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -346,6 +359,10 @@ impl RawPoolOrder for PartialStandingOrder {
         !self.hook_data.is_empty()
     }
 
+    fn hook(&self) -> Option<OrderHook> {
+        OrderHook::from_hook_data(&self.hook_data)
+    }
+
     fn min_amount(&self) -> u128 {
         self.min_amount_in
     }
@@ -473,6 +490,10 @@ impl RawPoolOrder for ExactStandingOrder {
         !self.hook_data.is_empty()
     }
 
+    fn hook(&self) -> Option<OrderHook> {
+        OrderHook::from_hook_data(&self.hook_data)
+    }
+
     fn min_amount(&self) -> u128 {
         self.amount
     }
@@ -580,6 +601,10 @@ impl RawPoolOrder for PartialFlashOrder {
         !self.hook_data.is_empty()
     }
 
+    fn hook(&self) -> Option<OrderHook> {
+        OrderHook::from_hook_data(&self.hook_data)
+    }
+
     fn min_amount(&self) -> u128 {
         self.min_amount_in
     }
@@ -707,6 +732,10 @@ impl RawPoolOrder for ExactFlashOrder {
         !self.hook_data.is_empty()
     }
 
+    fn hook(&self) -> Option<OrderHook> {
+        OrderHook::from_hook_data(&self.hook_data)
+    }
+
     fn min_amount(&self) -> u128 {
         self.amount
     }
@@ -808,6 +837,16 @@ impl RawPoolOrder for AllOrders {
         }
     }
 
+    fn hook(&self) -> Option<OrderHook> {
+        match self {
+            AllOrders::ExactStanding(p) => p.hook(),
+            AllOrders::PartialStanding(p) => p.hook(),
+            AllOrders::ExactFlash(p) => p.hook(),
+            AllOrders::PartialFlash(p) => p.hook(),
+            AllOrders::TOB(t) => t.hook()
+        }
+    }
+
     fn min_amount(&self) -> u128 {
         match self {
             AllOrders::ExactStanding(p) => p.min_amount(),
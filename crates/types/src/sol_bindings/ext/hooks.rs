@@ -0,0 +1,87 @@
+//! Order hooks. A hook is packed into an order's `hook_data` as the 20 byte
+//! hook address followed by an arbitrary payload. After the order is settled,
+//! Angstrom calls `compose(from, payload)` on the hook address and expects
+//! [`EXPECTED_HOOK_RETURN_MAGIC`] back, otherwise the whole bundle reverts.
+use alloy::primitives::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+
+/// `keccak256("Angstrom.hook.return-magic")[-4:]`
+pub const EXPECTED_HOOK_RETURN_MAGIC: u32 = 0x24a2e44b;
+
+alloy::sol! {
+    interface IAngstromComposable {
+        function compose(address from, bytes calldata payload) external returns (uint32);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderHook {
+    pub address: Address,
+    pub payload: Bytes
+}
+
+impl OrderHook {
+    /// decodes the hook out of the raw `hook_data` of an order. returns none if
+    /// the data is to short to contain the hook address.
+    pub fn from_hook_data(hook_data: &Bytes) -> Option<Self> {
+        if hook_data.len() < Address::len_bytes() {
+            return None;
+        }
+        let (address, payload) = hook_data.split_at(Address::len_bytes());
+
+        Some(Self {
+            address: Address::from_slice(address),
+            payload: Bytes::copy_from_slice(payload)
+        })
+    }
+
+    /// the calldata angstrom will use when triggering the hook for the given
+    /// order signer.
+    pub fn compose_calldata(&self, from: Address) -> Bytes {
+        use alloy::sol_types::SolCall;
+
+        IAngstromComposable::composeCall { from, payload: self.payload.clone() }
+            .abi_encode()
+            .into()
+    }
+
+    /// checks that the hook returned the magic value angstrom expects.
+    pub fn is_valid_return(output: &[u8]) -> bool {
+        use alloy::sol_types::SolCall;
+
+        IAngstromComposable::composeCall::abi_decode_returns(output)
+            .map(|magic| magic == EXPECTED_HOOK_RETURN_MAGIC)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::primitives::{address, bytes};
+
+    use super::*;
+
+    #[test]
+    fn decodes_address_and_payload() {
+        let data = bytes!("0x00000000000000000000000000000000000000aadeadbeef");
+        let hook = OrderHook::from_hook_data(&data).unwrap();
+
+        assert_eq!(hook.address, address!("0x00000000000000000000000000000000000000aa"));
+        assert_eq!(hook.payload, bytes!("0xdeadbeef"));
+    }
+
+    #[test]
+    fn rejects_short_hook_data() {
+        let data = bytes!("0xdeadbeef");
+        assert!(OrderHook::from_hook_data(&data).is_none());
+    }
+
+    #[test]
+    fn checks_return_magic() {
+        let mut output = [0u8; 32];
+        output[28..].copy_from_slice(&EXPECTED_HOOK_RETURN_MAGIC.to_be_bytes());
+        assert!(OrderHook::is_valid_return(&output));
+        assert!(!OrderHook::is_valid_return(&[0u8; 32]));
+        assert!(!OrderHook::is_valid_return(&[]));
+    }
+}
@@ -5,10 +5,14 @@ use alloy::primitives::{Address, FixedBytes, TxHash, U256};
 use alloy_primitives::Signature;
 use serde::{Deserialize, Serialize};
 
-use crate::{orders::OrderLocation, sol_bindings::Ray};
+use crate::{
+    orders::OrderLocation,
+    sol_bindings::{Ray, ext::hooks::OrderHook}
+};
 
 pub mod flips;
 pub mod grouped_orders;
+pub mod hooks;
 
 pub trait RawPoolOrder: fmt::Debug + Send + Sync + Clone + Unpin + 'static {
     fn max_gas_token_0(&self) -> u128;
@@ -109,6 +113,12 @@ pub trait RawPoolOrder: fmt::Debug + Send + Sync + Clone + Unpin + 'static {
     fn exact_in(&self) -> bool;
 
     fn has_hook(&self) -> bool;
+
+    /// the decoded hook of the order. none if there is no hook or the hook data
+    /// is malformed.
+    fn hook(&self) -> Option<OrderHook> {
+        None
+    }
 }
 
 pub trait GenerateFlippedOrder: Send + Sync + Clone + Unpin + 'static {
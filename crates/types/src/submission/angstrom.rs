@@ -35,6 +35,10 @@ impl AngstromSubmitter {
 }
 
 impl ChainSubmitter for AngstromSubmitter {
+    fn name(&self) -> &'static str {
+        "angstrom"
+    }
+
     fn angstrom_address(&self) -> Address {
         self.angstrom_address
     }
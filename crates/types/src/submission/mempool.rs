@@ -30,6 +30,10 @@ impl MempoolSubmitter {
 }
 
 impl ChainSubmitter for MempoolSubmitter {
+    fn name(&self) -> &'static str {
+        "mempool"
+    }
+
     fn angstrom_address(&self) -> Address {
         self.angstrom_address
     }
@@ -52,6 +52,10 @@ impl MevBoostSubmitter {
 }
 
 impl ChainSubmitter for MevBoostSubmitter {
+    fn name(&self) -> &'static str {
+        "mev_boost"
+    }
+
     fn angstrom_address(&self) -> Address {
         self.angstrom_address
     }
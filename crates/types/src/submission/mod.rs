@@ -14,6 +14,7 @@ use alloy::{
 };
 use alloy_primitives::TxHash;
 use angstrom::AngstromSubmitter;
+use dashmap::DashMap;
 use futures::StreamExt;
 use mempool::MempoolSubmitter;
 use mev_boost::MevBoostSubmitter;
@@ -43,6 +44,9 @@ pub struct TxFeatureInfo {
 /// a chain submitter is a trait that deals with submitting a bundle to the
 /// different configured endpoints.
 pub trait ChainSubmitter: Send + Sync + Unpin + 'static {
+    /// unique name of the submitter, used to toggle it at runtime.
+    fn name(&self) -> &'static str;
+
     fn angstrom_address(&self) -> Address;
 
     fn submit<'a, S: AngstromMetaSigner>(
@@ -115,12 +119,43 @@ pub trait ChainSubmitter: Send + Sync + Unpin + 'static {
     }
 }
 
+/// Runtime switches for the submitters of a [`SubmissionHandler`], keyed by
+/// [`ChainSubmitter::name`]. Submitters without an entry are enabled.
+#[derive(Debug, Clone, Default)]
+pub struct SubmitterToggles(Arc<DashMap<&'static str, bool>>);
+
+impl SubmitterToggles {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.0.get(name).is_none_or(|enabled| *enabled)
+    }
+
+    /// returns false if there is no submitter with the given name.
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> bool {
+        let Some(mut entry) = self.0.get_mut(name) else { return false };
+        *entry = enabled;
+
+        true
+    }
+
+    pub fn all(&self) -> Vec<(&'static str, bool)> {
+        self.0
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect()
+    }
+
+    fn register(&self, name: &'static str) {
+        self.0.entry(name).or_insert(true);
+    }
+}
+
 pub struct SubmissionHandler<P>
 where
     P: Provider + 'static
 {
     pub node_provider: Arc<P>,
-    pub submitters:    Vec<Box<dyn ChainSubmitterWrapper>>
+    pub submitters:    Vec<Box<dyn ChainSubmitterWrapper>>,
+    pub toggles:       SubmitterToggles
 }
 
 impl<P> Deref for SubmissionHandler<P>
@@ -159,7 +194,13 @@ where
             signer
         )) as Box<dyn ChainSubmitterWrapper>;
 
-        Self { node_provider, submitters: vec![mempool, angstrom, mev_boost] }
+        let submitters = vec![mempool, angstrom, mev_boost];
+        let toggles = SubmitterToggles::default();
+        submitters
+            .iter()
+            .for_each(|submitter| toggles.register(submitter.name()));
+
+        Self { node_provider, submitters, toggles }
     }
 
     pub async fn submit_tx<S: AngstromMetaSigner>(
@@ -194,6 +235,10 @@ where
 
         let mut futs = Vec::new();
         for submitter in &self.submitters {
+            if !self.toggles.is_enabled(submitter.name()) {
+                tracing::debug!(submitter = submitter.name(), "skipping disabled submitter");
+                continue;
+            }
             futs.push(submitter.submit(bundle.as_ref(), &tx_features));
         }
         let mut buffered_futs = futures::stream::iter(futs).buffer_unordered(10);
@@ -219,6 +264,8 @@ impl<I: ChainSubmitter, S: AngstromMetaSigner> ChainSubmitterHolder<I, S> {
 }
 
 pub trait ChainSubmitterWrapper: Send + Sync + Unpin + 'static {
+    fn name(&self) -> &'static str;
+
     fn angstrom_address(&self) -> Address;
 
     fn submit<'a>(
@@ -231,6 +278,10 @@ pub trait ChainSubmitterWrapper: Send + Sync + Unpin + 'static {
 impl<I: ChainSubmitter, S: AngstromMetaSigner + 'static> ChainSubmitterWrapper
     for ChainSubmitterHolder<I, S>
 {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
     fn angstrom_address(&self) -> Address {
         self.0.angstrom_address()
     }
@@ -243,3 +294,26 @@ impl<I: ChainSubmitter, S: AngstromMetaSigner + 'static> ChainSubmitterWrapper
         self.0.submit(&self.1, bundle, tx_features)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_registered_submitters_can_be_toggled() {
+        let toggles = SubmitterToggles::default();
+        toggles.register("mempool");
+
+        assert!(!toggles.set_enabled("angstrom", false));
+        assert!(toggles.is_enabled("angstrom"));
+
+        assert!(toggles.set_enabled("mempool", false));
+        assert!(!toggles.is_enabled("mempool"));
+        // shared with the handler
+        assert!(!toggles.clone().is_enabled("mempool"));
+        assert_eq!(toggles.all(), vec![("mempool", false)]);
+
+        assert!(toggles.set_enabled("mempool", true));
+        assert!(toggles.is_enabled("mempool"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Lets the trace of an order continue across the tasks and nodes it passes
+//! through. The spans are only linked when the node exports its traces and
+//! installed a [`TracePropagator`], without one every context is `None` and
+//! the `order_hash` field of the spans is what ties them together.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::OnceLock
+};
+
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+
+static PROPAGATOR: OnceLock<Box<dyn TracePropagator>> = OnceLock::new();
+
+/// the w3c trace context keys, the only ones taken from peers.
+const PEER_KEYS: [&str; 2] = ["traceparent", "tracestate"];
+/// the longest value taken from a peer, the w3c `tracestate` is capped at 512
+/// bytes and a `traceparent` is far shorter.
+const MAX_PEER_VALUE_LEN: usize = 512;
+
+/// Moves the context of a span in and out of a [`TraceContext`], implemented
+/// by whatever exports the traces.
+pub trait TracePropagator: Send + Sync + 'static {
+    /// writes the context of the span into the carrier.
+    fn inject(&self, span: &Span, context: &mut TraceContext);
+    /// makes the span a child of the span the context was taken from.
+    fn set_parent(&self, span: &Span, context: &TraceContext);
+}
+
+/// installs the propagator used for all trace contexts, returns false if one
+/// was already installed.
+pub fn set_trace_propagator(propagator: impl TracePropagator) -> bool {
+    PROPAGATOR.set(Box::new(propagator)).is_ok()
+}
+
+/// The context of a span in a form that can be held on to and sent to other
+/// nodes, e.g. its w3c `traceparent`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TraceContext(BTreeMap<String, String>);
+
+impl TraceContext {
+    /// the context of the span, `None` when there is no propagator or the
+    /// span isn't traced.
+    pub fn of(span: &Span) -> Option<Self> {
+        let propagator = PROPAGATOR.get()?;
+        let mut context = Self::default();
+        propagator.inject(span, &mut context);
+
+        (!context.0.is_empty()).then_some(context)
+    }
+
+    pub fn current() -> Option<Self> {
+        Self::of(&Span::current())
+    }
+
+    /// continues the trace this context was taken from in the span.
+    pub fn set_as_parent_of(&self, span: &Span) {
+        if let Some(propagator) = PROPAGATOR.get() {
+            propagator.set_parent(span, self);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: String, value: String) {
+        self.0.insert(key, value);
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// the context as it can be taken from a peer, only the w3c
+    /// `traceparent` and `tracestate` are kept and only if they aren't
+    /// oversized. `None` if nothing is left.
+    pub fn sanitized(mut self) -> Option<Self> {
+        self.0.retain(|key, value| {
+            PEER_KEYS.contains(&key.as_str()) && value.len() <= MAX_PEER_VALUE_LEN
+        });
+
+        (!self.0.is_empty()).then_some(self)
+    }
+}
+
+/// The trace context of the orders a node has seen, so that the later steps
+/// of an order (consensus, submission) join its trace. Holds at most `cap`
+/// orders, the oldest are dropped first.
+#[derive(Debug, Clone)]
+pub struct OrderTraces {
+    cap:      usize,
+    contexts: HashMap<B256, TraceContext>,
+    inserted: VecDeque<B256>
+}
+
+impl OrderTraces {
+    pub fn new(cap: usize) -> Self {
+        Self { cap, contexts: HashMap::default(), inserted: VecDeque::default() }
+    }
+
+    /// records the context of the current span for the order, the first
+    /// context recorded for an order is kept.
+    pub fn record_current(&mut self, order_hash: B256) {
+        if self.contexts.contains_key(&order_hash) {
+            return
+        }
+        let Some(context) = TraceContext::current() else { return };
+
+        if self.inserted.len() == self.cap {
+            if let Some(oldest) = self.inserted.pop_front() {
+                self.contexts.remove(&oldest);
+            }
+        }
+        self.inserted.push_back(order_hash);
+        self.contexts.insert(order_hash, context);
+    }
+
+    pub fn get(&self, order_hash: &B256) -> Option<&TraceContext> {
+        self.contexts.get(order_hash)
+    }
+
+    /// makes the span part of the trace of the order, if it has one.
+    pub fn set_parent_of(&self, span: &Span, order_hash: &B256) {
+        if let Some(context) = self.get(order_hash) {
+            context.set_as_parent_of(span);
+        }
+    }
+}
+
+impl Default for OrderTraces {
+    fn default() -> Self {
+        Self::new(100_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedPropagator;
+
+    impl TracePropagator for FixedPropagator {
+        fn inject(&self, _: &Span, context: &mut TraceContext) {
+            context.set("traceparent".to_string(), "00-1-1-01".to_string());
+        }
+
+        fn set_parent(&self, _: &Span, _: &TraceContext) {}
+    }
+
+    #[test]
+    fn keeps_the_traces_of_the_latest_orders() {
+        set_trace_propagator(FixedPropagator);
+
+        let mut traces = OrderTraces::new(2);
+        let orders = [B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3)];
+        for order in orders {
+            traces.record_current(order);
+        }
+        // recording an order twice doesn't push out another one
+        traces.record_current(orders[2]);
+
+        assert!(traces.get(&orders[0]).is_none());
+        assert_eq!(traces.get(&orders[1]).and_then(|c| c.get("traceparent")), Some("00-1-1-01"));
+        assert!(traces.get(&orders[2]).is_some());
+    }
+
+    #[test]
+    fn only_keeps_the_w3c_context_of_peers() {
+        let mut context = TraceContext::default();
+        context.set("traceparent".to_string(), "00-1-1-01".to_string());
+        context.set("tracestate".to_string(), "x".repeat(MAX_PEER_VALUE_LEN + 1));
+        context.set("baggage".to_string(), "user=1".to_string());
+
+        let context = context.sanitized().unwrap();
+        assert_eq!(context.keys().collect::<Vec<_>>(), vec!["traceparent"]);
+        assert_eq!(context.get("traceparent"), Some("00-1-1-01"));
+
+        let mut junk = TraceContext::default();
+        for i in 0..1000 {
+            junk.set(i.to_string(), "x".repeat(1024));
+        }
+        assert_eq!(junk.sanitized(), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Persisted checkpoint of the discovered angstrom pools. Discovering pools
+//! means replaying every `PoolConfigured` and `PoolRemoved` log since
+//! angstrom was deployed, so we save the set of pools at a block and only scan
+//! the blocks after it on startup. While running the checkpoint is kept up to
+//! date with the pools the eth manager sees being configured or removed.
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf}
+};
+
+use alloy_primitives::BlockNumber;
+use angstrom_eth::manager::EthEvent;
+use angstrom_types::{contract_bindings::angstrom::Angstrom::PoolKey, primitive::MAX_REORG_DEPTH};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum PoolChange {
+    Added(PoolKey),
+    Removed(PoolKey)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolRegistryCheckpoint {
+    /// the block that pools have been discovered up to, inclusive.
+    pub block:                  BlockNumber,
+    pub pools:                  HashSet<PoolKey>,
+    /// the pool changes of the last [`MAX_REORG_DEPTH`] blocks, so they can be
+    /// undone if their block is reorged.
+    #[serde(default)]
+    pub(crate) recent_changes:  Vec<(BlockNumber, PoolChange)>,
+    /// changes of the block that is being processed, they are assigned to it
+    /// once its transitions come in.
+    #[serde(skip)]
+    pub(crate) pending_changes: Vec<PoolChange>
+}
+
+impl PoolRegistryCheckpoint {
+    /// a checkpoint to discover all pools from the block angstrom was deployed
+    /// at.
+    pub fn at_deploy(deploy_block: BlockNumber) -> Self {
+        Self { block: deploy_block.saturating_sub(1), ..Default::default() }
+    }
+
+    /// loads the checkpoint saved at the path, `None` if there isn't one yet.
+    pub fn load(path: &Path) -> eyre::Result<Option<Self>> {
+        if !path.try_exists()? {
+            return Ok(None);
+        }
+
+        let checkpoint = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        Ok(Some(checkpoint))
+    }
+
+    /// writes the checkpoint to a temp file first so a crash mid write can't
+    /// corrupt the previous checkpoint.
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        // the rename is only atomic if the contents hit the disk before it
+        writer.into_inner()?.sync_all()?;
+        std::fs::rename(tmp, path)?;
+
+        Ok(())
+    }
+
+    pub fn pool_keys(&self) -> Vec<PoolKey> {
+        self.pools.iter().cloned().collect()
+    }
+
+    /// applies a eth event, returning true if the checkpoint changed.
+    pub fn apply(&mut self, event: &EthEvent) -> bool {
+        match event {
+            EthEvent::NewPool { pool } => {
+                self.pending_changes.push(PoolChange::Added(pool.clone()));
+                self.pools.insert(pool.clone())
+            }
+            EthEvent::RemovedPool { pool } => {
+                self.pending_changes.push(PoolChange::Removed(pool.clone()));
+                self.pools.remove(pool)
+            }
+            EthEvent::NewBlockTransitions { block_number, .. } if *block_number > self.block => {
+                self.commit_pending(*block_number);
+                self.block = *block_number;
+                true
+            }
+            EthEvent::ReorgedOrders(_, range) => self.reorg(range),
+            _ => false
+        }
+    }
+
+    /// the pool changes of the new tip come in before the reorg itself, so the
+    /// reorged changes are undone first and the new ones applied on top again.
+    fn reorg(&mut self, range: &RangeInclusive<BlockNumber>) -> bool {
+        let (reorged, kept) = std::mem::take(&mut self.recent_changes)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(block, _)| block >= range.start());
+        self.recent_changes = kept;
+        let changed =
+            *range.start() <= self.block || !reorged.is_empty() || !self.pending_changes.is_empty();
+
+        for (_, change) in reorged.into_iter().rev() {
+            match change {
+                PoolChange::Added(pool) => self.pools.remove(&pool),
+                PoolChange::Removed(pool) => self.pools.insert(pool)
+            };
+        }
+        for change in &self.pending_changes {
+            match change {
+                PoolChange::Added(pool) => self.pools.insert(pool.clone()),
+                PoolChange::Removed(pool) => self.pools.remove(pool)
+            };
+        }
+        // the new tip isn't part of the event, the last reorged block is the
+        // closest we know of.
+        self.commit_pending(*range.end());
+        // make sure the reorged blocks get rescanned on the next startup.
+        self.block = self.block.min(range.start().saturating_sub(1));
+
+        changed
+    }
+
+    fn commit_pending(&mut self, block_number: BlockNumber) {
+        self.recent_changes.extend(
+            self.pending_changes
+                .drain(..)
+                .map(|change| (block_number, change))
+        );
+        self.recent_changes
+            .retain(|(block, _)| block + MAX_REORG_DEPTH >= block_number);
+    }
+}
+
+/// keeps the checkpoint at the path up to date with the eth events until the
+/// stream ends.
+pub async fn track_pool_registry(
+    path: PathBuf,
+    mut checkpoint: PoolRegistryCheckpoint,
+    mut events: impl Stream<Item = EthEvent> + Unpin
+) {
+    while let Some(event) = events.next().await {
+        if !checkpoint.apply(&event) {
+            continue;
+        }
+        if let Err(error) = checkpoint.save(&path) {
+            tracing::error!(%error, path = %path.display(), "failed to save pool registry checkpoint");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, aliases::U24};
+
+    use super::*;
+
+    fn pool(fee: u32) -> PoolKey {
+        PoolKey { currency0: Address::ZERO, fee: U24::from(fee), ..Default::default() }
+    }
+
+    #[test]
+    fn applies_pool_events() {
+        let mut checkpoint = PoolRegistryCheckpoint::at_deploy(10);
+        assert_eq!(checkpoint.block, 9);
+
+        assert!(checkpoint.apply(&EthEvent::NewPool { pool: pool(1) }));
+        assert!(checkpoint.apply(&EthEvent::NewPool { pool: pool(2) }));
+        assert!(checkpoint.apply(&EthEvent::RemovedPool { pool: pool(1) }));
+        assert!(checkpoint.apply(&EthEvent::NewBlockTransitions {
+            block_number:      20,
+            filled_orders:     vec![],
+            fill_receipts:     vec![],
+            address_changeset: vec![]
+        }));
+        assert_eq!(checkpoint.pool_keys(), vec![pool(2)]);
+        assert_eq!(checkpoint.block, 20);
+
+        assert!(checkpoint.apply(&EthEvent::ReorgedOrders(vec![], 18..=20)));
+        assert_eq!(checkpoint.block, 17);
+        assert!(!checkpoint.apply(&EthEvent::NewBlock(21)));
+    }
+
+    fn transition(block_number: u64) -> EthEvent {
+        EthEvent::NewBlockTransitions {
+            block_number,
+            filled_orders: vec![],
+            fill_receipts: vec![],
+            address_changeset: vec![]
+        }
+    }
+
+    #[test]
+    fn reorg_undoes_pool_changes_of_reorged_blocks() {
+        let mut checkpoint = PoolRegistryCheckpoint {
+            block: 10,
+            pools: HashSet::from([pool(1), pool(2)]),
+            ..Default::default()
+        };
+        checkpoint.apply(&EthEvent::NewPool { pool: pool(3) });
+        checkpoint.apply(&EthEvent::RemovedPool { pool: pool(1) });
+        checkpoint.apply(&transition(11));
+        checkpoint.apply(&EthEvent::NewPool { pool: pool(4) });
+        checkpoint.apply(&transition(12));
+        assert_eq!(checkpoint.pools, HashSet::from([pool(2), pool(3), pool(4)]));
+
+        // the new tip configures pool 4 as well, which comes in before the reorg
+        checkpoint.apply(&EthEvent::NewPool { pool: pool(4) });
+        assert!(checkpoint.apply(&EthEvent::ReorgedOrders(vec![], 11..=12)));
+        assert_eq!(checkpoint.pools, HashSet::from([pool(1), pool(2), pool(4)]));
+        assert_eq!(checkpoint.block, 10);
+
+        // pool 4 now belongs to the new chain, so reorging that drops it again
+        assert!(checkpoint.apply(&EthEvent::ReorgedOrders(vec![], 12..=12)));
+        assert_eq!(checkpoint.pools, HashSet::from([pool(1), pool(2)]));
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let path =
+            std::env::temp_dir().join(format!("pool-checkpoint-{}.json", rand::random::<u64>()));
+        let checkpoint = PoolRegistryCheckpoint {
+            block: 5,
+            pools: HashSet::from([pool(3)]),
+            ..Default::default()
+        };
+
+        assert_eq!(PoolRegistryCheckpoint::load(&path).unwrap(), None);
+        checkpoint.save(&path).unwrap();
+        assert_eq!(PoolRegistryCheckpoint::load(&path).unwrap(), Some(checkpoint));
+        let _ = std::fs::remove_file(path);
+    }
+}
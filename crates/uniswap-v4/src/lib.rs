@@ -1,9 +1,21 @@
-use std::{collections::HashSet, pin::Pin, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc
+};
 
 pub const DEFAULT_TICKS: u16 = 400;
 
 use alloy::{
-    consensus::TxReceipt, primitives::aliases::I24, providers::Provider, sol_types::SolEvent
+    consensus::TxReceipt,
+    primitives::{
+        Log, U256,
+        aliases::{I24, U24}
+    },
+    providers::Provider,
+    rpc::types::Filter,
+    sol_types::SolEvent
 };
 use alloy_primitives::{Address, BlockNumber, FixedBytes};
 use angstrom_eth::manager::EthEvent;
@@ -11,8 +23,9 @@ use angstrom_types::{
     block_sync::BlockSyncConsumer,
     contract_bindings::{
         angstrom::Angstrom::PoolKey,
-        controller_v_1::ControllerV1::{PoolConfigured, PoolRemoved}
+        controller_v_1::ControllerV1::{self, PoolConfigured, PoolRemoved}
     },
+    contract_payloads::angstrom::AngstromPoolConfigStore,
     primitive::UniswapPoolRegistry
 };
 use futures::Stream;
@@ -23,9 +36,12 @@ use reth_provider::{
 };
 use uniswap::pool_factory::V4PoolFactory;
 
-use crate::uniswap::{
-    pool_data_loader::DataLoader, pool_manager::UniswapPoolManager,
-    pool_providers::canonical_state_adapter::CanonicalStateAdapter
+use crate::{
+    checkpoint::PoolRegistryCheckpoint,
+    uniswap::{
+        pool_data_loader::DataLoader, pool_manager::UniswapPoolManager,
+        pool_providers::canonical_state_adapter::CanonicalStateAdapter
+    }
 };
 
 /// This module should have information on all the Constant Function Market
@@ -33,6 +49,8 @@ use crate::uniswap::{
 /// ever any others they will be added here
 pub mod uniswap;
 
+pub mod checkpoint;
+
 ///  name           type    slot   offset  bytes    contract
 /// _controller | address| 0    | 0      | 20    | src/Angstrom.sol:Angstrom |
 /// We use this so that we are able to historically go back from the current
@@ -41,25 +59,29 @@ pub mod uniswap;
 /// means that this will be secure in the case of new deployments
 const CONTROLLER_ADDRESS_SLOT: FixedBytes<32> = FixedBytes::<32>::ZERO;
 
-/// Goes from the deploy block to the current block fetching all pools.
+/// the max amount of blocks requested per `eth_getLogs` call. most node
+/// providers reject larger ranges.
+pub(crate) const RPC_LOG_RANGE: u64 = 10_000;
+
+/// Goes from the block after the checkpoint to the end block, applying all
+/// the pools that were configured or removed to the checkpoint.
 pub async fn fetch_angstrom_pools<DB>(
-    // the block angstrom was deployed at
-    deploy_block: usize,
-    end_block: usize,
+    mut checkpoint: PoolRegistryCheckpoint,
+    end_block: u64,
     angstrom_address: Address,
     db: &DB
-) -> Vec<PoolKey>
+) -> PoolRegistryCheckpoint
 where
     DB: DatabaseProviderFactory + ReceiptProvider,
     <DB as DatabaseProviderFactory>::Provider: TryIntoHistoricalStateProvider
 {
-    let logs = (deploy_block..=end_block)
+    let logs = (checkpoint.block + 1..=end_block)
         .into_par_iter()
         .flat_map(|block| {
             let storage_provider = db
                 .database_provider_ro()
                 .unwrap()
-                .try_into_history_at_block(block as u64)
+                .try_into_history_at_block(block)
                 .unwrap();
 
             let controller_addr = Address::from_word(FixedBytes::new(
@@ -70,7 +92,7 @@ where
                     .to_be_bytes::<32>()
             ));
 
-            db.receipts_by_block((block as u64).into())
+            db.receipts_by_block(block.into())
                 .unwrap()
                 .unwrap_or_default()
                 .into_iter()
@@ -80,43 +102,126 @@ where
         })
         .collect::<Vec<_>>();
 
-    logs.into_iter()
-        .fold(HashSet::new(), |mut set, log| {
-            if let Ok(pool) = PoolConfigured::decode_log(&log) {
-                let pool_key = PoolKey {
-                    currency0:   pool.asset0,
-                    currency1:   pool.asset1,
-                    fee:         pool.bundleFee,
-                    tickSpacing: I24::try_from_be_slice(&{
-                        let bytes = pool.tickSpacing.to_be_bytes();
-                        let mut a = [0u8; 3];
-                        a[1..3].copy_from_slice(&bytes);
-                        a
-                    })
-                    .unwrap(),
-                    hooks:       angstrom_address
-                };
+    apply_pool_logs(&mut checkpoint.pools, angstrom_address, &logs);
+    checkpoint.block = checkpoint.block.max(end_block);
+    checkpoint
+}
 
-                set.insert(pool_key);
-                return set;
+/// Same as [`fetch_angstrom_pools`] but over json-rpc. Used when running as a
+/// sidecar to a external node.
+pub async fn fetch_angstrom_pools_from_rpc<P: Provider>(
+    mut checkpoint: PoolRegistryCheckpoint,
+    end_block: u64,
+    angstrom_address: Address,
+    provider: &P
+) -> eyre::Result<PoolRegistryCheckpoint> {
+    let mut logs = vec![];
+    let mut controllers = HashMap::new();
+
+    for from_block in (checkpoint.block + 1..=end_block).step_by(RPC_LOG_RANGE as usize) {
+        let filter = Filter::new()
+            .from_block(from_block)
+            .to_block(std::cmp::min(from_block + RPC_LOG_RANGE - 1, end_block))
+            .event_signature(vec![PoolConfigured::SIGNATURE_HASH, PoolRemoved::SIGNATURE_HASH]);
+
+        for log in provider.get_logs(&filter).await? {
+            let Some(block) = log.block_number else { continue };
+            // the controller is upgradable so we check it was the controller when the
+            // log was emitted.
+            let controller = match controllers.get(&block) {
+                Some(controller) => *controller,
+                None => {
+                    let slot = provider
+                        .get_storage_at(angstrom_address, CONTROLLER_ADDRESS_SLOT.into())
+                        .block_id(block.into())
+                        .await?;
+                    let controller = Address::from_word(slot.into());
+                    controllers.insert(block, controller);
+                    controller
+                }
+            };
+
+            if log.address() == controller {
+                logs.push(log.into_inner());
             }
+        }
+    }
 
-            if let Ok(pool) = PoolRemoved::decode_log(&log) {
-                let pool_key = PoolKey {
-                    currency0:   pool.asset0,
-                    currency1:   pool.asset1,
-                    fee:         pool.feeInE6,
-                    tickSpacing: pool.tickSpacing,
-                    hooks:       angstrom_address
-                };
+    apply_pool_logs(&mut checkpoint.pools, angstrom_address, &logs);
+    checkpoint.block = checkpoint.block.max(end_block);
+    Ok(checkpoint)
+}
 
-                set.remove(&pool_key);
-                return set;
-            }
-            set
-        })
-        .into_iter()
-        .collect::<Vec<_>>()
+/// Loads the pools that are currently configured straight from the controller
+/// contract's storage instead of going through the chain history. The config
+/// store needs to be loaded at the same block.
+pub async fn fetch_angstrom_pools_from_controller<P: Provider>(
+    controller_address: Address,
+    angstrom_address: Address,
+    config_store: &AngstromPoolConfigStore,
+    block: u64,
+    provider: &P
+) -> eyre::Result<PoolRegistryCheckpoint> {
+    let controller = ControllerV1::new(controller_address, provider);
+    let total_pools = controller.totalPools().block(block.into()).call().await?;
+
+    let mut pools = HashSet::new();
+    for index in 0..total_pools.to::<u64>() {
+        let pool = controller
+            .getPoolByIndex(U256::from(index))
+            .block(block.into())
+            .call()
+            .await?;
+        let entry = config_store
+            .get_entry(pool.asset0, pool.asset1)
+            .ok_or_else(|| eyre::eyre!("no config for pool {}/{}", pool.asset0, pool.asset1))?;
+
+        pools.insert(PoolKey {
+            currency0:   pool.asset0,
+            currency1:   pool.asset1,
+            fee:         U24::from(entry.fee_in_e6),
+            tickSpacing: I24::unchecked_from(entry.tick_spacing),
+            hooks:       angstrom_address
+        });
+    }
+
+    Ok(PoolRegistryCheckpoint { block, pools, ..Default::default() })
+}
+
+/// applies the pool configured and removed logs of the controller in order.
+fn apply_pool_logs(pools: &mut HashSet<PoolKey>, angstrom_address: Address, logs: &[Log]) {
+    for log in logs {
+        if let Ok(pool) = PoolConfigured::decode_log(log) {
+            let pool_key = PoolKey {
+                currency0:   pool.asset0,
+                currency1:   pool.asset1,
+                fee:         pool.bundleFee,
+                tickSpacing: I24::try_from_be_slice(&{
+                    let bytes = pool.tickSpacing.to_be_bytes();
+                    let mut a = [0u8; 3];
+                    a[1..3].copy_from_slice(&bytes);
+                    a
+                })
+                .unwrap(),
+                hooks:       angstrom_address
+            };
+
+            pools.insert(pool_key);
+            continue;
+        }
+
+        if let Ok(pool) = PoolRemoved::decode_log(log) {
+            let pool_key = PoolKey {
+                currency0:   pool.asset0,
+                currency1:   pool.asset1,
+                fee:         pool.feeInE6,
+                tickSpacing: pool.tickSpacing,
+                hooks:       angstrom_address
+            };
+
+            pools.remove(&pool_key);
+        }
+    }
 }
 
 pub async fn configure_uniswap_manager<BlockSync: BlockSyncConsumer, const TICKS: u16>(
@@ -126,7 +231,8 @@ pub async fn configure_uniswap_manager<BlockSync: BlockSyncConsumer, const TICKS
     current_block: BlockNumber,
     block_sync: BlockSync,
     pool_manager_address: Address,
-    update_stream: Pin<Box<dyn Stream<Item = EthEvent> + Send + Sync>>
+    update_stream: Pin<Box<dyn Stream<Item = EthEvent> + Send + Sync>>,
+    snapshot_path: Option<PathBuf>
 ) -> UniswapPoolManager<
     CanonicalStateAdapter<impl Provider + 'static>,
     impl Provider + 'static,
@@ -137,7 +243,8 @@ pub async fn configure_uniswap_manager<BlockSync: BlockSyncConsumer, const TICKS
         provider.clone(),
         uniswap_pool_registry,
         pool_manager_address
-    );
+    )
+    .with_snapshot_path(snapshot_path);
 
     let notifier =
         Arc::new(CanonicalStateAdapter::new(state_notification, provider.clone(), current_block));
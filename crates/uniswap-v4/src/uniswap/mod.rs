@@ -10,6 +10,7 @@ pub mod pool_data_loader;
 pub mod pool_factory;
 pub mod pool_manager;
 pub mod pool_providers;
+pub mod pool_snapshot;
 pub mod tob;
 
 const MIN_I24: i32 = -8_388_608_i32;
@@ -3,7 +3,7 @@ use std::{cmp::Ordering, collections::HashMap, fmt::Debug, ops::Rem, sync::Arc};
 // use std::iter::
 use alloy::{
     hex,
-    primitives::{Address, B256, BlockNumber, I256, U256, aliases::I24},
+    primitives::{Address, B256, BlockNumber, I256, Log, U256, aliases::I24},
     providers::Provider,
     transports::Transport
 };
@@ -19,7 +19,9 @@ use uniswap_v3_math::{
     tick_math::{MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK}
 };
 
-use super::{pool_data_loader::PoolData, pool_manager::TickRangeToLoad};
+use super::{
+    pool_data_loader::PoolData, pool_manager::TickRangeToLoad, pool_snapshot::PoolStateSnapshot
+};
 use crate::uniswap::{
     ConversionError, i32_to_i24,
     pool_data_loader::{DataLoader, PoolDataLoader, TickData}
@@ -538,6 +540,98 @@ where
         !(self.token0.is_zero() || self.token1.is_zero())
     }
 
+    pub fn snapshot_state(&self) -> PoolStateSnapshot {
+        PoolStateSnapshot {
+            token0:          self.token0,
+            token0_decimals: self.token0_decimals,
+            token1:          self.token1,
+            token1_decimals: self.token1_decimals,
+            liquidity:       self.liquidity,
+            liquidity_net:   self.liquidity_net,
+            sqrt_price_x96:  self.sqrt_price_x96,
+            tick:            self.tick,
+            tick_spacing:    self.tick_spacing,
+            tick_bitmap:     self.tick_bitmap.clone(),
+            ticks:           self.ticks.clone()
+        }
+    }
+
+    /// restores the pool to the state of a snapshot taken at the block. the
+    /// fee isn't part of the snapshot as it comes from the pool registry.
+    pub fn restore_state(&mut self, snapshot: PoolStateSnapshot, block_number: BlockNumber) {
+        self.token0 = snapshot.token0;
+        self.token0_decimals = snapshot.token0_decimals;
+        self.token1 = snapshot.token1;
+        self.token1_decimals = snapshot.token1_decimals;
+        self.liquidity = snapshot.liquidity;
+        self.liquidity_net = snapshot.liquidity_net;
+        self.sqrt_price_x96 = snapshot.sqrt_price_x96;
+        self.tick = snapshot.tick;
+        self.tick_spacing = snapshot.tick_spacing;
+        self.tick_bitmap = snapshot.tick_bitmap;
+        self.ticks = snapshot.ticks;
+        self.book_fee = self.data_loader.pool_fee();
+        self.block_number = block_number;
+    }
+
+    /// replays the swap and modify liquidity logs of this pool on top of its
+    /// current state. the logs need to be in the order they were emitted.
+    pub fn apply_logs(&mut self, logs: &[Log]) -> Result<(), PoolError> {
+        for log in logs {
+            if Loader::is_modify_position_event(log) {
+                let event = Loader::decode_modify_position_event(log)?;
+                self.modify_position(event.tick_lower, event.tick_upper, event.liquidity_delta)?;
+            } else if Loader::is_swap_event(log) {
+                let event = Loader::decode_swap_event(log)?;
+                self.sqrt_price_x96 = event.sqrt_price_x96;
+                self.liquidity = event.liquidity;
+                self.tick = event.tick;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn modify_position(
+        &mut self,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity_delta: i128
+    ) -> Result<(), PoolError> {
+        if liquidity_delta == 0 {
+            return Ok(());
+        }
+
+        // ticks past the ones we have loaded are missing their existing liquidity,
+        // so we leave them to be loaded from chain when a swap reaches them.
+        let loaded = self
+            .ticks
+            .keys()
+            .min()
+            .copied()
+            .zip(self.ticks.keys().max().copied());
+        for (tick, upper) in [(tick_lower, false), (tick_upper, true)] {
+            if !loaded.is_some_and(|(lowest, highest)| (lowest..=highest).contains(&tick)) {
+                continue;
+            }
+            if self.update_tick(tick, liquidity_delta, upper) {
+                self.flip_tick(tick, self.tick_spacing);
+                if liquidity_delta < 0 {
+                    self.ticks.remove(&tick);
+                }
+            }
+        }
+
+        if (tick_lower..tick_upper).contains(&self.tick) {
+            self.liquidity = self
+                .liquidity
+                .checked_add_signed(liquidity_delta)
+                .ok_or(SwapSimulationError::LiquidityUnderflow)?;
+        }
+
+        Ok(())
+    }
+
     #[cfg(test)]
     pub fn update_position(&mut self, tick_lower: i32, tick_upper: i32, liquidity_delta: i128) {
         let mut flipped_lower = false;
@@ -738,7 +832,7 @@ pub trait TickSpaceFill: Iterator<Item = TickData> {
 mod tests {
     use std::sync::Once;
 
-    use alloy::primitives::Log;
+    use alloy::sol_types::SolEvent;
     use tracing_subscriber::{EnvFilter, fmt};
     use uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick;
 
@@ -812,6 +906,12 @@ mod tests {
             unimplemented!()
         }
 
+        fn decode_modify_position_event(
+            _: &Log
+        ) -> Result<pool_data_loader::ModifyPositionEvent, PoolError> {
+            unimplemented!()
+        }
+
         fn pool_fee(&self) -> u32 {
             0
         }
@@ -988,6 +1088,58 @@ mod tests {
         assert!(matches!(result, Err(SwapSimulationError::InvalidSqrtPriceLimit)));
     }
 
+    #[test]
+    fn test_apply_logs() {
+        setup_tracing();
+        let mut pool = EnhancedUniswapPool::<DataLoader>::default();
+        pool.tick = 0;
+        pool.tick_spacing = 60;
+        pool.liquidity = 1000;
+        pool.update_position(-120, 120, 1000);
+
+        let log = |data: alloy::primitives::LogData| Log { address: Address::ZERO, data };
+        let modify = |tick_lower: i32, tick_upper: i32, delta: i64| {
+            log(pool_data_loader::IUniswapV4Pool::ModifyLiquidity {
+                id:             B256::ZERO,
+                sender:         Address::ZERO,
+                tickLower:      I24::unchecked_from(tick_lower),
+                tickUpper:      I24::unchecked_from(tick_upper),
+                liquidityDelta: I256::try_from(delta).unwrap(),
+                salt:           B256::ZERO
+            }
+            .encode_log_data())
+        };
+        let swap = log(pool_data_loader::IUniswapV4Pool::Swap {
+            id:           B256::ZERO,
+            sender:       Address::ZERO,
+            amount0:      0,
+            amount1:      0,
+            sqrtPriceX96: get_sqrt_ratio_at_tick(90).unwrap().to(),
+            liquidity:    1500,
+            tick:         I24::unchecked_from(90),
+            fee:          Default::default()
+        }
+        .encode_log_data());
+
+        pool.apply_logs(&[
+            modify(-60, 60, 500),
+            // outside of the loaded ticks so only the lower tick is applied
+            modify(60, 600, 200),
+            swap
+        ])
+        .unwrap();
+
+        assert_eq!(pool.ticks[&-60].liquidity_net, 500);
+        assert_eq!(pool.ticks[&60].liquidity_net, -300);
+        assert!(!pool.ticks.contains_key(&600));
+        assert_eq!(pool.liquidity, 1500);
+        assert_eq!(pool.tick, 90);
+
+        // removing it all clears the ticks again
+        pool.apply_logs(&[modify(-60, 60, -500)]).unwrap();
+        assert!(!pool.ticks.contains_key(&-60));
+    }
+
     #[test]
     fn test_fetch_pool_snapshot() {
         setup_tracing();
@@ -155,6 +155,7 @@ pub trait PoolDataLoader: Clone {
     fn is_swap_event(log: &Log) -> bool;
     fn is_modify_position_event(log: &Log) -> bool;
     fn decode_swap_event(log: &Log) -> Result<SwapEvent, PoolError>;
+    fn decode_modify_position_event(log: &Log) -> Result<ModifyPositionEvent, PoolError>;
 }
 
 impl DataLoader {
@@ -310,4 +311,16 @@ impl PoolDataLoader for DataLoader {
             tick:           swap_event.tick.as_i32()
         })
     }
+
+    fn decode_modify_position_event(log: &Log) -> Result<ModifyPositionEvent, PoolError> {
+        let modify_event = IUniswapV4Pool::ModifyLiquidity::decode_log(log)?;
+        Ok(ModifyPositionEvent {
+            sender:          modify_event.sender,
+            tick_lower:      modify_event.tickLower.as_i32(),
+            tick_upper:      modify_event.tickUpper.as_i32(),
+            liquidity_delta: modify_event.liquidityDelta.try_into().map_err(|_| {
+                PoolError::Eyre(eyre::eyre!("liquidity delta does not fit in a i128"))
+            })?
+        })
+    }
 }
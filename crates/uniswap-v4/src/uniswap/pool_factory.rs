@@ -1,8 +1,13 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc
+};
 
 use alloy::{
     primitives::{Address, aliases::U24},
-    providers::Provider
+    providers::Provider,
+    rpc::types::Filter
 };
 use angstrom_types::{
     contract_bindings::angstrom::Angstrom::PoolKey,
@@ -10,51 +15,129 @@ use angstrom_types::{
 };
 use futures::future::join_all;
 
-use super::{pool::EnhancedUniswapPool, pool_data_loader::PoolDataLoader};
-use crate::DataLoader;
+use super::{
+    pool::EnhancedUniswapPool, pool_data_loader::PoolDataLoader, pool_snapshot::PoolSnapshots
+};
+use crate::{DataLoader, RPC_LOG_RANGE};
 
 pub const INITIAL_TICKS_PER_SIDE: u16 = 400;
 
 #[derive(Clone)]
 pub struct V4PoolFactory<P, const TICKS: u16 = INITIAL_TICKS_PER_SIDE> {
-    provider:     Arc<P>,
-    registry:     UniswapPoolRegistry,
-    pool_manager: Address
+    provider:      Arc<P>,
+    registry:      UniswapPoolRegistry,
+    pool_manager:  Address,
+    snapshot_path: Option<PathBuf>
 }
 impl<P: Provider + 'static, const TICKS: u16> V4PoolFactory<P, TICKS>
 where
     DataLoader: PoolDataLoader
 {
     pub fn new(provider: Arc<P>, registry: UniswapPoolRegistry, pool_manager: Address) -> Self {
-        Self { provider, registry, pool_manager }
+        Self { provider, registry, pool_manager, snapshot_path: None }
+    }
+
+    /// restores the pools from the snapshots saved at the path on init.
+    pub fn with_snapshot_path(mut self, snapshot_path: Option<PathBuf>) -> Self {
+        self.snapshot_path = snapshot_path;
+        self
+    }
+
+    pub fn snapshot_path(&self) -> Option<&Path> {
+        self.snapshot_path.as_deref()
     }
 
     /// inits all uniswap pools found in [`UniswapPoolRegistry`]
     pub async fn init(&self, block: u64) -> Vec<EnhancedUniswapPool<DataLoader>> {
-        join_all(self.registry.pools().keys().map(async |pool_id| {
-            let internal = self
-                .registry
-                .conversion_map
-                .get(pool_id)
-                .expect("factory conversion map failure");
-
-            let mut pool = EnhancedUniswapPool::new(
-                DataLoader::new_with_registry(
-                    *internal,
-                    *pool_id,
-                    self.registry.clone(),
-                    self.pool_manager
-                ),
-                TICKS
-            );
-            pool.initialize(Some(block), self.provider.clone())
-                .await
-                .expect("failed to init pool");
-            pool
+        let mut restored = self.restore_pools(block).await.unwrap_or_else(|error| {
+            tracing::warn!(%error, "failed to restore pools from snapshot, loading them from chain");
+            HashMap::new()
+        });
+
+        join_all(self.registry.pools().keys().map(|pool_id| {
+            let restored = restored.remove(pool_id);
+            async move {
+                if let Some(pool) = restored {
+                    return pool;
+                }
+
+                let internal = self
+                    .registry
+                    .conversion_map
+                    .get(pool_id)
+                    .expect("factory conversion map failure");
+
+                let mut pool = self.new_pool(*pool_id, *internal);
+                pool.initialize(Some(block), self.provider.clone())
+                    .await
+                    .expect("failed to init pool");
+                pool
+            }
         }))
         .await
     }
 
+    /// restores the pools that have a snapshot and catches them up to the
+    /// block by replaying the pool manager logs since the snapshot was taken.
+    async fn restore_pools(
+        &self,
+        block: u64
+    ) -> eyre::Result<HashMap<PoolId, EnhancedUniswapPool<DataLoader>>> {
+        let Some(path) = self.snapshot_path() else { return Ok(HashMap::new()) };
+        let Some(snapshots) = PoolSnapshots::load(path)? else { return Ok(HashMap::new()) };
+        if snapshots.block > block {
+            eyre::bail!("snapshot at block {} is ahead of block {block}", snapshots.block);
+        }
+
+        let mut logs = Vec::new();
+        for from_block in (snapshots.block + 1..=block).step_by(RPC_LOG_RANGE as usize) {
+            let filter = Filter::new()
+                .address(self.pool_manager)
+                .from_block(from_block)
+                .to_block(std::cmp::min(from_block + RPC_LOG_RANGE - 1, block))
+                .event_signature(DataLoader::event_signatures());
+            logs.extend(
+                self.provider
+                    .get_logs(&filter)
+                    .await?
+                    .into_iter()
+                    .map(|log| log.into_inner())
+            );
+        }
+        // grouped by the private pool id as thats what the pool manager emits
+        let mut logs = DataLoader::group_logs(logs);
+
+        let mut pools = HashMap::new();
+        for (pool_id, snapshot) in snapshots.pools {
+            // the pool was removed since the snapshot
+            let Some(internal) = self.registry.conversion_map.get(&pool_id) else { continue };
+
+            let mut pool = self.new_pool(pool_id, *internal);
+            pool.restore_state(snapshot, block);
+            pool.apply_logs(&logs.remove(internal).unwrap_or_default())?;
+            pools.insert(pool_id, pool);
+        }
+        tracing::info!(
+            restored = pools.len(),
+            snapshot_block = snapshots.block,
+            "restored pools from snapshot"
+        );
+
+        Ok(pools)
+    }
+
+    fn new_pool(&self, public: PoolId, private: PoolId) -> EnhancedUniswapPool<DataLoader> {
+        EnhancedUniswapPool::new(
+            DataLoader::new_with_registry(
+                private,
+                public,
+                self.registry.clone(),
+                self.pool_manager
+            ),
+            TICKS
+        )
+    }
+
     pub fn current_pool_keys(&self) -> Vec<PoolKey> {
         self.registry.pools.values().cloned().collect()
     }
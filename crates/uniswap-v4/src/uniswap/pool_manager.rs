@@ -26,11 +26,15 @@ use arraydeque::ArrayDeque;
 use dashmap::DashMap;
 use futures::Stream;
 use futures_util::{StreamExt, stream::BoxStream};
+use reth_tasks::shutdown::GracefulShutdown;
 use telemetry_recorder::telemetry_event;
 use thiserror::Error;
 use tokio::sync::Notify;
 
-use super::{pool::PoolError, pool_factory::V4PoolFactory, pool_providers::PoolMangerBlocks};
+use super::{
+    pool::PoolError, pool_factory::V4PoolFactory, pool_providers::PoolMangerBlocks,
+    pool_snapshot::PoolSnapshots
+};
 use crate::uniswap::{
     pool::EnhancedUniswapPool,
     pool_data_loader::{DataLoader, PoolDataLoader},
@@ -43,6 +47,9 @@ pub type SyncedUniswapPool<Loader = DataLoader> = Arc<RwLock<EnhancedUniswapPool
 
 const MODULE_NAME: &str = "UniswapV4";
 
+/// how often, in blocks, the pool state is saved when snapshots are enabled.
+const SNAPSHOT_INTERVAL: u64 = 50;
+
 #[derive(Debug, Clone, Copy)]
 pub struct TickRangeToLoad {
     pub pool_id:    PoolId,
@@ -276,6 +283,10 @@ where
         );
 
         self.latest_synced_block = chain_head_block_number;
+        if self.latest_synced_block % SNAPSHOT_INTERVAL == 0 {
+            // the write goes through fsync, keep it off the poll.
+            drop(self.save_snapshots());
+        }
 
         telemetry_event!(
             self.latest_synced_block,
@@ -295,6 +306,46 @@ where
         }
     }
 
+    /// copies the state of all pools and writes it to disk on the blocking
+    /// pool if snapshots are enabled.
+    fn save_snapshots(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let path = self.factory.snapshot_path()?.to_path_buf();
+        let snapshots = PoolSnapshots {
+            block: self.latest_synced_block,
+            pools: self
+                .pools
+                .iter()
+                .filter_map(|pool| {
+                    let pool = pool.value().read().expect("lock busted");
+                    pool.data_is_populated()
+                        .then(|| (pool.public_address(), pool.snapshot_state()))
+                })
+                .collect()
+        };
+
+        Some(tokio::task::spawn_blocking(move || {
+            if let Err(error) = snapshots.save(&path) {
+                tracing::error!(%error, path = %path.display(), "failed to save pool snapshots");
+            }
+        }))
+    }
+
+    pub async fn run_until_graceful_shutdown(mut self, shutdown: GracefulShutdown) {
+        let mut graceful_guard = None;
+        tokio::select! {
+            _ = &mut self => {},
+            guard = shutdown => {
+                graceful_guard = Some(guard);
+            },
+        }
+        tracing::info!("uniswap pool manager shutting down...");
+        if let Some(save) = self.save_snapshots() {
+            let _ = save.await;
+        }
+
+        drop(graceful_guard);
+    }
+
     fn pool_update_workaround(block_number: u64, pools: SyncedUniswapPools, provider: Arc<P>) {
         tracing::info!("starting poll");
         for pool in pools.pools.iter() {
@@ -0,0 +1,64 @@
+//! Persisted state of the uniswap pools. Loading the ticks of every pool
+//! through the tick data helper contract is the slowest part of startup, so
+//! the pool manager saves the state of its pools every so often and on
+//! shutdown. On the next start the pools are restored from the snapshot and
+//! caught up by replaying the `ModifyLiquidity` and `Swap` logs since it was
+//! taken.
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path
+};
+
+use alloy::primitives::{Address, BlockNumber, U256};
+use angstrom_types::{matching::uniswap::TickInfo, primitive::PoolId};
+use serde::{Deserialize, Serialize};
+
+/// the state of a single pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolStateSnapshot {
+    pub token0:          Address,
+    pub token0_decimals: u8,
+    pub token1:          Address,
+    pub token1_decimals: u8,
+    pub liquidity:       u128,
+    pub liquidity_net:   i128,
+    pub sqrt_price_x96:  U256,
+    pub tick:            i32,
+    pub tick_spacing:    i32,
+    pub tick_bitmap:     HashMap<i16, U256>,
+    pub ticks:           HashMap<i32, TickInfo>
+}
+
+/// the state of all the pools at a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolSnapshots {
+    pub block: BlockNumber,
+    /// keyed by the public pool id
+    pub pools: HashMap<PoolId, PoolStateSnapshot>
+}
+
+impl PoolSnapshots {
+    /// loads the snapshots saved at the path, `None` if there aren't any yet.
+    pub fn load(path: &Path) -> eyre::Result<Option<Self>> {
+        if !path.try_exists()? {
+            return Ok(None);
+        }
+
+        let snapshots = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        Ok(Some(snapshots))
+    }
+
+    /// writes the snapshots to a temp file first so a crash mid write can't
+    /// corrupt the previous snapshots.
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.into_inner()?.sync_all()?;
+        std::fs::rename(tmp, path)?;
+
+        Ok(())
+    }
+}
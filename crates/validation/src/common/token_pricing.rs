@@ -8,7 +8,10 @@ use alloy::{
     providers::Provider
 };
 use angstrom_types::{
-    matching::SqrtPriceX96, orders::UpdatedGas, pair_with_price::PairsWithPrice, primitive::PoolId,
+    matching::SqrtPriceX96,
+    orders::{RouteHop, UpdatedGas},
+    pair_with_price::PairsWithPrice,
+    primitive::PoolId,
     sol_bindings::Ray
 };
 use futures::StreamExt;
@@ -480,6 +483,67 @@ impl TokenPriceGenerator {
         self.pair_to_pool.clone()
     }
 
+    /// the route with the least hops from `token_in` to `token_out`, at most
+    /// `max_hops` long. Ties go to the lower token addresses so that every
+    /// node finds the same route.
+    pub fn find_route(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        max_hops: usize
+    ) -> Option<Vec<RouteHop>> {
+        if token_in == token_out {
+            return None;
+        }
+
+        let mut neighbours: HashMap<Address, Vec<(Address, PoolId)>> = HashMap::default();
+        for (&(token0, token1), &pool_id) in &self.pair_to_pool {
+            neighbours
+                .entry(token0)
+                .or_default()
+                .push((token1, pool_id));
+            neighbours
+                .entry(token1)
+                .or_default()
+                .push((token0, pool_id));
+        }
+        neighbours.values_mut().for_each(|tokens| {
+            tokens.sort_unstable();
+            tokens.dedup();
+        });
+
+        // the token and pool each reached token was reached from
+        let mut reached_from: HashMap<Address, (Address, PoolId)> = HashMap::default();
+        let mut frontier = vec![token_in];
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for token in frontier {
+                for &(neighbour, pool_id) in neighbours.get(&token).into_iter().flatten() {
+                    if neighbour == token_in || reached_from.contains_key(&neighbour) {
+                        continue;
+                    }
+                    reached_from.insert(neighbour, (token, pool_id));
+                    next.push(neighbour);
+                }
+            }
+
+            if reached_from.contains_key(&token_out) {
+                let mut hops = Vec::new();
+                let mut token = token_out;
+                while let Some(&(from, pool_id)) = reached_from.get(&token) {
+                    hops.push(RouteHop { pool_id, token_in: from, token_out: token });
+                    token = from;
+                }
+                hops.reverse();
+
+                return Some(hops);
+            }
+            frontier = next;
+        }
+
+        None
+    }
+
     pub fn prev_prices(&self) -> HashMap<PoolId, VecDeque<PairsWithPrice>> {
         self.prev_prices.clone()
     }
@@ -597,6 +661,32 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_find_route() {
+        let token_conversion = setup();
+
+        let route = token_conversion.find_route(TOKEN3, TOKEN0, 3).unwrap();
+        let pools = route.iter().map(|hop| hop.pool_id).collect::<Vec<_>>();
+        assert_eq!(
+            pools,
+            vec![FixedBytes::<32>::with_last_byte(3), FixedBytes::<32>::with_last_byte(1)]
+        );
+        assert_eq!(route[0].token_in, TOKEN3);
+        assert_eq!(route[0].token_out, TOKEN2);
+        assert_eq!(route[1].token_out, TOKEN0);
+
+        // TOKEN3 -> TOKEN2 -> WETH -> TOKEN1 -> TOKEN4
+        assert!(token_conversion.find_route(TOKEN3, TOKEN4, 3).is_none());
+        assert_eq!(
+            token_conversion
+                .find_route(TOKEN3, TOKEN4, 4)
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(token_conversion.find_route(TOKEN3, TOKEN5, 4).is_none());
+    }
+
     #[test]
     fn test_direct_conversion() {
         let token_conversion = setup();
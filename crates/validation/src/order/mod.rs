@@ -2,8 +2,8 @@ use std::{fmt::Debug, future::Future, pin::Pin};
 
 use alloy::primitives::{Address, B256, U256};
 use angstrom_types::{
-    orders::{OrderOrigin, UpdatedGas},
-    primitive::OrderValidationError,
+    orders::{OrderOrigin, RouteHop, UpdatedGas},
+    primitive::{OrderValidationError, TokenBalance},
     sol_bindings::{
         ext::RawPoolOrder,
         grouped_orders::{AllOrders, OrderWithStorageData}
@@ -11,6 +11,7 @@ use angstrom_types::{
 };
 use sim::{GasReturn, SimValidation};
 use tokio::sync::oneshot::{Sender, channel};
+use tracing::Span;
 
 use crate::{common::TokenPriceGenerator, validator::ValidationRequest};
 
@@ -31,15 +32,39 @@ pub type GasEstimationFuture<'a> =
 
 pub type NonceFuture<'a> = Pin<Box<dyn Future<Output = u64> + Send + Sync + 'a>>;
 
+/// the most tokens the balances of a user can be asked for at once.
+pub const MAX_BALANCE_TOKENS: usize = 64;
+
+pub type BalancesFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Vec<TokenBalance>, String>> + Send + Sync + 'a>>;
+
+pub type RouteFuture<'a> = Pin<Box<dyn Future<Output = Option<Vec<RouteHop>>> + Send + Sync + 'a>>;
+
 pub enum OrderValidationRequest {
-    ValidateOrder(Sender<OrderValidationResults>, AllOrders, OrderOrigin)
+    /// the span is the one the order was submitted in, its validation is
+    /// traced as a child of it
+    ValidateOrder(Sender<OrderValidationResults>, AllOrders, OrderOrigin, Span)
+}
+
+impl OrderValidationRequest {
+    /// the span the order is validated in.
+    pub fn span(&self) -> Span {
+        let Self::ValidateOrder(_, order, origin, parent) = self;
+        tracing::error_span!(
+            parent: parent,
+            "validation",
+            order_hash = %order.order_hash(),
+            user = %order.from(),
+            ?origin
+        )
+    }
 }
 
 /// TODO: not a fan of all the conversions. can def simplify
 impl From<OrderValidationRequest> for OrderValidation {
     fn from(value: OrderValidationRequest) -> Self {
         match value {
-            OrderValidationRequest::ValidateOrder(tx, order, orign) => match order {
+            OrderValidationRequest::ValidateOrder(tx, order, orign, _) => match order {
                 order @ AllOrders::PartialStanding(_) => OrderValidation::Limit(tx, order, orign),
                 order @ AllOrders::ExactStanding(_) => OrderValidation::Limit(tx, order, orign),
                 order @ AllOrders::ExactFlash(_) => OrderValidation::Limit(tx, order, orign),
@@ -83,6 +108,16 @@ impl OrderValidationResults {
         if let Self::Valid(order) = this {
             let order_hash = order.order_hash();
             let finalized_order = if is_limit {
+                let hook_gas = match sim.calculate_hook_gas(&order, block) {
+                    Ok(gas) => gas,
+                    Err(error) => {
+                        tracing::info!(%error, "order hook failed simulation");
+                        *self = OrderValidationResults::Invalid { hash: order_hash, error };
+
+                        return;
+                    }
+                };
+
                 let res = Self::map_and_process(
                     order,
                     sim,
@@ -90,7 +125,9 @@ impl OrderValidationResults {
                     block,
                     |order| order,
                     |order| order,
-                    SimValidation::calculate_user_gas
+                    |sim, order, token_price, block| {
+                        sim.calculate_user_gas(order, token_price, block, hook_gas)
+                    }
                 );
 
                 if let Err(e) = res {
@@ -226,6 +263,14 @@ pub trait OrderValidatorHandle: Send + Sync + Clone + Debug + Unpin + 'static {
     ) -> GasEstimationFuture;
 
     fn valid_nonce_for_user(&self, address: Address) -> NonceFuture;
+
+    /// the users balances of the tokens, including what their pending orders
+    /// committed.
+    fn balances(&self, user: Address, tokens: Vec<Address>) -> BalancesFuture;
+
+    /// the pools to swap `token_in` for `token_out` through, with the least
+    /// hops.
+    fn find_route(&self, token_in: Address, token_out: Address, max_hops: usize) -> RouteFuture;
 }
 
 impl OrderValidatorHandle for ValidationClient {
@@ -257,6 +302,7 @@ impl OrderValidatorHandle for ValidationClient {
     }
 
     fn validate_order(&self, origin: OrderOrigin, transaction: Self::Order) -> ValidationFuture {
+        let span = Span::current();
         Box::pin(async move {
             let (tx, rx) = channel();
             let _ = self
@@ -264,7 +310,8 @@ impl OrderValidatorHandle for ValidationClient {
                 .send(ValidationRequest::Order(OrderValidationRequest::ValidateOrder(
                     tx,
                     transaction,
-                    origin
+                    origin,
+                    span
                 )));
 
             rx.await.unwrap()
@@ -302,4 +349,26 @@ impl OrderValidatorHandle for ValidationClient {
             rx.await.unwrap()
         })
     }
+
+    fn balances(&self, user: Address, tokens: Vec<Address>) -> BalancesFuture {
+        Box::pin(async move {
+            let (sender, rx) = channel();
+            let _ = self
+                .0
+                .send(ValidationRequest::Balances { sender, user, tokens });
+
+            rx.await.unwrap().map_err(|e| e.to_string())
+        })
+    }
+
+    fn find_route(&self, token_in: Address, token_out: Address, max_hops: usize) -> RouteFuture {
+        Box::pin(async move {
+            let (sender, rx) = channel();
+            let _ =
+                self.0
+                    .send(ValidationRequest::FindRoute { sender, token_in, token_out, max_hops });
+
+            rx.await.unwrap()
+        })
+    }
 }
@@ -6,14 +6,18 @@ use std::{
 
 use alloy::primitives::{Address, B256, BlockNumber};
 use angstrom_metrics::validation::ValidationMetrics;
-use angstrom_types::sol_bindings::{RawPoolOrder, grouped_orders::AllOrders};
+use angstrom_types::{
+    primitive::TokenBalance,
+    sol_bindings::{RawPoolOrder, grouped_orders::AllOrders}
+};
 use futures::Future;
 use rand::random;
-use tokio::runtime::Handle;
+use tokio::{runtime::Handle, sync::oneshot::Sender};
+use tracing::Instrument;
 use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
 use super::{
-    OrderValidationRequest,
+    MAX_BALANCE_TOKENS, OrderValidationRequest,
     sim::SimValidation,
     state::{
         StateValidation, account::user::UserAddress, db_state_utils::StateFetchUtils,
@@ -73,6 +77,35 @@ where
         self.state.cancel_order(user, hash);
     }
 
+    /// reads the balances on the thread pool, so that the db reads don't hold
+    /// up the other requests.
+    pub fn balances(
+        &self,
+        user: Address,
+        tokens: Vec<Address>,
+        sender: Sender<eyre::Result<Vec<TokenBalance>>>,
+        thread_pool: &mut KeySplitThreadpool<
+            UserAddress,
+            Pin<Box<dyn Future<Output = ()> + Send + Sync>>,
+            Handle
+        >
+    ) {
+        if tokens.len() > MAX_BALANCE_TOKENS {
+            let _ = sender.send(Err(eyre::eyre!(
+                "at most {MAX_BALANCE_TOKENS} tokens can be requested at once"
+            )));
+            return;
+        }
+
+        let accounts = self.state.user_account_tracker.clone();
+        thread_pool.add_new_task(
+            user,
+            Box::pin(async move {
+                let _ = sender.send(accounts.balances(user, &tokens));
+            })
+        );
+    }
+
     pub fn on_new_block(
         &mut self,
         block_number: BlockNumber,
@@ -102,74 +135,74 @@ where
         metrics: ValidationMetrics
     ) {
         let block_number = self.block_number.load(std::sync::atomic::Ordering::Relaxed);
+        let span = order.span();
         let order_validation: OrderValidation = order.into();
         let user = order_validation.user();
         let cloned_state = self.state.clone();
         let cloned_sim = self.sim.clone();
 
-        thread_pool.add_new_task(
-            user,
-            Box::pin(async move {
-                match order_validation {
-                    OrderValidation::Limit(tx, order, loc) => {
-                        metrics
-                            .new_order(false, || async {
-                                let mut results = cloned_state
-                                    .handle_orders(
-                                        order,
-                                        block_number,
-                                        metrics.clone(),
-                                        loc.is_revalidating(),
-                                        async |_, _| Ok((0u128, 0u128))
-                                    )
-                                    .await;
-
-                                results.add_gas_cost_or_invalidate(
-                                    &cloned_sim,
-                                    &token_conversion,
-                                    true,
-                                    block_number
-                                );
-
-                                let _ = tx.send(results);
-                            })
-                            .await;
-                    }
-                    OrderValidation::Searcher(tx, order, _) => {
-                        metrics
-                            .new_order(true, || async {
-                                let AllOrders::TOB(order) = order else { panic!() };
-                                let (mut t0, mut t1) = (order.token_in(), order.token_out());
-
-                                // ensure we are in order as it does matter here
-                                if t0 > t1 {
-                                    std::mem::swap(&mut t0, &mut t1);
-                                }
-
-                                let conversion_rate = token_conversion
-                                    .conversion_rate_of_pair(t0, t1)
-                                    .unwrap_or_default();
-
-                                let mut results = cloned_state
-                                    .handle_tob_order(
-                                        order,
-                                        block_number,
-                                        conversion_rate,
-                                        metrics.clone()
-                                    )
-                                    .await;
-                                results.add_gas_cost_or_invalidate(
-                                    &cloned_sim,
-                                    &token_conversion,
-                                    false,
-                                    block_number
-                                );
-                                let _ = tx.send(results);
-                            })
-                            .await;
-                    }
+        let validation = async move {
+            match order_validation {
+                OrderValidation::Limit(tx, order, loc) => {
+                    metrics
+                        .new_order(false, || async {
+                            let mut results = cloned_state
+                                .handle_orders(
+                                    order,
+                                    block_number,
+                                    metrics.clone(),
+                                    loc.is_revalidating(),
+                                    async |_, _| Ok((0u128, 0u128))
+                                )
+                                .await;
+
+                            results.add_gas_cost_or_invalidate(
+                                &cloned_sim,
+                                &token_conversion,
+                                true,
+                                block_number
+                            );
+
+                            let _ = tx.send(results);
+                        })
+                        .await;
                 }
-            })
-        );
+                OrderValidation::Searcher(tx, order, _) => {
+                    metrics
+                        .new_order(true, || async {
+                            let AllOrders::TOB(order) = order else { panic!() };
+                            let (mut t0, mut t1) = (order.token_in(), order.token_out());
+
+                            // ensure we are in order as it does matter here
+                            if t0 > t1 {
+                                std::mem::swap(&mut t0, &mut t1);
+                            }
+
+                            let conversion_rate = token_conversion
+                                .conversion_rate_of_pair(t0, t1)
+                                .unwrap_or_default();
+
+                            let mut results = cloned_state
+                                .handle_tob_order(
+                                    order,
+                                    block_number,
+                                    conversion_rate,
+                                    metrics.clone()
+                                )
+                                .await;
+                            results.add_gas_cost_or_invalidate(
+                                &cloned_sim,
+                                &token_conversion,
+                                false,
+                                block_number
+                            );
+                            let _ = tx.send(results);
+                        })
+                        .await;
+                }
+            }
+        };
+
+        thread_pool.add_new_task(user, Box::pin(validation.instrument(span)));
     }
 }
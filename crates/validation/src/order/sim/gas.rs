@@ -1,13 +1,21 @@
 use std::{fmt::Debug, sync::Arc};
 
-use alloy::primitives::Address;
-use angstrom_types::sol_bindings::{
-    ext::RawPoolOrder,
-    grouped_orders::{AllOrders, OrderWithStorageData},
-    rpc_orders::TopOfBlockOrder
+use alloy::primitives::{Address, U256};
+use angstrom_types::{
+    primitive::{CHAIN_ID, OrderValidationError},
+    sol_bindings::{
+        ext::{RawPoolOrder, hooks::OrderHook},
+        grouped_orders::{AllOrders, OrderWithStorageData},
+        rpc_orders::TopOfBlockOrder
+    }
 };
 use reth_provider::BlockNumReader;
-use revm::database::CacheDB;
+use revm::{
+    Context, ExecuteEvm, Journal, MainBuilder,
+    context::{BlockEnv, CfgEnv, JournalTr, LocalContext, TxEnv},
+    database::CacheDB,
+    primitives::{TxKind, hardfork::SpecId}
+};
 
 // use super::gas_inspector::{GasSimulationInspector, GasUsed};
 use super::GasUsed;
@@ -21,10 +29,17 @@ pub const TOB_GAS_NORMAL: u64 = 160_000;
 pub const TOB_GAS_INTERNAL_NORMAL: u64 = 150_000;
 pub const TOB_GAS_SUB: u64 = 1;
 pub const TOB_GAS_INTERNAL_SUB: u64 = 1;
+/// the max amount of gas an order hook is allowed to use. Hooks are executed
+/// inside of the bundle so an unbounded hook would eat into the gas of every
+/// other order.
+pub const HOOK_GAS_CAP: u64 = 250_000;
 
 /// 0.7 gwei gas.
 pub const SWITCH_WEI: u128 = 700000000;
 
+/// base cost of a transaction, see [`OrderGasCalculations::gas_of_hook`].
+const TX_BASE_GAS: u64 = 21_000;
+
 /// deals with the calculation of gas for a given type of order.
 /// user orders and tob orders take different paths and are different size and
 /// as such, pay different amount of gas in order to execute.
@@ -35,12 +50,12 @@ pub const SWITCH_WEI: u128 = 700000000;
 /// (Bundle execution cost - Sum(Orders Gas payed)) / len(Orders)
 #[derive(Clone)]
 pub struct OrderGasCalculations<DB> {
-    _db:               CacheDB<Arc<DB>>,
+    db:               CacheDB<Arc<DB>>,
     // the dkeployed addresses in cache_db
-    _angstrom_address: Address,
+    angstrom_address: Address,
     /// the address(pubkey) of this node.
     #[allow(unused)]
-    node_address:      Option<Address>
+    node_address:     Option<Address>
 }
 
 impl<DB> OrderGasCalculations<DB>
@@ -61,16 +76,12 @@ where
         // );
 
         if let Some(angstrom_address) = angstrom_address {
-            Ok(Self {
-                _db:               CacheDB::new(db),
-                _angstrom_address: angstrom_address,
-                node_address:      Some(node_address)
-            })
+            Ok(Self { db: CacheDB::new(db), angstrom_address, node_address: Some(node_address) })
         } else {
             Ok(Self {
-                _db:               CacheDB::new(db),
-                _angstrom_address: angstrom_address.unwrap_or_default(),
-                node_address:      None
+                db:               CacheDB::new(db),
+                angstrom_address: angstrom_address.unwrap_or_default(),
+                node_address:     None
             })
         }
     }
@@ -172,6 +183,88 @@ where
         // .map_err(|e| eyre!("user order err={} {:?}", e, order.from()))
         if order.use_internal() { Ok(BOOK_GAS_INTERNAL) } else { Ok(BOOK_GAS) }
     }
+}
+
+impl<DB> OrderGasCalculations<DB>
+where
+    DB: Unpin + Clone + 'static + revm::DatabaseRef,
+    <DB as revm::DatabaseRef>::Error: Send + Sync + Debug
+{
+    /// simulates the orders hook being triggered by angstrom after settlement.
+    /// returns the gas used by the hook or an error if the hook reverted,
+    /// didn't return the expected magic value or went over
+    /// [`HOOK_GAS_CAP`].
+    pub fn gas_of_hook(
+        &self,
+        order: &OrderWithStorageData<AllOrders>,
+        block: u64
+    ) -> Result<GasUsed, OrderValidationError> {
+        let Some(hook) = order.hook() else { return Ok(0) };
+        let from = order.from();
+        let calldata = hook.compose_calldata(from);
+
+        let mut evm = Context {
+            tx:              TxEnv::default(),
+            block:           BlockEnv::default(),
+            cfg:             CfgEnv::<SpecId>::default(),
+            journaled_state: Journal::<CacheDB<Arc<DB>>>::new(self.db.clone()),
+            chain:           (),
+            error:           Ok(()),
+            local:           LocalContext::default()
+        }
+        .modify_cfg_chained(|cfg| {
+            cfg.chain_id = *CHAIN_ID.get().unwrap();
+            cfg.disable_nonce_check = true;
+            cfg.disable_balance_check = true;
+            // the hook is called by the angstrom contract.
+            cfg.disable_eip3607 = true;
+        })
+        .modify_block_chained(|block_env| {
+            block_env.number = U256::from(block + 1);
+        })
+        .modify_tx_chained(|tx| {
+            tx.caller = self.angstrom_address;
+            tx.kind = TxKind::Call(hook.address);
+            tx.chain_id = Some(*CHAIN_ID.get().unwrap());
+            // leave headroom over the cap so that we can tell an expensive hook apart
+            // from a reverting one.
+            tx.gas_limit = Self::tx_overhead(&calldata) + 2 * HOOK_GAS_CAP;
+            tx.data = calldata.clone();
+        })
+        .build_mainnet();
+
+        let result = evm.replay().map_err(|e| {
+            tracing::debug!(?e, hook=?hook.address, "failed to simulate order hook");
+            OrderValidationError::HookReverted { hook: hook.address }
+        })?;
+
+        let gas_used = result
+            .result
+            .gas_used()
+            .saturating_sub(Self::tx_overhead(&calldata));
+        if gas_used > HOOK_GAS_CAP {
+            return Err(OrderValidationError::HookGasExceeded { gas_used, gas_cap: HOOK_GAS_CAP });
+        }
+
+        let valid_return = result
+            .result
+            .output()
+            .is_some_and(|output| OrderHook::is_valid_return(output));
+        if !result.result.is_success() || !valid_return {
+            return Err(OrderValidationError::HookReverted { hook: hook.address });
+        }
+
+        Ok(gas_used)
+    }
+
+    /// the intrinsic gas of the simulated transaction. the hook is invoked as a
+    /// inner call of the bundle so the user doesn't pay for this.
+    fn tx_overhead(calldata: &[u8]) -> u64 {
+        let zero_bytes = calldata.iter().filter(|b| **b == 0).count() as u64;
+        let non_zero_bytes = calldata.len() as u64 - zero_bytes;
+
+        TX_BASE_GAS + zero_bytes * 4 + non_zero_bytes * 16
+    }
 
     // fn execute_with_db<D: DatabaseRef, F>(db: D, f: F) ->
     // eyre::Result<(ResultAndState, D)> where
@@ -306,3 +399,89 @@ where
 //     let final_address = factory.create2(B256::from(salt), init_code_hash);
 //     (final_address, salt)
 // }
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Bytes, bytes};
+    use angstrom_types::primitive::AngstromAddressConfig;
+    use revm::{bytecode::Bytecode, database::EmptyDB, state::AccountInfo};
+    use testing_tools::type_generator::orders::UserOrderBuilder;
+
+    use super::*;
+
+    fn calculations_with_hook(code: Bytes) -> (OrderGasCalculations<EmptyDB>, Address) {
+        AngstromAddressConfig::INTERNAL_TESTNET.try_init();
+        let hook = Address::random();
+        let bytecode = Bytecode::new_raw(code);
+
+        let mut db = CacheDB::new(Arc::new(EmptyDB::default()));
+        db.insert_account_info(
+            hook,
+            AccountInfo {
+                code_hash: bytecode.hash_slow(),
+                code: Some(bytecode),
+                ..Default::default()
+            }
+        );
+
+        (OrderGasCalculations { db, angstrom_address: Address::random(), node_address: None }, hook)
+    }
+
+    fn hooked_order(hook: Address) -> OrderWithStorageData<AllOrders> {
+        UserOrderBuilder::new()
+            .standing()
+            .exact()
+            .hook(hook, bytes!("0x0102"))
+            .with_storage()
+            .build()
+    }
+
+    #[test]
+    fn hook_returning_magic_is_charged_its_gas() {
+        // mstore(0, magic) return(0, 32)
+        let (calculations, hook) = calculations_with_hook(bytes!("0x6324a2e44b60005260206000f3"));
+
+        let gas = calculations.gas_of_hook(&hooked_order(hook), 1).unwrap();
+        assert!(gas > 0 && gas < HOOK_GAS_CAP, "unexpected hook gas {gas}");
+    }
+
+    #[test]
+    fn reverting_hook_is_rejected() {
+        // revert(0, 0)
+        let (calculations, hook) = calculations_with_hook(bytes!("0x60006000fd"));
+
+        assert_eq!(
+            calculations.gas_of_hook(&hooked_order(hook), 1),
+            Err(OrderValidationError::HookReverted { hook })
+        );
+    }
+
+    #[test]
+    fn hook_without_magic_is_rejected() {
+        // stop
+        let (calculations, hook) = calculations_with_hook(bytes!("0x00"));
+
+        assert_eq!(
+            calculations.gas_of_hook(&hooked_order(hook), 1),
+            Err(OrderValidationError::HookReverted { hook })
+        );
+    }
+
+    #[test]
+    fn hook_over_the_gas_cap_is_rejected() {
+        // a loop that burns all of the gas it is given
+        let (calculations, hook) = calculations_with_hook(bytes!("0x5b600056"));
+
+        let err = calculations
+            .gas_of_hook(&hooked_order(hook), 1)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                OrderValidationError::HookGasExceeded { gas_used, gas_cap: HOOK_GAS_CAP }
+                    if gas_used > HOOK_GAS_CAP
+            ),
+            "unexpected error {err:?}"
+        );
+    }
+}
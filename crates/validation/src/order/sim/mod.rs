@@ -3,7 +3,7 @@ use std::{fmt::Debug, sync::Arc};
 use alloy::primitives::Address;
 use angstrom_metrics::validation::ValidationMetrics;
 use angstrom_types::{
-    primitive::UserAccountVerificationError,
+    primitive::{OrderValidationError, UserAccountVerificationError},
     sol_bindings::{
         RawPoolOrder,
         grouped_orders::{AllOrders, OrderWithStorageData},
@@ -19,8 +19,8 @@ use crate::common::TokenPriceGenerator;
 pub mod console_log;
 mod gas;
 pub use gas::{
-    BOOK_GAS, BOOK_GAS_INTERNAL, SWITCH_WEI, TOB_GAS_INTERNAL_NORMAL, TOB_GAS_INTERNAL_SUB,
-    TOB_GAS_NORMAL, TOB_GAS_SUB
+    BOOK_GAS, BOOK_GAS_INTERNAL, HOOK_GAS_CAP, SWITCH_WEI, TOB_GAS_INTERNAL_NORMAL,
+    TOB_GAS_INTERNAL_SUB, TOB_GAS_NORMAL, TOB_GAS_SUB
 };
 
 pub type GasUsed = u64;
@@ -86,20 +86,40 @@ where
         })
     }
 
+    /// simulates the hook of the order if it has one. Orders whose hook reverts
+    /// or goes over the [`HOOK_GAS_CAP`] are rejected.
+    pub fn calculate_hook_gas(
+        &self,
+        order: &OrderWithStorageData<AllOrders>,
+        block: u64
+    ) -> Result<GasUsed, OrderValidationError> {
+        if !order.has_hook() {
+            return Ok(0);
+        }
+
+        let hash = order.order_hash();
+        let user = order.from();
+        let span = error_span!("hook", ?hash, ?user);
+        span.in_scope(|| self.gas_calculator.gas_of_hook(order, block))
+    }
+
     /// returns an error if we fail to convert prices or if the amount of token
-    /// zero for gas is greater than the max amount specified.
+    /// zero for gas is greater than the max amount specified. `hook_gas` is
+    /// added on top of the book gas as the user pays for the execution of
+    /// there hook.
     pub fn calculate_user_gas(
         &self,
         order: &OrderWithStorageData<AllOrders>,
         conversion: &TokenPriceGenerator,
-        block: u64
+        block: u64,
+        hook_gas: GasUsed
     ) -> eyre::Result<GasReturn> {
         let hash = order.order_hash();
         let user = order.from();
         let span = error_span!("user", ?hash, ?user);
         span.in_scope(|| {
             self.metrics.fetch_gas_for_user(false, || {
-                let gas_in_wei = self.gas_calculator.gas_of_book_order(order, block)?;
+                let gas_in_wei = self.gas_calculator.gas_of_book_order(order, block)? + hook_gas;
                 // grab order tokens;
                 let (token0, token1, max_gas) = if order.token_in() < order.token_out() {
                     (order.token_in(), order.token_out(), order.max_gas_token_0())
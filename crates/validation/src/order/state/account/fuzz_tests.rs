@@ -11,7 +11,7 @@ use alloy::{
     signers::local::PrivateKeySigner
 };
 use angstrom_types::{
-    primitive::{AngstromAddressConfig, AngstromSigner, PoolId},
+    primitive::{AngstromAddressConfig, AngstromSigner, PoolId, UserAccountVerificationError},
     sol_bindings::grouped_orders::AllOrders
 };
 use testing_tools::type_generator::orders::{ToBOrderBuilder, UserOrderBuilder};
@@ -1671,4 +1671,58 @@ mod unit_tests {
         assert!(result1.is_currently_valid(), "Token1 order should be valid {result1:#?}");
         assert!(result2.is_currently_valid(), "Token2 order should be valid {result2:#?}");
     }
+
+    #[tokio::test]
+    async fn test_hook_data_requires_hook_address() {
+        let (processor, mock_pool) = setup_test_environment();
+        let signer = AngstromSigner::random();
+        let test_user = signer.address();
+        let token_in = Address::random();
+        let token_out = Address::random();
+        mock_pool.add_pool(token_in, token_out, PoolId::default());
+
+        processor
+            .fetch_utils
+            .set_balance_for_user(test_user, token_in, U256::from(1000u128));
+        processor
+            .fetch_utils
+            .set_approval_for_user(test_user, token_in, U256::from(1000u128));
+
+        let builder = UserOrderBuilder::new()
+            .standing()
+            .exact()
+            .exact_in(true)
+            .amount(500)
+            .recipient(test_user)
+            .asset_in(token_in)
+            .asset_out(token_out)
+            .signing_key(Some(signer));
+
+        // hook data that is to short to hold the hook address
+        let malformed = builder
+            .clone()
+            .nonce(1)
+            .hook(Address::ZERO, Default::default())
+            .build();
+        let AllOrders::ExactStanding(mut malformed) = malformed else { unreachable!() };
+        malformed.hook_data = malformed.hook_data.slice(..10).into();
+        let malformed = AllOrders::ExactStanding(malformed);
+
+        let pool_info = mock_pool.fetch_pool_info_for_order(&malformed).unwrap();
+        let result = processor
+            .verify_order(malformed, pool_info, 420, false, async |_, _| Ok((0, 0)))
+            .await;
+        assert_eq!(result.unwrap_err(), UserAccountVerificationError::InvalidHookData);
+
+        let hooked = builder
+            .nonce(2)
+            .hook(Address::random(), vec![1, 2, 3].into())
+            .build();
+        let pool_info = mock_pool.fetch_pool_info_for_order(&hooked).unwrap();
+        let result = processor
+            .verify_order(hooked, pool_info, 420, false, async |_, _| Ok((0, 0)))
+            .await
+            .unwrap();
+        assert!(result.is_currently_valid(), "order with a hook should be valid {result:#?}");
+    }
 }
@@ -3,7 +3,7 @@
 use alloy::primitives::{Address, B256, U256};
 use angstrom_types::{
     orders::OrderId,
-    primitive::{UserAccountVerificationError, UserOrderPoolInfo},
+    primitive::{TokenBalance, UserAccountVerificationError, UserOrderPoolInfo},
     sol_bindings::{ext::RawPoolOrder, grouped_orders::OrderWithStorageData}
 };
 use user::UserAccounts;
@@ -43,6 +43,10 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
         self.user_accounts.cancel_order(&user, &hash);
     }
 
+    pub fn balances(&self, user: Address, tokens: &[Address]) -> eyre::Result<Vec<TokenBalance>> {
+        self.user_accounts.balances(user, tokens, &self.fetch_utils)
+    }
+
     pub async fn verify_order<O: RawPoolOrder>(
         &self,
         mut order: O,
@@ -84,9 +88,10 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
             }
         }
 
-        // verify that there is not any hooks set.
-        if order.has_hook() {
-            return Err(UserAccountVerificationError::NonEmptyHook);
+        // verify that the hook data at least contains the hook address. Execution of
+        // the hook itself is checked in simulation.
+        if order.has_hook() && order.hook().is_none() {
+            return Err(UserAccountVerificationError::InvalidHookData);
         }
 
         // very we don't have a respend conflict
@@ -7,7 +7,7 @@ use std::{
 
 use alloy::primitives::{Address, B256, U256};
 use angstrom_types::{
-    primitive::{UserAccountVerificationError, UserOrderPoolInfo},
+    primitive::{TokenBalance, UserAccountVerificationError, UserOrderPoolInfo},
     sol_bindings::{OrderValidationPriority, Ray, RespendAvoidanceMethod, ext::RawPoolOrder}
 };
 use angstrom_utils::FnResultOption;
@@ -315,6 +315,44 @@ impl UserAccounts {
         Ok(())
     }
 
+    /// the users current on chain balances of the tokens along with what their
+    /// pending orders have committed of them.
+    pub fn balances<S: StateFetchUtils>(
+        &self,
+        user: UserAddress,
+        tokens: &[TokenAddress],
+        utils: &S
+    ) -> eyre::Result<Vec<TokenBalance>> {
+        tokens
+            .iter()
+            .map(|&token| {
+                let (committed_approval, committed_balance, committed_angstrom_balance) =
+                    self.iter_of_tob_and_book_unique_tob(user, token).fold(
+                        (Amount::ZERO, Amount::ZERO, Amount::ZERO),
+                        |(approval, balance, angstrom), action| {
+                            (
+                                approval.saturating_add(action.token_approval),
+                                balance.saturating_add(action.token_delta),
+                                angstrom.saturating_add(action.angstrom_delta)
+                            )
+                        }
+                    );
+
+                Ok(TokenBalance {
+                    token,
+                    balance: utils.fetch_balance_for_token(user, token)?,
+                    approval: utils
+                        .fetch_approval_balance_for_token(user, token)?
+                        .unwrap_or_default(),
+                    angstrom_balance: utils.fetch_token_balance_in_angstrom(user, token)?,
+                    committed_balance,
+                    committed_approval,
+                    committed_angstrom_balance
+                })
+            })
+            .collect()
+    }
+
     /// inserts the user action and returns all pending user action hashes that
     /// this, invalidates. i.e higher nonce but no balance / approval
     /// available.
@@ -476,6 +514,7 @@ mod tests {
     use angstrom_types::primitive::PoolId;
 
     use super::*;
+    use crate::order::state::db_state_utils::test_fetching::MockFetch;
 
     fn setup_test_accounts() -> UserAccounts {
         UserAccounts::new()
@@ -722,6 +761,71 @@ mod tests {
         // 100
     }
 
+    #[test]
+    fn test_balances_include_committed_amounts() {
+        let accounts = setup_test_accounts();
+        let fetch = MockFetch::default();
+        let user = address!("1234567890123456789012345678901234567890");
+        let token = address!("deadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
+        let other_token = address!("beefdeadbeefdeadbeefdeadbeefdeadbeefdead");
+        fetch.set_balance_for_user(user, token, U256::from(1000));
+        fetch.set_approval_for_user(user, token, U256::from(800));
+
+        accounts.insert_pending_user_action(
+            false,
+            user,
+            create_test_pending_action(
+                token,
+                U256::from(100),
+                U256::ZERO,
+                U256::from(100),
+                1,
+                false,
+                true
+            )
+        );
+        accounts.insert_pending_user_action(
+            false,
+            user,
+            create_test_pending_action(
+                token,
+                U256::ZERO,
+                U256::from(50),
+                U256::from(50),
+                2,
+                false,
+                true
+            )
+        );
+        accounts.insert_pending_user_action(
+            false,
+            user,
+            create_test_pending_action(
+                other_token,
+                U256::from(300),
+                U256::ZERO,
+                U256::from(300),
+                3,
+                false,
+                true
+            )
+        );
+
+        let balances = accounts.balances(user, &[token], &fetch).unwrap();
+        assert_eq!(
+            balances,
+            vec![TokenBalance {
+                token,
+                balance: U256::from(1000),
+                approval: U256::from(800),
+                angstrom_balance: U256::ZERO,
+                committed_balance: U256::from(100),
+                committed_approval: U256::from(150),
+                committed_angstrom_balance: U256::from(50)
+            }]
+        );
+    }
+
     #[test]
     fn test_insert_pending_user_action_with_invalidation() {
         let accounts = setup_test_accounts();
@@ -3,6 +3,8 @@ use std::{fmt::Debug, sync::Arc, task::Poll};
 use alloy::primitives::{Address, B256, U256};
 use angstrom_types::{
     contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
+    orders::RouteHop,
+    primitive::TokenBalance,
     reth_db_wrapper::SetBlock
 };
 use futures_util::{Future, FutureExt};
@@ -46,6 +48,17 @@ pub enum ValidationRequest {
         sender:       tokio::sync::oneshot::Sender<u64>,
         user_address: Address
     },
+    Balances {
+        sender: tokio::sync::oneshot::Sender<eyre::Result<Vec<TokenBalance>>>,
+        user:   Address,
+        tokens: Vec<Address>
+    },
+    FindRoute {
+        sender:    tokio::sync::oneshot::Sender<Option<Vec<RouteHop>>>,
+        token_in:  Address,
+        token_out: Address,
+        max_hops:  usize
+    },
     /// NOTE: this cancel order should already be verified
     CancelOrder {
         user:       Address,
@@ -151,6 +164,17 @@ where
                 let nonce = self.order_validator.fetch_nonce(user_address);
                 let _ = sender.send(nonce);
             }
+            ValidationRequest::Balances { sender, user, tokens } => {
+                self.order_validator
+                    .balances(user, tokens, sender, &mut self.utils.thread_pool);
+            }
+            ValidationRequest::FindRoute { sender, token_in, token_out, max_hops } => {
+                let route = self
+                    .utils
+                    .token_pricing_ref()
+                    .find_route(token_in, token_out, max_hops);
+                let _ = sender.send(route);
+            }
             ValidationRequest::GasEstimation {
                 sender,
                 is_book,
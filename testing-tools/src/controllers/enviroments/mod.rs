@@ -186,8 +186,11 @@ where
                     futures::future::join_all(other_rxs.into_iter().map(|mut rx| {
                         let value = expected_orders.clone();
                         async move {
-                            (Some(NetworkOrderEvent::IncomingOrders { peer_id, orders: value })
-                                == rx.next().await) as usize
+                            (Some(NetworkOrderEvent::IncomingOrders {
+                                peer_id,
+                                orders: value,
+                                trace: None
+                            }) == rx.next().await) as usize
                         }
                     }))
                     .await
@@ -27,7 +27,7 @@ use consensus::{AngstromValidator, ConsensusManager, ManagerNetworkDeps};
 use dashmap::DashMap;
 use eyre::eyre;
 use futures::{Stream, StreamExt};
-use matching_engine::MatchingManager;
+use matching_engine::{MatchingManager, solver::SolverConfig};
 use order_pool::{PoolConfig, order_storage::OrderStorage};
 use reth::{providers::CanonStateSubscriptions, tasks::TaskExecutor};
 use reth_metrics::common::mpsc::metered_unbounded_channel;
@@ -209,7 +209,8 @@ pub async fn initialize_strom_components_at_block<Provider: WithWalletProvider>(
         block_id,
         global_block_sync.clone(),
         pool_manager,
-        network_stream
+        network_stream,
+        None
     )
     .await;
 
@@ -286,7 +287,11 @@ pub async fn initialize_strom_components_at_block<Provider: WithWalletProvider>(
     tracing::info!("pool manager start");
 
     // spinup matching engine
-    let matching_handle = MatchingManager::spawn(executor.clone(), validation_client.clone());
+    let matching_handle = MatchingManager::spawn(
+        executor.clone(),
+        validation_client.clone(),
+        SolverConfig::default()
+    );
 
     let (state_tx, state_rx) = tokio::sync::mpsc::unbounded_channel();
     let manager = ConsensusManager::new(
@@ -26,13 +26,13 @@ use angstrom_types::{
     pair_with_price::PairsWithPrice,
     primitive::{PoolId, UniswapPoolRegistry},
     sol_bindings::testnet::TestnetHub,
-    submission::{ChainSubmitterHolder, SubmissionHandler},
+    submission::{ChainSubmitterHolder, SubmissionHandler, SubmitterToggles},
     testnet::InitialTestnetState
 };
 use consensus::{AngstromValidator, ConsensusHandler, ConsensusManager, ManagerNetworkDeps};
 use futures::{Future, Stream, StreamExt};
 use jsonrpsee::server::ServerBuilder;
-use matching_engine::{MatchingManager, manager::MatcherHandle};
+use matching_engine::{MatchingManager, manager::MatcherHandle, solver::SolverConfig};
 use order_pool::{PoolConfig, order_storage::OrderStorage};
 use reth_provider::{BlockNumReader, CanonStateSubscriptions};
 use reth_tasks::TaskExecutor;
@@ -102,7 +102,11 @@ impl<P: WithWalletProvider> AngstromNodeInternals<P> {
         let tx_strom_handles = (&strom_handles).into();
 
         let validation_client = ValidationClient(strom_handles.validator_tx);
-        let matching_handle = MatchingManager::spawn(executor.clone(), validation_client.clone());
+        let matching_handle = MatchingManager::spawn(
+            executor.clone(),
+            validation_client.clone(),
+            SolverConfig::default()
+        );
         let consensus_client = ConsensusHandler(strom_handles.consensus_tx_rpc.clone());
 
         let consensus_api = ConsensusApi::new(consensus_client.clone(), executor.clone());
@@ -166,7 +170,9 @@ impl<P: WithWalletProvider> AngstromNodeInternals<P> {
             pool_config_store.clone(),
             block_sync.clone(),
             node_set,
-            vec![]
+            vec![],
+            None,
+            None
         )
         .unwrap();
 
@@ -190,7 +196,8 @@ impl<P: WithWalletProvider> AngstromNodeInternals<P> {
             block_number,
             block_sync.clone(),
             inital_angstrom_state.pool_manager_addr,
-            network_stream
+            network_stream,
+            None
         )
         .await;
         tracing::debug!("uniswap configured");
@@ -312,7 +319,8 @@ impl<P: WithWalletProvider> AngstromNodeInternals<P> {
             submitters:    vec![Box::new(ChainSubmitterHolder::new(
                 anvil,
                 node_config.angstrom_signer()
-            ))]
+            ))],
+            toggles:       SubmitterToggles::default()
         };
 
         tracing::debug!("created mev boost provider");
@@ -400,7 +400,7 @@ where
         self.strom
             .tx_strom_handles
             .network_tx
-            .send(NetworkOrderEvent::IncomingOrders { peer_id, orders })?;
+            .send(NetworkOrderEvent::IncomingOrders { peer_id, orders, trace: None })?;
 
         tracing::info!("sent {num_orders} bundles to the network");
 
@@ -27,5 +27,7 @@ pub mod contracts;
 
 pub mod controllers;
 pub mod replay;
+/// Deterministic multi node consensus simulations
+pub mod simulation;
 pub mod types;
 pub mod utils;
@@ -27,7 +27,12 @@ impl MockEthEventHandle {
         address_changeset: Vec<Address>
     ) {
         self.tx
-            .send(EthEvent::NewBlockTransitions { block_number, filled_orders, address_changeset })
+            .send(EthEvent::NewBlockTransitions {
+                block_number,
+                filled_orders,
+                fill_receipts: vec![],
+                address_changeset
+            })
             .expect("failed to send");
     }
 
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
-use alloy::primitives::Address;
+use alloy::primitives::{Address, B256};
 use angstrom_types::{
     contract_payloads::angstrom::BundleGasDetails,
     orders::PoolSolution,
@@ -19,7 +19,9 @@ impl MatchingEngineHandle for MockMatchingEngine {
         &self,
         _: Vec<BookOrder>,
         _: Vec<OrderWithStorageData<TopOfBlockOrder>>,
-        _: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>
+        _: HashMap<PoolId, (Address, Address, BaselinePoolState, u16)>,
+        _: Vec<Vec<B256>>,
+        _: Duration
     ) -> BoxFuture<Result<(Vec<PoolSolution>, BundleGasDetails), MatchingEngineError>> {
         async move { Ok((vec![], BundleGasDetails::default())) }.boxed()
     }
@@ -52,7 +52,7 @@ impl MockNetworkHandle {
 
     pub fn send_orders_from_peers(&self, peer_id: PeerId, orders: Vec<AllOrders>) {
         self.order_sender
-            .send(NetworkOrderEvent::IncomingOrders { peer_id, orders })
+            .send(NetworkOrderEvent::IncomingOrders { peer_id, orders, trace: None })
             .expect("failed to send orders");
     }
 
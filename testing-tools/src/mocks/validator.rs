@@ -4,6 +4,7 @@ use alloy_primitives::{Address, FixedBytes, U256, keccak256};
 use angstrom_types::{
     self,
     contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
+    primitive::TokenBalance,
     sol_bindings::{ext::RawPoolOrder, grouped_orders::AllOrders}
 };
 use eyre::OptionExt;
@@ -82,6 +83,18 @@ impl OrderValidatorHandle for MockValidator {
     fn valid_nonce_for_user(&self, _: Address) -> validation::order::NonceFuture {
         Box::pin(async move { 10 })
     }
+
+    fn balances(&self, _: Address, tokens: Vec<Address>) -> validation::order::BalancesFuture {
+        let balances = tokens
+            .into_iter()
+            .map(|token| TokenBalance { token, ..Default::default() })
+            .collect();
+        Box::pin(future::ready(Ok(balances)))
+    }
+
+    fn find_route(&self, _: Address, _: Address, _: usize) -> validation::order::RouteFuture {
+        Box::pin(future::ready(None))
+    }
 }
 
 impl BundleValidatorHandle for MockValidator {
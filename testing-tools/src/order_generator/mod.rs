@@ -1,8 +1,9 @@
 use std::ops::Range;
 
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
 use angstrom_rpc::api::OrderApiClient;
 use angstrom_types::{
-    primitive::PoolId,
+    primitive::{AngstromSigner, PoolId},
     sol_bindings::{grouped_orders::AllOrders, rpc_orders::TopOfBlockOrder}
 };
 use futures::stream::StreamExt;
@@ -26,6 +27,56 @@ pub enum InternalBalanceMode {
     Random(f64)
 }
 
+/// The users generated orders are signed by and how the flow is spread over
+/// them.
+#[derive(Debug, Clone)]
+pub struct UserFlow {
+    keys:      Vec<AngstromSigner<PrivateKeySigner>>,
+    /// the first `hot_users` keys sign `hot_share` of all orders
+    hot_users: usize,
+    hot_share: f64
+}
+
+impl Default for UserFlow {
+    fn default() -> Self {
+        Self { keys: vec![AngstromSigner::random(); 10], hot_users: 0, hot_share: 0.0 }
+    }
+}
+
+impl UserFlow {
+    /// spreads orders evenly over `users` random users.
+    pub fn random(users: usize) -> Self {
+        assert!(users > 0, "need at least one user");
+        let keys = (0..users).map(|_| AngstromSigner::random()).collect();
+
+        Self { keys, hot_users: 0, hot_share: 0.0 }
+    }
+
+    /// has `hot_users` of the users sign `hot_share` (0.0 to 1.0) of the
+    /// orders, the rest are spread over everyone.
+    pub fn with_hot_users(mut self, hot_users: usize, hot_share: f64) -> Self {
+        self.hot_users = hot_users.min(self.keys.len());
+        self.hot_share = hot_share;
+        self
+    }
+
+    pub fn signer_of(&self, user: Address) -> Option<&AngstromSigner<PrivateKeySigner>> {
+        self.keys.iter().find(|key| key.address() == user)
+    }
+
+    /// the user that signs the next order.
+    pub fn pick(&self) -> &AngstromSigner<PrivateKeySigner> {
+        let mut rng = rand::rng();
+        let users = if self.hot_users > 0 && rng.random_bool(self.hot_share) {
+            self.hot_users
+        } else {
+            self.keys.len()
+        };
+
+        &self.keys[rng.random_range(0..users)]
+    }
+}
+
 pub struct OrderGenerator<T: OrderApiClient> {
     pools:             Vec<PoolOrderGenerator<T>>,
     /// lower and upper bounds for the amount of book orders to generate
@@ -1,13 +1,9 @@
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use alloy::{
-    primitives::{I256, U256},
-    signers::local::PrivateKeySigner
-};
+use alloy::primitives::{I256, U256};
 use alloy_primitives::Address;
 use angstrom_types::{
     matching::{Ray, SqrtPriceX96},
-    primitive::AngstromSigner,
     sol_bindings::{grouped_orders::AllOrders, rpc_orders::TopOfBlockOrder}
 };
 use rand::Rng;
@@ -18,12 +14,12 @@ use uniswap_v4::uniswap::{
 };
 
 use crate::{
-    order_generator::InternalBalanceMode,
+    order_generator::{InternalBalanceMode, UserFlow},
     type_generator::orders::{ToBOrderBuilder, UserOrderBuilder}
 };
 
 pub struct OrderBuilder {
-    keys:                  Vec<AngstromSigner<PrivateKeySigner>>,
+    users:                 UserFlow,
     t0:                    Address,
     t1:                    Address,
     /// pools to based orders off of
@@ -38,7 +34,11 @@ impl OrderBuilder {
         let t1 = lock.token1;
         drop(lock);
 
-        Self { keys: vec![AngstromSigner::random(); 10], pool_data, t0, t1, internal_balance_mode }
+        Self { users: UserFlow::default(), pool_data, t0, t1, internal_balance_mode }
+    }
+
+    pub fn set_user_flow(&mut self, users: UserFlow) {
+        self.users = users;
     }
 
     pub fn get_token0_token1(&self) -> (Address, Address) {
@@ -101,7 +101,7 @@ impl OrderBuilder {
         }
 
         ToBOrderBuilder::new()
-            .signing_key(self.keys.get(rng.random_range(0..10)).cloned())
+            .signing_key(Some(self.users.pick().clone()))
             .asset_in(if zfo { token0 } else { token1 })
             .asset_out(if !zfo { token0 } else { token1 })
             .quantity_in(amount_in)
@@ -167,7 +167,7 @@ impl OrderBuilder {
         .as_secs();
 
         UserOrderBuilder::new()
-            .signing_key(self.keys.get(rng.random_range(0..10)).cloned())
+            .signing_key(Some(self.users.pick().clone()))
             .is_exact(!is_partial)
             .asset_in(if zfo { token0 } else { token1 })
             .asset_out(if !zfo { token0 } else { token1 })
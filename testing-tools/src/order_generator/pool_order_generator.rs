@@ -3,7 +3,8 @@ use angstrom_types::primitive::PoolId;
 use uniswap_v4::uniswap::pool_manager::SyncedUniswapPool;
 
 use super::{
-    GeneratedPoolOrders, InternalBalanceMode, PriceDistribution, order_builder::OrderBuilder
+    GeneratedPoolOrders, InternalBalanceMode, PriceDistribution, UserFlow,
+    order_builder::OrderBuilder
 };
 /// Order Generator is used for generating orders based off of
 /// the current pool state.
@@ -56,6 +57,18 @@ impl<T: OrderApiClient + Clone> PoolOrderGenerator<T> {
         Self { block_number, price_distribution, cur_price, builder, pool_id, client: None }
     }
 
+    /// estimates the gas of generated orders with the client.
+    pub fn with_client(mut self, client: T) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// signs generated orders with the given users.
+    pub fn with_user_flow(mut self, users: UserFlow) -> Self {
+        self.builder.set_user_flow(users);
+        self
+    }
+
     /// updates the block number and samples a new true price.
     pub fn new_block(&mut self, block: u64) {
         self.block_number = block;
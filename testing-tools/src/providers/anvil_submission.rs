@@ -19,6 +19,10 @@ pub struct AnvilSubmissionProvider {
     pub angstrom_address: Address
 }
 impl ChainSubmitter for AnvilSubmissionProvider {
+    fn name(&self) -> &'static str {
+        "anvil"
+    }
+
     fn angstrom_address(&self) -> alloy_primitives::Address {
         self.angstrom_address
     }
@@ -30,7 +30,7 @@ use angstrom_types::{
     contract_payloads::angstrom::{AngstromPoolConfigStore, UniswapAngstromRegistry},
     pair_with_price::PairsWithPrice,
     primitive::{AngstromSigner, UniswapPoolRegistry, try_init_with_chain_id, *},
-    submission::{ChainSubmitterHolder, SubmissionHandler}
+    submission::{ChainSubmitterHolder, SubmissionHandler, SubmitterToggles}
 };
 use consensus::{
     AngstromValidator, ConsensusHandler, ConsensusManager, ConsensusTimingConfig,
@@ -38,7 +38,7 @@ use consensus::{
 };
 use futures::{Stream, StreamExt};
 use jsonrpsee::server::ServerBuilder;
-use matching_engine::MatchingManager;
+use matching_engine::{MatchingManager, solver::SolverConfig};
 use order_pool::{OrderPoolHandle, PoolConfig};
 use reth_provider::CanonStateSubscriptions;
 use reth_tasks::TaskExecutor;
@@ -221,7 +221,11 @@ impl ReplayRunner {
             .collect::<HashSet<_>>();
 
         let validation_client = ValidationClient(strom_handles.validator_tx);
-        let matching_handle = MatchingManager::spawn(executor.clone(), validation_client.clone());
+        let matching_handle = MatchingManager::spawn(
+            executor.clone(),
+            validation_client.clone(),
+            SolverConfig::default()
+        );
         let consensus_client = ConsensusHandler(strom_handles.consensus_tx_rpc.clone());
 
         let consensus_api = ConsensusApi::new(consensus_client.clone(), executor.clone());
@@ -272,7 +276,9 @@ impl ReplayRunner {
             eth_snap.pool_store.clone(),
             global_block_sync.clone(),
             eth_snap.node_set.clone(),
-            vec![]
+            vec![],
+            None,
+            None
         )
         .unwrap();
 
@@ -288,7 +294,8 @@ impl ReplayRunner {
             block_number + 1,
             global_block_sync.clone(),
             pool_manager,
-            network_stream
+            network_stream,
+            None
         )
         .await;
         tracing::debug!("uniswap configured");
@@ -414,7 +421,8 @@ impl ReplayRunner {
             submitters:    vec![Box::new(ChainSubmitterHolder::new(
                 anvil_sub,
                 angstrom_signer.clone()
-            ))]
+            ))],
+            toggles:       SubmitterToggles::default()
         };
 
         tracing::debug!("created mev boost provider");
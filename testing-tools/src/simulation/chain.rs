@@ -0,0 +1,131 @@
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering}
+    },
+    task::{Context, Poll, Waker}
+};
+
+use alloy::{
+    consensus::Header,
+    providers::RootProvider,
+    rpc::{
+        client::ClientBuilder,
+        json_rpc::{
+            ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload,
+            SerializedRequest
+        }
+    },
+    transports::{TransportError, TransportFut}
+};
+use angstrom_types::block_sync::{BlockSyncConsumer, GlobalBlockState};
+use reth_primitives::{Block, BlockBody, RecoveredBlock};
+use reth_provider::{CanonStateNotification, Chain, ExecutionOutcome};
+use serde_json::json;
+use tower::Service;
+
+/// Answers the calls the leader makes when it submits, so that rounds can
+/// complete without a node behind them. Nothing is submitted as the
+/// simulated nodes have no submitters.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedChainTransport;
+
+impl SimulatedChainTransport {
+    pub fn provider() -> RootProvider {
+        RootProvider::new(ClientBuilder::default().transport(Self, true))
+    }
+
+    fn respond(req: &SerializedRequest) -> Response {
+        let result = match req.method() {
+            "eth_chainId" => json!("0x1"),
+            "eth_blockNumber" | "eth_getTransactionCount" => json!("0x0"),
+            "eth_feeHistory" => json!({
+                "oldestBlock": "0x0",
+                "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"],
+                "gasUsedRatio": [0.5],
+                "reward": [["0x3b9aca00"]]
+            }),
+            _ => {
+                return Response {
+                    id:      req.id().clone(),
+                    payload: ResponsePayload::Failure(ErrorPayload::method_not_found())
+                };
+            }
+        };
+
+        Response {
+            id:      req.id().clone(),
+            payload: ResponsePayload::Success(serde_json::value::to_raw_value(&result).unwrap())
+        }
+    }
+}
+
+impl Service<RequestPacket> for SimulatedChainTransport {
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+    type Response = ResponsePacket;
+
+    #[inline]
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let res = match req {
+            RequestPacket::Single(req) => ResponsePacket::Single(Self::respond(&req)),
+            RequestPacket::Batch(reqs) => {
+                ResponsePacket::Batch(reqs.iter().map(Self::respond).collect())
+            }
+        };
+
+        Box::pin(async move { Ok(res) })
+    }
+}
+
+/// Block sync for a node that only runs consensus, every block is signed off
+/// on straight away.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedBlockSync {
+    block_number: Arc<AtomicU64>
+}
+
+impl BlockSyncConsumer for SimulatedBlockSync {
+    fn sign_off_reorg(&self, _: &'static str, block_range: RangeInclusive<u64>, _: Option<Waker>) {
+        self.block_number
+            .store(*block_range.end(), Ordering::SeqCst);
+    }
+
+    fn sign_off_on_block(&self, _: &'static str, block_number: u64, _: Option<Waker>) {
+        self.block_number.store(block_number, Ordering::SeqCst);
+    }
+
+    fn current_block_number(&self) -> u64 {
+        self.block_number.load(Ordering::SeqCst)
+    }
+
+    fn has_proposal(&self) -> bool {
+        false
+    }
+
+    fn fetch_current_proposal(&self) -> Option<GlobalBlockState> {
+        None
+    }
+
+    fn register(&self, _: &'static str) {}
+}
+
+/// an empty block at the given height, consensus only looks at the number.
+pub fn block_notification(number: u64) -> CanonStateNotification {
+    let block =
+        Block { header: Header { number, ..Default::default() }, body: BlockBody::default() };
+
+    let mut chain = Chain::default();
+    chain.execution_outcome_mut().set_first_block(number);
+    chain.append_block(
+        RecoveredBlock::new_unhashed(block, vec![]),
+        ExecutionOutcome::default().with_receipts(vec![vec![]])
+    );
+
+    CanonStateNotification::Commit { new: Arc::new(chain) }
+}
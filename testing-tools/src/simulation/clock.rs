@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use angstrom_types::consensus::{ManualSlotClock, Slot, SlotClock};
+use tokio::time::Instant;
+
+/// A slot clock that follows tokio's clock instead of the system time. With
+/// the runtime paused, slots pass as fast as the runtime can advance.
+#[derive(Clone)]
+pub struct VirtualSlotClock {
+    clock:   ManualSlotClock,
+    /// when slot zero started
+    genesis: Instant
+}
+
+impl VirtualSlotClock {
+    /// uses mainnet slots, with slot zero starting at `genesis`.
+    pub fn new(genesis: Instant) -> Self {
+        Self { clock: ManualSlotClock::new_with_chain_id(1).unwrap(), genesis }
+    }
+}
+
+impl SlotClock for VirtualSlotClock {
+    fn new_with_chain_id(_: u64) -> Option<Self> {
+        Some(Self::new(Instant::now()))
+    }
+
+    fn now(&self) -> Option<Slot> {
+        self.clock.slot_of(self.now_duration()?)
+    }
+
+    fn is_prior_to_genesis(&self) -> Option<bool> {
+        Some(Instant::now() < self.genesis)
+    }
+
+    fn now_duration(&self) -> Option<Duration> {
+        Some(SlotClock::genesis_duration(&self.clock) + self.genesis.elapsed())
+    }
+
+    fn slot_of(&self, now: Duration) -> Option<Slot> {
+        self.clock.slot_of(now)
+    }
+
+    fn slot_duration(&self) -> Duration {
+        self.clock.slot_duration()
+    }
+
+    fn duration_to_next_slot(&self) -> Option<Duration> {
+        self.clock.duration_to_next_slot_from(self.now_duration()?)
+    }
+
+    fn start_of(&self, slot: Slot) -> Option<Duration> {
+        self.clock.start_of(slot)
+    }
+
+    fn genesis_slot(&self) -> Slot {
+        self.clock.genesis_slot()
+    }
+
+    fn genesis_duration(&self) -> Duration {
+        SlotClock::genesis_duration(&self.clock)
+    }
+}
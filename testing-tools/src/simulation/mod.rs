@@ -0,0 +1,212 @@
+//! Runs a set of [`ConsensusManager`]s in one process against a simulated
+//! strom network and a virtual slot clock.
+//!
+//! Everything runs on a single threaded, paused tokio runtime, so slots pass
+//! as fast as the nodes can process them and a seed always plays out the
+//! same way. This makes it possible to check the liveness and safety of the
+//! round state machine under delays, drops, partitions and byzantine nodes in
+//! CI.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, atomic::AtomicUsize},
+    time::Duration
+};
+
+use alloy::{primitives::B256, signers::local::PrivateKeySigner};
+use angstrom_network::StromNetworkHandle;
+use angstrom_types::{
+    contract_payloads::angstrom::{AngstromPoolConfigStore, UniswapAngstromRegistry},
+    primitive::{AngstromSigner, UniswapPoolRegistry},
+    submission::{SubmissionHandler, SubmitterToggles}
+};
+use consensus::{
+    AngstromValidator, ConsensusManager, ConsensusTimingConfig, ManagerNetworkDeps,
+    WeightedRoundRobin
+};
+use dashmap::DashMap;
+use order_pool::{PoolConfig, order_storage::OrderStorage};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use reth_metrics::common::mpsc::{UnboundedMeteredSender, metered_unbounded_channel};
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::Instant
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
+
+use crate::mocks::matching_engine::MockMatchingEngine;
+
+mod chain;
+mod clock;
+mod network;
+mod report;
+
+pub use chain::*;
+pub use clock::*;
+pub use network::*;
+pub use report::*;
+
+/// the block the nodes start at, the first simulated round is for the block
+/// after it.
+const START_BLOCK: u64 = 100;
+/// mainnet slots, which the virtual slot clock follows.
+const SLOT_DURATION: Duration = Duration::from_secs(12);
+
+/// What to simulate. Build one with [`Scenario::new`] and adjust the network
+/// from there.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub seed:       u64,
+    pub nodes:      usize,
+    /// how many blocks, and so rounds, to run for.
+    pub blocks:     u64,
+    pub link:       LinkConditions,
+    pub partitions: Vec<Partition>,
+    pub byzantine:  HashMap<usize, Byzantine>,
+    pub timing:     ConsensusTimingConfig
+}
+
+impl Scenario {
+    pub fn new(seed: u64, nodes: usize, blocks: u64) -> Self {
+        Self {
+            seed,
+            nodes,
+            blocks,
+            link: LinkConditions::default(),
+            partitions: vec![],
+            byzantine: HashMap::new(),
+            timing: ConsensusTimingConfig::default()
+        }
+    }
+
+    pub fn with_link(mut self, link: LinkConditions) -> Self {
+        self.link = link;
+        self
+    }
+
+    pub fn with_partition(mut self, partition: Partition) -> Self {
+        self.partitions.push(partition);
+        self
+    }
+
+    pub fn with_byzantine(mut self, node: usize, behaviour: Byzantine) -> Self {
+        self.byzantine.insert(node, behaviour);
+        self
+    }
+
+    /// the start of the given slot, counted from the start of the simulation.
+    /// The round for the n-th simulated block runs in slot `n - 1`.
+    pub fn slot(n: u32) -> Duration {
+        SLOT_DURATION * n
+    }
+
+    /// runs the scenario on its own paused runtime.
+    pub fn run(self) -> SimulationReport {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .expect("failed to build the simulation runtime")
+            .block_on(self.simulate())
+    }
+
+    async fn simulate(self) -> SimulationReport {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let signers = (0..self.nodes)
+            .map(|_| {
+                let key = B256::from(rng.random::<[u8; 32]>());
+                AngstromSigner::new(PrivateKeySigner::from_bytes(&key).expect("invalid node key"))
+            })
+            .collect::<Vec<_>>();
+        let validators = signers
+            .iter()
+            .map(|signer| AngstromValidator::new(signer.address(), 100))
+            .collect::<Vec<_>>();
+
+        let genesis = Instant::now();
+        let (blocks_tx, _) = broadcast::channel(self.blocks as usize + 1);
+
+        let peers = signers
+            .iter()
+            .enumerate()
+            .map(|(id, signer)| {
+                let (handle_tx, handle_rx) = mpsc::unbounded_channel();
+                let handle = StromNetworkHandle::new(
+                    Arc::new(AtomicUsize::new(self.nodes - 1)),
+                    UnboundedMeteredSender::new(handle_tx, "simulated strom handle")
+                );
+                let (consensus_tx, consensus_rx) = metered_unbounded_channel("simulated consensus");
+                let (_, rpc_rx) = mpsc::unbounded_channel();
+                let (pool_updates, _) = mpsc::channel(1);
+
+                let manager = ConsensusManager::new(
+                    ManagerNetworkDeps::new(handle, blocks_tx.subscribe(), consensus_rx),
+                    signer.clone(),
+                    validators.clone(),
+                    Arc::new(OrderStorage::new(&PoolConfig::default())),
+                    START_BLOCK,
+                    START_BLOCK,
+                    UniswapAngstromRegistry::new(
+                        UniswapPoolRegistry::default(),
+                        Arc::new(AngstromPoolConfigStore::default())
+                    ),
+                    SyncedUniswapPools::new(Arc::new(DashMap::new()), pool_updates),
+                    SubmissionHandler {
+                        node_provider: Arc::new(SimulatedChainTransport::provider()),
+                        submitters:    vec![],
+                        toggles:       SubmitterToggles::default()
+                    },
+                    MockMatchingEngine {},
+                    SimulatedBlockSync::default(),
+                    rpc_rx,
+                    None,
+                    self.timing,
+                    VirtualSlotClock::new(genesis)
+                )
+                .with_jitter_seed(rng.random());
+                tokio::spawn(manager);
+
+                SimulatedPeer::new(
+                    signer.clone(),
+                    consensus_tx,
+                    UnboundedReceiverStream::new(handle_rx),
+                    self.byzantine.get(&id).copied()
+                )
+            })
+            .collect();
+
+        let network = SimulatedNetwork::new(
+            peers,
+            self.link,
+            self.partitions.clone(),
+            StdRng::seed_from_u64(rng.random()),
+            genesis
+        );
+        let log = network.log();
+        tokio::spawn(network.run());
+
+        // a block lands at the start of every slot.
+        for n in 1..=self.blocks {
+            let _ = blocks_tx.send(block_notification(START_BLOCK + n));
+            tokio::time::sleep_until(genesis + Self::slot(n as u32)).await;
+        }
+
+        // the nodes pick their leaders the same way.
+        let mut leader_selection = WeightedRoundRobin::new(validators, START_BLOCK);
+        leader_selection.choose_proposer(START_BLOCK);
+        let leaders = (1..=self.blocks)
+            .map(|n| {
+                let block = START_BLOCK + n;
+                (block, leader_selection.choose_proposer(block).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        let honest = (0..self.nodes)
+            .map(|id| !self.byzantine.contains_key(&id))
+            .collect::<Vec<_>>();
+        let log = std::mem::take(&mut *log.lock());
+
+        SimulationReport::new(self.seed, &signers, &honest, &leaders, log)
+    }
+}
@@ -0,0 +1,312 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use alloy::{
+    primitives::{Address, keccak256},
+    signers::local::PrivateKeySigner
+};
+use angstrom_network::{StromMessage, StromNetworkHandleMsg};
+use angstrom_types::{
+    consensus::{PreProposal, Proposal, StromConsensusEvent},
+    orders::PoolSolution,
+    primitive::{AngstromSigner, PoolId}
+};
+use futures::{
+    StreamExt,
+    stream::{BoxStream, SelectAll}
+};
+use parking_lot::Mutex;
+use rand::{Rng, rngs::StdRng};
+use reth_metrics::common::mpsc::UnboundedMeteredSender;
+use tokio::time::Instant;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// How messages travel between any two nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct LinkConditions {
+    /// every message is delayed by a duration picked uniformly between the
+    /// min and max delay. Messages whose delays overlap can arrive out of
+    /// order.
+    pub min_delay: Duration,
+    pub max_delay: Duration,
+    /// chance that a message is lost.
+    pub drop_rate: f64
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            min_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(50),
+            drop_rate: 0.0
+        }
+    }
+}
+
+/// Cuts the given nodes off from the rest for a while. Times are counted from
+/// the start of the simulation.
+#[derive(Debug, Clone)]
+pub struct Partition {
+    pub isolated: HashSet<usize>,
+    pub from:     Duration,
+    pub until:    Duration
+}
+
+impl Partition {
+    pub fn new(isolated: impl IntoIterator<Item = usize>, from: Duration, until: Duration) -> Self {
+        Self { isolated: isolated.into_iter().collect(), from, until }
+    }
+
+    fn separates(&self, at: Duration, a: usize, b: usize) -> bool {
+        (self.from..self.until).contains(&at)
+            && self.isolated.contains(&a) != self.isolated.contains(&b)
+    }
+}
+
+/// How a faulty node deviates from the protocol. Only what the node sends is
+/// changed, it still runs the normal round state machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Byzantine {
+    /// never sends anything.
+    Silent,
+    /// signs a second, conflicting pre-proposal and proposal and sends them to
+    /// half of its peers.
+    Equivocate,
+    /// changes its proposals after signing them, so they fail verification.
+    InvalidProposal
+}
+
+/// What happened to a single message sent from one node to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageRecord {
+    /// since the start of the simulation
+    pub sent_at:      Duration,
+    pub from:         usize,
+    pub to:           usize,
+    pub block:        u64,
+    pub kind:         &'static str,
+    /// `None` if the message was dropped or partitioned away
+    pub delivered_at: Option<Duration>
+}
+
+#[derive(Debug, Default)]
+pub struct NetworkLog {
+    pub messages:   Vec<MessageRecord>,
+    /// everything the nodes asked to broadcast, before any byzantine changes,
+    /// in the order it was sent.
+    pub broadcasts: Vec<(usize, StromMessage)>
+}
+
+pub struct SimulatedPeer {
+    signer:       AngstromSigner<PrivateKeySigner>,
+    to_consensus: UnboundedMeteredSender<StromConsensusEvent>,
+    from_handle:  UnboundedReceiverStream<StromNetworkHandleMsg>,
+    byzantine:    Option<Byzantine>
+}
+
+impl SimulatedPeer {
+    pub fn new(
+        signer: AngstromSigner<PrivateKeySigner>,
+        to_consensus: UnboundedMeteredSender<StromConsensusEvent>,
+        from_handle: UnboundedReceiverStream<StromNetworkHandleMsg>,
+        byzantine: Option<Byzantine>
+    ) -> Self {
+        Self { signer, to_consensus, from_handle, byzantine }
+    }
+}
+
+/// Routes the broadcasts of every node's [`StromNetworkHandle`] to the other
+/// nodes' consensus. What happens to each message is drawn from a seeded rng
+/// in the order the messages are sent, so on a single threaded, paused
+/// runtime a seed always plays out the same way.
+///
+/// [`StromNetworkHandle`]: angstrom_network::StromNetworkHandle
+pub struct SimulatedNetwork {
+    peers:      Vec<(AngstromSigner<PrivateKeySigner>, Option<Byzantine>)>,
+    senders:    Vec<UnboundedMeteredSender<StromConsensusEvent>>,
+    outbound:   SelectAll<BoxStream<'static, (usize, StromNetworkHandleMsg)>>,
+    link:       LinkConditions,
+    partitions: Vec<Partition>,
+    rng:        StdRng,
+    genesis:    Instant,
+    log:        Arc<Mutex<NetworkLog>>
+}
+
+impl SimulatedNetwork {
+    pub fn new(
+        peers: Vec<SimulatedPeer>,
+        link: LinkConditions,
+        partitions: Vec<Partition>,
+        rng: StdRng,
+        genesis: Instant
+    ) -> Self {
+        let mut outbound = SelectAll::new();
+        let mut senders = Vec::with_capacity(peers.len());
+        let peers = peers
+            .into_iter()
+            .enumerate()
+            .map(|(id, peer)| {
+                outbound.push(peer.from_handle.map(move |msg| (id, msg)).boxed());
+                senders.push(peer.to_consensus);
+
+                (peer.signer, peer.byzantine)
+            })
+            .collect();
+
+        Self {
+            peers,
+            senders,
+            outbound,
+            link,
+            partitions,
+            rng,
+            genesis,
+            log: Arc::new(Mutex::new(NetworkLog::default()))
+        }
+    }
+
+    pub fn log(&self) -> Arc<Mutex<NetworkLog>> {
+        self.log.clone()
+    }
+
+    pub async fn run(mut self) {
+        while let Some((from, msg)) = self.outbound.next().await {
+            // consensus only ever broadcasts.
+            if let StromNetworkHandleMsg::BroadcastStromMessage { msg } = msg {
+                self.broadcast(from, msg);
+            }
+        }
+    }
+
+    fn broadcast(&mut self, from: usize, msg: StromMessage) {
+        let sent_at = self.genesis.elapsed();
+        let sender = self.peers[from].0.address();
+        self.log.lock().broadcasts.push((from, msg.clone()));
+
+        for (to, msg) in self.tamper(from, msg) {
+            let Some(event) = to_consensus_event(sender, msg) else { continue };
+
+            let delivered_at = self.delay(sent_at, from, to).map(|delay| {
+                let tx = self.senders[to].clone();
+                let event = event.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = tx.send(event);
+                });
+
+                sent_at + delay
+            });
+
+            self.log.lock().messages.push(MessageRecord {
+                sent_at,
+                from,
+                to,
+                block: event.block_height(),
+                kind: event.message_type(),
+                delivered_at
+            });
+        }
+    }
+
+    /// how long the message takes, `None` if it never arrives.
+    fn delay(&mut self, at: Duration, from: usize, to: usize) -> Option<Duration> {
+        // always draw both, so one dropped message doesn't shift every delay
+        // after it.
+        let dropped = self.rng.random_bool(self.link.drop_rate);
+        let delay = self
+            .rng
+            .random_range(self.link.min_delay..=self.link.max_delay);
+
+        let partitioned = self
+            .partitions
+            .iter()
+            .any(|partition| partition.separates(at, from, to));
+
+        (!dropped && !partitioned).then_some(delay)
+    }
+
+    /// what each peer receives when `from` broadcasts the message.
+    fn tamper(&self, from: usize, msg: StromMessage) -> Vec<(usize, StromMessage)> {
+        let (signer, byzantine) = &self.peers[from];
+        let peers = (0..self.peers.len()).filter(|to| *to != from);
+
+        match (byzantine, &msg) {
+            (Some(Byzantine::Silent), _) => vec![],
+            (Some(Byzantine::Equivocate), StromMessage::PrePropose(pre_proposal))
+                if pre_proposal.source == signer.id() =>
+            {
+                let twin = StromMessage::PrePropose(conflicting_pre_proposal(pre_proposal, signer));
+                peers
+                    .map(|to| (to, if to % 2 == 0 { msg.clone() } else { twin.clone() }))
+                    .collect()
+            }
+            (Some(Byzantine::Equivocate), StromMessage::Propose(proposal))
+                if proposal.source == signer.id() =>
+            {
+                let twin = StromMessage::Propose(conflicting_proposal(proposal, signer));
+                peers
+                    .map(|to| (to, if to % 2 == 0 { msg.clone() } else { twin.clone() }))
+                    .collect()
+            }
+            (Some(Byzantine::InvalidProposal), StromMessage::Propose(proposal))
+                if proposal.source == signer.id() =>
+            {
+                let mut invalid = proposal.clone();
+                invalid.solutions.push(fake_solution());
+                peers
+                    .map(|to| (to, StromMessage::Propose(invalid.clone())))
+                    .collect()
+            }
+            _ => peers.map(|to| (to, msg.clone())).collect()
+        }
+    }
+}
+
+/// the same pre-proposal with an extra order nobody else has seen.
+fn conflicting_pre_proposal(
+    pre_proposal: &PreProposal,
+    signer: &AngstromSigner<PrivateKeySigner>
+) -> PreProposal {
+    let mut limit = pre_proposal.limit.clone();
+    limit.push(keccak256(pre_proposal.signature.as_bytes()));
+
+    PreProposal::generate_pre_proposal(
+        pre_proposal.block_height,
+        signer,
+        limit,
+        pre_proposal.searcher.clone()
+    )
+}
+
+/// a properly signed proposal with an extra solution.
+fn conflicting_proposal(
+    proposal: &Proposal,
+    signer: &AngstromSigner<PrivateKeySigner>
+) -> Proposal {
+    let mut solutions = proposal.solutions.clone();
+    solutions.push(fake_solution());
+
+    Proposal::generate_proposal(
+        proposal.block_height,
+        signer,
+        proposal.preproposals.clone(),
+        solutions
+    )
+}
+
+fn fake_solution() -> PoolSolution {
+    PoolSolution { id: PoolId::repeat_byte(0xee), ..Default::default() }
+}
+
+/// the event the receiving node's consensus sees, the same as the strom
+/// network manager hands it over.
+fn to_consensus_event(sender: Address, msg: StromMessage) -> Option<StromConsensusEvent> {
+    Some(match msg {
+        StromMessage::PrePropose(p) => StromConsensusEvent::PreProposal(sender, p),
+        StromMessage::PreProposeAgg(p) => StromConsensusEvent::PreProposalAgg(sender, p),
+        StromMessage::Propose(p) => StromConsensusEvent::Proposal(sender, p),
+        StromMessage::BundleUnlockAttestation(block, p) => {
+            StromConsensusEvent::BundleUnlockAttestation(sender, block, p)
+        }
+        _ => return None
+    })
+}
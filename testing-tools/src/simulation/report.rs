@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+use angstrom_network::StromMessage;
+use angstrom_types::primitive::AngstromSigner;
+
+use super::network::{MessageRecord, NetworkLog};
+
+/// How a single block's round played out on the honest nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockReport {
+    pub number:           u64,
+    pub leader:           usize,
+    pub honest_leader:    bool,
+    /// the proposal each honest node accepted. Proposals are numbered in the
+    /// order they were first sent.
+    pub accepted:         BTreeMap<usize, usize>,
+    /// honest nodes that passed on a proposal that fails verification.
+    pub accepted_invalid: Vec<usize>,
+    /// honest nodes that signed more than one pre-proposal.
+    pub equivocated:      Vec<usize>
+}
+
+impl BlockReport {
+    /// proposals the honest nodes accepted, without duplicates.
+    pub fn distinct_proposals(&self) -> usize {
+        self.accepted.values().collect::<HashSet<_>>().len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    pub seed:     u64,
+    /// how many nodes have to accept a proposal for it to count.
+    pub quorum:   usize,
+    pub blocks:   Vec<BlockReport>,
+    pub messages: Vec<MessageRecord>
+}
+
+impl SimulationReport {
+    pub(crate) fn new(
+        seed: u64,
+        signers: &[AngstromSigner<PrivateKeySigner>],
+        honest: &[bool],
+        leaders: &[(u64, Address)],
+        log: NetworkLog
+    ) -> Self {
+        let quorum = (2 * signers.len()).div_ceil(3);
+        let node_of = |address: Address| {
+            signers
+                .iter()
+                .position(|signer| signer.address() == address)
+                .unwrap()
+        };
+
+        let mut blocks = leaders
+            .iter()
+            .map(|(number, leader)| {
+                let leader = node_of(*leader);
+                (
+                    *number,
+                    BlockReport {
+                        number: *number,
+                        leader,
+                        honest_leader: honest[leader],
+                        accepted: BTreeMap::new(),
+                        accepted_invalid: vec![],
+                        equivocated: vec![]
+                    }
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut proposal_ids = HashMap::new();
+        let mut pre_proposals = HashMap::<_, HashSet<_>>::new();
+
+        for (node, msg) in log.broadcasts {
+            if !honest[node] {
+                continue;
+            }
+
+            match msg {
+                StromMessage::Propose(proposal) => {
+                    let Some(block) = blocks.get_mut(&proposal.block_height) else { continue };
+
+                    let from_leader =
+                        proposal.recover_signer() == Some(signers[block.leader].address());
+                    if !from_leader || !proposal.is_valid(&block.number, quorum) {
+                        block.accepted_invalid.push(node);
+                    }
+
+                    let next = proposal_ids.len();
+                    let id = *proposal_ids
+                        .entry(proposal.signature.as_bytes())
+                        .or_insert(next);
+                    block.accepted.entry(node).or_insert(id);
+                }
+                // honest nodes pass on everyone's pre-proposals, only their own count.
+                StromMessage::PrePropose(pre_proposal)
+                    if pre_proposal.source == signers[node].id() =>
+                {
+                    pre_proposals
+                        .entry((node, pre_proposal.block_height))
+                        .or_default()
+                        .insert(pre_proposal.signature.as_bytes());
+                }
+                _ => {}
+            }
+        }
+
+        for ((node, number), _) in pre_proposals
+            .into_iter()
+            .filter(|(_, signed)| signed.len() > 1)
+        {
+            if let Some(block) = blocks.get_mut(&number) {
+                block.equivocated.push(node);
+            }
+        }
+
+        let blocks = blocks
+            .into_values()
+            .map(|mut block| {
+                block.accepted_invalid.sort();
+                block.accepted_invalid.dedup();
+                block.equivocated.sort();
+                block
+            })
+            .collect();
+
+        Self { seed, quorum, blocks, messages: log.messages }
+    }
+
+    /// every way the honest nodes broke safety: accepting an invalid
+    /// proposal, signing two pre-proposals for one block or, with an honest
+    /// leader, accepting different proposals.
+    pub fn safety_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        for block in &self.blocks {
+            if !block.accepted_invalid.is_empty() {
+                violations.push(format!(
+                    "block {}: nodes {:?} accepted an invalid proposal",
+                    block.number, block.accepted_invalid
+                ));
+            }
+            if !block.equivocated.is_empty() {
+                violations.push(format!(
+                    "block {}: honest nodes {:?} equivocated",
+                    block.number, block.equivocated
+                ));
+            }
+            if block.honest_leader && block.distinct_proposals() > 1 {
+                violations.push(format!(
+                    "block {}: honest nodes accepted {} different proposals",
+                    block.number,
+                    block.distinct_proposals()
+                ));
+            }
+        }
+
+        violations
+    }
+
+    /// blocks with an honest leader whose proposal wasn't accepted by a
+    /// quorum.
+    pub fn missed_blocks(&self) -> Vec<u64> {
+        self.blocks
+            .iter()
+            .filter(|block| block.honest_leader && block.accepted.len() < self.quorum)
+            .map(|block| block.number)
+            .collect()
+    }
+}
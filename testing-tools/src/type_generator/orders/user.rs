@@ -1,5 +1,5 @@
 use alloy::{
-    primitives::{Address, U256},
+    primitives::{Address, Bytes, U256},
     signers::{SignerSync, local::PrivateKeySigner}
 };
 use alloy_primitives::aliases::U40;
@@ -35,6 +35,7 @@ pub struct UserOrderBuilder {
     gas_0:        Option<u128>,
     min_price:    Ray,
     deadline:     U256,
+    hook_data:    Bytes,
     signing_key:  Option<AngstromSigner<PrivateKeySigner>>
 }
 
@@ -85,6 +86,12 @@ impl UserOrderBuilder {
         Self { recipient, ..self }
     }
 
+    /// Sets a hook that angstrom will call with `payload` once the order is
+    /// settled
+    pub fn hook(self, hook: Address, payload: Bytes) -> Self {
+        Self { hook_data: [hook.as_slice(), payload.as_ref()].concat().into(), ..self }
+    }
+
     pub fn asset_in(self, asset_in: Address) -> Self {
         Self { asset_in, ..self }
     }
@@ -241,6 +248,7 @@ impl UserOrderBuilder {
                     exact_in: self.exact_in,
                     deadline: U40::from(self.deadline.to::<u32>()),
                     use_internal: self.use_internal,
+                    hook_data: self.hook_data.clone(),
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
@@ -266,6 +274,7 @@ impl UserOrderBuilder {
                     recipient: self.recipient,
                     deadline: U40::from(self.deadline.to::<u32>()),
                     use_internal: self.use_internal,
+                    hook_data: self.hook_data.clone(),
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
@@ -290,6 +299,7 @@ impl UserOrderBuilder {
                     recipient: self.recipient,
                     exact_in: self.exact_in,
                     use_internal: self.use_internal,
+                    hook_data: self.hook_data.clone(),
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
@@ -314,6 +324,7 @@ impl UserOrderBuilder {
                     min_price: *self.min_price,
                     recipient: self.recipient,
                     use_internal: self.use_internal,
+                    hook_data: self.hook_data.clone(),
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
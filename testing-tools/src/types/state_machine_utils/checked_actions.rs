@@ -51,7 +51,7 @@ where
         let f = |testnet: &'a mut AngstromTestnet<C, DevnetConfig, WalletProvider>| {
             pin_action(testnet.broadcast_orders_message(
                 None,
-                StromMessage::PropagatePooledOrders(orders.clone()),
+                StromMessage::PropagatePooledOrders(orders.clone(), None),
                 orders
             ))
         };